@@ -0,0 +1,568 @@
+// Bundles everything a project's levels reference into a folder the native runtime can play.
+// The saved state is handled as plain json so fields the export doesn't care about pass through.
+
+use image::imageops::FilterType;
+use serde::Serialize;
+use serde_json::{json, Value};
+use std::collections::BTreeSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::ktx2::{encode_mask, encode_texture};
+use crate::read_normalized_heights;
+
+pub const MANIFEST_VERSION: u32 = 1;
+
+// heightfields are "MPHF", then version, width and height as u32, then normalized f32 heights,
+// all little endian, so the runtime can skip TIFF decoding
+pub const HEIGHTFIELD_MAGIC: &[u8; 4] = b"MPHF";
+pub const HEIGHTFIELD_VERSION: u32 = 1;
+
+// every exported image is a KTX2, textures are BC1 and landscape masks BC4
+pub const TEXTURE_FORMAT: &str = "ktx2";
+
+// larger textures are downscaled, the landscape shader resamples everything to the mask size anyway
+const MAX_TEXTURE_SIZE: u32 = 2048;
+
+#[derive(Serialize)]
+pub struct ExportedAsset {
+    kind: String,
+    id: String,
+    path: String,
+    bytes: usize,
+}
+
+#[derive(Serialize)]
+pub struct ExportSummary {
+    pub exportDir: String,
+    pub assets: Vec<ExportedAsset>,
+}
+
+struct PendingFile {
+    path: String,
+    data: Vec<u8>,
+}
+
+#[derive(Default)]
+struct References {
+    models: BTreeSet<String>,
+    landscapes: BTreeSet<String>,
+    textures: BTreeSet<String>,
+}
+
+fn str_field<'a>(value: &'a Value, key: &str) -> Option<&'a str> {
+    value.get(key).and_then(Value::as_str)
+}
+
+fn array_field<'a>(value: &'a Value, key: &str) -> impl Iterator<Item = &'a Value> {
+    value
+        .get(key)
+        .and_then(Value::as_array)
+        .into_iter()
+        .flatten()
+}
+
+fn collect_references(saved_state: &Value) -> References {
+    let mut references = References::default();
+
+    for level in array_field(saved_state, "levels") {
+        for component in array_field(level, "components") {
+            let asset_id = str_field(component, "asset_id")
+                .unwrap_or_default()
+                .to_string();
+
+            match str_field(component, "kind") {
+                Some("Model") | Some("Foliage") => {
+                    references.models.insert(asset_id);
+                }
+                Some("Landscape") => {
+                    references.landscapes.insert(asset_id);
+
+                    if let Some(properties) = component.get("landscape_properties") {
+                        for key in [
+                            "primary_texture_id",
+                            "rockmap_texture_id",
+                            "soil_texture_id",
+                        ] {
+                            if let Some(texture_id) = str_field(properties, key) {
+                                references.textures.insert(texture_id.to_string());
+                            }
+                        }
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+
+    references
+}
+
+fn with_extension(filename: &str, extension: &str) -> String {
+    let mut path = PathBuf::from(filename);
+    path.set_extension(extension);
+    path.to_string_lossy().to_string()
+}
+
+fn open_image(path: &Path) -> Result<image::DynamicImage, String> {
+    image::open(path).map_err(|e| format!("Couldn't open image {:?}: {}", path, e))
+}
+
+fn convert_texture(path: &Path) -> Result<Vec<u8>, String> {
+    let mut image = open_image(path)?;
+    if image.width() > MAX_TEXTURE_SIZE || image.height() > MAX_TEXTURE_SIZE {
+        image = image.resize(MAX_TEXTURE_SIZE, MAX_TEXTURE_SIZE, FilterType::Triangle);
+    }
+
+    Ok(encode_texture(&image.to_rgba8()))
+}
+
+// the landscape shader only reads a mask's red channel
+fn convert_mask(path: &Path) -> Result<Vec<u8>, String> {
+    Ok(encode_mask(&open_image(path)?.to_luma8()))
+}
+
+fn encode_heightfield(width: usize, height: usize, heights: &[f32]) -> Vec<u8> {
+    let mut data = Vec::with_capacity(16 + heights.len() * 4);
+    data.extend_from_slice(HEIGHTFIELD_MAGIC);
+    data.extend_from_slice(&HEIGHTFIELD_VERSION.to_le_bytes());
+    data.extend_from_slice(&(width as u32).to_le_bytes());
+    data.extend_from_slice(&(height as u32).to_le_bytes());
+    for value in heights {
+        data.extend_from_slice(&value.to_le_bytes());
+    }
+
+    data
+}
+
+fn export_model(project_dir: &Path, model: &mut Value) -> Result<PendingFile, String> {
+    let filename = str_field(model, "fileName").ok_or("Model has no file name")?;
+    let data = fs::read(project_dir.join("models").join(filename))
+        .map_err(|e| format!("Couldn't read model {}: {}", filename, e))?;
+
+    // the runtime only loads binary gltf with an embedded buffer
+    let gltf = gltf::Gltf::from_slice(&data)
+        .map_err(|e| format!("Model {} isn't a valid glb: {}", filename, e))?;
+    if gltf.blob.is_none() {
+        return Err(format!("Model {} has no embedded binary data", filename));
+    }
+
+    Ok(PendingFile {
+        path: format!("models/{}", filename),
+        data,
+    })
+}
+
+// the heightmap becomes a binary heightfield and the primary mask is always written out,
+// since the runtime can no longer derive it from the original TIFF
+fn export_landscape(project_dir: &Path, landscape: &mut Value) -> Result<Vec<PendingFile>, String> {
+    let landscape_id = str_field(landscape, "id").unwrap_or_default().to_string();
+    let landscape_dir = project_dir.join("landscapes").join(&landscape_id);
+    let export_dir = format!("landscapes/{}", landscape_id);
+
+    let mut files = Vec::new();
+
+    let heightmap_filename = landscape
+        .get("heightmap")
+        .and_then(|heightmap| str_field(heightmap, "fileName"))
+        .ok_or(format!("Landscape {} has no heightmap", landscape_id))?
+        .to_string();
+
+    let (width, height, heights) =
+        read_normalized_heights(&landscape_dir.join("heightmaps").join(&heightmap_filename))?;
+    if width < 2 || height < 2 || heights.len() != width * height {
+        return Err(format!(
+            "Heightmap {} doesn't match its dimensions",
+            heightmap_filename
+        ));
+    }
+
+    let heightfield_filename = with_extension(&heightmap_filename, "heightfield");
+    files.push(PendingFile {
+        path: format!("{}/heightmaps/{}", export_dir, heightfield_filename),
+        data: encode_heightfield(width, height, &heights),
+    });
+
+    let primary_mask_filename = with_extension(&heightmap_filename, TEXTURE_FORMAT);
+    let painted_mask = landscape_dir
+        .join("primarymasks")
+        .join(with_extension(&heightmap_filename, "png"));
+    let primary_mask = if painted_mask.exists() {
+        convert_mask(&painted_mask)?
+    } else {
+        let pixels = heights.iter().map(|h| (h * 255.0).round() as u8).collect();
+        let image = image::GrayImage::from_raw(width as u32, height as u32, pixels)
+            .ok_or("Primary mask doesn't match its dimensions")?;
+        encode_mask(&image)
+    };
+    files.push(PendingFile {
+        path: format!("{}/primarymasks/{}", export_dir, primary_mask_filename),
+        data: primary_mask,
+    });
+
+    landscape["heightmap"]["fileName"] = Value::String(heightfield_filename);
+
+    for (key, folder) in [("rockmap", "rockmaps"), ("soil", "soils")] {
+        let filename = match landscape
+            .get(key)
+            .and_then(|map| str_field(map, "fileName"))
+        {
+            Some(filename) => filename.to_string(),
+            None => continue,
+        };
+
+        let converted_filename = with_extension(&filename, TEXTURE_FORMAT);
+        files.push(PendingFile {
+            path: format!("{}/{}/{}", export_dir, folder, converted_filename),
+            data: convert_mask(&landscape_dir.join(folder).join(&filename))?,
+        });
+
+        landscape[key]["fileName"] = Value::String(converted_filename);
+    }
+
+    Ok(files)
+}
+
+fn export_texture(project_dir: &Path, texture: &mut Value) -> Result<PendingFile, String> {
+    let filename = str_field(texture, "fileName")
+        .ok_or("Texture has no file name")?
+        .to_string();
+
+    let data = convert_texture(&project_dir.join("textures").join(&filename))?;
+
+    let converted_filename = with_extension(&filename, TEXTURE_FORMAT);
+    texture["fileName"] = Value::String(converted_filename.clone());
+
+    Ok(PendingFile {
+        path: format!("textures/{}", converted_filename),
+        data,
+    })
+}
+
+// keeps only the referenced entries of a saved state list, converting each one in place
+fn export_list(
+    saved_state: &mut Value,
+    key: &str,
+    referenced: &BTreeSet<String>,
+    errors: &mut Vec<String>,
+    files: &mut Vec<(String, String, PendingFile)>,
+    kind: &str,
+    mut export: impl FnMut(&mut Value) -> Result<Vec<PendingFile>, String>,
+) {
+    let entries = array_field(saved_state, key).cloned().collect::<Vec<_>>();
+    let mut kept = Vec::new();
+
+    for id in referenced {
+        let mut entry = match entries
+            .iter()
+            .find(|entry| str_field(entry, "id") == Some(id.as_str()))
+        {
+            Some(entry) => entry.clone(),
+            None => {
+                errors.push(format!("Level references missing {} {}", kind, id));
+                continue;
+            }
+        };
+
+        match export(&mut entry) {
+            Ok(exported) => {
+                files.extend(
+                    exported
+                        .into_iter()
+                        .map(|file| (kind.to_string(), id.clone(), file)),
+                );
+                kept.push(entry);
+            }
+            Err(e) => errors.push(e),
+        }
+    }
+
+    saved_state[key] = Value::Array(kept);
+}
+
+// validates and converts every referenced asset before anything is written,
+// so a failed export never leaves a half finished folder behind
+pub fn export_project(
+    project_dir: &Path,
+    export_dir: &Path,
+    project_id: &str,
+    mut saved_state: Value,
+) -> Result<ExportSummary, String> {
+    let references = collect_references(&saved_state);
+    let mut errors = Vec::new();
+    let mut files = Vec::new();
+
+    export_list(
+        &mut saved_state,
+        "models",
+        &references.models,
+        &mut errors,
+        &mut files,
+        "model",
+        |model| export_model(project_dir, model).map(|file| vec![file]),
+    );
+    export_list(
+        &mut saved_state,
+        "landscapes",
+        &references.landscapes,
+        &mut errors,
+        &mut files,
+        "landscape",
+        |landscape| export_landscape(project_dir, landscape),
+    );
+    export_list(
+        &mut saved_state,
+        "textures",
+        &references.textures,
+        &mut errors,
+        &mut files,
+        "texture",
+        |texture| export_texture(project_dir, texture).map(|file| vec![file]),
+    );
+
+    if !errors.is_empty() {
+        return Err(format!("Export failed:\n{}", errors.join("\n")));
+    }
+
+    // concepts only matter while editing
+    saved_state["concepts"] = Value::Array(Vec::new());
+
+    // everything is written next to the export first and swapped in once complete,
+    // so a failed write leaves the previous export as it was
+    let staging_dir = sibling_dir(export_dir, "staging");
+    if staging_dir.exists() {
+        fs::remove_dir_all(&staging_dir)
+            .map_err(|e| format!("Couldn't clear unfinished export: {}", e))?;
+    }
+
+    let result = write_export(&staging_dir, project_id, &saved_state, files)
+        .and_then(|assets| replace_dir(&staging_dir, export_dir).map(|_| assets));
+
+    let assets = match result {
+        Ok(assets) => assets,
+        Err(e) => {
+            fs::remove_dir_all(&staging_dir).ok();
+            return Err(e);
+        }
+    };
+
+    Ok(ExportSummary {
+        exportDir: export_dir.to_string_lossy().to_string(),
+        assets,
+    })
+}
+
+// a hidden folder beside the export, on the same drive so renaming it is cheap
+fn sibling_dir(dir: &Path, suffix: &str) -> PathBuf {
+    let name = dir
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_default();
+
+    dir.with_file_name(format!(".{}.{}", name, suffix))
+}
+
+// the old export is only removed once the new one is in place
+fn replace_dir(source: &Path, target: &Path) -> Result<(), String> {
+    let previous = sibling_dir(target, "previous");
+    if previous.exists() {
+        fs::remove_dir_all(&previous)
+            .map_err(|e| format!("Couldn't clear previous export: {}", e))?;
+    }
+
+    if target.exists() {
+        fs::rename(target, &previous)
+            .map_err(|e| format!("Couldn't move previous export aside: {}", e))?;
+    }
+
+    if let Err(e) = fs::rename(source, target) {
+        if previous.exists() {
+            fs::rename(&previous, target).ok();
+        }
+        return Err(format!("Couldn't move export into place: {}", e));
+    }
+
+    if previous.exists() {
+        fs::remove_dir_all(&previous)
+            .map_err(|e| format!("Couldn't remove previous export: {}", e))?;
+    }
+
+    Ok(())
+}
+
+fn write_export(
+    export_dir: &Path,
+    project_id: &str,
+    saved_state: &Value,
+    files: Vec<(String, String, PendingFile)>,
+) -> Result<Vec<ExportedAsset>, String> {
+    let mut assets = Vec::new();
+    for (kind, id, file) in files {
+        let path = export_dir.join(&file.path);
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)
+                .map_err(|e| format!("Couldn't create export directory: {}", e))?;
+        }
+        fs::write(&path, &file.data).map_err(|e| format!("Couldn't write {}: {}", file.path, e))?;
+
+        assets.push(ExportedAsset {
+            kind,
+            id,
+            path: file.path,
+            bytes: file.data.len(),
+        });
+    }
+
+    fs::create_dir_all(export_dir)
+        .map_err(|e| format!("Couldn't create export directory: {}", e))?;
+
+    let levels: Vec<&str> = array_field(saved_state, "levels")
+        .filter_map(|level| str_field(level, "id"))
+        .collect();
+    let exported_at = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |elapsed| elapsed.as_secs());
+
+    let manifest = json!({
+        "version": MANIFEST_VERSION,
+        "projectId": project_id,
+        "exportedAt": exported_at,
+        "state": "project.json",
+        "levels": levels,
+        // block compressed, see ktx2.rs
+        "textureFormat": TEXTURE_FORMAT,
+        "assets": assets,
+    });
+
+    let state_json = serde_json::to_string_pretty(saved_state)
+        .map_err(|e| format!("Couldn't serialize saved state: {}", e))?;
+    fs::write(export_dir.join("project.json"), state_json)
+        .map_err(|e| format!("Couldn't write project.json: {}", e))?;
+
+    let manifest_json = serde_json::to_string_pretty(&manifest)
+        .map_err(|e| format!("Couldn't serialize manifest: {}", e))?;
+    fs::write(export_dir.join("manifest.json"), manifest_json)
+        .map_err(|e| format!("Couldn't write manifest.json: {}", e))?;
+
+    Ok(assets)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // the runtime's reader is tested against the same file, which pins the format on both sides
+    const SAMPLE_HEIGHTFIELD: &[u8] = include_bytes!("../tests/fixtures/sample.heightfield");
+    const SAMPLE_HEIGHTS: [f32; 6] = [0.0, 0.25, 0.5, 0.75, 1.0, 0.125];
+
+    fn scratch_dir(name: &str) -> PathBuf {
+        let dir =
+            std::env::temp_dir().join(format!("midpoint-export-{}-{}", name, std::process::id()));
+        if dir.exists() {
+            fs::remove_dir_all(&dir).unwrap();
+        }
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn heightfield_matches_the_runtime_format() {
+        let data = encode_heightfield(3, 2, &SAMPLE_HEIGHTS);
+
+        assert_eq!(&data[0..4], HEIGHTFIELD_MAGIC);
+        assert_eq!(data.len(), 16 + SAMPLE_HEIGHTS.len() * 4);
+        assert_eq!(data, SAMPLE_HEIGHTFIELD);
+    }
+
+    #[test]
+    fn references_cover_every_level() {
+        let saved_state = json!({
+            "levels": [
+                {
+                    "components": [
+                        { "kind": "Model", "asset_id": "model-1" },
+                        { "kind": "Foliage", "asset_id": "tree-1" },
+                        {
+                            "kind": "Landscape",
+                            "asset_id": "landscape-1",
+                            "landscape_properties": {
+                                "primary_texture_id": "grass",
+                                "rockmap_texture_id": "rock",
+                                "soil_texture_id": null
+                            }
+                        }
+                    ]
+                },
+                {
+                    "components": [
+                        { "kind": "Model", "asset_id": "model-1" },
+                        { "kind": "Model", "asset_id": "model-2" },
+                        { "kind": "Unknown", "asset_id": "ignored" }
+                    ]
+                }
+            ]
+        });
+
+        let references = collect_references(&saved_state);
+
+        let ids = |set: &BTreeSet<String>| set.iter().cloned().collect::<Vec<_>>();
+        assert_eq!(ids(&references.models), ["model-1", "model-2", "tree-1"]);
+        assert_eq!(ids(&references.landscapes), ["landscape-1"]);
+        assert_eq!(ids(&references.textures), ["grass", "rock"]);
+    }
+
+    #[test]
+    fn failed_export_keeps_the_previous_one() {
+        let root = scratch_dir("failed");
+        let project_dir = root.join("project");
+        let export_dir = root.join("export");
+        fs::create_dir_all(&project_dir).unwrap();
+        fs::create_dir_all(&export_dir).unwrap();
+        fs::write(export_dir.join("manifest.json"), "previous").unwrap();
+
+        let saved_state = json!({
+            "levels": [{ "components": [{ "kind": "Model", "asset_id": "missing" }] }],
+            "models": []
+        });
+
+        assert!(export_project(&project_dir, &export_dir, "project", saved_state).is_err());
+        assert_eq!(
+            fs::read_to_string(export_dir.join("manifest.json")).unwrap(),
+            "previous"
+        );
+
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn export_replaces_the_previous_one() {
+        let root = scratch_dir("replace");
+        let project_dir = root.join("project");
+        let export_dir = root.join("export");
+        fs::create_dir_all(&project_dir).unwrap();
+        fs::create_dir_all(&export_dir).unwrap();
+        fs::write(export_dir.join("stale.txt"), "stale").unwrap();
+
+        let summary = export_project(
+            &project_dir,
+            &export_dir,
+            "project",
+            json!({ "levels": [] }),
+        )
+        .expect("Couldn't export an empty project");
+
+        assert!(summary.assets.is_empty());
+        assert!(export_dir.join("manifest.json").exists());
+        assert!(!export_dir.join("stale.txt").exists());
+        assert!(!sibling_dir(&export_dir, "staging").exists());
+        assert!(!sibling_dir(&export_dir, "previous").exists());
+
+        let manifest: Value =
+            serde_json::from_str(&fs::read_to_string(export_dir.join("manifest.json")).unwrap())
+                .unwrap();
+        assert_eq!(manifest["textureFormat"], TEXTURE_FORMAT);
+
+        fs::remove_dir_all(&root).unwrap();
+    }
+}
@@ -0,0 +1,497 @@
+// Procedural heightmap generation: seeded gradient noise, domain warping and erosion.
+// Everything here is driven by GeneratorParams alone, so a given seed always
+// produces the same terrain.
+
+use serde::Deserialize;
+
+#[derive(Deserialize, Clone, Copy, PartialEq, Debug)]
+pub enum NoiseKind {
+    Fbm,
+    Ridged,
+}
+
+#[derive(Deserialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct GeneratorParams {
+    pub seed: u32,
+    pub size: u32,
+    pub noise: NoiseKind,
+    pub octaves: u32,
+    pub frequency: f32,
+    pub lacunarity: f32,
+    pub persistence: f32,
+    pub warp_strength: f32,
+    pub thermal_iterations: u32,
+    pub hydraulic_iterations: u32,
+}
+
+impl Default for GeneratorParams {
+    fn default() -> Self {
+        Self {
+            seed: 1,
+            size: 512,
+            noise: NoiseKind::Fbm,
+            octaves: 6,
+            frequency: 2.0,
+            lacunarity: 2.0,
+            persistence: 0.5,
+            warp_strength: 0.3,
+            thermal_iterations: 10,
+            hydraulic_iterations: 20000,
+        }
+    }
+}
+
+pub struct Heightfield {
+    pub width: usize,
+    pub height: usize,
+    pub values: Vec<f32>, // row-major, normalized to 0.0 - 1.0
+}
+
+impl Heightfield {
+    fn get(&self, x: usize, y: usize) -> f32 {
+        self.values[y * self.width + x]
+    }
+
+    fn normalize(&mut self) {
+        let min = self.values.iter().cloned().fold(f32::MAX, f32::min);
+        let max = self.values.iter().cloned().fold(f32::MIN, f32::max);
+        let range = (max - min).max(f32::EPSILON);
+
+        for value in self.values.iter_mut() {
+            *value = (*value - min) / range;
+        }
+    }
+
+    // steepness at a cell as the largest height difference to a neighbour
+    pub fn slope(&self, x: usize, y: usize) -> f32 {
+        let center = self.get(x, y);
+        let mut slope: f32 = 0.0;
+
+        for (nx, ny) in neighbours(x, y, self.width, self.height) {
+            slope = slope.max((self.get(nx, ny) - center).abs());
+        }
+
+        slope
+    }
+}
+
+// small deterministic generator so results don't depend on an external RNG's algorithm. it's the
+// SplitMix64 from the editor's rng module, copied since the backend doesn't depend on that crate
+struct SplitMix {
+    state: u64,
+}
+
+impl SplitMix {
+    fn new(seed: u32) -> Self {
+        Self {
+            state: seed as u64 ^ 0x9E37_79B9_7F4A_7C15,
+        }
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    fn next_f32(&mut self) -> f32 {
+        (self.next_u64() >> 40) as f32 / (1u64 << 24) as f32
+    }
+}
+
+// classic 2D Perlin noise over a seeded permutation table
+pub struct Perlin {
+    permutation: [u8; 512],
+}
+
+impl Perlin {
+    pub fn new(seed: u32) -> Self {
+        let mut rng = SplitMix::new(seed);
+        let mut table: Vec<u8> = (0..=255).collect();
+
+        // Fisher-Yates shuffle
+        for i in (1..256).rev() {
+            let j = (rng.next_u64() % (i as u64 + 1)) as usize;
+            table.swap(i, j);
+        }
+
+        let mut permutation = [0u8; 512];
+        for i in 0..512 {
+            permutation[i] = table[i % 256];
+        }
+
+        Self { permutation }
+    }
+
+    fn gradient(hash: u8, x: f32, y: f32) -> f32 {
+        match hash & 7 {
+            0 => x + y,
+            1 => -x + y,
+            2 => x - y,
+            3 => -x - y,
+            4 => x,
+            5 => -x,
+            6 => y,
+            _ => -y,
+        }
+    }
+
+    // roughly -1.0 - 1.0
+    pub fn noise(&self, x: f32, y: f32) -> f32 {
+        let xi = (x.floor() as i32 & 255) as usize;
+        let yi = (y.floor() as i32 & 255) as usize;
+        let xf = x - x.floor();
+        let yf = y - y.floor();
+
+        let fade = |t: f32| t * t * t * (t * (t * 6.0 - 15.0) + 10.0);
+        let u = fade(xf);
+        let v = fade(yf);
+
+        let p = &self.permutation;
+        let aa = p[p[xi] as usize + yi];
+        let ab = p[p[xi] as usize + yi + 1];
+        let ba = p[p[xi + 1] as usize + yi];
+        let bb = p[p[xi + 1] as usize + yi + 1];
+
+        let x1 = lerp(
+            Self::gradient(aa, xf, yf),
+            Self::gradient(ba, xf - 1.0, yf),
+            u,
+        );
+        let x2 = lerp(
+            Self::gradient(ab, xf, yf - 1.0),
+            Self::gradient(bb, xf - 1.0, yf - 1.0),
+            u,
+        );
+
+        lerp(x1, x2, v)
+    }
+
+    pub fn fbm(&self, x: f32, y: f32, params: &GeneratorParams) -> f32 {
+        let mut total = 0.0;
+        let mut amplitude = 1.0;
+        let mut frequency = 1.0;
+        let mut max_amplitude = 0.0;
+
+        for _ in 0..params.octaves.max(1) {
+            total += self.noise(x * frequency, y * frequency) * amplitude;
+            max_amplitude += amplitude;
+            amplitude *= params.persistence;
+            frequency *= params.lacunarity;
+        }
+
+        total / max_amplitude
+    }
+
+    // sharp crests from inverted absolute noise, weighted by the previous octave
+    pub fn ridged(&self, x: f32, y: f32, params: &GeneratorParams) -> f32 {
+        let mut total = 0.0;
+        let mut amplitude = 1.0;
+        let mut frequency = 1.0;
+        let mut max_amplitude = 0.0;
+        let mut weight = 1.0;
+
+        for _ in 0..params.octaves.max(1) {
+            let mut signal = 1.0 - self.noise(x * frequency, y * frequency).abs();
+            signal *= signal * weight;
+            weight = signal.clamp(0.0, 1.0);
+
+            total += signal * amplitude;
+            max_amplitude += amplitude;
+            amplitude *= params.persistence;
+            frequency *= params.lacunarity;
+        }
+
+        total / max_amplitude
+    }
+}
+
+fn lerp(a: f32, b: f32, t: f32) -> f32 {
+    a + (b - a) * t
+}
+
+fn neighbours(x: usize, y: usize, width: usize, height: usize) -> Vec<(usize, usize)> {
+    let mut result = Vec::with_capacity(4);
+    if x > 0 {
+        result.push((x - 1, y));
+    }
+    if x + 1 < width {
+        result.push((x + 1, y));
+    }
+    if y > 0 {
+        result.push((x, y - 1));
+    }
+    if y + 1 < height {
+        result.push((x, y + 1));
+    }
+    result
+}
+
+pub fn generate_heightfield(params: &GeneratorParams) -> Heightfield {
+    let size = params.size.max(2) as usize;
+    let perlin = Perlin::new(params.seed);
+    // a second, differently seeded field drives the domain warp
+    let warp = Perlin::new(params.seed.wrapping_add(0x5bd1_e995));
+
+    let mut values = Vec::with_capacity(size * size);
+
+    for y in 0..size {
+        for x in 0..size {
+            let nx = x as f32 / size as f32 * params.frequency;
+            let ny = y as f32 / size as f32 * params.frequency;
+
+            let wx = nx + params.warp_strength * warp.fbm(nx + 5.2, ny + 1.3, params);
+            let wy = ny + params.warp_strength * warp.fbm(nx + 9.7, ny + 2.8, params);
+
+            let value = match params.noise {
+                NoiseKind::Fbm => perlin.fbm(wx, wy, params),
+                NoiseKind::Ridged => perlin.ridged(wx, wy, params),
+            };
+
+            values.push(value);
+        }
+    }
+
+    let mut field = Heightfield {
+        width: size,
+        height: size,
+        values,
+    };
+    field.normalize();
+
+    thermal_erosion(&mut field, params.thermal_iterations);
+    hydraulic_erosion(&mut field, params.hydraulic_iterations, params.seed);
+    field.normalize();
+
+    field
+}
+
+// material slides downhill wherever the slope exceeds the talus threshold
+pub fn thermal_erosion(field: &mut Heightfield, iterations: u32) {
+    let talus = 4.0 / field.width as f32;
+    let rate = 0.5;
+
+    for _ in 0..iterations {
+        let mut deltas = vec![0.0f32; field.values.len()];
+
+        for y in 0..field.height {
+            for x in 0..field.width {
+                let index = y * field.width + x;
+                let center = field.values[index];
+
+                let lower: Vec<(usize, f32)> = neighbours(x, y, field.width, field.height)
+                    .into_iter()
+                    .map(|(nx, ny)| {
+                        let n = ny * field.width + nx;
+                        (n, center - field.values[n])
+                    })
+                    .filter(|(_, difference)| *difference > talus)
+                    .collect();
+
+                let total: f32 = lower.iter().map(|(_, difference)| difference - talus).sum();
+                if total <= 0.0 {
+                    continue;
+                }
+
+                let moved = rate * total / 2.0;
+                deltas[index] -= moved;
+                for (n, difference) in lower {
+                    deltas[n] += moved * (difference - talus) / total;
+                }
+            }
+        }
+
+        for (value, delta) in field.values.iter_mut().zip(deltas) {
+            *value += delta;
+        }
+    }
+}
+
+fn gradient_at(field: &Heightfield, x: f32, y: f32) -> (f32, f32, f32) {
+    let xi = (x.floor() as usize).min(field.width - 2);
+    let yi = (y.floor() as usize).min(field.height - 2);
+    let u = x - xi as f32;
+    let v = y - yi as f32;
+
+    let h00 = field.get(xi, yi);
+    let h10 = field.get(xi + 1, yi);
+    let h01 = field.get(xi, yi + 1);
+    let h11 = field.get(xi + 1, yi + 1);
+
+    let gx = (h10 - h00) * (1.0 - v) + (h11 - h01) * v;
+    let gy = (h01 - h00) * (1.0 - u) + (h11 - h10) * u;
+    let height = h00 * (1.0 - u) * (1.0 - v) + h10 * u * (1.0 - v) + h01 * (1.0 - u) * v + h11 * u * v;
+
+    (gx, gy, height)
+}
+
+fn deposit(field: &mut Heightfield, x: f32, y: f32, amount: f32) {
+    let xi = (x.floor() as usize).min(field.width - 2);
+    let yi = (y.floor() as usize).min(field.height - 2);
+    let u = x - xi as f32;
+    let v = y - yi as f32;
+    let w = field.width;
+
+    field.values[yi * w + xi] += amount * (1.0 - u) * (1.0 - v);
+    field.values[yi * w + xi + 1] += amount * u * (1.0 - v);
+    field.values[(yi + 1) * w + xi] += amount * (1.0 - u) * v;
+    field.values[(yi + 1) * w + xi + 1] += amount * u * v;
+}
+
+// particle-based erosion: droplets pick up sediment going downhill and drop it as they slow
+pub fn hydraulic_erosion(field: &mut Heightfield, droplets: u32, seed: u32) {
+    if field.width < 3 || field.height < 3 {
+        return;
+    }
+
+    let mut rng = SplitMix::new(seed.wrapping_mul(31).wrapping_add(7));
+
+    let inertia = 0.05;
+    let capacity_factor = 4.0;
+    let min_capacity = 0.01;
+    let erode_rate = 0.3;
+    let deposit_rate = 0.3;
+    let evaporation = 0.01;
+    let gravity = 4.0;
+    let max_steps = 64;
+
+    let max_x = (field.width - 1) as f32;
+    let max_y = (field.height - 1) as f32;
+
+    for _ in 0..droplets {
+        let mut x = rng.next_f32() * (max_x - 1.0);
+        let mut y = rng.next_f32() * (max_y - 1.0);
+        let mut dir_x = 0.0;
+        let mut dir_y = 0.0;
+        let mut speed = 1.0;
+        let mut water = 1.0;
+        let mut sediment = 0.0;
+
+        for _ in 0..max_steps {
+            let (gx, gy, height) = gradient_at(field, x, y);
+
+            dir_x = dir_x * inertia - gx * (1.0 - inertia);
+            dir_y = dir_y * inertia - gy * (1.0 - inertia);
+            let length = (dir_x * dir_x + dir_y * dir_y).sqrt();
+            if length <= f32::EPSILON {
+                break;
+            }
+            dir_x /= length;
+            dir_y /= length;
+
+            let next_x = x + dir_x;
+            let next_y = y + dir_y;
+            if next_x < 0.0 || next_y < 0.0 || next_x >= max_x || next_y >= max_y {
+                break;
+            }
+
+            let (_, _, next_height) = gradient_at(field, next_x, next_y);
+            let delta = next_height - height;
+
+            let capacity = (-delta * speed * water * capacity_factor).max(min_capacity);
+
+            if sediment > capacity || delta > 0.0 {
+                // fill pits when going uphill, otherwise drop the excess
+                let amount = if delta > 0.0 {
+                    delta.min(sediment)
+                } else {
+                    (sediment - capacity) * deposit_rate
+                };
+                sediment -= amount;
+                deposit(field, x, y, amount);
+            } else {
+                let amount = ((capacity - sediment) * erode_rate).min(-delta);
+                sediment += amount;
+                deposit(field, x, y, -amount);
+            }
+
+            speed = (speed * speed + delta.abs() * gravity).sqrt();
+            water *= 1.0 - evaporation;
+            x = next_x;
+            y = next_y;
+        }
+    }
+}
+
+// rock on steep slopes, soil on flat lowlands, as 8-bit grayscale weights
+pub fn generate_masks(field: &Heightfield) -> (Vec<u8>, Vec<u8>) {
+    let mut rockmap = Vec::with_capacity(field.values.len());
+    let mut soil = Vec::with_capacity(field.values.len());
+
+    let steep = 6.0 / field.width as f32;
+
+    for y in 0..field.height {
+        for x in 0..field.width {
+            let rock = (field.slope(x, y) / steep).clamp(0.0, 1.0);
+            let lowland = 1.0 - field.get(x, y);
+            let soil_weight = ((1.0 - rock) * lowland).clamp(0.0, 1.0);
+
+            rockmap.push((rock * 255.0).round() as u8);
+            soil.push((soil_weight * 255.0).round() as u8);
+        }
+    }
+
+    (rockmap, soil)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // small enough to keep the erosion passes quick
+    fn small_params(seed: u32) -> GeneratorParams {
+        GeneratorParams {
+            seed,
+            size: 32,
+            thermal_iterations: 2,
+            hydraulic_iterations: 500,
+            ..GeneratorParams::default()
+        }
+    }
+
+    fn to_bytes(field: &Heightfield) -> Vec<u8> {
+        field.values.iter().flat_map(|value| value.to_le_bytes()).collect()
+    }
+
+    #[test]
+    fn same_seed_gives_identical_terrain() {
+        for noise in [NoiseKind::Fbm, NoiseKind::Ridged] {
+            let params = GeneratorParams {
+                noise,
+                ..small_params(42)
+            };
+
+            let first = generate_heightfield(&params);
+            let second = generate_heightfield(&params);
+
+            assert_eq!(to_bytes(&first), to_bytes(&second));
+            assert_eq!(generate_masks(&first), generate_masks(&second));
+        }
+    }
+
+    #[test]
+    fn different_seeds_give_different_terrain() {
+        let first = generate_heightfield(&small_params(1));
+        let second = generate_heightfield(&small_params(2));
+
+        assert_ne!(to_bytes(&first), to_bytes(&second));
+        assert_ne!(generate_masks(&first), generate_masks(&second));
+    }
+
+    #[test]
+    fn output_matches_the_requested_size() {
+        let params = small_params(7);
+        let field = generate_heightfield(&params);
+        let (rockmap, soil) = generate_masks(&field);
+
+        let cells = (params.size * params.size) as usize;
+        assert_eq!((field.width, field.height), (32, 32));
+        assert_eq!(field.values.len(), cells);
+        assert_eq!(rockmap.len(), cells);
+        assert_eq!(soil.len(), cells);
+        assert!(field.values.iter().all(|value| (0.0..=1.0).contains(value)));
+    }
+}
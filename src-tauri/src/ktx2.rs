@@ -0,0 +1,333 @@
+// Block compressed textures for exports, in a KTX2 container with a single mip level.
+// Material textures are BC1 (opaque srgb color), landscape masks are BC4 since the shader only
+// reads their red channel. Both use 8 bytes per 4x4 block, a quarter or an eighth of RGBA8.
+
+use image::{GrayImage, RgbaImage};
+
+pub const KTX2_IDENTIFIER: [u8; 12] = [
+    0xab, 0x4b, 0x54, 0x58, 0x20, 0x32, 0x30, 0xbb, 0x0d, 0x0a, 0x1a, 0x0a,
+];
+
+// vulkan format numbers, which is how KTX2 names its formats
+pub const VK_FORMAT_BC1_RGB_SRGB_BLOCK: u32 = 132;
+pub const VK_FORMAT_BC4_UNORM_BLOCK: u32 = 139;
+
+const BLOCK_BYTES: usize = 8;
+
+// data format descriptor values for the two block formats
+const KHR_DF_MODEL_BC1A: u8 = 128;
+const KHR_DF_MODEL_BC4: u8 = 131;
+const KHR_DF_PRIMARIES_BT709: u8 = 1;
+const KHR_DF_TRANSFER_LINEAR: u8 = 1;
+const KHR_DF_TRANSFER_SRGB: u8 = 2;
+
+// 5:6:5 color, expanded the way decoders do so endpoints are matched against what's shown
+fn pack_565(color: [f32; 3]) -> u16 {
+    let r = (color[0].clamp(0.0, 255.0) * 31.0 / 255.0).round() as u16;
+    let g = (color[1].clamp(0.0, 255.0) * 63.0 / 255.0).round() as u16;
+    let b = (color[2].clamp(0.0, 255.0) * 31.0 / 255.0).round() as u16;
+    (r << 11) | (g << 5) | b
+}
+
+fn unpack_565(color: u16) -> [f32; 3] {
+    let r = (color >> 11) & 0x1f;
+    let g = (color >> 5) & 0x3f;
+    let b = color & 0x1f;
+    [
+        ((r << 3) | (r >> 2)) as f32,
+        ((g << 2) | (g >> 4)) as f32,
+        ((b << 3) | (b >> 2)) as f32,
+    ]
+}
+
+fn distance_squared(a: [f32; 3], b: [f32; 3]) -> f32 {
+    (0..3).map(|i| (a[i] - b[i]) * (a[i] - b[i])).sum()
+}
+
+// the 16 pixels of the block at bx, by, repeating the last row and column past the edges
+fn block_pixels<P: Copy>(
+    width: u32,
+    height: u32,
+    bx: u32,
+    by: u32,
+    pixel: impl Fn(u32, u32) -> P,
+) -> [P; 16] {
+    std::array::from_fn(|i| {
+        let x = (bx * 4 + i as u32 % 4).min(width - 1);
+        let y = (by * 4 + i as u32 / 4).min(height - 1);
+        pixel(x, y)
+    })
+}
+
+// endpoints are the extremes along the block's main color axis, found by power iteration
+fn encode_bc1_block(pixels: &[[f32; 3]; 16]) -> [u8; BLOCK_BYTES] {
+    let mean = [0, 1, 2].map(|c| pixels.iter().map(|p| p[c]).sum::<f32>() / 16.0);
+
+    let mut covariance = [[0.0f32; 3]; 3];
+    for pixel in pixels {
+        let d = [0, 1, 2].map(|c| pixel[c] - mean[c]);
+        for (row, value) in covariance.iter_mut().enumerate() {
+            for (column, entry) in value.iter_mut().enumerate() {
+                *entry += d[row] * d[column];
+            }
+        }
+    }
+
+    let mut axis = [1.0f32, 1.0, 1.0];
+    for _ in 0..8 {
+        let next = [0, 1, 2].map(|row| (0..3).map(|c| covariance[row][c] * axis[c]).sum::<f32>());
+        let length = next.iter().map(|v| v * v).sum::<f32>().sqrt();
+        if length < f32::EPSILON {
+            break;
+        }
+        axis = next.map(|v| v / length);
+    }
+
+    let project = |p: &[f32; 3]| (0..3).map(|c| (p[c] - mean[c]) * axis[c]).sum::<f32>();
+    let (mut low, mut high) = (f32::MAX, f32::MIN);
+    for pixel in pixels {
+        low = low.min(project(pixel));
+        high = high.max(project(pixel));
+    }
+
+    let mut color0 = pack_565([0, 1, 2].map(|c| mean[c] + axis[c] * high));
+    let mut color1 = pack_565([0, 1, 2].map(|c| mean[c] + axis[c] * low));
+
+    // the first endpoint has to be the larger one for four color mode
+    if color0 < color1 {
+        std::mem::swap(&mut color0, &mut color1);
+    }
+
+    let mut indices = 0u32;
+    if color0 != color1 {
+        let (c0, c1) = (unpack_565(color0), unpack_565(color1));
+        let palette = [
+            c0,
+            c1,
+            [0, 1, 2].map(|c| (2.0 * c0[c] + c1[c]) / 3.0),
+            [0, 1, 2].map(|c| (c0[c] + 2.0 * c1[c]) / 3.0),
+        ];
+
+        for (i, pixel) in pixels.iter().enumerate() {
+            let index = (0..4)
+                .min_by(|a, b| {
+                    distance_squared(*pixel, palette[*a])
+                        .total_cmp(&distance_squared(*pixel, palette[*b]))
+                })
+                .unwrap_or(0);
+            indices |= (index as u32) << (i * 2);
+        }
+    }
+
+    let mut block = [0u8; BLOCK_BYTES];
+    block[0..2].copy_from_slice(&color0.to_le_bytes());
+    block[2..4].copy_from_slice(&color1.to_le_bytes());
+    block[4..8].copy_from_slice(&indices.to_le_bytes());
+    block
+}
+
+// eight value mode between the block's extremes
+fn encode_bc4_block(values: &[u8; 16]) -> [u8; BLOCK_BYTES] {
+    let red0 = *values.iter().max().unwrap_or(&0);
+    let red1 = *values.iter().min().unwrap_or(&0);
+
+    let mut indices = 0u64;
+    if red0 != red1 {
+        let palette: [f32; 8] = std::array::from_fn(|i| match i {
+            0 => red0 as f32,
+            1 => red1 as f32,
+            _ => ((8 - i) as f32 * red0 as f32 + (i - 1) as f32 * red1 as f32) / 7.0,
+        });
+
+        for (i, value) in values.iter().enumerate() {
+            let index = (0..8)
+                .min_by(|a, b| {
+                    (*value as f32 - palette[*a])
+                        .abs()
+                        .total_cmp(&(*value as f32 - palette[*b]).abs())
+                })
+                .unwrap_or(0);
+            indices |= (index as u64) << (i * 3);
+        }
+    }
+
+    let mut block = [0u8; BLOCK_BYTES];
+    block[0] = red0;
+    block[1] = red1;
+    block[2..8].copy_from_slice(&indices.to_le_bytes()[0..6]);
+    block
+}
+
+fn blocks(width: u32, height: u32, mut encode: impl FnMut(u32, u32) -> [u8; 8]) -> Vec<u8> {
+    let (blocks_x, blocks_y) = (width.div_ceil(4), height.div_ceil(4));
+    let mut data = Vec::with_capacity((blocks_x * blocks_y) as usize * BLOCK_BYTES);
+
+    for by in 0..blocks_y {
+        for bx in 0..blocks_x {
+            data.extend_from_slice(&encode(bx, by));
+        }
+    }
+
+    data
+}
+
+// alpha is dropped, exported textures are all opaque materials
+pub fn encode_bc1(image: &RgbaImage) -> Vec<u8> {
+    let (width, height) = image.dimensions();
+
+    blocks(width, height, |bx, by| {
+        let pixels = block_pixels(width, height, bx, by, |x, y| {
+            let pixel = image.get_pixel(x, y);
+            [pixel[0] as f32, pixel[1] as f32, pixel[2] as f32]
+        });
+        encode_bc1_block(&pixels)
+    })
+}
+
+pub fn encode_bc4(image: &GrayImage) -> Vec<u8> {
+    let (width, height) = image.dimensions();
+
+    blocks(width, height, |bx, by| {
+        let values = block_pixels(width, height, bx, by, |x, y| image.get_pixel(x, y)[0]);
+        encode_bc4_block(&values)
+    })
+}
+
+// the basic data format descriptor KTX2 requires, one 64 bit sample per block
+fn data_format_descriptor(model: u8, transfer: u8) -> Vec<u8> {
+    let block_size: u16 = 24 + 16;
+
+    let mut dfd = Vec::with_capacity(4 + block_size as usize);
+    dfd.extend_from_slice(&(4 + block_size as u32).to_le_bytes());
+    dfd.extend_from_slice(&0u32.to_le_bytes()); // khronos vendor, basic descriptor type
+    dfd.extend_from_slice(&2u16.to_le_bytes()); // version
+    dfd.extend_from_slice(&block_size.to_le_bytes());
+    dfd.extend_from_slice(&[model, KHR_DF_PRIMARIES_BT709, transfer, 0]);
+    dfd.extend_from_slice(&[3, 3, 0, 0]); // 4x4 blocks
+    dfd.extend_from_slice(&[BLOCK_BYTES as u8, 0, 0, 0, 0, 0, 0, 0]);
+    dfd.extend_from_slice(&0u16.to_le_bytes()); // bit offset
+    dfd.extend_from_slice(&[63, 0]); // bit length - 1, channel 0 (the block's color or data)
+    dfd.extend_from_slice(&[0, 0, 0, 0]); // sample position
+    dfd.extend_from_slice(&0u32.to_le_bytes());
+    dfd.extend_from_slice(&u32::MAX.to_le_bytes());
+    dfd
+}
+
+fn ktx2(vk_format: u32, dfd: Vec<u8>, width: u32, height: u32, level: Vec<u8>) -> Vec<u8> {
+    const HEADER_BYTES: usize = 12 + 9 * 4 + 4 * 4 + 2 * 8;
+    const LEVEL_INDEX_BYTES: usize = 3 * 8;
+
+    let dfd_offset = HEADER_BYTES + LEVEL_INDEX_BYTES;
+    // level data is aligned to the block size
+    let level_offset = (dfd_offset + dfd.len()).next_multiple_of(BLOCK_BYTES);
+
+    let mut data = Vec::with_capacity(level_offset + level.len());
+    data.extend_from_slice(&KTX2_IDENTIFIER);
+    for value in [
+        vk_format, 1, // type size
+        width, height, 0, // depth
+        0, // layers
+        1, // faces
+        1, // levels
+        0, // no supercompression
+    ] {
+        data.extend_from_slice(&value.to_le_bytes());
+    }
+
+    data.extend_from_slice(&(dfd_offset as u32).to_le_bytes());
+    data.extend_from_slice(&(dfd.len() as u32).to_le_bytes());
+    data.extend_from_slice(&0u32.to_le_bytes()); // no key/value data
+    data.extend_from_slice(&0u32.to_le_bytes());
+    data.extend_from_slice(&0u64.to_le_bytes()); // no supercompression data
+    data.extend_from_slice(&0u64.to_le_bytes());
+
+    data.extend_from_slice(&(level_offset as u64).to_le_bytes());
+    data.extend_from_slice(&(level.len() as u64).to_le_bytes());
+    data.extend_from_slice(&(level.len() as u64).to_le_bytes());
+
+    data.extend_from_slice(&dfd);
+    data.resize(level_offset, 0);
+    data.extend_from_slice(&level);
+    data
+}
+
+pub fn encode_texture(image: &RgbaImage) -> Vec<u8> {
+    let (width, height) = image.dimensions();
+
+    ktx2(
+        VK_FORMAT_BC1_RGB_SRGB_BLOCK,
+        data_format_descriptor(KHR_DF_MODEL_BC1A, KHR_DF_TRANSFER_SRGB),
+        width,
+        height,
+        encode_bc1(image),
+    )
+}
+
+pub fn encode_mask(image: &GrayImage) -> Vec<u8> {
+    let (width, height) = image.dimensions();
+
+    ktx2(
+        VK_FORMAT_BC4_UNORM_BLOCK,
+        data_format_descriptor(KHR_DF_MODEL_BC4, KHR_DF_TRANSFER_LINEAR),
+        width,
+        height,
+        encode_bc4(image),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // the runtime's reader is tested against the same files, which pins the format on both sides
+    const SAMPLE_TEXTURE: &[u8] = include_bytes!("../tests/fixtures/sample-texture.ktx2");
+    const SAMPLE_MASK: &[u8] = include_bytes!("../tests/fixtures/sample-mask.ktx2");
+
+    // 6x5 so the second column and row of blocks are only partly covered. BC1 can only
+    // represent colors along a line within a block, so the texture is a single gradient
+    fn sample_texture() -> RgbaImage {
+        RgbaImage::from_fn(6, 5, |x, y| {
+            let t = x + y * 6;
+            image::Rgba([(t * 8) as u8, (255 - t * 6) as u8, (40 + t * 4) as u8, 255])
+        })
+    }
+
+    fn sample_mask() -> GrayImage {
+        GrayImage::from_fn(6, 5, |x, y| image::Luma([(x * 30 + y * 20) as u8]))
+    }
+
+    #[test]
+    fn samples_match_the_runtime_fixtures() {
+        assert_eq!(encode_texture(&sample_texture()), SAMPLE_TEXTURE);
+        assert_eq!(encode_mask(&sample_mask()), SAMPLE_MASK);
+    }
+
+    #[test]
+    fn headers_describe_the_image() {
+        let data = encode_texture(&sample_texture());
+        let word = |index: usize| u32::from_le_bytes(data[index..index + 4].try_into().unwrap());
+
+        assert_eq!(data[0..12], KTX2_IDENTIFIER);
+        assert_eq!(word(12), VK_FORMAT_BC1_RGB_SRGB_BLOCK);
+        assert_eq!((word(20), word(24)), (6, 5));
+
+        // two by two blocks, after the aligned header
+        let level_offset = u64::from_le_bytes(data[80..88].try_into().unwrap()) as usize;
+        assert_eq!(level_offset % BLOCK_BYTES, 0);
+        assert_eq!(data.len() - level_offset, 4 * BLOCK_BYTES);
+    }
+
+    #[test]
+    fn flat_blocks_are_exact() {
+        let gray = GrayImage::from_pixel(4, 4, image::Luma([77]));
+        assert_eq!(encode_bc4(&gray), [77, 77, 0, 0, 0, 0, 0, 0]);
+
+        // pure red survives 5:6:5
+        let red = RgbaImage::from_pixel(4, 4, image::Rgba([255, 0, 0, 255]));
+        let block = encode_bc1(&red);
+        assert_eq!(
+            unpack_565(u16::from_le_bytes([block[0], block[1]])),
+            [255.0, 0.0, 0.0]
+        );
+    }
+}
@@ -1,22 +1,44 @@
 // Prevents additional console window on Windows in release, DO NOT REMOVE!!
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
 
-use base64::decode;
+mod export;
+mod generator;
+mod ktx2;
+mod metadata;
+mod texture;
+mod thumbnail;
+
+use base64::{decode, encode};
+use chrono::Utc;
 use image::io::Reader as ImageReader;
 use image::GenericImageView;
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 use std::convert::TryFrom;
 use std::fs;
 use std::fs::File;
-use std::io::Read;
+use std::io::{Cursor, Read};
 use std::path::Path;
 use std::path::PathBuf;
 use std::sync::Arc;
+use std::time::SystemTime;
 use tauri::api::path::{app_data_dir, resolve_path, BaseDirectory};
 use tauri::{App, AppHandle, Manager};
 use tiff::decoder::{Decoder, DecodingResult};
+use tiff::encoder::{colortype, TiffEncoder};
+use tiff::tags::Tag;
 use uuid::Uuid;
 
+use export::ExportSummary;
+use generator::{generate_heightfield, generate_masks, GeneratorParams};
+use metadata::{
+    asset_path, children_of, measure, read_sidecar, write_sidecar, AssetMetadata, AssetRef,
+};
+use texture::{make_tileable, normal_map, roughness_map, TextureProcessOptions};
+use thumbnail::{
+    invalidate_thumbnail, render_heightmap_thumbnail, render_model_thumbnail, thumbnail_path,
+    THUMBNAIL_SIZE,
+};
+
 struct AppState {
     handle: AppHandle,
 }
@@ -70,6 +92,190 @@ fn create_project(state: tauri::State<'_, AppState>, projectId: String) -> Strin
     "success".to_string()
 }
 
+// offline projects keep their saved state in project.json, the same file exports and the
+// runtime read, with the listing details alongside in project.meta.json
+#[derive(Serialize, Deserialize)]
+struct LocalProjectMeta {
+    id: String,
+    title: String,
+    createdAt: String,
+    updatedAt: String,
+}
+
+#[derive(Serialize)]
+struct LocalProject {
+    id: String,
+    title: String,
+    context: serde_json::Value,
+    createdAt: String,
+    updatedAt: String,
+}
+
+fn projects_dir() -> PathBuf {
+    let sync_dir = PathBuf::from("C:/Users/alext/CommonOSFiles");
+    sync_dir.join("midpoint/projects")
+}
+
+// ids come from the webview, so anything that isn't a uuid is refused before it reaches a path
+fn local_project_dir(project_id: &str) -> Result<PathBuf, String> {
+    let id =
+        Uuid::parse_str(project_id).map_err(|_| format!("Invalid project id: {}", project_id))?;
+
+    Ok(projects_dir().join(id.to_string()))
+}
+
+fn read_project_meta(project_dir: &Path) -> Result<LocalProjectMeta, String> {
+    let meta = fs::read_to_string(project_dir.join("project.meta.json"))
+        .map_err(|e| format!("Couldn't read project metadata: {}", e))?;
+
+    serde_json::from_str(&meta).map_err(|e| format!("Invalid project metadata: {}", e))
+}
+
+fn write_project_files(
+    project_dir: &Path,
+    meta: &LocalProjectMeta,
+    context: &serde_json::Value,
+) -> Result<(), String> {
+    let meta = serde_json::to_string_pretty(meta)
+        .map_err(|e| format!("Couldn't serialize project metadata: {}", e))?;
+    let context = serde_json::to_string_pretty(context)
+        .map_err(|e| format!("Couldn't serialize project context: {}", e))?;
+
+    fs::write(project_dir.join("project.meta.json"), meta)
+        .map_err(|e| format!("Couldn't write project metadata: {}", e))?;
+    fs::write(project_dir.join("project.json"), context)
+        .map_err(|e| format!("Couldn't write project context: {}", e))?;
+
+    Ok(())
+}
+
+#[tauri::command]
+fn list_local_projects(state: tauri::State<'_, AppState>) -> Result<Vec<LocalProjectMeta>, String> {
+    let handle = &state.handle;
+    let config = handle.config();
+    let package_info = handle.package_info();
+    let env = handle.env();
+
+    let projects_dir = projects_dir();
+    if !projects_dir.exists() {
+        return Ok(Vec::new());
+    }
+
+    let entries =
+        fs::read_dir(&projects_dir).map_err(|e| format!("Couldn't list projects: {}", e))?;
+
+    // folders without metadata belong to cloud projects and are left out
+    let mut projects: Vec<LocalProjectMeta> = entries
+        .filter_map(|entry| entry.ok())
+        .filter_map(|entry| read_project_meta(&entry.path()).ok())
+        .collect();
+
+    projects.sort_by(|a, b| b.updatedAt.cmp(&a.updatedAt));
+
+    Ok(projects)
+}
+
+#[tauri::command]
+fn read_local_project(
+    state: tauri::State<'_, AppState>,
+    projectId: String,
+) -> Result<LocalProject, String> {
+    let handle = &state.handle;
+    let config = handle.config();
+    let package_info = handle.package_info();
+    let env = handle.env();
+
+    let project_dir = local_project_dir(&projectId)?;
+    let meta = read_project_meta(&project_dir)?;
+
+    let context = fs::read_to_string(project_dir.join("project.json"))
+        .map_err(|e| format!("Couldn't read project context: {}", e))?;
+    let context =
+        serde_json::from_str(&context).map_err(|e| format!("Invalid project context: {}", e))?;
+
+    Ok(LocalProject {
+        id: meta.id,
+        title: meta.title,
+        context,
+        createdAt: meta.createdAt,
+        updatedAt: meta.updatedAt,
+    })
+}
+
+// the frontend passes its default context so this side doesn't need to know the saved state
+#[tauri::command]
+fn create_local_project(
+    state: tauri::State<'_, AppState>,
+    title: String,
+    context: serde_json::Value,
+) -> Result<String, String> {
+    let handle = &state.handle;
+    let config = handle.config();
+    let package_info = handle.package_info();
+    let env = handle.env();
+
+    let project_id = Uuid::new_v4().to_string();
+    let project_dir = projects_dir().join(&project_id);
+
+    fs::create_dir_all(&project_dir)
+        .map_err(|e| format!("Couldn't create project directory: {}", e))?;
+
+    let now = Utc::now().to_rfc3339();
+    let meta = LocalProjectMeta {
+        id: project_id.clone(),
+        title,
+        createdAt: now.clone(),
+        updatedAt: now,
+    };
+
+    write_project_files(&project_dir, &meta, &context)?;
+
+    Ok(project_id)
+}
+
+#[tauri::command]
+fn save_local_project(
+    state: tauri::State<'_, AppState>,
+    projectId: String,
+    title: Option<String>,
+    context: serde_json::Value,
+) -> Result<String, String> {
+    let handle = &state.handle;
+    let config = handle.config();
+    let package_info = handle.package_info();
+    let env = handle.env();
+
+    let project_dir = local_project_dir(&projectId)?;
+    let meta = read_project_meta(&project_dir)?;
+
+    let meta = LocalProjectMeta {
+        title: title.unwrap_or(meta.title),
+        updatedAt: Utc::now().to_rfc3339(),
+        ..meta
+    };
+
+    write_project_files(&project_dir, &meta, &context)?;
+
+    Ok("success".to_string())
+}
+
+// only drops the project from the list, its assets stay on disk like they do for cloud projects
+#[tauri::command]
+fn delete_local_project(
+    state: tauri::State<'_, AppState>,
+    projectId: String,
+) -> Result<String, String> {
+    let handle = &state.handle;
+    let config = handle.config();
+    let package_info = handle.package_info();
+    let env = handle.env();
+
+    let meta_path = local_project_dir(&projectId)?.join("project.meta.json");
+    fs::remove_file(meta_path).map_err(|e| format!("Couldn't delete project: {}", e))?;
+
+    Ok("success".to_string())
+}
+
 #[derive(Serialize)]
 struct LandscapeData {
     width: usize,
@@ -130,26 +336,66 @@ struct PixelData {
 //     (width, height, pixel_data)
 // }
 
+// the editor's landscape size, heights are scaled to LANDSCAPE_HEIGHT once normalized
+const LANDSCAPE_WIDTH: f32 = 2048.0;
+const LANDSCAPE_LENGTH: f32 = 2048.0;
+const LANDSCAPE_HEIGHT: f32 = 250.0;
+
+// ImageDescription of heightmaps saved after sculpting. Their values are already a fraction of
+// LANDSCAPE_HEIGHT, and may go past 0..1, so loading them must not stretch them min to max again
+const NORMALIZED_HEIGHTS_DESCRIPTION: &str = "midpoint:normalized-heights";
+
+// heights as a fraction of the landscape height. Imported heightmaps are stretched to fill 0..1,
+// sculpted ones are read as saved
+fn read_normalized_heights(path: &Path) -> Result<(usize, usize, Vec<f32>), String> {
+    let file = File::open(path).map_err(|e| format!("Couldn't open tif file: {}", e))?;
+    let mut decoder = Decoder::new(file).map_err(|e| format!("Couldn't decode tif file: {}", e))?;
+
+    let (width, height) = decoder
+        .dimensions()
+        .map_err(|e| format!("Couldn't get tif dimensions: {}", e))?;
+    let pre_normalized = decoder
+        .get_tag_ascii_string(Tag::ImageDescription)
+        .map_or(false, |description| {
+            description == NORMALIZED_HEIGHTS_DESCRIPTION
+        });
+
+    let values = match decoder
+        .read_image()
+        .map_err(|e| format!("Couldn't read image data from tif: {}", e))?
+    {
+        DecodingResult::F32(values) => values,
+        _ => return Err("Heightmap isn't a 32-bit float tif".to_string()),
+    };
+
+    if pre_normalized {
+        return Ok((width as usize, height as usize, values));
+    }
+
+    let min_height = values.iter().cloned().fold(f32::MAX, f32::min);
+    let max_height = values.iter().cloned().fold(f32::MIN, f32::max);
+    let height_range = (max_height - min_height).max(f32::EPSILON);
+
+    let values = values
+        .iter()
+        .map(|value| (value - min_height) / height_range)
+        .collect();
+
+    Ok((width as usize, height as usize, values))
+}
+
 fn read_tiff_heightmap(
-    landscape_path: &str,
+    landscape_path: &Path,
     target_width: f32,
     target_length: f32,
     target_height: f32,
 ) -> (usize, usize, Vec<Vec<PixelData>>) {
-    let file = File::open(landscape_path).expect("Couldn't open tif file");
-    let mut decoder = Decoder::new(file).expect("Couldn't decode tif file");
-
-    let (width, height) = decoder.dimensions().expect("Couldn't get tif dimensions");
-
-    let width = usize::try_from(width).unwrap();
-    let height = usize::try_from(height).unwrap();
-
-    let image = match decoder
-        .read_image()
-        .expect("Couldn't read image data from tif")
-    {
-        DecodingResult::F32(vec) => vec,
-        _ => return (0, 0, Vec::new()),
+    let (width, height, image) = match read_normalized_heights(landscape_path) {
+        Ok(heights) => heights,
+        Err(e) => {
+            println!("Couldn't read heightmap {:?}: {}", landscape_path, e);
+            return (0, 0, Vec::new());
+        }
     };
 
     let mut pixel_data = Vec::new();
@@ -159,22 +405,11 @@ fn read_tiff_heightmap(
     let y_scale = target_length / height as f32;
     let z_scale = target_height;
 
-    let min_height = *image
-        .iter()
-        .min_by(|a, b| a.partial_cmp(b).unwrap())
-        .unwrap();
-    let max_height = *image
-        .iter()
-        .max_by(|a, b| a.partial_cmp(b).unwrap())
-        .unwrap();
-    let height_range = max_height - min_height;
-
     for y in 0..height {
         let mut row = Vec::new();
         for x in 0..width {
             let idx = (y * width + x) as usize;
-            let normalized_height = (image[idx] - min_height) / height_range;
-            let height_value = normalized_height * z_scale;
+            let height_value = image[idx] * z_scale;
 
             let position = [
                 x as f32 * x_scale - target_width / 2.0,
@@ -217,12 +452,10 @@ fn get_landscape_pixels(
     println!("landscape_path {:?}", landscape_path);
 
     let (width, height, pixel_data) = read_tiff_heightmap(
-        landscape_path
-            .to_str()
-            .expect("Couldn't form landscape string"),
-        2048.0,
-        2048.0,
-        250.0,
+        &landscape_path,
+        LANDSCAPE_WIDTH,
+        LANDSCAPE_LENGTH,
+        LANDSCAPE_HEIGHT,
     );
 
     LandscapeData {
@@ -426,6 +659,50 @@ async fn read_landscape_texture(
     })
 }
 
+fn landscape_mask_path(
+    sync_dir: &PathBuf,
+    project_id: &str,
+    landscape_id: &str,
+    mask_kind: &str,
+    mask_filename: &str,
+) -> PathBuf {
+    let kind_slug = match mask_kind {
+        "Primary" => "heightmaps",
+        "Rockmap" => "rockmaps",
+        "Soil" => "soils",
+        _ => "",
+    };
+
+    sync_dir.join(format!(
+        "midpoint/projects/{}/landscapes/{}/{}/{}",
+        project_id, landscape_id, kind_slug, mask_filename
+    ))
+}
+
+// the primary mask is read from the heightmap itself, so painted copies are
+// kept in their own folder rather than overwriting the TIFF
+fn painted_mask_path(
+    sync_dir: &PathBuf,
+    project_id: &str,
+    landscape_id: &str,
+    mask_kind: &str,
+    mask_filename: &str,
+) -> Option<PathBuf> {
+    if mask_kind != "Primary" {
+        return None;
+    }
+
+    let mut filename = PathBuf::from(mask_filename);
+    filename.set_extension("png");
+
+    Some(sync_dir.join(format!(
+        "midpoint/projects/{}/landscapes/{}/primarymasks/{}",
+        project_id,
+        landscape_id,
+        filename.to_string_lossy()
+    )))
+}
+
 #[tauri::command]
 async fn read_landscape_mask(
     state: tauri::State<'_, AppState>,
@@ -439,18 +716,14 @@ async fn read_landscape_mask(
     let package_info = handle.package_info();
     let env = handle.env();
 
-    let kind_slug = match maskKind.as_str() {
-        "Primary" => "heightmaps",
-        "Rockmap" => "rockmaps",
-        "Soil" => "soils",
-        _ => "",
-    };
-
     let sync_dir = PathBuf::from("C:/Users/alext/CommonOSFiles");
-    let mask_path = sync_dir.join(format!(
-        "midpoint/projects/{}/landscapes/{}/{}/{}",
-        projectId, landscapeId, kind_slug, maskFilename
-    ));
+
+    // prefer a painted primary mask over the heightmap it was derived from
+    let painted_path = painted_mask_path(&sync_dir, &projectId, &landscapeId, &maskKind, &maskFilename);
+    let mask_path = match painted_path {
+        Some(painted_path) if painted_path.exists() => painted_path,
+        _ => landscape_mask_path(&sync_dir, &projectId, &landscapeId, &maskKind, &maskFilename),
+    };
 
     println!("mask_path {:?}", mask_path);
 
@@ -472,6 +745,50 @@ async fn read_landscape_mask(
     })
 }
 
+#[tauri::command]
+async fn save_landscape_mask(
+    state: tauri::State<'_, AppState>,
+    projectId: String,
+    landscapeId: String,
+    maskFilename: String,
+    maskKind: String,
+    maskBase64: String,
+) -> Result<String, String> {
+    let handle = &state.handle;
+    let config = handle.config();
+    let package_info = handle.package_info();
+    let env = handle.env();
+
+    let sync_dir = PathBuf::from("C:/Users/alext/CommonOSFiles");
+    let mask_path = painted_mask_path(&sync_dir, &projectId, &landscapeId, &maskKind, &maskFilename)
+        .unwrap_or_else(|| {
+            landscape_mask_path(&sync_dir, &projectId, &landscapeId, &maskKind, &maskFilename)
+        });
+
+    println!("mask_path {:?}", mask_path);
+
+    if let Some(mask_dir) = mask_path.parent() {
+        if !mask_dir.exists() {
+            fs::create_dir_all(mask_dir).map_err(|e| format!("Couldn't create mask directory: {}", e))?;
+        }
+    }
+
+    let base64_data = maskBase64
+        .strip_prefix("data:image/png;base64,")
+        .ok_or("Invalid base64 mask string")?;
+
+    let mask_data =
+        decode(base64_data).map_err(|e| format!("Couldn't decode base64 string for mask: {}", e))?;
+
+    // make sure it's a readable image before replacing the mask on disk
+    image::load_from_memory_with_format(&mask_data, image::ImageFormat::Png)
+        .map_err(|e| format!("Invalid landscape mask: {}", e))?;
+
+    fs::write(&mask_path, mask_data).map_err(|e| format!("Couldn't save landscape mask: {}", e))?;
+
+    Ok("success".to_string())
+}
+
 #[tauri::command]
 fn save_landscape(
     state: tauri::State<'_, AppState>,
@@ -488,20 +805,124 @@ fn save_landscape(
     let package_info = handle.package_info();
     let env = handle.env();
 
+    // prefix is pre-stripped on frontend
+    let base64_data = landscapeBase64;
+    let heightmap_data = decode(base64_data)
+        .map_err(|e| format!("Couldn't decode base64 string for heightmap: {}", e))
+        .expect("Couldn't decode base64 string for heightmap");
+
+    let base64_data = rockmapBase64;
+    let rockmap_data = decode(base64_data)
+        .map_err(|e| format!("Couldn't decode base64 string for rockmap: {}", e))
+        .expect("Couldn't decode base64 string for rockmap");
+
+    let base64_data = soilBase64;
+    let soil_data = decode(base64_data)
+        .map_err(|e| format!("Couldn't decode base64 string for soil: {}", e))
+        .expect("Couldn't decode base64 string for soil");
+
+    write_landscape_files(
+        &projectId,
+        &landscapeFilename,
+        heightmap_data,
+        &rockmapFilename,
+        rockmap_data,
+        &soilFilename,
+        soil_data,
+    );
+
+    "success".to_string()
+}
+
+#[tauri::command]
+fn save_landscape_heightmap(
+    state: tauri::State<'_, AppState>,
+    projectId: String,
+    landscapeAssetId: String,
+    landscapeFilename: String,
+    width: u32,
+    height: u32,
+    heights: Vec<f32>,
+) -> Result<String, String> {
+    let handle = &state.handle;
+    let config = handle.config();
+    let package_info = handle.package_info();
+    let env = handle.env();
+
+    if heights.len() != (width as usize) * (height as usize) {
+        return Err(format!(
+            "Heightmap has {} values, expected {}x{}",
+            heights.len(),
+            width,
+            height
+        ));
+    }
+
+    let sync_dir = PathBuf::from("C:/Users/alext/CommonOSFiles");
+    let heightmaps_dir = sync_dir.join(format!(
+        "midpoint/projects/{}/landscapes/{}/heightmaps",
+        projectId, landscapeAssetId
+    ));
+
+    if !Path::new(&heightmaps_dir).exists() {
+        fs::create_dir_all(&heightmaps_dir).expect("Couldn't create heightmaps directory");
+    }
+
+    let heightmap_path = heightmaps_dir.join(landscapeFilename);
+
+    println!("heightmap_path {:?}", heightmap_path);
+
+    // the vertex heights are in world units, saving them as a fraction of the landscape height
+    // and tagging the file keeps reloads from rescaling a sculpt that went past the old range
+    let normalized: Vec<f32> = heights
+        .iter()
+        .map(|height| height / LANDSCAPE_HEIGHT)
+        .collect();
+
+    let file = File::create(&heightmap_path)
+        .map_err(|e| format!("Couldn't create heightmap file: {}", e))?;
+    let mut encoder =
+        TiffEncoder::new(file).map_err(|e| format!("Couldn't create tif encoder: {}", e))?;
+    let mut image = encoder
+        .new_image::<colortype::Gray32Float>(width, height)
+        .map_err(|e| format!("Couldn't create heightmap tif: {}", e))?;
+    image
+        .encoder()
+        .write_tag(Tag::ImageDescription, NORMALIZED_HEIGHTS_DESCRIPTION)
+        .map_err(|e| format!("Couldn't tag heightmap tif: {}", e))?;
+    image
+        .write_data(&normalized)
+        .map_err(|e| format!("Couldn't write heightmap tif: {}", e))?;
+
+    invalidate_thumbnail(&heightmap_path);
+
+    Ok("success".to_string())
+}
+
+// writes a new landscape asset's heightmap and masks, returning its id
+fn write_landscape_files(
+    project_id: &str,
+    heightmap_filename: &str,
+    heightmap_data: Vec<u8>,
+    rockmap_filename: &str,
+    rockmap_data: Vec<u8>,
+    soil_filename: &str,
+    soil_data: Vec<u8>,
+) -> Uuid {
     let landscape_id = Uuid::new_v4();
 
     let sync_dir = PathBuf::from("C:/Users/alext/CommonOSFiles");
     let heightmaps_dir = sync_dir.join(format!(
         "midpoint/projects/{}/landscapes/{}/heightmaps",
-        projectId, landscape_id,
+        project_id, landscape_id,
     ));
     let rockmaps_dir = sync_dir.join(format!(
         "midpoint/projects/{}/landscapes/{}/rockmaps",
-        projectId, landscape_id
+        project_id, landscape_id
     ));
     let soils_dir = sync_dir.join(format!(
         "midpoint/projects/{}/landscapes/{}/soils",
-        projectId, landscape_id
+        project_id, landscape_id
     ));
 
     // Check if the concepts directory exists, create if it doesn't
@@ -515,36 +936,385 @@ fn save_landscape(
         fs::create_dir_all(&soils_dir).expect("Couldn't create soils directory");
     }
 
-    let heightmap_path = heightmaps_dir.join(landscapeFilename);
-    let rockmap_path = rockmaps_dir.join(rockmapFilename);
-    let soil_path = soils_dir.join(soilFilename);
+    let heightmap_path = heightmaps_dir.join(heightmap_filename);
+    let rockmap_path = rockmaps_dir.join(rockmap_filename);
+    let soil_path = soils_dir.join(soil_filename);
 
-    // prefix is pre-stripped on frontend
-    let base64_data = landscapeBase64;
-    let heightmap_data = decode(base64_data)
-        .map_err(|e| format!("Couldn't decode base64 string for heightmap: {}", e))
-        .expect("Couldn't decode base64 string for heightmap");
     fs::write(heightmap_path, heightmap_data)
         .map_err(|e| format!("Couldn't save heightmap file: {}", e))
         .expect("Couldn't save heightmap file");
 
-    let base64_data = rockmapBase64;
-    let rockmap_data = decode(base64_data)
-        .map_err(|e| format!("Couldn't decode base64 string for rockmap: {}", e))
-        .expect("Couldn't decode base64 string for rockmap");
     fs::write(rockmap_path, rockmap_data)
         .map_err(|e| format!("Couldn't save rockmap file: {}", e))
         .expect("Couldn't save rockmap file");
 
-    let base64_data = soilBase64;
-    let soil_data = decode(base64_data)
-        .map_err(|e| format!("Couldn't decode base64 string for soil: {}", e))
-        .expect("Couldn't decode base64 string for soil");
     fs::write(soil_path, soil_data)
         .map_err(|e| format!("Couldn't save soil file: {}", e))
         .expect("Couldn't save soil file");
 
-    "success".to_string()
+    landscape_id
+}
+
+#[derive(Serialize)]
+struct GeneratedLandscape {
+    landscapeId: String,
+    heightmapFilename: String,
+    rockmapFilename: String,
+    soilFilename: String,
+}
+
+fn encode_grayscale_png(width: u32, height: u32, data: Vec<u8>) -> Result<Vec<u8>, String> {
+    let img = image::GrayImage::from_raw(width, height, data)
+        .ok_or("Mask data doesn't match its dimensions")?;
+
+    let mut bytes = Cursor::new(Vec::new());
+    img.write_to(&mut bytes, image::ImageFormat::Png)
+        .map_err(|e| format!("Couldn't encode mask png: {}", e))?;
+
+    Ok(bytes.into_inner())
+}
+
+#[tauri::command]
+async fn generate_landscape(
+    state: tauri::State<'_, AppState>,
+    projectId: String,
+    params: GeneratorParams,
+) -> Result<GeneratedLandscape, String> {
+    let handle = &state.handle;
+    let config = handle.config();
+    let package_info = handle.package_info();
+    let env = handle.env();
+
+    println!("generate_landscape {:?}", params);
+
+    // noise and erosion take seconds at the larger sizes, keep them off the async runtime
+    tauri::async_runtime::spawn_blocking(move || {
+        let field = generate_heightfield(&params);
+        let (rockmap, soil) = generate_masks(&field);
+
+        let width = field.width as u32;
+        let height = field.height as u32;
+
+        // same formats as imported landscapes: float TIFF heightmap, PNG masks
+        let mut heightmap_data = Cursor::new(Vec::new());
+        TiffEncoder::new(&mut heightmap_data)
+            .map_err(|e| format!("Couldn't create tif encoder: {}", e))?
+            .write_image::<colortype::Gray32Float>(width, height, &field.values)
+            .map_err(|e| format!("Couldn't write heightmap tif: {}", e))?;
+
+        let rockmap_data = encode_grayscale_png(width, height, rockmap)?;
+        let soil_data = encode_grayscale_png(width, height, soil)?;
+
+        let heightmapFilename = format!("generated-{}.tif", params.seed);
+        let rockmapFilename = format!("generated-{}-rockmap.png", params.seed);
+        let soilFilename = format!("generated-{}-soil.png", params.seed);
+
+        let landscape_id = write_landscape_files(
+            &projectId,
+            &heightmapFilename,
+            heightmap_data.into_inner(),
+            &rockmapFilename,
+            rockmap_data,
+            &soilFilename,
+            soil_data,
+        );
+
+        Ok(GeneratedLandscape {
+            landscapeId: landscape_id.to_string(),
+            heightmapFilename,
+            rockmapFilename,
+            soilFilename,
+        })
+    })
+    .await
+    .map_err(|e| format!("Landscape generation failed: {}", e))?
+}
+
+#[derive(Serialize)]
+struct ProcessedTexture {
+    tileableFilename: Option<String>,
+    // the editor lists the tileable texture from this, like generated ones
+    tileableUrl: Option<String>,
+    normalFilename: Option<String>,
+    roughnessFilename: Option<String>,
+}
+
+// derived maps are saved beside the source texture, e.g. rock.png -> rock-tileable.png,
+// rock-normal.png and rock-roughness.png. The maps are taken from the tileable version
+// when one is made, so they tile too
+#[tauri::command]
+async fn process_texture(
+    state: tauri::State<'_, AppState>,
+    projectId: String,
+    textureFilename: String,
+    options: TextureProcessOptions,
+) -> Result<ProcessedTexture, String> {
+    let handle = &state.handle;
+    let config = handle.config();
+    let package_info = handle.package_info();
+    let env = handle.env();
+
+    println!("process_texture {} {:?}", textureFilename, options);
+
+    let sync_dir = PathBuf::from("C:/Users/alext/CommonOSFiles");
+    let textures_dir = sync_dir.join(format!("midpoint/projects/{}/textures", projectId));
+    let texture_path = textures_dir.join(&textureFilename);
+
+    let source = image::open(&texture_path)
+        .map_err(|e| format!("Failed to open texture: {}", e))?
+        .to_rgba8();
+
+    let stem = Path::new(&textureFilename)
+        .file_stem()
+        .and_then(|stem| stem.to_str())
+        .ok_or("Invalid texture filename")?
+        .to_string();
+
+    let save = |suffix: &str, image: image::DynamicImage| -> Result<String, String> {
+        let filename = format!("{}-{}.png", stem, suffix);
+        let path = textures_dir.join(&filename);
+        image
+            .save_with_format(&path, image::ImageFormat::Png)
+            .map_err(|e| format!("Couldn't save {} map: {}", suffix, e))?;
+
+        // derived maps point back at their source texture
+        write_sidecar(
+            &path,
+            &AssetMetadata {
+                parent: Some(AssetRef {
+                    kind: "texture".to_string(),
+                    filename: textureFilename.clone(),
+                }),
+                generator: Some(format!("texture-processing/{}", suffix)),
+                created_at: Some(Utc::now().to_rfc3339()),
+                width: Some(image.width()),
+                height: Some(image.height()),
+                ..Default::default()
+            },
+        )?;
+
+        Ok(filename)
+    };
+
+    let (albedo, tileableFilename, tileableUrl) = if options.make_tileable {
+        let tileable = make_tileable(&source, options.blend);
+        let filename = save("tileable", tileable.clone().into())?;
+        let data = fs::read(textures_dir.join(&filename))
+            .map_err(|e| format!("Couldn't read tileable texture: {}", e))?;
+        let url = format!("data:image/png;base64,{}", encode(data));
+        (tileable, Some(filename), Some(url))
+    } else {
+        (source, None, None)
+    };
+
+    let normalFilename = if options.normal_map {
+        Some(save(
+            "normal",
+            normal_map(&albedo, options.normal_strength).into(),
+        )?)
+    } else {
+        None
+    };
+
+    let roughnessFilename = if options.roughness_map {
+        Some(save("roughness", roughness_map(&albedo).into())?)
+    } else {
+        None
+    };
+
+    Ok(ProcessedTexture {
+        tileableFilename,
+        tileableUrl,
+        normalFilename,
+        roughnessFilename,
+    })
+}
+
+fn modified_time(path: &Path) -> Option<SystemTime> {
+    fs::metadata(path).and_then(|m| m.modified()).ok()
+}
+
+#[derive(Serialize)]
+struct AssetDetails {
+    metadata: AssetMetadata,
+    // assets made from this one, e.g. the models generated from a concept
+    children: Vec<AssetRef>,
+}
+
+// assets saved before sidecars existed, or imported by hand, get one started from the file
+// itself the first time they're viewed
+#[tauri::command]
+async fn read_asset_metadata(
+    state: tauri::State<'_, AppState>,
+    projectId: String,
+    assetKind: String,
+    assetFilename: String,
+) -> Result<AssetDetails, String> {
+    let handle = &state.handle;
+    let config = handle.config();
+    let package_info = handle.package_info();
+    let env = handle.env();
+
+    let sync_dir = PathBuf::from("C:/Users/alext/CommonOSFiles");
+    let asset_path = asset_path(&sync_dir, &projectId, &assetKind, &assetFilename)?;
+
+    if !asset_path.exists() {
+        return Err(format!("Couldn't find asset {}", assetFilename));
+    }
+
+    let metadata = match read_sidecar(&asset_path) {
+        Some(metadata) => metadata,
+        None => {
+            let mut metadata = AssetMetadata {
+                created_at: modified_time(&asset_path)
+                    .map(|modified| chrono::DateTime::<Utc>::from(modified).to_rfc3339()),
+                ..Default::default()
+            };
+
+            if let Err(e) = measure(&assetKind, &asset_path, &mut metadata) {
+                println!("Couldn't measure {}: {}", assetFilename, e);
+            }
+
+            write_sidecar(&asset_path, &metadata)?;
+
+            metadata
+        }
+    };
+
+    let children = children_of(
+        &sync_dir,
+        &projectId,
+        &AssetRef {
+            kind: assetKind,
+            filename: assetFilename,
+        },
+    );
+
+    Ok(AssetDetails { metadata, children })
+}
+
+// keeps the first recorded created-at and re-measures the asset, so callers only need to
+// fill in what they know
+#[tauri::command]
+async fn save_asset_metadata(
+    state: tauri::State<'_, AppState>,
+    projectId: String,
+    assetKind: String,
+    assetFilename: String,
+    metadata: AssetMetadata,
+) -> Result<AssetMetadata, String> {
+    let handle = &state.handle;
+    let config = handle.config();
+    let package_info = handle.package_info();
+    let env = handle.env();
+
+    let sync_dir = PathBuf::from("C:/Users/alext/CommonOSFiles");
+    let asset_path = asset_path(&sync_dir, &projectId, &assetKind, &assetFilename)?;
+
+    let mut metadata = metadata;
+
+    // assets made from another one keep the prompt behind it, e.g. a model's concept prompt
+    if metadata.source_prompt.is_none() {
+        if let Some(parent) = metadata.parent.as_ref() {
+            metadata.source_prompt =
+                asset_path(&sync_dir, &projectId, &parent.kind, &parent.filename)
+                    .ok()
+                    .and_then(|parent_path| read_sidecar(&parent_path))
+                    .and_then(|parent| parent.source_prompt);
+        }
+    }
+
+    metadata.created_at = read_sidecar(&asset_path)
+        .and_then(|existing| existing.created_at)
+        .or(metadata.created_at)
+        .or_else(|| Some(Utc::now().to_rfc3339()));
+
+    if let Err(e) = measure(&assetKind, &asset_path, &mut metadata) {
+        println!("Couldn't measure {}: {}", assetFilename, e);
+    }
+
+    write_sidecar(&asset_path, &metadata)?;
+
+    Ok(metadata)
+}
+
+// thumbnails are cached next to their asset and re-rendered whenever the asset
+// is newer, so sculpting or replacing a file refreshes them on the next request
+#[tauri::command]
+async fn get_asset_thumbnail(
+    state: tauri::State<'_, AppState>,
+    projectId: String,
+    assetKind: String,
+    assetId: String,
+    assetFilename: String,
+) -> Result<String, String> {
+    let handle = &state.handle;
+    let config = handle.config();
+    let package_info = handle.package_info();
+    let env = handle.env();
+
+    let sync_dir = PathBuf::from("C:/Users/alext/CommonOSFiles");
+    let asset_path = match assetKind.as_str() {
+        "model" => sync_dir.join(format!(
+            "midpoint/projects/{}/models/{}",
+            projectId, assetFilename
+        )),
+        "landscape" => sync_dir.join(format!(
+            "midpoint/projects/{}/landscapes/{}/heightmaps/{}",
+            projectId, assetId, assetFilename
+        )),
+        _ => return Err(format!("No thumbnails for asset kind {}", assetKind)),
+    };
+    let thumbnail_path = thumbnail_path(&asset_path);
+
+    let asset_modified = modified_time(&asset_path).ok_or("Couldn't read asset file")?;
+    let is_stale = match modified_time(&thumbnail_path) {
+        Some(thumbnail_modified) => thumbnail_modified < asset_modified,
+        None => true,
+    };
+
+    if is_stale {
+        println!("rendering thumbnail {:?}", thumbnail_path);
+
+        let image = if assetKind == "model" {
+            let bytes =
+                fs::read(&asset_path).map_err(|e| format!("Failed to read model: {}", e))?;
+            render_model_thumbnail(&bytes, THUMBNAIL_SIZE)?
+        } else {
+            let (width, height, values) = read_normalized_heights(&asset_path)?;
+            render_heightmap_thumbnail(width, height, &values, THUMBNAIL_SIZE)?
+        };
+
+        image
+            .save_with_format(&thumbnail_path, image::ImageFormat::Png)
+            .map_err(|e| format!("Couldn't save thumbnail: {}", e))?;
+    }
+
+    let thumbnail_data =
+        fs::read(&thumbnail_path).map_err(|e| format!("Couldn't read thumbnail: {}", e))?;
+
+    Ok(format!("data:image/png;base64,{}", encode(thumbnail_data)))
+}
+
+// exports sit beside the projects rather than inside one, so they're never mistaken for assets
+#[tauri::command]
+async fn export_project(
+    state: tauri::State<'_, AppState>,
+    projectId: String,
+    savedState: serde_json::Value,
+) -> Result<ExportSummary, String> {
+    let handle = &state.handle;
+    let config = handle.config();
+    let package_info = handle.package_info();
+    let env = handle.env();
+
+    let sync_dir = PathBuf::from("C:/Users/alext/CommonOSFiles");
+    let project_dir = sync_dir.join(format!("midpoint/projects/{}", projectId));
+    let export_dir = sync_dir.join(format!("midpoint/exports/{}", projectId));
+
+    println!("export_dir {:?}", export_dir);
+
+    export::export_project(&project_dir, &export_dir, &projectId, savedState)
 }
 
 fn main() {
@@ -560,6 +1330,11 @@ fn main() {
         .invoke_handler(tauri::generate_handler![
             read_token,
             create_project,
+            list_local_projects,
+            read_local_project,
+            create_local_project,
+            save_local_project,
+            delete_local_project,
             save_concept,
             save_model,
             read_model,
@@ -568,6 +1343,14 @@ fn main() {
             save_texture,
             read_landscape_texture,
             read_landscape_mask,
+            save_landscape_heightmap,
+            save_landscape_mask,
+            generate_landscape,
+            get_asset_thumbnail,
+            process_texture,
+            read_asset_metadata,
+            save_asset_metadata,
+            export_project,
         ])
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
@@ -0,0 +1,139 @@
+// Asset metadata kept in a sidecar next to each asset, e.g. rock.png.meta.json, so it travels
+// with the file through the sync folder. Records where an asset came from (prompt, generator,
+// parent asset) and measurements that would otherwise mean reopening the file.
+
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+// the asset folders that can have sidecars, by the kind names the editor uses
+const ASSET_KINDS: [(&str, &str); 3] = [
+    ("concept", "concepts"),
+    ("texture", "textures"),
+    ("model", "models"),
+];
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct AssetRef {
+    pub kind: String,
+    pub filename: String,
+}
+
+#[derive(Serialize, Deserialize, Clone, Default, Debug)]
+#[serde(rename_all = "camelCase", default)]
+pub struct AssetMetadata {
+    pub source_prompt: Option<String>,
+    // the asset this one was made from, e.g. the concept behind a model
+    pub parent: Option<AssetRef>,
+    pub generator: Option<String>,
+    // RFC 3339
+    pub created_at: Option<String>,
+    pub tags: Vec<String>,
+    pub width: Option<u32>,
+    pub height: Option<u32>,
+    pub poly_count: Option<u32>,
+}
+
+pub fn asset_path(
+    sync_dir: &Path,
+    project_id: &str,
+    kind: &str,
+    filename: &str,
+) -> Result<PathBuf, String> {
+    let (_, folder) = ASSET_KINDS
+        .iter()
+        .find(|(name, _)| *name == kind)
+        .ok_or(format!("No metadata for asset kind {}", kind))?;
+
+    Ok(sync_dir.join(format!(
+        "midpoint/projects/{}/{}/{}",
+        project_id, folder, filename
+    )))
+}
+
+fn sidecar_path(asset_path: &Path) -> PathBuf {
+    let filename = asset_path
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_default();
+
+    asset_path.with_file_name(format!("{}.meta.json", filename))
+}
+
+pub fn read_sidecar(asset_path: &Path) -> Option<AssetMetadata> {
+    let json = fs::read_to_string(sidecar_path(asset_path)).ok()?;
+    serde_json::from_str(&json).ok()
+}
+
+pub fn write_sidecar(asset_path: &Path, metadata: &AssetMetadata) -> Result<(), String> {
+    let json = serde_json::to_string_pretty(metadata)
+        .map_err(|e| format!("Couldn't serialize metadata: {}", e))?;
+
+    fs::write(sidecar_path(asset_path), json).map_err(|e| format!("Couldn't save metadata: {}", e))
+}
+
+// fills in the dimensions or poly count from the asset itself
+pub fn measure(kind: &str, asset_path: &Path, metadata: &mut AssetMetadata) -> Result<(), String> {
+    if kind == "model" {
+        let bytes = fs::read(asset_path).map_err(|e| format!("Failed to read model: {}", e))?;
+        let gltf =
+            gltf::Gltf::from_slice(&bytes).map_err(|e| format!("Failed to parse model: {}", e))?;
+
+        // accessor counts are in the json, so the buffers don't need decoding
+        let poly_count = gltf
+            .meshes()
+            .flat_map(|mesh| mesh.primitives())
+            .map(|primitive| {
+                let vertices = primitive
+                    .indices()
+                    .or_else(|| primitive.get(&gltf::Semantic::Positions))
+                    .map(|accessor| accessor.count())
+                    .unwrap_or(0);
+                (vertices / 3) as u32
+            })
+            .sum();
+
+        metadata.poly_count = Some(poly_count);
+    } else {
+        let (width, height) = image::image_dimensions(asset_path)
+            .map_err(|e| format!("Failed to read image size: {}", e))?;
+
+        metadata.width = Some(width);
+        metadata.height = Some(height);
+    }
+
+    Ok(())
+}
+
+// every asset in the project whose sidecar names this one as its parent
+pub fn children_of(sync_dir: &Path, project_id: &str, asset: &AssetRef) -> Vec<AssetRef> {
+    let mut children = Vec::new();
+
+    for (kind, folder) in ASSET_KINDS {
+        let dir = sync_dir.join(format!("midpoint/projects/{}/{}", project_id, folder));
+        let Ok(entries) = fs::read_dir(&dir) else {
+            continue;
+        };
+
+        for entry in entries.flatten() {
+            let name = entry.file_name().to_string_lossy().into_owned();
+            let Some(filename) = name.strip_suffix(".meta.json") else {
+                continue;
+            };
+
+            let is_child = read_sidecar(&dir.join(filename))
+                .and_then(|metadata| metadata.parent)
+                .is_some_and(|parent| &parent == asset);
+
+            if is_child {
+                children.push(AssetRef {
+                    kind: kind.to_string(),
+                    filename: filename.to_string(),
+                });
+            }
+        }
+    }
+
+    children
+}
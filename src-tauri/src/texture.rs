@@ -0,0 +1,122 @@
+// Texture post-processing for landscape layers, which the shader tiles many times over:
+// making an image tileable, and deriving a normal map and a roughness estimate from albedo.
+// Every pass wraps around the edges, so a tileable input gives tileable maps.
+
+use image::{GrayImage, Luma, Rgba, RgbaImage};
+use serde::Deserialize;
+
+#[derive(Deserialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct TextureProcessOptions {
+    pub make_tileable: bool,
+    // how far the unshifted image fades in from the center, 0.0 - 1.0 of the half size
+    pub blend: f32,
+    pub normal_map: bool,
+    pub normal_strength: f32,
+    pub roughness_map: bool,
+}
+
+fn luminance(pixel: &Rgba<u8>) -> f32 {
+    (0.2126 * pixel[0] as f32 + 0.7152 * pixel[1] as f32 + 0.0722 * pixel[2] as f32) / 255.0
+}
+
+// reads with wrap around, the way the shader samples a repeating texture
+fn wrapped(width: u32, height: u32, x: i64, y: i64) -> (u32, u32) {
+    (
+        x.rem_euclid(width as i64) as u32,
+        y.rem_euclid(height as i64) as u32,
+    )
+}
+
+// Offset-and-blend: shifting by half the size moves the wrap seams to the middle. Each axis
+// fades between the unshifted image near its center and the shifted one near its edges, and
+// the four combinations are mixed, so every seam lands where its copy has no weight.
+pub fn make_tileable(image: &RgbaImage, blend: f32) -> RgbaImage {
+    let (width, height) = image.dimensions();
+    let blend = blend.clamp(0.05, 1.0);
+
+    // weight of the unshifted image along one axis, 1.0 in the middle and 0.0 at the edges
+    let center_weight = |position: u32, size: u32| {
+        let half = size as f32 / 2.0;
+        let t = (1.0 - (position as f32 + 0.5 - half).abs() / (blend * half)).clamp(0.0, 1.0);
+        // smoothstep so the fade has no visible edge of its own
+        t * t * (3.0 - 2.0 * t)
+    };
+
+    RgbaImage::from_fn(width, height, |x, y| {
+        let wx = center_weight(x, width);
+        let wy = center_weight(y, height);
+
+        let shifted_x = (x + width / 2) % width;
+        let shifted_y = (y + height / 2) % height;
+
+        let copies = [
+            (image.get_pixel(x, y), wx * wy),
+            (image.get_pixel(shifted_x, y), (1.0 - wx) * wy),
+            (image.get_pixel(x, shifted_y), wx * (1.0 - wy)),
+            (
+                image.get_pixel(shifted_x, shifted_y),
+                (1.0 - wx) * (1.0 - wy),
+            ),
+        ];
+
+        let channel = |c: usize| {
+            let value: f32 = copies
+                .iter()
+                .map(|(pixel, weight)| pixel[c] as f32 * weight)
+                .sum();
+            value.round().clamp(0.0, 255.0) as u8
+        };
+
+        Rgba([channel(0), channel(1), channel(2), channel(3)])
+    })
+}
+
+// tangent space normals from luminance treated as height, using a sobel filter
+pub fn normal_map(image: &RgbaImage, strength: f32) -> RgbaImage {
+    let (width, height) = image.dimensions();
+
+    let height_at = |x: u32, y: u32, dx: i64, dy: i64| {
+        let (x, y) = wrapped(width, height, x as i64 + dx, y as i64 + dy);
+        luminance(image.get_pixel(x, y))
+    };
+
+    RgbaImage::from_fn(width, height, |x, y| {
+        let h = |dx, dy| height_at(x, y, dx, dy);
+
+        let gradient_x =
+            (h(1, -1) + 2.0 * h(1, 0) + h(1, 1)) - (h(-1, -1) + 2.0 * h(-1, 0) + h(-1, 1));
+        let gradient_y =
+            (h(-1, 1) + 2.0 * h(0, 1) + h(1, 1)) - (h(-1, -1) + 2.0 * h(0, -1) + h(1, -1));
+
+        let normal = [-gradient_x * strength, -gradient_y * strength, 1.0];
+        let length = (normal[0] * normal[0] + normal[1] * normal[1] + normal[2] * normal[2]).sqrt();
+
+        let encode = |v: f32| ((v / length * 0.5 + 0.5) * 255.0) as u8;
+
+        Rgba([encode(normal[0]), encode(normal[1]), encode(normal[2]), 255])
+    })
+}
+
+// A rough guess: darker and busier surfaces (soil, rock) read as rougher than bright, flat
+// ones (wet sand, snow). Good enough as a starting point to tweak by hand.
+pub fn roughness_map(image: &RgbaImage) -> GrayImage {
+    let (width, height) = image.dimensions();
+
+    GrayImage::from_fn(width, height, |x, y| {
+        let center = luminance(image.get_pixel(x, y));
+
+        // local contrast over the 3x3 neighbourhood
+        let mut detail: f32 = 0.0;
+        for dy in -1..=1 {
+            for dx in -1..=1 {
+                let (nx, ny) = wrapped(width, height, x as i64 + dx, y as i64 + dy);
+                detail = detail.max((luminance(image.get_pixel(nx, ny)) - center).abs());
+            }
+        }
+
+        let roughness = 0.45 + (1.0 - center) * 0.35 + detail * 1.5;
+
+        Luma([(roughness.clamp(0.0, 1.0) * 255.0) as u8])
+    })
+}
@@ -0,0 +1,361 @@
+// Asset thumbnails rendered on the CPU: a flat shaded three-quarter view for models
+// and a shaded relief for heightmaps. Small enough that no gpu is needed.
+
+use image::{Rgba, RgbaImage};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+pub const THUMBNAIL_SIZE: u32 = 256;
+
+// light from the upper left, shared by both kinds of thumbnail
+const LIGHT_DIRECTION: [f32; 3] = [-0.5, 0.8, 0.3];
+const AMBIENT: f32 = 0.3;
+
+type Vec3 = [f32; 3];
+type Mat4 = [[f32; 4]; 4]; // column-major, as gltf stores node transforms
+
+const IDENTITY: Mat4 = [
+    [1.0, 0.0, 0.0, 0.0],
+    [0.0, 1.0, 0.0, 0.0],
+    [0.0, 0.0, 1.0, 0.0],
+    [0.0, 0.0, 0.0, 1.0],
+];
+
+// cached next to the asset, named after it
+pub fn thumbnail_path(asset_path: &Path) -> PathBuf {
+    let filename = asset_path
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_default();
+
+    asset_path.with_file_name(format!("{}.thumb.png", filename))
+}
+
+// saves can land within the same mtime tick as the cached thumbnail, so drop it outright
+pub fn invalidate_thumbnail(asset_path: &Path) {
+    let path = thumbnail_path(asset_path);
+
+    if path.exists() {
+        if let Err(e) = fs::remove_file(&path) {
+            println!("Couldn't remove stale thumbnail {:?}: {}", path, e);
+        }
+    }
+}
+
+fn sub(a: Vec3, b: Vec3) -> Vec3 {
+    [a[0] - b[0], a[1] - b[1], a[2] - b[2]]
+}
+
+fn dot(a: Vec3, b: Vec3) -> f32 {
+    a[0] * b[0] + a[1] * b[1] + a[2] * b[2]
+}
+
+fn cross(a: Vec3, b: Vec3) -> Vec3 {
+    [
+        a[1] * b[2] - a[2] * b[1],
+        a[2] * b[0] - a[0] * b[2],
+        a[0] * b[1] - a[1] * b[0],
+    ]
+}
+
+fn mul(a: Mat4, b: Mat4) -> Mat4 {
+    let mut out = [[0.0; 4]; 4];
+    for column in 0..4 {
+        for row in 0..4 {
+            out[column][row] = (0..4).map(|k| a[k][row] * b[column][k]).sum();
+        }
+    }
+    out
+}
+
+fn transform_point(m: Mat4, p: Vec3) -> Vec3 {
+    [0, 1, 2].map(|row| m[0][row] * p[0] + m[1][row] * p[1] + m[2][row] * p[2] + m[3][row])
+}
+
+fn normalize(v: Vec3) -> Vec3 {
+    let length = dot(v, v).sqrt().max(f32::EPSILON);
+    [v[0] / length, v[1] / length, v[2] / length]
+}
+
+fn shade(normal: Vec3) -> f32 {
+    AMBIENT + (1.0 - AMBIENT) * dot(normalize(normal), normalize(LIGHT_DIRECTION)).max(0.0)
+}
+
+struct Triangle {
+    positions: [Vec3; 3],
+    color: [f32; 4],
+}
+
+fn read_mesh(mesh: &gltf::Mesh, transform: Mat4, blob: &[u8], triangles: &mut Vec<Triangle>) {
+    for primitive in mesh.primitives() {
+        let reader = primitive.reader(|_| Some(blob));
+        let color = primitive
+            .material()
+            .pbr_metallic_roughness()
+            .base_color_factor();
+
+        let positions: Vec<Vec3> = match reader.read_positions() {
+            Some(positions) => positions.map(|p| transform_point(transform, p)).collect(),
+            None => continue,
+        };
+        let indices: Vec<u32> = reader
+            .read_indices()
+            .map(|indices| indices.into_u32().collect())
+            .unwrap_or_else(|| (0..positions.len() as u32).collect());
+
+        for corners in indices.chunks_exact(3) {
+            let corner = |i: usize| positions.get(corners[i] as usize).copied();
+
+            if let (Some(a), Some(b), Some(c)) = (corner(0), corner(1), corner(2)) {
+                triangles.push(Triangle {
+                    positions: [a, b, c],
+                    color,
+                });
+            }
+        }
+    }
+}
+
+// meshes are placed by their node and every parent above it
+fn read_node(node: &gltf::Node, parent: Mat4, blob: &[u8], triangles: &mut Vec<Triangle>) {
+    let transform = mul(parent, node.transform().matrix());
+
+    if let Some(mesh) = node.mesh() {
+        read_mesh(&mesh, transform, blob, triangles);
+    }
+
+    for child in node.children() {
+        read_node(&child, transform, blob, triangles);
+    }
+}
+
+fn read_triangles(bytes: &[u8]) -> Result<Vec<Triangle>, String> {
+    let gltf = gltf::Gltf::from_slice(bytes).map_err(|e| format!("Invalid glb: {}", e))?;
+    let blob = gltf.blob.as_deref().ok_or("No binary data found in glb")?;
+
+    let mut triangles = Vec::new();
+
+    match gltf.default_scene().or_else(|| gltf.scenes().next()) {
+        Some(scene) => {
+            for node in scene.nodes() {
+                read_node(&node, IDENTITY, blob, &mut triangles);
+            }
+        }
+        // without a scene there are no nodes to place meshes, draw them as stored
+        None => {
+            for mesh in gltf.meshes() {
+                read_mesh(&mesh, IDENTITY, blob, &mut triangles);
+            }
+        }
+    }
+
+    Ok(triangles)
+}
+
+// orthographic three-quarter view framed by the model's bounding box
+pub fn render_model_thumbnail(bytes: &[u8], size: u32) -> Result<RgbaImage, String> {
+    let triangles = read_triangles(bytes)?;
+    if triangles.is_empty() {
+        return Err("Model has no triangles".to_string());
+    }
+
+    let mut min = [f32::MAX; 3];
+    let mut max = [f32::MIN; 3];
+    for position in triangles.iter().flat_map(|t| t.positions.iter()) {
+        for axis in 0..3 {
+            min[axis] = min[axis].min(position[axis]);
+            max[axis] = max[axis].max(position[axis]);
+        }
+    }
+    let center = [
+        (min[0] + max[0]) / 2.0,
+        (min[1] + max[1]) / 2.0,
+        (min[2] + max[2]) / 2.0,
+    ];
+
+    // same angle the headless renderer frames scenes from
+    let forward = normalize([-1.0, -0.6, -1.0]);
+    let right = normalize(cross(forward, [0.0, 1.0, 0.0]));
+    let up = cross(right, forward);
+
+    let project = |p: Vec3| {
+        let local = sub(p, center);
+        (dot(local, right), dot(local, up), dot(local, forward))
+    };
+
+    let extent = triangles
+        .iter()
+        .flat_map(|t| t.positions.iter())
+        .map(|p| {
+            let (x, y, _) = project(*p);
+            x.abs().max(y.abs())
+        })
+        .fold(f32::EPSILON, f32::max);
+
+    // leave a small margin around the model
+    let scale = size as f32 * 0.45 / extent;
+    let half = size as f32 / 2.0;
+
+    let mut image = RgbaImage::new(size, size);
+    let mut depth = vec![f32::MAX; (size * size) as usize];
+
+    for triangle in &triangles {
+        let normal = cross(
+            sub(triangle.positions[1], triangle.positions[0]),
+            sub(triangle.positions[2], triangle.positions[0]),
+        );
+        // models aren't guaranteed to be closed, so light whichever side faces the camera
+        let normal = if dot(normal, forward) > 0.0 {
+            [-normal[0], -normal[1], -normal[2]]
+        } else {
+            normal
+        };
+        let light = shade(normal);
+
+        let screen = triangle.positions.map(|p| {
+            let (x, y, z) = project(p);
+            (half + x * scale, half - y * scale, z)
+        });
+
+        let area = (screen[1].0 - screen[0].0) * (screen[2].1 - screen[0].1)
+            - (screen[2].0 - screen[0].0) * (screen[1].1 - screen[0].1);
+        if area.abs() < f32::EPSILON {
+            continue;
+        }
+
+        let min_x = screen
+            .iter()
+            .map(|s| s.0)
+            .fold(f32::MAX, f32::min)
+            .floor()
+            .max(0.0) as u32;
+        let max_x = screen
+            .iter()
+            .map(|s| s.0)
+            .fold(f32::MIN, f32::max)
+            .ceil()
+            .min(size as f32 - 1.0) as u32;
+        let min_y = screen
+            .iter()
+            .map(|s| s.1)
+            .fold(f32::MAX, f32::min)
+            .floor()
+            .max(0.0) as u32;
+        let max_y = screen
+            .iter()
+            .map(|s| s.1)
+            .fold(f32::MIN, f32::max)
+            .ceil()
+            .min(size as f32 - 1.0) as u32;
+
+        for y in min_y..=max_y {
+            for x in min_x..=max_x {
+                let (px, py) = (x as f32 + 0.5, y as f32 + 0.5);
+
+                let edge = |a: (f32, f32, f32), b: (f32, f32, f32)| {
+                    ((b.0 - a.0) * (py - a.1) - (b.1 - a.1) * (px - a.0)) / area
+                };
+                let w0 = edge(screen[1], screen[2]);
+                let w1 = edge(screen[2], screen[0]);
+                let w2 = edge(screen[0], screen[1]);
+
+                if w0 < 0.0 || w1 < 0.0 || w2 < 0.0 {
+                    continue;
+                }
+
+                let z = w0 * screen[0].2 + w1 * screen[1].2 + w2 * screen[2].2;
+                let index = (y * size + x) as usize;
+                if z >= depth[index] {
+                    continue;
+                }
+                depth[index] = z;
+
+                let channel = |c: f32| ((c * light).clamp(0.0, 1.0) * 255.0) as u8;
+                image.put_pixel(
+                    x,
+                    y,
+                    Rgba([
+                        channel(triangle.color[0]),
+                        channel(triangle.color[1]),
+                        channel(triangle.color[2]),
+                        255,
+                    ]),
+                );
+            }
+        }
+    }
+
+    Ok(image)
+}
+
+// low ground is green, mid slopes brown and peaks white
+fn elevation_tint(height: f32) -> Vec3 {
+    const STOPS: [(f32, Vec3); 4] = [
+        (0.0, [0.22, 0.38, 0.2]),
+        (0.45, [0.45, 0.5, 0.3]),
+        (0.75, [0.5, 0.42, 0.33]),
+        (1.0, [0.95, 0.95, 0.95]),
+    ];
+
+    for pair in STOPS.windows(2) {
+        let ((h0, c0), (h1, c1)) = (pair[0], pair[1]);
+        if height <= h1 {
+            let t = ((height - h0) / (h1 - h0)).clamp(0.0, 1.0);
+            return [
+                c0[0] + (c1[0] - c0[0]) * t,
+                c0[1] + (c1[1] - c0[1]) * t,
+                c0[2] + (c1[2] - c0[2]) * t,
+            ];
+        }
+    }
+
+    STOPS[STOPS.len() - 1].1
+}
+
+// top down hillshade tinted by elevation, values are any range and get normalized
+pub fn render_heightmap_thumbnail(
+    width: usize,
+    height: usize,
+    values: &[f32],
+    size: u32,
+) -> Result<RgbaImage, String> {
+    if width < 2 || height < 2 || values.len() != width * height {
+        return Err("Heightmap data doesn't match its dimensions".to_string());
+    }
+
+    let min = values.iter().cloned().fold(f32::MAX, f32::min);
+    let max = values.iter().cloned().fold(f32::MIN, f32::max);
+    let range = (max - min).max(f32::EPSILON);
+
+    let sample = |x: f32, y: f32| {
+        let x = (x.clamp(0.0, 1.0) * (width - 1) as f32).round() as usize;
+        let y = (y.clamp(0.0, 1.0) * (height - 1) as f32).round() as usize;
+        (values[y * width + x] - min) / range
+    };
+
+    // vertical scale relative to the width, a little steeper than imported landscapes
+    let relief = 0.25;
+    let step = 1.0 / size as f32;
+
+    let mut image = RgbaImage::new(size, size);
+    for y in 0..size {
+        for x in 0..size {
+            let (u, v) = (x as f32 / (size - 1) as f32, y as f32 / (size - 1) as f32);
+
+            let dx = (sample(u + step, v) - sample(u - step, v)) * relief;
+            let dy = (sample(u, v + step) - sample(u, v - step)) * relief;
+            let light = shade([-dx, 2.0 * step, -dy]);
+
+            let tint = elevation_tint(sample(u, v));
+            let channel = |c: f32| ((c * light).clamp(0.0, 1.0) * 255.0) as u8;
+
+            image.put_pixel(
+                x,
+                y,
+                Rgba([channel(tint[0]), channel(tint[1]), channel(tint[2]), 255]),
+            );
+        }
+    }
+
+    Ok(image)
+}
@@ -1,39 +1,52 @@
 use serde::{Deserialize, Serialize};
 use serde_wasm_bindgen::to_value;
-use wasm_bindgen::prelude::*;
 use wasm_bindgen_futures::spawn_local;
 use yew::prelude::*;
 
+use crate::backend::load_backend_kind;
+use crate::components::AutoSave::AutoSave;
+use crate::components::JobRunner::JobRunner;
 use crate::components::PrimaryView::PrimaryView;
 use crate::components::ProjectSocket::ProjectSocket;
 use crate::components::TokenLoader::TokenLoader;
+use crate::components::UndoShortcuts::UndoShortcuts;
 use crate::components::ViewNav::ViewNav;
+use crate::contexts::jobs::{JobsContextType, JobsState};
 use crate::contexts::local::{LocalContextType, LocalState};
+use crate::contexts::renderer::RendererContextType;
 use crate::contexts::saved::{SavedContextType, SavedState};
-
-#[wasm_bindgen]
-extern "C" {
-    #[wasm_bindgen(js_namespace = ["window", "__TAURI__", "tauri"])]
-    pub async fn invoke(cmd: &str, args: JsValue) -> JsValue;
-}
+use crate::generation::load_provider_kind;
 
 #[function_component(App)]
 pub fn app() -> Html {
-    let local_state = use_reducer(LocalState::default);
+    let local_state = use_reducer(|| LocalState {
+        backend: load_backend_kind(),
+        generation: load_provider_kind(),
+        ..LocalState::default()
+    });
     let saved_state = use_reducer(SavedState::default);
+    let jobs_state = use_reducer(JobsState::default);
+    let renderer = use_state(|| None);
 
     html! {
         <ContextProvider<LocalContextType> context={local_state}>
             <ContextProvider<SavedContextType> context={saved_state}>
-                <main class="container">
-                    <TokenLoader />
-                    <ProjectSocket />
-                    <ViewNav />
-                    <section>
-                        // <PrimaryToolbar />
-                        <PrimaryView />
-                    </section>
-                </main>
+                <ContextProvider<RendererContextType> context={renderer}>
+                    <ContextProvider<JobsContextType> context={jobs_state}>
+                        <main class="container">
+                            <TokenLoader />
+                            <ProjectSocket />
+                            <AutoSave />
+                            <UndoShortcuts />
+                            <JobRunner />
+                            <ViewNav />
+                            <section>
+                                // <PrimaryToolbar />
+                                <PrimaryView />
+                            </section>
+                        </main>
+                    </ContextProvider<JobsContextType>>
+                </ContextProvider<RendererContextType>>
             </ContextProvider<SavedContextType>>
         </ContextProvider<LocalContextType>>
     }
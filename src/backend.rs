@@ -0,0 +1,115 @@
+// Where projects are listed, loaded and saved. The cloud backend talks to the GraphQL server,
+// the offline one keeps everything in the project folder through Tauri, so the editor works
+// without the server running.
+
+use serde::{Deserialize, Serialize};
+
+use crate::contexts::local::{LocalState, MdProject};
+use crate::contexts::saved::SavedState;
+use crate::gql::getMdProject;
+
+pub mod offline;
+pub mod remote;
+
+use offline::OfflineBackend;
+use remote::RemoteBackend;
+
+const BACKEND_STORAGE_KEY: &str = "midpoint-backend";
+
+#[derive(Clone, Copy, PartialEq, Serialize, Deserialize, Debug)]
+pub enum BackendKind {
+    Remote,
+    Offline,
+}
+
+// only driven from the single threaded wasm executor, so the futures never need to be Send
+#[allow(async_fn_in_trait)]
+pub trait ProjectBackend {
+    async fn list_projects(&self) -> Result<Vec<MdProject>, String>;
+    async fn get_project(&self, project_id: String) -> Result<getMdProject::MdProject, String>;
+    // returns the new project's id
+    async fn create_project(&self) -> Result<String, String>;
+    // a title of None leaves the current one in place
+    async fn update_project(
+        &self,
+        project_id: String,
+        title: Option<String>,
+        context: &SavedState,
+    ) -> Result<(), String>;
+    async fn delete_project(&self, project_id: String) -> Result<(), String>;
+}
+
+pub enum Backend {
+    Remote(RemoteBackend),
+    Offline(OfflineBackend),
+}
+
+impl ProjectBackend for Backend {
+    async fn list_projects(&self) -> Result<Vec<MdProject>, String> {
+        match self {
+            Backend::Remote(backend) => backend.list_projects().await,
+            Backend::Offline(backend) => backend.list_projects().await,
+        }
+    }
+
+    async fn get_project(&self, project_id: String) -> Result<getMdProject::MdProject, String> {
+        match self {
+            Backend::Remote(backend) => backend.get_project(project_id).await,
+            Backend::Offline(backend) => backend.get_project(project_id).await,
+        }
+    }
+
+    async fn create_project(&self) -> Result<String, String> {
+        match self {
+            Backend::Remote(backend) => backend.create_project().await,
+            Backend::Offline(backend) => backend.create_project().await,
+        }
+    }
+
+    async fn update_project(
+        &self,
+        project_id: String,
+        title: Option<String>,
+        context: &SavedState,
+    ) -> Result<(), String> {
+        match self {
+            Backend::Remote(backend) => backend.update_project(project_id, title, context).await,
+            Backend::Offline(backend) => backend.update_project(project_id, title, context).await,
+        }
+    }
+
+    async fn delete_project(&self, project_id: String) -> Result<(), String> {
+        match self {
+            Backend::Remote(backend) => backend.delete_project(project_id).await,
+            Backend::Offline(backend) => backend.delete_project(project_id).await,
+        }
+    }
+}
+
+// the backend chosen for this session, the cloud one carries the current auth token
+pub fn project_backend(local_state: &LocalState) -> Backend {
+    match local_state.backend {
+        BackendKind::Remote => Backend::Remote(RemoteBackend::new(local_state.token.clone())),
+        BackendKind::Offline => Backend::Offline(OfflineBackend),
+    }
+}
+
+fn local_storage() -> Option<web_sys::Storage> {
+    web_sys::window().and_then(|window| window.local_storage().ok().flatten())
+}
+
+// the choice is remembered so the editor starts with the same backend next time
+pub fn load_backend_kind() -> BackendKind {
+    let stored = local_storage().and_then(|storage| storage.get_item(BACKEND_STORAGE_KEY).ok());
+
+    match stored.flatten().as_deref() {
+        Some("Offline") => BackendKind::Offline,
+        _ => BackendKind::Remote,
+    }
+}
+
+pub fn store_backend_kind(kind: BackendKind) {
+    if let Some(storage) = local_storage() {
+        let _ = storage.set_item(BACKEND_STORAGE_KEY, &format!("{:?}", kind));
+    }
+}
@@ -0,0 +1,107 @@
+use gloo_utils::format::JsValueSerdeExt;
+use serde::{de::DeserializeOwned, Serialize};
+use serde_wasm_bindgen::to_value;
+
+use crate::backend::ProjectBackend;
+use crate::contexts::local::MdProject;
+use crate::contexts::saved::SavedState;
+use crate::gql::getMdProject;
+use crate::invoke::try_invoke;
+
+const NEW_PROJECT_TITLE: &str = "Untitled Project";
+
+#[derive(Serialize)]
+struct NoParams {}
+
+#[derive(Serialize)]
+struct ProjectParams {
+    projectId: String,
+}
+
+#[derive(Serialize)]
+struct CreateParams<'a> {
+    title: String,
+    context: &'a SavedState,
+}
+
+#[derive(Serialize)]
+struct SaveParams<'a> {
+    projectId: String,
+    title: Option<String>,
+    context: &'a SavedState,
+}
+
+// tauri rejects with the command's error string
+async fn call<T: DeserializeOwned>(command: &str, params: impl Serialize) -> Result<T, String> {
+    let params = to_value(&params).map_err(|e| format!("Couldn't serialize params: {}", e))?;
+
+    let result = try_invoke(command, params).await.map_err(|e| {
+        e.as_string()
+            .unwrap_or_else(|| format!("{} failed", command))
+    })?;
+
+    result
+        .into_serde()
+        .map_err(|e| format!("Invalid {} response: {}", command, e))
+}
+
+// projects stored in the sync folder by the tauri backend, no server required
+pub struct OfflineBackend;
+
+impl ProjectBackend for OfflineBackend {
+    async fn list_projects(&self) -> Result<Vec<MdProject>, String> {
+        call("list_local_projects", NoParams {}).await
+    }
+
+    async fn get_project(&self, project_id: String) -> Result<getMdProject::MdProject, String> {
+        call(
+            "read_local_project",
+            ProjectParams {
+                projectId: project_id,
+            },
+        )
+        .await
+    }
+
+    async fn create_project(&self) -> Result<String, String> {
+        call(
+            "create_local_project",
+            CreateParams {
+                title: NEW_PROJECT_TITLE.to_string(),
+                context: &SavedState::default(),
+            },
+        )
+        .await
+    }
+
+    async fn update_project(
+        &self,
+        project_id: String,
+        title: Option<String>,
+        context: &SavedState,
+    ) -> Result<(), String> {
+        call::<String>(
+            "save_local_project",
+            SaveParams {
+                projectId: project_id,
+                title,
+                context,
+            },
+        )
+        .await?;
+
+        Ok(())
+    }
+
+    async fn delete_project(&self, project_id: String) -> Result<(), String> {
+        call::<String>(
+            "delete_local_project",
+            ProjectParams {
+                projectId: project_id,
+            },
+        )
+        .await?;
+
+        Ok(())
+    }
+}
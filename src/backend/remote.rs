@@ -0,0 +1,71 @@
+use crate::backend::ProjectBackend;
+use crate::contexts::local::MdProject;
+use crate::contexts::saved::SavedState;
+use crate::gql::createMdProject::create_md_project;
+use crate::gql::deleteMdProject::delete_md_project;
+use crate::gql::getMdProject::{self, get_md_project};
+use crate::gql::getMdProjects::get_md_projects;
+use crate::gql::updateMdProject::update_md_project;
+
+pub struct RemoteBackend {
+    token: Option<String>,
+}
+
+impl RemoteBackend {
+    pub fn new(token: Option<String>) -> Self {
+        Self { token }
+    }
+
+    fn token(&self) -> Result<String, String> {
+        self.token
+            .clone()
+            .ok_or("Log in via CommonOS File Manager to use cloud projects".to_string())
+    }
+}
+
+impl ProjectBackend for RemoteBackend {
+    async fn list_projects(&self) -> Result<Vec<MdProject>, String> {
+        let data = get_md_projects(self.token()?)
+            .await
+            .map_err(|e| format!("Couldn't fetch projects: {}", e))?;
+
+        Ok(data.getMdProjects)
+    }
+
+    async fn get_project(&self, project_id: String) -> Result<getMdProject::MdProject, String> {
+        let data = get_md_project(self.token()?, project_id)
+            .await
+            .map_err(|e| format!("Couldn't fetch project: {}", e))?;
+
+        Ok(data.getMdProject)
+    }
+
+    async fn create_project(&self) -> Result<String, String> {
+        let data = create_md_project(self.token()?)
+            .await
+            .map_err(|e| format!("Couldn't create project: {}", e))?;
+
+        Ok(data.createMdProject.id)
+    }
+
+    async fn update_project(
+        &self,
+        project_id: String,
+        title: Option<String>,
+        context: &SavedState,
+    ) -> Result<(), String> {
+        update_md_project(self.token()?, project_id, title, context)
+            .await
+            .map_err(|e| format!("Couldn't save project: {}", e))?;
+
+        Ok(())
+    }
+
+    async fn delete_project(&self, project_id: String) -> Result<(), String> {
+        delete_md_project(self.token()?, project_id)
+            .await
+            .map_err(|e| format!("Couldn't delete project: {}", e))?;
+
+        Ok(())
+    }
+}
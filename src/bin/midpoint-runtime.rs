@@ -0,0 +1,124 @@
+// Plays a saved project without the editor:
+// midpoint-runtime <project dir> [saved state json] [--level <level id>]
+// the saved state defaults to project.json inside the project directory
+
+#[cfg(not(target_arch = "wasm32"))]
+fn main() {
+    use std::path::PathBuf;
+    use std::sync::Arc;
+    use std::time::Instant;
+    use winit::{
+        dpi::LogicalSize,
+        event::{DeviceEvent, ElementState, Event, KeyEvent, MouseButton, WindowEvent},
+        event_loop::{ControlFlow, EventLoop},
+        keyboard::{KeyCode, PhysicalKey},
+        window::WindowBuilder,
+    };
+
+    use midpoint_ui::contexts::saved::SavedState;
+    use midpoint_ui::renderer::Runtime::RuntimeRenderer;
+
+    let mut args = std::env::args().skip(1);
+    let mut positional = Vec::new();
+    let mut level_id = None;
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--level" => level_id = args.next(),
+            _ => positional.push(arg),
+        }
+    }
+
+    let project_dir = match positional.first() {
+        Some(project_dir) => PathBuf::from(project_dir),
+        None => {
+            eprintln!(
+                "Usage: midpoint-runtime <project dir> [saved state json] [--level <level id>]"
+            );
+            std::process::exit(1);
+        }
+    };
+    let state_path = positional
+        .get(1)
+        .map(PathBuf::from)
+        .unwrap_or_else(|| project_dir.join("project.json"));
+
+    let saved: SavedState = std::fs::read_to_string(&state_path)
+        .map_err(|e| format!("Couldn't read saved state {:?}: {}", state_path, e))
+        .and_then(|json| {
+            serde_json::from_str(&json).map_err(|e| format!("Invalid saved state: {}", e))
+        })
+        .unwrap_or_else(|e| {
+            eprintln!("{}", e);
+            std::process::exit(1);
+        });
+
+    let event_loop = EventLoop::new().expect("Couldn't create event loop");
+    let window = Arc::new(
+        WindowBuilder::new()
+            .with_title("Midpoint")
+            .with_inner_size(LogicalSize::new(1280, 720))
+            .build(&event_loop)
+            .expect("Couldn't create window"),
+    );
+
+    let mut renderer = futures::executor::block_on(RuntimeRenderer::new(window.clone()))
+        .expect("Couldn't create renderer");
+    if let Err(e) = renderer.load_level(&project_dir, &saved, level_id.as_deref()) {
+        eprintln!("{}", e);
+        std::process::exit(1);
+    }
+
+    // the camera only looks around while the right mouse button is held
+    let mut looking = false;
+    let mut last_frame = Instant::now();
+
+    event_loop.set_control_flow(ControlFlow::Poll);
+    event_loop
+        .run(move |event, target| match event {
+            Event::WindowEvent { event, .. } => match event {
+                WindowEvent::CloseRequested => target.exit(),
+                WindowEvent::Resized(size) => renderer.resize(size.width, size.height),
+                WindowEvent::KeyboardInput {
+                    event:
+                        KeyEvent {
+                            physical_key: PhysicalKey::Code(key_code),
+                            state,
+                            ..
+                        },
+                    ..
+                } => {
+                    if key_code == KeyCode::Escape {
+                        target.exit();
+                    }
+                    renderer.handle_key(key_code, state == ElementState::Pressed);
+                }
+                WindowEvent::MouseInput {
+                    button: MouseButton::Right,
+                    state,
+                    ..
+                } => looking = state == ElementState::Pressed,
+                WindowEvent::RedrawRequested => {
+                    let now = Instant::now();
+                    renderer.update((now - last_frame).as_secs_f32());
+                    last_frame = now;
+
+                    if let Err(e) = renderer.render() {
+                        eprintln!("{}", e);
+                        target.exit();
+                    }
+                }
+                _ => {}
+            },
+            Event::DeviceEvent {
+                event: DeviceEvent::MouseMotion { delta },
+                ..
+            } if looking => renderer.handle_mouse_motion(delta.0 as f32, delta.1 as f32),
+            Event::AboutToWait => renderer.window().request_redraw(),
+            _ => {}
+        })
+        .expect("Event loop failed");
+}
+
+// the runtime is native only, the wasm build is the editor
+#[cfg(target_arch = "wasm32")]
+fn main() {}
@@ -1,6 +1,17 @@
+pub mod AssetThumbnail;
+pub mod AutoSave;
+pub mod BrushControls;
 pub mod ComponentView;
+pub mod ExportTools;
 pub mod FileBrowser;
 pub mod FileViewer;
+pub mod FoliageTools;
+pub mod GenerationPrompt;
+pub mod JobRunner;
+pub mod JobsPanel;
+pub mod LandscapeGenerator;
+pub mod LevelTools;
+pub mod MaskPaintTools;
 pub mod MdButton;
 pub mod MdFileInput;
 pub mod MdIcon;
@@ -9,5 +20,11 @@ pub mod PrimaryView;
 pub mod ProjectSocket;
 pub mod SceneCanvas;
 pub mod SceneView;
+pub mod SkyTools;
+pub mod TerrainTools;
+pub mod TextureProcessor;
 pub mod TokenLoader;
+pub mod UndoShortcuts;
 pub mod ViewNav;
+pub mod WaterTools;
+pub mod inputs;
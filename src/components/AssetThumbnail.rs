@@ -0,0 +1,92 @@
+use serde::Serialize;
+use serde_wasm_bindgen::to_value;
+use wasm_bindgen_futures::spawn_local;
+use yew::prelude::*;
+
+use crate::contexts::local::LocalContextType;
+use crate::invoke::invoke_error;
+
+#[derive(Clone, PartialEq)]
+pub enum ThumbnailKind {
+    Model,
+    Landscape,
+}
+
+impl ThumbnailKind {
+    fn as_str(&self) -> &'static str {
+        match self {
+            ThumbnailKind::Model => "model",
+            ThumbnailKind::Landscape => "landscape",
+        }
+    }
+}
+
+#[derive(Clone, PartialEq, Properties)]
+pub struct AssetThumbnailProps {
+    pub kind: ThumbnailKind,
+    // landscapes are stored under their asset id, models only by filename
+    #[prop_or_default]
+    pub asset_id: String,
+    pub filename: String,
+}
+
+#[derive(Serialize)]
+struct GetAssetThumbnailParams {
+    projectId: String,
+    assetKind: String,
+    assetId: String,
+    assetFilename: String,
+}
+
+#[function_component]
+pub fn AssetThumbnail(props: &AssetThumbnailProps) -> Html {
+    let local_context = use_context::<LocalContextType>().expect("No LocalContext found");
+
+    let src = use_state(|| None::<String>);
+
+    {
+        let src = src.clone();
+
+        // the backend re-renders stale thumbnails, so refetching after a save picks up edits
+        use_effect_with(
+            (
+                local_context.current_project_id.clone(),
+                local_context.asset_revision,
+                props.clone(),
+            ),
+            move |(project_id, _, props)| {
+                src.set(None);
+
+                if let Some(projectId) = project_id.clone() {
+                    let params = to_value(&GetAssetThumbnailParams {
+                        projectId,
+                        assetKind: props.kind.as_str().to_string(),
+                        assetId: props.asset_id.clone(),
+                        assetFilename: props.filename.clone(),
+                    })
+                    .unwrap();
+
+                    spawn_local(async move {
+                        // a thumbnail that can't be rendered keeps the placeholder
+                        match crate::invoke::try_invoke("get_asset_thumbnail", params).await {
+                            Ok(result) => src.set(result.as_string()),
+                            Err(e) => web_sys::console::error_1(
+                                &invoke_error(e, "Couldn't load the asset thumbnail").into(),
+                            ),
+                        }
+                    });
+                }
+            },
+        );
+    }
+
+    html! {
+        <div class="asset-thumbnail">
+            if let Some(src) = (*src).clone() {
+                <img src={src} />
+            } else {
+                <div class="asset-thumbnail-placeholder" />
+            }
+        </div>
+    }
+}
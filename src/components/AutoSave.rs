@@ -0,0 +1,86 @@
+use wasm_bindgen::prelude::*;
+use wasm_bindgen::JsCast;
+use wasm_bindgen_futures::spawn_local;
+use yew::prelude::*;
+
+use crate::backend::{project_backend, ProjectBackend};
+use crate::contexts::local::{LocalAction, LocalContextType, SaveStatus};
+use crate::contexts::saved::SavedContextType;
+
+// quiet period after the last edit before the project is written
+const SAVE_DELAY_MS: i32 = 1500;
+
+#[function_component]
+pub fn AutoSave() -> Html {
+    let local_context = use_context::<LocalContextType>().expect("No LocalContext found");
+    let saved_context = use_context::<SavedContextType>().expect("No SavedContext found");
+
+    let pending_timeout = use_mut_ref(|| None::<i32>);
+    let latest_revision = use_mut_ref(|| 0);
+
+    *latest_revision.borrow_mut() = saved_context.revision;
+
+    {
+        let local_context = local_context.clone();
+        let saved_context = saved_context.clone();
+
+        // loading a project doesn't change the revision, so only edits made here get saved
+        use_effect_with(saved_context.revision, move |revision| {
+            let revision = *revision;
+            let window = web_sys::window().expect("Couldn't get window");
+
+            if let Some(project_id) = local_context.current_project_id.clone() {
+                if revision != 0 {
+                    local_context.dispatch(LocalAction::SetSaveStatus(SaveStatus::Unsaved));
+
+                    if let Some(handle) = pending_timeout.borrow_mut().take() {
+                        window.clear_timeout_with_handle(handle);
+                    }
+
+                    // the snapshot is taken now, so switching projects can't mix up contexts
+                    let context = (*saved_context).clone();
+                    let pending_timeout_inner = pending_timeout.clone();
+
+                    let save = Closure::once_into_js(move || {
+                        pending_timeout_inner.borrow_mut().take();
+
+                        local_context.dispatch(LocalAction::SetSaveStatus(SaveStatus::Saving));
+
+                        web_sys::console::log_1(&"Saving project...".into());
+
+                        spawn_local(async move {
+                            let result = project_backend(&local_context)
+                                .update_project(project_id, None, &context)
+                                .await;
+
+                            // a newer edit is already waiting for its own save
+                            if *latest_revision.borrow() != revision {
+                                return;
+                            }
+
+                            match result {
+                                Ok(()) => local_context
+                                    .dispatch(LocalAction::SetSaveStatus(SaveStatus::Saved)),
+                                Err(e) => local_context
+                                    .dispatch(LocalAction::SetSaveStatus(SaveStatus::Failed(e))),
+                            }
+                        });
+                    });
+
+                    let handle = window
+                        .set_timeout_with_callback_and_timeout_and_arguments_0(
+                            save.unchecked_ref(),
+                            SAVE_DELAY_MS,
+                        )
+                        .expect("Couldn't schedule save");
+
+                    *pending_timeout.borrow_mut() = Some(handle);
+                }
+            }
+        });
+    }
+
+    html! {
+        <></>
+    }
+}
@@ -0,0 +1,35 @@
+use yew::prelude::*;
+
+use crate::components::inputs::range_callback;
+
+// size and feel of a landscape brush, shared by sculpting and mask painting
+#[derive(Clone, PartialEq, Properties)]
+pub struct BrushControlsProps {
+    pub radius: UseStateHandle<f32>,
+    pub strength: UseStateHandle<f32>,
+    pub falloff: UseStateHandle<f32>,
+}
+
+#[function_component]
+pub fn BrushControls(props: &BrushControlsProps) -> Html {
+    let radius = props.radius.clone();
+    let strength = props.strength.clone();
+    let falloff = props.falloff.clone();
+
+    html! {
+        <>
+            <div>
+                <label>{format!("Radius ({:.0})", *radius)}</label>
+                <input type="range" min="1" max="200" step="1" value={radius.to_string()} oninput={range_callback(radius.clone())} />
+            </div>
+            <div>
+                <label>{format!("Strength ({:.2})", *strength)}</label>
+                <input type="range" min="0" max="1" step="0.01" value={strength.to_string()} oninput={range_callback(strength.clone())} />
+            </div>
+            <div>
+                <label>{format!("Falloff ({:.2})", *falloff)}</label>
+                <input type="range" min="0" max="1" step="0.01" value={falloff.to_string()} oninput={range_callback(falloff.clone())} />
+            </div>
+        </>
+    }
+}
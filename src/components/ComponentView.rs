@@ -1,17 +1,21 @@
 use lazy_static::lazy_static;
 use std::sync::Arc;
-use web_sys::HtmlSelectElement;
+use uuid::Uuid;
+use web_sys::{HtmlInputElement, HtmlSelectElement};
 use yew::prelude::*;
 
 use crate::contexts::{
-    local::LocalContextType,
+    local::{LocalAction, LocalContextType},
     saved::{
         ComponentData, ComponentKind, File, GenericProperties, LandscapeData,
         LandscapeTextureKinds, SavedAction, SavedContextType,
     },
 };
 
-use crate::renderer::core::handle_add_landscape_texture;
+use crate::components::FoliageTools::FoliageTools;
+use crate::components::MaskPaintTools::MaskPaintTools;
+use crate::components::MdButton::{MdButton, MdButtonKind, MdButtonVariant};
+use crate::components::TerrainTools::TerrainTools;
 
 const EMPTY_COMPONENT_DATA: ComponentData = ComponentData {
     id: String::new(),
@@ -22,6 +26,7 @@ const EMPTY_COMPONENT_DATA: ComponentData = ComponentData {
     },
     landscape_properties: None,
     model_properties: None,
+    foliage_properties: None,
 };
 
 const EMPTY_LANDSCAPE_ASSET_DATA: LandscapeData = LandscapeData {
@@ -51,6 +56,23 @@ pub fn component_view() -> Html {
     web_sys::console::log_1(&format!("selected_component_id..., {}", selected_component_id).into());
     web_sys::console::log_1(&format!("saved_context.levels: {:#?}", saved_context.levels).into());
 
+    // edits go to whichever level holds the selected component
+    let selected_level_id = saved_context
+        .levels
+        .as_ref()
+        .and_then(|levels| {
+            levels.iter().find(|level| {
+                level
+                    .components
+                    .as_ref()
+                    .unwrap_or(&NONE_VEC)
+                    .iter()
+                    .any(|component| component.id == selected_component_id)
+            })
+        })
+        .map(|level| level.id.clone())
+        .unwrap_or_default();
+
     let selected_component_data = saved_context.levels.as_ref().and_then(|levels| {
         levels
             .iter()
@@ -84,6 +106,59 @@ pub fn component_view() -> Html {
 
     let available_textures = saved_context.textures.clone().unwrap_or(Vec::new());
 
+    // applied on change rather than per keystroke, so a rename is a single undo step
+    let on_rename = {
+        let saved_context = saved_context.clone();
+        let selected_level_id = selected_level_id.clone();
+        let selected_component_id = selected_component_id.clone();
+
+        Callback::from(move |e: Event| {
+            let input: HtmlInputElement = e.target_unchecked_into();
+
+            saved_context.dispatch(SavedAction::RenameComponent(
+                selected_level_id.clone(),
+                selected_component_id.clone(),
+                input.value(),
+            ));
+        })
+    };
+
+    // the scene view loads the copy and unloads deleted components
+    let on_duplicate = {
+        let local_context = local_context.clone();
+        let saved_context = saved_context.clone();
+        let selected_level_id = selected_level_id.clone();
+        let selected_component_id = selected_component_id.clone();
+
+        Callback::from(move |_| {
+            let new_component_id = Uuid::new_v4().to_string();
+
+            saved_context.dispatch(SavedAction::DuplicateComponent(
+                selected_level_id.clone(),
+                selected_component_id.clone(),
+                new_component_id.clone(),
+            ));
+
+            local_context.dispatch(LocalAction::SetSelectedComponent(new_component_id));
+        })
+    };
+
+    let on_delete = {
+        let local_context = local_context.clone();
+        let saved_context = saved_context.clone();
+        let selected_level_id = selected_level_id.clone();
+        let selected_component_id = selected_component_id.clone();
+
+        Callback::from(move |_| {
+            saved_context.dispatch(SavedAction::RemoveComponent(
+                selected_level_id.clone(),
+                selected_component_id.clone(),
+            ));
+
+            local_context.dispatch(LocalAction::ClearSelectedComponent);
+        })
+    };
+
     // components = instances
     // assets = originals
 
@@ -95,6 +170,34 @@ pub fn component_view() -> Html {
                     <div class="view-row">
                         <div class="panel">
                             <span>{"Generic Properties"}</span>
+                            <div>
+                                <label>{"Name"}</label>
+                                <input
+                                    type="text"
+                                    value={selected_component_data.generic_properties.name.clone()}
+                                    onchange={on_rename}
+                                />
+                            </div>
+                            <div class="btn-row">
+                                <MdButton
+                                    label="Duplicate"
+                                    icon={""}
+                                    on_click={on_duplicate}
+                                    disabled={false}
+                                    loading={false}
+                                    kind={MdButtonKind::SmallShort}
+                                    variant={MdButtonVariant::Light}
+                                />
+                                <MdButton
+                                    label="Delete"
+                                    icon={""}
+                                    on_click={on_delete}
+                                    disabled={false}
+                                    loading={false}
+                                    kind={MdButtonKind::SmallShort}
+                                    variant={MdButtonVariant::Negative}
+                                />
+                            </div>
                         </div>
                         if selected_component_data.kind == Some(ComponentKind::Landscape) {
                             <div class="panel">
@@ -107,45 +210,25 @@ pub fn component_view() -> Html {
                                         <label>{"Primary Texture"}</label>
                                         <select
                                             onchange={{
-                                                let local_context = local_context.clone();
-                                                let available_textures = available_textures.clone();
+                                                let selected_level_id = selected_level_id.clone();
                                                 let selected_component_id = selected_component_id.clone();
-                                                let asset_id = landscape_asset_data.clone().id;
                                                 let saved_context = saved_context.clone();
 
                                                 Callback::from(move |e: Event| {
-                                                    let local_context = local_context.clone();
-                                                    let available_textures = available_textures.clone();
+                                                    let selected_level_id = selected_level_id.clone();
                                                     let selected_component_id = selected_component_id.clone();
                                                     let saved_context = saved_context.clone();
-                                                    let asset_id = asset_id.clone();
                                                     let input = e.target_dyn_into::<HtmlSelectElement>();
 
                                                     if let Some(input) = input {
                                                         saved_context.dispatch(
                                                             SavedAction::SetLandscapeTexture(
+                                                                selected_level_id.clone(),
                                                                 selected_component_id.clone(),
                                                                 LandscapeTextureKinds::Primary,
                                                                 input.value()
                                                             )
                                                         );
-
-                                                        let landscape = saved_context.landscapes.as_ref().expect("No landscapes?").iter().find(|l| l.id == asset_id);
-
-                                                        if let Some(texture) = available_textures.clone().iter().find(|t| t.id.clone() == input.value()) {
-                                                            handle_add_landscape_texture(
-                                                                local_context.current_project_id.clone().expect("Couldn't get project id"),
-                                                                selected_component_id.clone(),
-                                                                asset_id.clone(),
-                                                                texture.fileName.clone(),
-                                                                "Primary".to_string(),
-                                                                landscape.clone().expect("No landscape?").heightmap.clone().expect("No heightmap?").fileName
-                                                            );
-                                                        } else {
-                                                            web_sys::console::error_1(
-                                                                &"Couldn't add landscape texture".into(),
-                                                            );
-                                                        }
                                                     }
                                                 })
                                             }}
@@ -162,46 +245,25 @@ pub fn component_view() -> Html {
                                         <label>{"RockMap Texture"}</label>
                                         <select
                                             onchange={{
-                                                let local_context = local_context.clone();
-                                                let available_textures = available_textures.clone();
+                                                let selected_level_id = selected_level_id.clone();
                                                 let selected_component_id = selected_component_id.clone();
-                                                let asset_id = landscape_asset_data.clone().id;
                                                 let saved_context = saved_context.clone();
 
-
                                                 Callback::from(move |e: Event| {
-                                                    let local_context = local_context.clone();
-                                                    let available_textures = available_textures.clone();
+                                                    let selected_level_id = selected_level_id.clone();
                                                     let selected_component_id = selected_component_id.clone();
                                                     let saved_context = saved_context.clone();
-                                                    let asset_id = asset_id.clone();
                                                     let input = e.target_dyn_into::<HtmlSelectElement>();
 
                                                     if let Some(input) = input {
                                                         saved_context.dispatch(
                                                             SavedAction::SetLandscapeTexture(
+                                                                selected_level_id.clone(),
                                                                 selected_component_id.clone(),
                                                                 LandscapeTextureKinds::Rockmap,
                                                                 input.value()
                                                             )
                                                         );
-
-                                                        let landscape = saved_context.landscapes.as_ref().expect("No landscapes?").iter().find(|l| l.id == asset_id);
-
-                                                        if let Some(texture) = available_textures.clone().iter().find(|t| t.id.clone() == input.value()) {
-                                                            handle_add_landscape_texture(
-                                                                local_context.current_project_id.clone().expect("Couldn't get project id"),
-                                                                selected_component_id.clone(),
-                                                                asset_id.clone(),
-                                                                texture.fileName.clone(),
-                                                                "Rockmap".to_string(),
-                                                                landscape.clone().expect("No landscape?").rockmap.clone().expect("No rockmap?").fileName
-                                                            );
-                                                        } else {
-                                                            web_sys::console::error_1(
-                                                                &"Couldn't add landscape texture".into(),
-                                                            );
-                                                        }
                                                     }
                                                 })
                                             }}
@@ -218,45 +280,25 @@ pub fn component_view() -> Html {
                                         <label>{"Soil Texture"}</label>
                                         <select
                                             onchange={{
-                                                let local_context = local_context.clone();
-                                                let available_textures = available_textures.clone();
+                                                let selected_level_id = selected_level_id.clone();
                                                 let selected_component_id = selected_component_id.clone();
-                                                let asset_id = landscape_asset_data.clone().id;
                                                 let saved_context = saved_context.clone();
 
                                                 Callback::from(move |e: Event| {
-                                                    let local_context = local_context.clone();
-                                                    let available_textures = available_textures.clone();
+                                                    let selected_level_id = selected_level_id.clone();
                                                     let selected_component_id = selected_component_id.clone();
                                                     let saved_context = saved_context.clone();
-                                                    let asset_id = asset_id.clone();
                                                     let input = e.target_dyn_into::<HtmlSelectElement>();
 
                                                     if let Some(input) = input {
                                                         saved_context.dispatch(
                                                             SavedAction::SetLandscapeTexture(
+                                                                selected_level_id.clone(),
                                                                 selected_component_id.clone(),
                                                                 LandscapeTextureKinds::Soil,
                                                                 input.value()
                                                             )
                                                         );
-
-                                                        let landscape = saved_context.landscapes.as_ref().expect("No landscapes?").iter().find(|l| l.id == asset_id);
-
-                                                        if let Some(texture) = available_textures.clone().iter().find(|t| t.id.clone() == input.value()) {
-                                                            handle_add_landscape_texture(
-                                                                local_context.current_project_id.clone().expect("Couldn't get project id"),
-                                                                selected_component_id.clone(),
-                                                                asset_id.clone(),
-                                                                texture.fileName.clone(),
-                                                                "Soil".to_string(),
-                                                                landscape.clone().expect("No landscape?").soil.clone().expect("No soil?").fileName
-                                                            );
-                                                        } else {
-                                                            web_sys::console::error_1(
-                                                                &"Couldn't add landscape texture".into(),
-                                                            );
-                                                        }
                                                     }
                                                 })
                                             }}
@@ -271,6 +313,30 @@ pub fn component_view() -> Html {
                                     </div>
                                 </div>
                             </div>
+                            <TerrainTools
+                                landscape_component_id={selected_component_data.id.clone()}
+                                landscape_asset_id={landscape_asset_data.id.clone()}
+                                heightmap_filename={landscape_asset_data.heightmap.clone().unwrap_or_default().fileName}
+                            />
+                            <MaskPaintTools
+                                landscape_component_id={selected_component_data.id.clone()}
+                                landscape_asset_id={landscape_asset_data.id.clone()}
+                                primary_mask_filename={landscape_asset_data.heightmap.clone().unwrap_or_default().fileName}
+                                rockmap_mask_filename={landscape_asset_data.rockmap.clone().unwrap_or_default().fileName}
+                                soil_mask_filename={landscape_asset_data.soil.clone().unwrap_or_default().fileName}
+                            />
+                            <FoliageTools
+                                key={format!("new-foliage-{}", selected_component_data.id)}
+                                landscape_component_id={selected_component_data.id.clone()}
+                                foliage={None}
+                            />
+                        }
+                        if selected_component_data.kind == Some(ComponentKind::Foliage) {
+                            <FoliageTools
+                                key={selected_component_data.id.clone()}
+                                landscape_component_id={selected_component_data.foliage_properties.clone().map(|p| p.landscape_component_id).unwrap_or_default()}
+                                foliage={Some(selected_component_data.clone())}
+                            />
                         }
                         if selected_component_data.kind == Some(ComponentKind::Model) {
                             <div class="panel">
@@ -0,0 +1,113 @@
+use gloo_utils::format::JsValueSerdeExt;
+use serde::{Deserialize, Serialize};
+use serde_wasm_bindgen::to_value;
+use wasm_bindgen_futures::spawn_local;
+use yew::prelude::*;
+
+use crate::components::MdButton::{MdButton, MdButtonKind, MdButtonVariant};
+use crate::contexts::local::LocalContextType;
+use crate::contexts::saved::{SavedContextType, SavedState};
+
+#[derive(Serialize)]
+struct ExportProjectParams {
+    projectId: String,
+    savedState: SavedState,
+}
+
+#[derive(Deserialize)]
+struct ExportedAsset {
+    kind: String,
+    path: String,
+}
+
+#[derive(Deserialize)]
+struct ExportSummary {
+    exportDir: String,
+    assets: Vec<ExportedAsset>,
+}
+
+#[function_component]
+pub fn ExportTools() -> Html {
+    let local_context = use_context::<LocalContextType>().expect("No LocalContext found");
+    let saved_context = use_context::<SavedContextType>().expect("No SavedContext found");
+
+    let loading = use_state(|| false);
+    let summary = use_state(|| None::<ExportSummary>);
+    let error = use_state(|| None::<String>);
+
+    let on_export = {
+        let local_context = local_context.clone();
+        let saved_context = saved_context.clone();
+        let loading = loading.clone();
+        let summary = summary.clone();
+        let error = error.clone();
+
+        Callback::from(move |_| {
+            let loading = loading.clone();
+            let summary = summary.clone();
+            let error = error.clone();
+
+            let params = to_value(&ExportProjectParams {
+                projectId: local_context
+                    .current_project_id
+                    .clone()
+                    .expect("Couldn't get project id"),
+                savedState: (*saved_context).clone(),
+            })
+            .unwrap();
+
+            loading.set(true);
+            summary.set(None);
+            error.set(None);
+
+            web_sys::console::log_1(&"Exporting project...".into());
+
+            spawn_local(async move {
+                match crate::invoke::try_invoke("export_project", params).await {
+                    Ok(result) => match result.into_serde::<ExportSummary>() {
+                        Ok(result) => summary.set(Some(result)),
+                        Err(e) => error.set(Some(format!("Invalid export summary: {}", e))),
+                    },
+                    Err(e) => error.set(Some(
+                        e.as_string()
+                            .unwrap_or_else(|| "Export failed".to_string()),
+                    )),
+                }
+
+                loading.set(false);
+            });
+        })
+    };
+
+    html! {
+        <div class="panel export-tools">
+            <span>{"Export"}</span>
+            <p>{"Bundles every asset used by the project's levels into a folder the native runtime can play."}</p>
+            <MdButton
+                label="Export Project"
+                icon={""}
+                on_click={on_export}
+                disabled={*loading}
+                loading={*loading}
+                kind={MdButtonKind::SmallShort}
+                variant={MdButtonVariant::Green}
+            />
+            if let Some(summary) = (*summary).as_ref() {
+                <div>
+                    <label>{format!("Exported {} files to", summary.assets.len())}</label>
+                    <pre>{summary.exportDir.clone()}</pre>
+                    <ul>
+                        {summary.assets.iter().map(|asset| {
+                            html!{
+                                <li>{format!("{}: {}", asset.kind, asset.path)}</li>
+                            }
+                        }).collect::<Html>()}
+                    </ul>
+                </div>
+            }
+            if let Some(error) = (*error).as_ref() {
+                <pre>{error.clone()}</pre>
+            }
+        </div>
+    }
+}
@@ -3,27 +3,27 @@ use serde::Serialize;
 use serde_wasm_bindgen::to_value;
 use std::{ops::Deref, path::PathBuf};
 use uuid::Uuid;
-use wasm_bindgen::closure::Closure;
-use wasm_bindgen::JsCast;
 use wasm_bindgen_futures::spawn_local;
-use web_sys::HtmlTextAreaElement;
-use web_sys::{Event, FileReader, HtmlInputElement};
+use web_sys::{FileReader, HtmlInputElement};
 use yew::prelude::*;
 
 use crate::contexts::local::LocalAction;
 use crate::contexts::saved::{
-    ComponentData, ComponentKind, GenericProperties, LandscapeData, LandscapeProperties,
-    SavedAction, SavedContextType,
+    AssetRef, ComponentData, ComponentKind, GeneratedFileKind, GenericProperties, LandscapeData,
+    LandscapeProperties, ModelProperties, SavedAction, SavedContextType,
 };
-use crate::gql::generateTexture::generate_texture;
-use crate::renderer::core::handle_add_landscape;
 use crate::{
+    components::AssetThumbnail::{AssetThumbnail, ThumbnailKind},
+    components::GenerationPrompt::GenerationPrompt,
+    components::LandscapeGenerator::LandscapeGenerator,
     components::MdButton::{MdButton, MdButtonKind, MdButtonVariant},
     components::MdFileInput::MdFileInput,
-    contexts::{local::LocalContextType, saved::File},
-    gql::generateConcept::generate_concept,
-    gql::generateModel::generate_model,
-    renderer::core::handle_add_model,
+    components::TextureProcessor::TextureProcessor,
+    contexts::{
+        jobs::{GenerationJob, JobsAction, JobsContextType},
+        local::LocalContextType,
+        saved::File,
+    },
 };
 
 #[derive(Clone, PartialEq)]
@@ -48,27 +48,6 @@ pub struct FileBrowserProps {
     pub landscapes: Option<Vec<LandscapeData>>,
 }
 
-#[derive(Serialize)]
-struct SaveConceptParams {
-    projectId: String,
-    conceptBase64: String,
-    conceptFilename: String,
-}
-
-#[derive(Serialize)]
-struct SaveTextureParams {
-    projectId: String,
-    textureBase64: String,
-    textureFilename: String,
-}
-
-#[derive(Serialize)]
-struct SaveModelParams {
-    projectId: String,
-    modelBase64: String,
-    modelFilename: String,
-}
-
 #[derive(Serialize)]
 struct SaveLandscapeParams {
     projectId: String,
@@ -102,10 +81,14 @@ fn change_extension_to_glb(filename: &str) -> String {
 pub fn FileBrowser(props: &FileBrowserProps) -> Html {
     let local_context = use_context::<LocalContextType>().expect("No LocalContext found");
     let saved_context = use_context::<SavedContextType>().expect("No SavedContext found");
+    let jobs_context = use_context::<JobsContextType>().expect("No JobsContext found");
 
-    let loading = use_state(|| false);
+    // new components are placed in the level being edited
+    let level_id = saved_context
+        .current_level(local_context.current_level_id.as_ref())
+        .map(|level| level.id.clone());
 
-    let prompt_value = use_state(String::default);
+    let loading = use_state(|| false);
 
     let landscape_filename = use_state(|| "".to_string());
     let landscape_base64 = use_state(|| "".to_string());
@@ -116,90 +99,32 @@ pub fn FileBrowser(props: &FileBrowserProps) -> Html {
 
     let loading = use_state(|| false);
 
-    let handle_prompt_change = {
-        let prompt_value = prompt_value.clone();
-
-        Callback::from(move |e: Event| {
-            let input = e.target_dyn_into::<HtmlTextAreaElement>();
-
-            if let Some(input) = input {
-                prompt_value.set(input.value());
-            }
-        })
+    // shows the file's metadata in the FileViewer
+    let details_button = |kind: GeneratedFileKind, filename: String| {
+        let local_context = local_context.clone();
+
+        html! {
+            <MdButton
+                label="Details"
+                icon={""}
+                on_click={Callback::from(move |_| {
+                    local_context.dispatch(LocalAction::SetSelectedAsset(AssetRef {
+                        kind,
+                        filename: filename.clone(),
+                    }));
+                })}
+                disabled={false}
+                loading={false}
+                kind={MdButtonKind::SmallShort}
+                variant={MdButtonVariant::Light}
+            />
+        }
     };
 
-    let callback = {
-        let loading = loading.clone();
-
-        Callback::from(move |_| {
-            loading.set(false);
-        })
-    };
-
-    // Convert the callback to a js_sys::Function
-    let js_callback: js_sys::Function = Closure::wrap(Box::new(move || {
-        callback.emit(());
-    }) as Box<dyn FnMut()>)
-    .into_js_value()
-    .unchecked_into();
-
     html! {
         <section class="file-browser">
             if props.variant == FileVariant::Concept {
-                <div class="file-prompt">
-                    <label>{"Describe your concept"}</label>
-                    <textarea onchange={handle_prompt_change.clone()} rows="3">{(*prompt_value).clone()}</textarea>
-                    <MdButton
-                        label="Generate"
-                        icon={""}
-                        on_click={Callback::from({
-                            let prompt_value = prompt_value.clone();
-                            let local_context = local_context.clone();
-                            let loading = loading.clone();
-
-                            move |_| {
-                                let prompt_value = prompt_value.clone();
-                                let local_context = local_context.clone();
-                                let loading = loading.clone();
-
-                                loading.set(true);
-
-                                web_sys::console::log_1(&"Generating concept...".into());
-
-                                spawn_local(async move {
-                                    // generate concept via GraphQL or Socket
-                                    let concept_data = generate_concept(local_context.token.clone().expect("Failed token fetch"), (*prompt_value).clone()).await;
-
-                                    web_sys::console::log_1(&"Concept generated, saving now...".into());
-
-                                    let conceptBase64 = concept_data.expect("Couldn't unwrap concept data").generateConcept;
-
-                                    // determine filename
-                                    let concept_prompt_str: String = (*prompt_value).clone();
-                                    let conceptFilename = getFilename(concept_prompt_str);
-                                    let conceptFilename = conceptFilename + ".png";
-
-                                    web_sys::console::log_1(&conceptFilename.clone().into());
-
-                                    // save as image inside folder within sync folder: /CommonOSFiles/midpoint/projects/project_id/concepts/
-                                    let projectId = local_context.current_project_id.clone().expect("No project selected?");
-                                    let params = to_value(&SaveConceptParams {
-                                        projectId,
-                                        conceptBase64,
-                                        conceptFilename
-                                    }).unwrap();
-                                    let result = crate::app::invoke("save_concept", params).await;
-
-                                    loading.set(false);
-                                });
-                            }
-                        })}
-                        disabled={*loading}
-                        loading={*loading}
-                        kind={MdButtonKind::SmallShort}
-                        variant={MdButtonVariant::Green}
-                    />
-                </div>
+                <GenerationPrompt label="Describe your concept" kind={GeneratedFileKind::Concept} />
             }
             if props.kind == FileKind::Landscape {
                 <>
@@ -275,7 +200,7 @@ pub fn FileBrowser(props: &FileBrowserProps) -> Html {
                                         soilFilename,
                                         soilBase64,
                                     }).unwrap();
-                                    let result = crate::app::invoke("save_landscape", params).await;
+                                    let result = crate::invoke::invoke("save_landscape", params).await;
 
                                     loading.set(false);
                                 });
@@ -287,7 +212,7 @@ pub fn FileBrowser(props: &FileBrowserProps) -> Html {
                         variant={MdButtonVariant::Green}
                     />
 
-
+                    <LandscapeGenerator />
 
                     <div class="file-grid">
                         {
@@ -301,6 +226,7 @@ pub fn FileBrowser(props: &FileBrowserProps) -> Html {
 
                                 html!{
                                     <div class="file-item" key={landscape_id.clone()}>
+                                        <AssetThumbnail kind={ThumbnailKind::Landscape} asset_id={landscape_id.clone()} filename={heightmapFilename.clone()} />
                                         <span>{heightmapFilename.clone()}</span>
                                         if hasRockmap {
                                             <span>{"Has RockMap"}</span>
@@ -314,26 +240,26 @@ pub fn FileBrowser(props: &FileBrowserProps) -> Html {
                                             on_click={Callback::from({
                                                 let local_context = local_context.clone();
                                                 let saved_context = saved_context.clone();
-                                                let loading = loading.clone();
-                                                let js_callback = js_callback.clone();
-                                                let fileName = heightmapFilename.clone();
+                                                let level_id = level_id.clone();
 
                                                 move |_| {
+                                                    let level_id = match level_id.clone() {
+                                                        Some(level_id) => level_id,
+                                                        None => {
+                                                            web_sys::console::error_1(&"Add a level before adding components".into());
+                                                            return;
+                                                        }
+                                                    };
+
                                                     let local_context = local_context.clone();
                                                     let saved_context = saved_context.clone();
-                                                    let loading = loading.clone();
-                                                    let js_callback = js_callback.clone();
 
                                                     web_sys::console::log_1(&"Adding landscape to scene...".into());
 
-                                                    loading.set(true);
-
-                                                    let projectId = local_context.current_project_id.clone().expect("No project selected?");
-                                                    let landscapeFilename = fileName.clone();
                                                     let landscapeComponentId = Uuid::new_v4().to_string();
 
                                                     // add to `levels.components` in SavedContext
-                                                    saved_context.dispatch(SavedAction::AddComponent(ComponentData {
+                                                    saved_context.dispatch(SavedAction::AddComponent(level_id, ComponentData {
                                                         id: landscapeComponentId.clone(),
                                                         kind: Some(ComponentKind::Landscape),
                                                         asset_id: landscape_id.clone(),
@@ -346,14 +272,12 @@ pub fn FileBrowser(props: &FileBrowserProps) -> Html {
                                                             rockmap_texture_id: None,
                                                             soil_texture_id: None
                                                         }),
-                                                        model_properties: None
+                                                        model_properties: None,
+                                                        foliage_properties: None
                                                     }));
 
-                                                    // update selected_component_id in LocalContext
+                                                    // update selected_component_id in LocalContext, the scene view renders it from the level
                                                     local_context.dispatch(LocalAction::SetSelectedComponent(landscapeComponentId.clone()));
-
-                                                    // actually render the landscape in wgpu
-                                                    handle_add_landscape(projectId, landscape_id.clone(), landscapeComponentId.clone(), landscapeFilename, js_callback);
                                                 }
                                             })}
                                             disabled={*loading}
@@ -377,55 +301,41 @@ pub fn FileBrowser(props: &FileBrowserProps) -> Html {
                                 <div class="file-item" key={file.clone().id}>
                                     <img src={file.clone().cloudfrontUrl} />
                                     <span>{file.clone().fileName}</span>
+                                    {details_button(GeneratedFileKind::Concept, file.fileName.clone())}
                                     <MdButton
                                         label="Generate Model"
                                         icon={""}
                                         on_click={Callback::from({
                                             let local_context = local_context.clone();
-                                            let loading = loading.clone();
-                                            let file = file.clone();
+                                            let jobs_context = jobs_context.clone();
                                             let cloudfrontUrl = file.cloudfrontUrl.clone();
                                             let fileName = file.fileName.clone();
 
                                             move |_| {
-                                                let local_context = local_context.clone();
-                                                let loading = loading.clone();
-                                                let cloudfrontUrl = cloudfrontUrl.clone();
-                                                let fileName = fileName.clone();
-
-                                                loading.set(true);
-                                                // local_context.dispatch(LocalAction::SetRoute("/".to_string()));
-
-                                                web_sys::console::log_1(&"Generating model...".into());
-
-                                                spawn_local(async move {
-
-                                                    let model_data = generate_model(local_context.token.clone().expect("Failed token fetch"), cloudfrontUrl).await;
+                                                web_sys::console::log_1(&"Queueing model generation...".into());
 
-                                                    web_sys::console::log_1(&"Concept generated, saving now...".into());
-
-                                                    let modelBase64 = model_data.expect("Couldn't unwrap model data").generateModel;
-
-                                                    // determine filename
-                                                    let modelFilename = change_extension_to_glb(&fileName);
-
-                                                    web_sys::console::log_1(&modelFilename.clone().into());
+                                                // determine filename
+                                                let modelFilename = change_extension_to_glb(&fileName);
+                                                let projectId = local_context.current_project_id.clone().expect("No project selected?");
 
-                                                    // save as image inside folder within sync folder: /CommonOSFiles/midpoint/projects/project_id/concepts/
-                                                    let projectId = local_context.current_project_id.clone().expect("No project selected?");
-                                                    let params = to_value(&SaveModelParams {
+                                                // the concept is recorded as the model's parent
+                                                jobs_context.dispatch(JobsAction::Enqueue(GenerationJob {
+                                                    parent: Some(AssetRef {
+                                                        kind: GeneratedFileKind::Concept,
+                                                        filename: fileName.clone(),
+                                                    }),
+                                                    ..GenerationJob::new(
+                                                        GeneratedFileKind::Model,
                                                         projectId,
-                                                        modelBase64,
+                                                        cloudfrontUrl.clone(),
+                                                        None,
                                                         modelFilename
-                                                    }).unwrap();
-                                                    let result = crate::app::invoke("save_model", params).await;
-
-                                                    loading.set(false);
-                                                });
+                                                    )
+                                                }));
                                             }
                                         })}
-                                        disabled={*loading}
-                                        loading={*loading}
+                                        disabled={false}
+                                        loading={false}
                                         kind={MdButtonKind::SmallShort}
                                         variant={MdButtonVariant::Green}
                                     />
@@ -442,26 +352,48 @@ pub fn FileBrowser(props: &FileBrowserProps) -> Html {
                             let cloudfrontUrl = file.cloudfrontUrl.clone();
 
                             html!{
-                                <div class="file-item" key={file.id}>
+                                <div class="file-item" key={file.id.clone()}>
+                                    <AssetThumbnail kind={ThumbnailKind::Model} filename={file.fileName.clone()} />
                                     <span>{file.fileName.clone()}</span>
+                                    {details_button(GeneratedFileKind::Model, file.fileName.clone())}
                                     <MdButton
                                         label="Add to Scene"
                                         icon={""}
                                         on_click={Callback::from({
                                             let local_context = local_context.clone();
-                                            let loading = loading.clone();
-                                            let fileName = file.fileName.clone();
+                                            let saved_context = saved_context.clone();
+                                            let modelId = file.id.clone();
+                                            let level_id = level_id.clone();
 
                                             move |_| {
+                                                let level_id = match level_id.clone() {
+                                                    Some(level_id) => level_id,
+                                                    None => {
+                                                        web_sys::console::error_1(&"Add a level before adding components".into());
+                                                        return;
+                                                    }
+                                                };
+
                                                 let local_context = local_context.clone();
-                                                let loading = loading.clone();
 
                                                 web_sys::console::log_1(&"Adding model to scene...".into());
 
-                                                let projectId = local_context.current_project_id.clone().expect("No project selected?");
-                                                let modelFilename = fileName.clone();
-
-                                                handle_add_model(projectId, modelFilename);
+                                                let modelComponentId = Uuid::new_v4().to_string();
+
+                                                // the scene view loads the model once it's part of the level
+                                                saved_context.dispatch(SavedAction::AddComponent(level_id, ComponentData {
+                                                    id: modelComponentId.clone(),
+                                                    kind: Some(ComponentKind::Model),
+                                                    asset_id: modelId.clone(),
+                                                    generic_properties: GenericProperties {
+                                                        name: "New Model Component".to_string()
+                                                    },
+                                                    landscape_properties: None,
+                                                    model_properties: Some(ModelProperties {}),
+                                                    foliage_properties: None
+                                                }));
+
+                                                local_context.dispatch(LocalAction::SetSelectedComponent(modelComponentId.clone()));
                                             }
                                         })}
                                         disabled={*loading}
@@ -476,62 +408,7 @@ pub fn FileBrowser(props: &FileBrowserProps) -> Html {
                 </div>
             }
             if props.kind == FileKind::Texture {
-                <>
-                    <div class="file-prompt">
-                        <label>{"Describe your texture"}</label>
-                        <textarea onchange={handle_prompt_change.clone()} rows="3">{(*prompt_value).clone()}</textarea>
-                        <MdButton
-                            label="Generate"
-                            icon={""}
-                            on_click={Callback::from({
-                                let prompt_value = prompt_value.clone();
-                                let local_context = local_context.clone();
-                                let loading = loading.clone();
-
-                                move |_| {
-                                    let prompt_value = prompt_value.clone();
-                                    let local_context = local_context.clone();
-                                    let loading = loading.clone();
-
-                                    loading.set(true);
-
-                                    web_sys::console::log_1(&"Generating texture...".into());
-
-                                    spawn_local(async move {
-                                        // generate concept via GraphQL or Socket
-                                        let texture_data = generate_texture(local_context.token.clone().expect("Failed token fetch"), (*prompt_value).clone()).await;
-
-                                        web_sys::console::log_1(&"Texture generated, saving now...".into());
-
-                                        let textureBase64 = texture_data.expect("Couldn't unwrap texture data").generateTexture;
-
-                                        // determine filename
-                                        let texture_prompt_str: String = (*prompt_value).clone();
-                                        let textureFilename = getFilename(texture_prompt_str);
-                                        let textureFilename = textureFilename + ".png";
-
-                                        web_sys::console::log_1(&textureFilename.clone().into());
-
-                                        // save as image inside folder within sync folder: /CommonOSFiles/midpoint/projects/project_id/concepts/
-                                        let projectId = local_context.current_project_id.clone().expect("No project selected?");
-                                        let params = to_value(&SaveTextureParams {
-                                            projectId,
-                                            textureBase64,
-                                            textureFilename
-                                        }).unwrap();
-                                        let result = crate::app::invoke("save_texture", params).await;
-
-                                        loading.set(false);
-                                    });
-                                }
-                            })}
-                            disabled={*loading}
-                            loading={*loading}
-                            kind={MdButtonKind::SmallShort}
-                            variant={MdButtonVariant::Green}
-                        />
-                    </div>
-                </>
+                <GenerationPrompt label="Describe your texture" kind={GeneratedFileKind::Texture} />
                 <div class="file-grid">
                     {
                         props.files.clone().unwrap_or_default().into_iter().map(|file| {
@@ -540,6 +417,8 @@ pub fn FileBrowser(props: &FileBrowserProps) -> Html {
                                 <div class="file-item" key={file.clone().id}>
                                     <img src={file.clone().cloudfrontUrl} />
                                     <span>{file.clone().fileName}</span>
+                                    {details_button(GeneratedFileKind::Texture, file.fileName.clone())}
+                                    <TextureProcessor filename={file.fileName.clone()} />
                                 </div>
                             }
                         }).collect::<Html>()
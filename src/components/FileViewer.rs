@@ -1,15 +1,262 @@
+use serde::{Deserialize, Serialize};
+use serde_wasm_bindgen::{from_value, to_value};
+use wasm_bindgen_futures::spawn_local;
+use web_sys::HtmlInputElement;
 use yew::prelude::*;
 
-#[derive(Clone, PartialEq, Properties)]
-pub struct FileViewerProps {
-    // pub icon: String,
-    // pub width: String,
-    // pub height: String,
+use crate::contexts::jobs::{JobStatus, JobsContextType};
+use crate::contexts::local::{LocalAction, LocalContextType};
+use crate::contexts::saved::{AssetMetadata, AssetRef, GeneratedFileKind, SavedContextType};
+use crate::invoke::invoke_error;
+
+#[derive(Serialize)]
+struct AssetMetadataParams {
+    projectId: String,
+    assetKind: GeneratedFileKind,
+    assetFilename: String,
+}
+
+#[derive(Serialize)]
+struct SaveAssetMetadataParams {
+    projectId: String,
+    assetKind: GeneratedFileKind,
+    assetFilename: String,
+    metadata: AssetMetadata,
+}
+
+#[derive(Clone, PartialEq, Deserialize)]
+struct AssetDetails {
+    metadata: AssetMetadata,
+    children: Vec<AssetRef>,
+}
+
+// writes the asset's sidecar, the created-at and measurements are filled in on the tauri side
+pub async fn save_asset_metadata(
+    project_id: String,
+    asset: AssetRef,
+    metadata: AssetMetadata,
+) -> Result<AssetMetadata, String> {
+    let params = to_value(&SaveAssetMetadataParams {
+        projectId: project_id,
+        assetKind: asset.kind,
+        assetFilename: asset.filename,
+        metadata,
+    })
+    .map_err(|e| format!("Couldn't serialize metadata: {}", e))?;
+
+    let result = crate::invoke::try_invoke("save_asset_metadata", params)
+        .await
+        .map_err(|e| invoke_error(e, "Couldn't save asset metadata"))?;
+
+    from_value(result).map_err(|e| format!("Couldn't parse asset metadata: {}", e))
+}
+
+async fn read_asset_details(project_id: String, asset: AssetRef) -> Result<AssetDetails, String> {
+    let params = to_value(&AssetMetadataParams {
+        projectId: project_id,
+        assetKind: asset.kind,
+        assetFilename: asset.filename,
+    })
+    .map_err(|e| format!("Couldn't serialize metadata params: {}", e))?;
+
+    let result = crate::invoke::try_invoke("read_asset_metadata", params)
+        .await
+        .map_err(|e| invoke_error(e, "Couldn't read asset metadata"))?;
+
+    from_value(result).map_err(|e| format!("Couldn't parse asset metadata: {}", e))
+}
+
+fn kind_label(kind: GeneratedFileKind) -> &'static str {
+    match kind {
+        GeneratedFileKind::Concept => "Concept",
+        GeneratedFileKind::Texture => "Texture",
+        GeneratedFileKind::Model => "Model",
+    }
 }
 
 #[function_component]
-pub fn FileViewer(props: &FileViewerProps) -> Html {
+pub fn FileViewer() -> Html {
+    let local_context = use_context::<LocalContextType>().expect("No LocalContext found");
+    let saved_context = use_context::<SavedContextType>().expect("No SavedContext found");
+    let jobs_context = use_context::<JobsContextType>().expect("No JobsContext found");
+
+    let details = use_state(|| None::<Result<AssetDetails, String>>);
+    // responses for an asset that's no longer selected are dropped
+    let latest_asset = use_mut_ref(|| None::<AssetRef>);
+
+    *latest_asset.borrow_mut() = local_context.selected_asset.clone();
+
+    // finished jobs and processed textures can add children or rewrite the selected sidecar
+    let finished_jobs = jobs_context
+        .jobs
+        .iter()
+        .filter(|job| job.status == JobStatus::Done)
+        .count();
+
+    {
+        let details = details.clone();
+
+        use_effect_with(
+            (
+                local_context.current_project_id.clone(),
+                local_context.selected_asset.clone(),
+                finished_jobs,
+                local_context.asset_revision,
+            ),
+            move |(project_id, asset, _, _)| {
+                details.set(None);
+
+                if let (Some(project_id), Some(asset)) = (project_id.clone(), asset.clone()) {
+                    spawn_local(async move {
+                        let result = read_asset_details(project_id, asset.clone()).await;
+
+                        if latest_asset.borrow().as_ref() == Some(&asset) {
+                            details.set(Some(result));
+                        }
+                    });
+                }
+            },
+        );
+    }
+
+    let asset = match local_context.selected_asset.clone() {
+        Some(asset) => asset,
+        None => {
+            return html! {
+                <section class="panel file-viewer">
+                    <span>{"Asset Details"}</span>
+                    <p>{"Pick Details on a concept, texture or model to see where it came from."}</p>
+                </section>
+            };
+        }
+    };
+
+    // the params a generated file was made with live on the file in the project context
+    let files = match asset.kind {
+        GeneratedFileKind::Concept => Some(&saved_context.concepts),
+        GeneratedFileKind::Model => Some(&saved_context.models),
+        GeneratedFileKind::Texture => saved_context.textures.as_ref(),
+    };
+    let generation = files
+        .and_then(|files| files.iter().find(|file| file.fileName == asset.filename))
+        .and_then(|file| file.generation.clone());
+
+    let select = |target: AssetRef| {
+        let local_context = local_context.clone();
+
+        Callback::from(move |e: MouseEvent| {
+            e.prevent_default();
+            local_context.dispatch(LocalAction::SetSelectedAsset(target.clone()));
+        })
+    };
+
+    let asset_link = |target: &AssetRef| {
+        html! {
+            <a href="#" onclick={select(target.clone())}>
+                {kind_label(target.kind)}{": "}{target.filename.clone()}
+            </a>
+        }
+    };
+
+    let on_tags_change = {
+        let local_context = local_context.clone();
+        let details = details.clone();
+        let asset = asset.clone();
+
+        Callback::from(move |e: Event| {
+            let input: HtmlInputElement = e.target_unchecked_into();
+
+            let (project_id, current) =
+                match (local_context.current_project_id.clone(), (*details).clone()) {
+                    (Some(project_id), Some(Ok(current))) => (project_id, current),
+                    _ => return,
+                };
+
+            let mut metadata = current.metadata.clone();
+            metadata.tags = input
+                .value()
+                .split(',')
+                .map(|tag| tag.trim().to_string())
+                .filter(|tag| !tag.is_empty())
+                .collect();
+
+            let details = details.clone();
+            let asset = asset.clone();
+
+            spawn_local(async move {
+                match save_asset_metadata(project_id, asset, metadata).await {
+                    Ok(metadata) => details.set(Some(Ok(AssetDetails {
+                        metadata,
+                        ..current
+                    }))),
+                    Err(e) => web_sys::console::error_1(&e.into()),
+                }
+            });
+        })
+    };
+
     html! {
-        <section></section>
+        <section class="panel file-viewer">
+            <span>{"Asset Details"}</span>
+            <h5>{kind_label(asset.kind)}{": "}{asset.filename.clone()}</h5>
+            {match (*details).clone() {
+                None => html! { <p>{"Loading..."}</p> },
+                Some(Err(e)) => html! { <pre>{e}</pre> },
+                Some(Ok(details)) => {
+                    let metadata = &details.metadata;
+
+                    html! {
+                        <>
+                            if let Some(prompt) = metadata.source_prompt.clone() {
+                                <p>{"Prompt: "}{prompt}</p>
+                            }
+                            if let Some(generation) = generation.clone() {
+                                if !generation.negative_prompt.is_empty() {
+                                    <p>{"Negative Prompt: "}{generation.negative_prompt.clone()}</p>
+                                }
+                                <p>
+                                    {format!(
+                                        "Seed {} · {} x {} · {:?}",
+                                        generation.seed, generation.width, generation.height, generation.style
+                                    )}
+                                    if generation.seamless {
+                                        {" · Seamless"}
+                                    }
+                                </p>
+                            }
+                            if let Some(generator) = metadata.generator.clone() {
+                                <p>{"Generator: "}{generator}</p>
+                            }
+                            if let Some(created_at) = metadata.created_at.clone() {
+                                <p>{"Created: "}{created_at}</p>
+                            }
+                            if let (Some(width), Some(height)) = (metadata.width, metadata.height) {
+                                <p>{format!("Dimensions: {} x {}", width, height)}</p>
+                            }
+                            if let Some(poly_count) = metadata.poly_count {
+                                <p>{format!("Triangles: {}", poly_count)}</p>
+                            }
+                            if let Some(parent) = metadata.parent.as_ref() {
+                                <p>{"Made from "}{asset_link(parent)}</p>
+                            }
+                            if !details.children.is_empty() {
+                                <div>
+                                    <label>{"Used to make"}</label>
+                                    <ul>
+                                        {details.children.iter().map(|child| {
+                                            html! { <li key={format!("{:?}-{}", child.kind, child.filename)}>{asset_link(child)}</li> }
+                                        }).collect::<Html>()}
+                                    </ul>
+                                </div>
+                            }
+                            <div>
+                                <label>{"Tags (comma separated)"}</label>
+                                <input type="text" value={metadata.tags.join(", ")} onchange={on_tags_change} />
+                            </div>
+                        </>
+                    }
+                }
+            }}
+        </section>
     }
 }
@@ -0,0 +1,301 @@
+use gloo_utils::format::JsValueSerdeExt;
+use serde_wasm_bindgen::to_value;
+use uuid::Uuid;
+use wasm_bindgen::closure::Closure;
+use wasm_bindgen::{JsCast, JsValue};
+use web_sys::{HtmlInputElement, HtmlSelectElement};
+use yew::prelude::*;
+
+use crate::components::inputs::range_callback;
+use crate::components::MdButton::{MdButton, MdButtonKind, MdButtonVariant};
+use crate::contexts::local::{LocalAction, LocalContextType};
+use crate::contexts::renderer::RendererContextType;
+use crate::contexts::saved::{
+    ComponentData, ComponentKind, FoliageInstance, FoliageProperties, GenericProperties,
+    LandscapeTextureKinds, SavedAction, SavedContextType,
+};
+
+const MASK_LAYERS: [&str; 3] = ["Primary", "Rockmap", "Soil"];
+
+#[derive(Clone, PartialEq, Properties)]
+pub struct FoliageToolsProps {
+    pub landscape_component_id: String,
+    // existing foliage component to rescatter, or None to create a new one
+    pub foliage: Option<ComponentData>,
+}
+
+fn mask_kind_from_str(kind: &str) -> LandscapeTextureKinds {
+    match kind {
+        "Rockmap" => LandscapeTextureKinds::Rockmap,
+        "Soil" => LandscapeTextureKinds::Soil,
+        _ => LandscapeTextureKinds::Primary,
+    }
+}
+
+fn mask_kind_to_str(kind: &LandscapeTextureKinds) -> &'static str {
+    match kind {
+        LandscapeTextureKinds::Rockmap | LandscapeTextureKinds::RockmapMask => "Rockmap",
+        LandscapeTextureKinds::Soil | LandscapeTextureKinds::SoilMask => "Soil",
+        _ => "Primary",
+    }
+}
+
+#[function_component]
+pub fn FoliageTools(props: &FoliageToolsProps) -> Html {
+    let local_context = use_context::<LocalContextType>().expect("No LocalContext found");
+    let saved_context = use_context::<SavedContextType>().expect("No SavedContext found");
+    let renderer_context = use_context::<RendererContextType>().expect("No RendererContext found");
+
+    let existing = props
+        .foliage
+        .as_ref()
+        .and_then(|component| component.foliage_properties.clone());
+
+    let model_id = use_state({
+        let foliage = props.foliage.clone();
+        move || {
+            foliage
+                .map(|component| component.asset_id)
+                .unwrap_or_default()
+        }
+    });
+    let mask_kind = use_state({
+        let existing = existing.clone();
+        move || {
+            existing
+                .map(|settings| mask_kind_to_str(&settings.mask_kind).to_string())
+                .unwrap_or("Primary".to_string())
+        }
+    });
+    let density = use_state(|| existing.as_ref().map_or(1.0, |s| s.density));
+    let min_slope = use_state(|| existing.as_ref().map_or(0.0, |s| s.min_slope));
+    let max_slope = use_state(|| existing.as_ref().map_or(30.0, |s| s.max_slope));
+    let min_scale = use_state(|| existing.as_ref().map_or(0.8, |s| s.min_scale));
+    let max_scale = use_state(|| existing.as_ref().map_or(1.2, |s| s.max_scale));
+    let seed = use_state(|| existing.as_ref().map_or(1, |s| s.seed));
+    let scattering = use_state(|| false);
+
+    let level_id = saved_context
+        .current_level(local_context.current_level_id.as_ref())
+        .map(|level| level.id.clone())
+        .unwrap_or_default();
+
+    let is_new = props.foliage.is_none();
+    let models = saved_context.models.clone();
+
+    let on_model_change = {
+        let model_id = model_id.clone();
+
+        Callback::from(move |e: Event| {
+            let input = e.target_dyn_into::<HtmlSelectElement>();
+
+            if let Some(input) = input {
+                model_id.set(input.value());
+            }
+        })
+    };
+
+    let on_mask_change = {
+        let mask_kind = mask_kind.clone();
+
+        Callback::from(move |e: Event| {
+            let input = e.target_dyn_into::<HtmlSelectElement>();
+
+            if let Some(input) = input {
+                mask_kind.set(input.value());
+            }
+        })
+    };
+
+    let on_seed_change = {
+        let seed = seed.clone();
+
+        Callback::from(move |e: InputEvent| {
+            let input: HtmlInputElement = e.target_unchecked_into();
+            if let Ok(parsed) = input.value().parse::<u32>() {
+                seed.set(parsed);
+            }
+        })
+    };
+
+    let on_scatter = {
+        let local_context = local_context.clone();
+        let saved_context = saved_context.clone();
+        let renderer_context = renderer_context.clone();
+        let props = props.clone();
+        let level_id = level_id.clone();
+        let models = models.clone();
+        let model_id = model_id.clone();
+        let mask_kind = mask_kind.clone();
+        let density = density.clone();
+        let min_slope = min_slope.clone();
+        let max_slope = max_slope.clone();
+        let min_scale = min_scale.clone();
+        let max_scale = max_scale.clone();
+        let seed = seed.clone();
+        let scattering = scattering.clone();
+
+        Callback::from(move |_| {
+            let renderer = match renderer_context.as_ref() {
+                Some(renderer) => renderer,
+                None => return,
+            };
+
+            let model = match models.iter().find(|m| m.id == *model_id) {
+                Some(model) => model.clone(),
+                None => {
+                    web_sys::console::error_1(&"Select a model to scatter".into());
+                    return;
+                }
+            };
+
+            let settings = FoliageProperties {
+                landscape_component_id: props.landscape_component_id.clone(),
+                mask_kind: mask_kind_from_str(&mask_kind),
+                density: *density,
+                min_slope: *min_slope,
+                max_slope: *max_slope,
+                min_scale: *min_scale,
+                max_scale: *max_scale,
+                seed: *seed,
+                instances: Vec::new(),
+            };
+
+            let foliage_component_id = match &props.foliage {
+                Some(component) => component.id.clone(),
+                None => {
+                    let foliage_component_id = Uuid::new_v4().to_string();
+
+                    saved_context.dispatch(SavedAction::AddComponent(
+                        level_id.clone(),
+                        ComponentData {
+                            id: foliage_component_id.clone(),
+                            kind: Some(ComponentKind::Foliage),
+                            asset_id: model.id.clone(),
+                            generic_properties: GenericProperties {
+                                name: "New Foliage Component".to_string(),
+                            },
+                            landscape_properties: None,
+                            model_properties: None,
+                            foliage_properties: Some(settings.clone()),
+                        },
+                    ));
+
+                    foliage_component_id
+                }
+            };
+
+            scattering.set(true);
+
+            web_sys::console::log_1(&"Scattering foliage...".into());
+
+            // persist the scatter once the renderer has placed the instances
+            let callback = {
+                let saved_context = saved_context.clone();
+                let local_context = local_context.clone();
+                let scattering = scattering.clone();
+                let level_id = level_id.clone();
+                let foliage_component_id = foliage_component_id.clone();
+                let settings = settings.clone();
+                let select_new = props.foliage.is_none();
+
+                Closure::wrap(Box::new(move |instances: JsValue| {
+                    scattering.set(false);
+
+                    let instances: Vec<FoliageInstance> = match instances.into_serde() {
+                        Ok(instances) => instances,
+                        Err(_) => return,
+                    };
+
+                    web_sys::console::log_1(
+                        &format!("Scattered {} instances", instances.len()).into(),
+                    );
+
+                    saved_context.dispatch(SavedAction::SetFoliageProperties(
+                        level_id.clone(),
+                        foliage_component_id.clone(),
+                        FoliageProperties {
+                            instances,
+                            ..settings.clone()
+                        },
+                    ));
+
+                    if select_new {
+                        local_context.dispatch(LocalAction::SetSelectedComponent(
+                            foliage_component_id.clone(),
+                        ));
+                    }
+                }) as Box<dyn FnMut(JsValue)>)
+                .into_js_value()
+                .unchecked_into::<js_sys::Function>()
+            };
+
+            renderer.handle_scatter_foliage(
+                local_context
+                    .current_project_id
+                    .clone()
+                    .expect("Couldn't get project id"),
+                foliage_component_id,
+                model.fileName.clone(),
+                to_value(&settings).unwrap(),
+                callback,
+            );
+        })
+    };
+
+    html! {
+        <div class="panel foliage-tools">
+            <span>{if is_new { "Scatter Foliage" } else { "Foliage Properties" }}</span>
+            <div>
+                <label>{"Model"}</label>
+                <select onchange={on_model_change} disabled={!is_new}>
+                    <option value="" selected={model_id.is_empty()}>{"Select Model"}</option>
+                    {models.iter().map(|model| {
+                        html!{
+                            <option value={model.id.clone()} selected={*model_id == model.id}>{model.fileName.clone()}</option>
+                        }
+                    }).collect::<Html>()}
+                </select>
+            </div>
+            <div>
+                <label>{"Mask Layer"}</label>
+                <select onchange={on_mask_change}>
+                    {MASK_LAYERS.iter().map(|layer| {
+                        html!{
+                            <option value={layer.to_string()} selected={*mask_kind == *layer}>{layer.to_string()}</option>
+                        }
+                    }).collect::<Html>()}
+                </select>
+            </div>
+            <div>
+                <label>{format!("Density ({:.1} per 100 sq)", *density)}</label>
+                <input type="range" min="0" max="20" step="0.1" value={density.to_string()} oninput={range_callback(density.clone())} />
+            </div>
+            <div>
+                <label>{format!("Slope ({:.0}° - {:.0}°)", *min_slope, *max_slope)}</label>
+                <input type="range" min="0" max="90" step="1" value={min_slope.to_string()} oninput={range_callback(min_slope.clone())} />
+                <input type="range" min="0" max="90" step="1" value={max_slope.to_string()} oninput={range_callback(max_slope.clone())} />
+            </div>
+            <div>
+                <label>{format!("Scale ({:.2} - {:.2})", *min_scale, *max_scale)}</label>
+                <input type="range" min="0.1" max="5" step="0.05" value={min_scale.to_string()} oninput={range_callback(min_scale.clone())} />
+                <input type="range" min="0.1" max="5" step="0.05" value={max_scale.to_string()} oninput={range_callback(max_scale.clone())} />
+            </div>
+            <div>
+                <label>{"Seed"}</label>
+                <input type="number" min="0" value={seed.to_string()} oninput={on_seed_change} />
+            </div>
+            <div class="btn-row">
+                <MdButton
+                    label={if is_new { "Scatter" } else { "Rescatter" }}
+                    icon={""}
+                    on_click={on_scatter}
+                    disabled={*scattering}
+                    loading={*scattering}
+                    kind={MdButtonKind::SmallShort}
+                    variant={MdButtonVariant::Green}
+                />
+            </div>
+        </div>
+    }
+}
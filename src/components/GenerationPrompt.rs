@@ -0,0 +1,223 @@
+use web_sys::{HtmlInputElement, HtmlSelectElement, HtmlTextAreaElement};
+use yew::prelude::*;
+
+use crate::components::FileBrowser::getFilename;
+use crate::components::MdButton::{MdButton, MdButtonKind, MdButtonVariant};
+use crate::contexts::jobs::{GenerationJob, JobsAction, JobsContextType};
+use crate::contexts::local::LocalContextType;
+use crate::contexts::saved::{GeneratedFileKind, GenerationParams, StylePreset};
+
+const RESOLUTIONS: [(u32, u32); 4] = [(512, 512), (768, 512), (512, 768), (1024, 1024)];
+const MAX_VARIANTS: u32 = 4;
+
+fn style_label(style: StylePreset) -> &'static str {
+    match style {
+        StylePreset::None => "None",
+        StylePreset::Photographic => "Photographic",
+        StylePreset::Painterly => "Painterly",
+        StylePreset::Stylized => "Stylized",
+        StylePreset::PixelArt => "Pixel Art",
+    }
+}
+
+#[derive(Clone, PartialEq, Properties)]
+pub struct GenerationPromptProps {
+    pub label: String,
+    // concepts or textures, models are generated from a concept instead
+    pub kind: GeneratedFileKind,
+}
+
+#[function_component]
+pub fn GenerationPrompt(props: &GenerationPromptProps) -> Html {
+    let local_context = use_context::<LocalContextType>().expect("No LocalContext found");
+    let jobs_context = use_context::<JobsContextType>().expect("No JobsContext found");
+
+    let prompt = use_state(String::default);
+    let negative_prompt = use_state(String::default);
+    // left blank for a random seed
+    let seed = use_state(String::default);
+    let resolution = use_state(|| RESOLUTIONS[0]);
+    let style = use_state(StylePreset::default);
+    let variants = use_state(|| 1u32);
+    let seamless = use_state(|| true);
+
+    let is_texture = props.kind == GeneratedFileKind::Texture;
+
+    let on_prompt_change = {
+        let prompt = prompt.clone();
+
+        Callback::from(move |e: Event| {
+            let input: HtmlTextAreaElement = e.target_unchecked_into();
+            prompt.set(input.value());
+        })
+    };
+
+    let on_negative_prompt_change = {
+        let negative_prompt = negative_prompt.clone();
+
+        Callback::from(move |e: Event| {
+            let input: HtmlTextAreaElement = e.target_unchecked_into();
+            negative_prompt.set(input.value());
+        })
+    };
+
+    let on_seed_change = {
+        let seed = seed.clone();
+
+        Callback::from(move |e: Event| {
+            let input: HtmlInputElement = e.target_unchecked_into();
+            seed.set(input.value());
+        })
+    };
+
+    let on_resolution_change = {
+        let resolution = resolution.clone();
+
+        Callback::from(move |e: Event| {
+            let select: HtmlSelectElement = e.target_unchecked_into();
+            if let Some(size) = select
+                .value()
+                .parse::<usize>()
+                .ok()
+                .and_then(|index| RESOLUTIONS.get(index))
+            {
+                resolution.set(*size);
+            }
+        })
+    };
+
+    let on_style_change = {
+        let style = style.clone();
+
+        Callback::from(move |e: Event| {
+            let select: HtmlSelectElement = e.target_unchecked_into();
+            if let Some(preset) = select
+                .value()
+                .parse::<usize>()
+                .ok()
+                .and_then(|index| StylePreset::ALL.get(index))
+            {
+                style.set(*preset);
+            }
+        })
+    };
+
+    let on_variants_change = {
+        let variants = variants.clone();
+
+        Callback::from(move |e: Event| {
+            let input: HtmlInputElement = e.target_unchecked_into();
+            if let Ok(count) = input.value().parse::<u32>() {
+                variants.set(count.clamp(1, MAX_VARIANTS));
+            }
+        })
+    };
+
+    let on_seamless_change = {
+        let seamless = seamless.clone();
+
+        Callback::from(move |e: Event| {
+            let input: HtmlInputElement = e.target_unchecked_into();
+            seamless.set(input.checked());
+        })
+    };
+
+    let on_generate = {
+        let kind = props.kind;
+        let prompt = prompt.clone();
+        let negative_prompt = negative_prompt.clone();
+        let seed = seed.clone();
+        let resolution = resolution.clone();
+        let style = style.clone();
+        let variants = variants.clone();
+        let seamless = seamless.clone();
+
+        Callback::from(move |_| {
+            web_sys::console::log_1(&format!("Queueing {:?} generation...", kind).into());
+
+            // pick the seed now so it's stored with the files and can be reused. Kept within a
+            // signed int since that's what the api takes
+            let seed = seed
+                .trim()
+                .parse::<u32>()
+                .unwrap_or_else(|_| (js_sys::Math::random() * i32::MAX as f64) as u32);
+            let (width, height) = *resolution;
+
+            let params = GenerationParams {
+                prompt: (*prompt).clone(),
+                negative_prompt: (*negative_prompt).clone(),
+                seed,
+                width,
+                height,
+                style: *style,
+                variants: *variants,
+                seamless: kind == GeneratedFileKind::Texture && *seamless,
+            };
+
+            // determine filename
+            let filename = getFilename((*prompt).clone()) + ".png";
+            let projectId = local_context
+                .current_project_id
+                .clone()
+                .expect("No project selected?");
+
+            jobs_context.dispatch(JobsAction::Enqueue(GenerationJob::new(
+                kind,
+                projectId,
+                (*prompt).clone(),
+                Some(params),
+                filename,
+            )));
+        })
+    };
+
+    html! {
+        <div class="file-prompt">
+            <label>{props.label.clone()}</label>
+            <textarea onchange={on_prompt_change} rows="3">{(*prompt).clone()}</textarea>
+            <label>{"Leave out (negative prompt)"}</label>
+            <textarea onchange={on_negative_prompt_change} rows="2">{(*negative_prompt).clone()}</textarea>
+            <div class="btn-row">
+                <label>{"Seed"}</label>
+                <input type="number" min="0" placeholder="Random" value={(*seed).clone()} onchange={on_seed_change} />
+                <label>{"Size"}</label>
+                <select onchange={on_resolution_change}>
+                    {RESOLUTIONS.iter().enumerate().map(|(index, (width, height))| {
+                        html! {
+                            <option value={index.to_string()} selected={*resolution == (*width, *height)}>
+                                {format!("{} x {}", width, height)}
+                            </option>
+                        }
+                    }).collect::<Html>()}
+                </select>
+            </div>
+            <div class="btn-row">
+                <label>{"Style"}</label>
+                <select onchange={on_style_change}>
+                    {StylePreset::ALL.iter().enumerate().map(|(index, preset)| {
+                        html! {
+                            <option value={index.to_string()} selected={*style == *preset}>
+                                {style_label(*preset)}
+                            </option>
+                        }
+                    }).collect::<Html>()}
+                </select>
+                <label>{"Variants"}</label>
+                <input type="number" min="1" max={MAX_VARIANTS.to_string()} value={variants.to_string()} onchange={on_variants_change} />
+                if is_texture {
+                    <label>{"Seamless"}</label>
+                    <input type="checkbox" checked={*seamless} onchange={on_seamless_change} />
+                }
+            </div>
+            <MdButton
+                label="Generate"
+                icon={""}
+                on_click={on_generate}
+                disabled={false}
+                loading={false}
+                kind={MdButtonKind::SmallShort}
+                variant={MdButtonVariant::Green}
+            />
+        </div>
+    }
+}
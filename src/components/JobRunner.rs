@@ -0,0 +1,218 @@
+use serde::Serialize;
+use serde_wasm_bindgen::to_value;
+use std::cell::RefCell;
+use std::rc::Rc;
+use wasm_bindgen_futures::spawn_local;
+use yew::prelude::*;
+
+use crate::components::FileViewer::save_asset_metadata;
+use crate::contexts::jobs::{GenerationJob, JobStatus, JobsAction, JobsContextType};
+use crate::contexts::local::LocalContextType;
+use crate::contexts::saved::{
+    AssetMetadata, AssetRef, File, GeneratedFileKind, SavedAction, SavedContextType,
+};
+use crate::generation::{generation_provider, GenerationProvider, Provider};
+
+// generations are slow and often paid for, so they run one after another
+const MAX_RUNNING_JOBS: usize = 1;
+
+#[derive(Serialize)]
+struct SaveConceptParams {
+    projectId: String,
+    conceptBase64: String,
+    conceptFilename: String,
+}
+
+#[derive(Serialize)]
+struct SaveTextureParams {
+    projectId: String,
+    textureBase64: String,
+    textureFilename: String,
+}
+
+#[derive(Serialize)]
+struct SaveModelParams {
+    projectId: String,
+    modelBase64: String,
+    modelFilename: String,
+}
+
+// lives beside the views rather than in them, so jobs keep going when the route changes
+#[function_component]
+pub fn JobRunner() -> Html {
+    let local_context = use_context::<LocalContextType>().expect("No LocalContext found");
+    let jobs_context = use_context::<JobsContextType>().expect("No JobsContext found");
+    let saved_context = use_context::<SavedContextType>().expect("No SavedContext found");
+
+    let latest_project_id = use_mut_ref(|| None);
+
+    *latest_project_id.borrow_mut() = local_context.current_project_id.clone();
+
+    {
+        let jobs_context = jobs_context.clone();
+
+        use_effect_with(jobs_context.jobs.clone(), move |jobs| {
+            // cancelled jobs hold their slot until the request returns
+            let running = jobs.iter().filter(|job| job.in_flight).count();
+
+            if running < MAX_RUNNING_JOBS {
+                if let Some(job) = jobs.iter().find(|job| job.status == JobStatus::Queued) {
+                    let job = job.clone();
+                    let provider = generation_provider(&local_context);
+
+                    jobs_context.dispatch(JobsAction::Start(job.id.clone()));
+
+                    spawn_local(async move {
+                        let job_id = job.id.clone();
+
+                        match run_job(job, provider, latest_project_id, saved_context).await {
+                            Ok(outputs) => {
+                                jobs_context.dispatch(JobsAction::Complete(job_id, outputs))
+                            }
+                            Err(e) => {
+                                web_sys::console::error_1(
+                                    &format!("Generation failed: {}", e).into(),
+                                );
+                                jobs_context.dispatch(JobsAction::Fail(job_id, e));
+                            }
+                        }
+                    });
+                }
+            }
+        });
+    }
+
+    html! {
+        <></>
+    }
+}
+
+// the first variant keeps the requested name, the rest become name-2.png, name-3.png...
+fn variant_filename(filename: &str, variant: usize) -> String {
+    if variant == 0 {
+        return filename.to_string();
+    }
+
+    match filename.rsplit_once('.') {
+        Some((stem, extension)) => format!("{}-{}.{}", stem, variant + 1, extension),
+        None => format!("{}-{}", filename, variant + 1),
+    }
+}
+
+async fn run_job(
+    job: GenerationJob,
+    provider: Provider,
+    latest_project_id: Rc<RefCell<Option<String>>>,
+    saved_context: SavedContextType,
+) -> Result<Vec<String>, String> {
+    web_sys::console::log_1(&format!("Running {:?} job...", job.kind).into());
+
+    let generator = provider.name();
+
+    let outputs = match (job.kind, &job.params) {
+        (GeneratedFileKind::Concept, Some(params)) => provider.generate_concept(params).await?,
+        (GeneratedFileKind::Texture, Some(params)) => provider.generate_texture(params).await?,
+        (GeneratedFileKind::Model, _) => vec![provider.generate_model(job.input.clone()).await?],
+        _ => return Err("Missing generation params".to_string()),
+    };
+
+    let mut saved_filenames = Vec::new();
+
+    for (variant, output) in outputs.into_iter().enumerate() {
+        // requests in flight can't be aborted, so a cancelled job only drops what's left of its result
+        if job.cancel_token.is_cancelled() {
+            break;
+        }
+
+        let filename = variant_filename(&job.output_filename, variant);
+
+        // images are listed and turned into models from their data url, models load from disk
+        let url = match job.kind {
+            GeneratedFileKind::Model => String::new(),
+            _ => output.clone(),
+        };
+
+        // save inside the project's folder in the sync folder, e.g. /CommonOSFiles/midpoint/projects/project_id/concepts/
+        let (command, params) = match job.kind {
+            GeneratedFileKind::Concept => (
+                "save_concept",
+                to_value(&SaveConceptParams {
+                    projectId: job.project_id.clone(),
+                    conceptBase64: output,
+                    conceptFilename: filename.clone(),
+                }),
+            ),
+            GeneratedFileKind::Texture => (
+                "save_texture",
+                to_value(&SaveTextureParams {
+                    projectId: job.project_id.clone(),
+                    textureBase64: output,
+                    textureFilename: filename.clone(),
+                }),
+            ),
+            GeneratedFileKind::Model => (
+                "save_model",
+                to_value(&SaveModelParams {
+                    projectId: job.project_id.clone(),
+                    modelBase64: output,
+                    modelFilename: filename.clone(),
+                }),
+            ),
+        };
+
+        let params = params.map_err(|e| format!("Couldn't serialize save params: {}", e))?;
+
+        crate::invoke::try_invoke(command, params)
+            .await
+            .map_err(|e| {
+                e.as_string()
+                    .unwrap_or_else(|| "Couldn't save generated file".to_string())
+            })?;
+
+        // where the file came from, in a sidecar next to it
+        let metadata = AssetMetadata {
+            source_prompt: job.params.as_ref().map(|params| params.prompt.clone()),
+            parent: job.parent.clone(),
+            generator: Some(generator.to_string()),
+            ..Default::default()
+        };
+        let asset = AssetRef {
+            kind: job.kind,
+            filename: filename.clone(),
+        };
+
+        if let Err(e) = save_asset_metadata(job.project_id.clone(), asset, metadata).await {
+            web_sys::console::error_1(
+                &format!("Couldn't save metadata for {}: {}", filename, e).into(),
+            );
+        }
+
+        // keep the params with the file so the same image can be made again. The file is still
+        // saved if the job was cancelled or the project was switched meanwhile, it just isn't
+        // listed until the next sync
+        if job.cancel_token.is_cancelled()
+            || latest_project_id.borrow().as_ref() != Some(&job.project_id)
+        {
+            saved_filenames.push(filename);
+            continue;
+        }
+
+        saved_context.dispatch(SavedAction::RecordGeneratedFile(
+            job.kind,
+            File {
+                id: uuid::Uuid::new_v4().to_string(),
+                fileName: filename.clone(),
+                cloudfrontUrl: url,
+                normalFilePath: String::new(),
+                generation: job
+                    .params
+                    .as_ref()
+                    .map(|params| params.for_variant(variant as u32)),
+            },
+        ));
+
+        saved_filenames.push(filename);
+    }
+
+    Ok(saved_filenames)
+}
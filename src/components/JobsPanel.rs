@@ -0,0 +1,110 @@
+use yew::prelude::*;
+
+use crate::components::MdButton::{MdButton, MdButtonKind, MdButtonVariant};
+use crate::contexts::jobs::{GenerationJob, JobStatus, JobsAction, JobsContextType};
+use crate::contexts::saved::GeneratedFileKind;
+
+fn kind_label(kind: &GeneratedFileKind) -> &'static str {
+    match kind {
+        GeneratedFileKind::Concept => "Concept",
+        GeneratedFileKind::Texture => "Texture",
+        GeneratedFileKind::Model => "Model",
+    }
+}
+
+fn status_label(status: &JobStatus) -> String {
+    match status {
+        JobStatus::Queued => "Queued".to_string(),
+        JobStatus::Running => "Running...".to_string(),
+        JobStatus::Done => "Done".to_string(),
+        JobStatus::Failed(e) => format!("Failed: {}", e),
+        JobStatus::Cancelled => "Cancelled".to_string(),
+    }
+}
+
+fn time_label(timestamp: f64) -> String {
+    js_sys::Date::new(&timestamp.into())
+        .to_locale_time_string("default")
+        .into()
+}
+
+// how long the job took, or has been running so far
+fn duration_label(job: &GenerationJob) -> Option<String> {
+    let started_at = job.started_at?;
+    let until = job.finished_at.unwrap_or_else(js_sys::Date::now);
+
+    Some(format!("{:.0}s", (until - started_at) / 1000.0))
+}
+
+#[function_component]
+pub fn JobsPanel() -> Html {
+    let jobs_context = use_context::<JobsContextType>().expect("No JobsContext found");
+
+    let has_finished = jobs_context.jobs.iter().any(|job| job.is_finished());
+
+    let on_clear = {
+        let jobs_context = jobs_context.clone();
+
+        Callback::from(move |_| {
+            jobs_context.dispatch(JobsAction::ClearFinished);
+        })
+    };
+
+    html! {
+        <div class="panel jobs-panel">
+            <span>{"Generation Jobs"}</span>
+            if jobs_context.jobs.is_empty() {
+                <p>{"Generated concepts, textures and models will show up here."}</p>
+            }
+            // newest first
+            {jobs_context.jobs.iter().rev().map(|job| {
+                let on_cancel = {
+                    let jobs_context = jobs_context.clone();
+                    let job_id = job.id.clone();
+
+                    Callback::from(move |_| {
+                        jobs_context.dispatch(JobsAction::Cancel(job_id.clone()));
+                    })
+                };
+
+                html! {
+                    <div class="job-item" key={job.id.clone()}>
+                        <span>{kind_label(&job.kind)}{" · "}{time_label(job.created_at)}</span>
+                        <p class="job-input">{job.input.clone()}</p>
+                        <span class="job-status">
+                            {status_label(&job.status)}
+                            if let Some(duration) = duration_label(job) {
+                                {" ("}{duration}{")"}
+                            }
+                        </span>
+                        if job.status == JobStatus::Done {
+                            <span>{"Saved as "}{job.outputs.join(", ")}</span>
+                        }
+                        if !job.is_finished() {
+                            <MdButton
+                                label="Cancel"
+                                icon={""}
+                                on_click={on_cancel}
+                                disabled={false}
+                                loading={false}
+                                kind={MdButtonKind::SmallShort}
+                                variant={MdButtonVariant::Negative}
+                            />
+                        }
+                    </div>
+                }
+            }).collect::<Html>()}
+            if has_finished {
+                <MdButton
+                    label="Clear Finished"
+                    icon={""}
+                    on_click={on_clear}
+                    disabled={false}
+                    loading={false}
+                    kind={MdButtonKind::SmallShort}
+                    variant={MdButtonVariant::Light}
+                />
+            }
+        </div>
+    }
+}
@@ -0,0 +1,235 @@
+use serde::{Deserialize, Serialize};
+use serde_wasm_bindgen::{from_value, to_value};
+use wasm_bindgen_futures::spawn_local;
+use web_sys::{HtmlInputElement, HtmlSelectElement};
+use yew::prelude::*;
+
+use crate::components::MdButton::{MdButton, MdButtonKind, MdButtonVariant};
+use crate::contexts::local::{LocalAction, LocalContextType};
+use crate::contexts::saved::{File, LandscapeData, SavedAction, SavedContextType};
+use crate::invoke::invoke_error;
+
+const NOISE_KINDS: [&str; 2] = ["Fbm", "Ridged"];
+const SIZES: [u32; 4] = [256, 512, 1024, 2048];
+
+#[derive(Serialize)]
+struct GeneratorParams {
+    seed: u32,
+    size: u32,
+    noise: String,
+    octaves: u32,
+    frequency: f32,
+    lacunarity: f32,
+    persistence: f32,
+    warpStrength: f32,
+    thermalIterations: u32,
+    hydraulicIterations: u32,
+}
+
+#[derive(Serialize)]
+struct GenerateLandscapeParams {
+    projectId: String,
+    params: GeneratorParams,
+}
+
+#[derive(Deserialize)]
+struct GeneratedLandscape {
+    landscapeId: String,
+    heightmapFilename: String,
+    rockmapFilename: String,
+    soilFilename: String,
+}
+
+fn landscape_file(file_name: String) -> Option<File> {
+    Some(File {
+        id: uuid::Uuid::new_v4().to_string(),
+        fileName: file_name,
+        cloudfrontUrl: String::new(),
+        normalFilePath: String::new(),
+        generation: None,
+    })
+}
+
+fn number_callback<T: std::str::FromStr + 'static>(value: UseStateHandle<T>) -> Callback<InputEvent> {
+    Callback::from(move |e: InputEvent| {
+        let input: HtmlInputElement = e.target_unchecked_into();
+        if let Ok(parsed) = input.value().parse::<T>() {
+            value.set(parsed);
+        }
+    })
+}
+
+fn select_callback<T: std::str::FromStr + 'static>(value: UseStateHandle<T>) -> Callback<Event> {
+    Callback::from(move |e: Event| {
+        let input = e.target_dyn_into::<HtmlSelectElement>();
+
+        if let Some(input) = input {
+            if let Ok(parsed) = input.value().parse::<T>() {
+                value.set(parsed);
+            }
+        }
+    })
+}
+
+#[function_component]
+pub fn LandscapeGenerator() -> Html {
+    let local_context = use_context::<LocalContextType>().expect("No LocalContext found");
+    let saved_context = use_context::<SavedContextType>().expect("No SavedContext found");
+
+    let seed = use_state(|| 1u32);
+    let size = use_state(|| 512u32);
+    let noise = use_state(|| "Fbm".to_string());
+    let octaves = use_state(|| 6u32);
+    let frequency = use_state(|| 2.0f32);
+    let warp_strength = use_state(|| 0.3f32);
+    let thermal_iterations = use_state(|| 10u32);
+    let hydraulic_iterations = use_state(|| 20000u32);
+    let loading = use_state(|| false);
+    let error = use_state(|| None::<String>);
+
+    html! {
+        <div class="panel landscape-generator">
+            <h5>{"Or generate a landscape"}</h5>
+            <div>
+                <label>{"Seed"}</label>
+                <input type="number" min="0" value={seed.to_string()} oninput={number_callback(seed.clone())} />
+            </div>
+            <div>
+                <label>{"Size"}</label>
+                <select onchange={select_callback(size.clone())}>
+                    {SIZES.iter().map(|option| {
+                        html!{
+                            <option value={option.to_string()} selected={*size == *option}>{format!("{}x{}", option, option)}</option>
+                        }
+                    }).collect::<Html>()}
+                </select>
+            </div>
+            <div>
+                <label>{"Noise"}</label>
+                <select onchange={select_callback(noise.clone())}>
+                    {NOISE_KINDS.iter().map(|kind| {
+                        html!{
+                            <option value={kind.to_string()} selected={*noise == *kind}>{kind.to_string()}</option>
+                        }
+                    }).collect::<Html>()}
+                </select>
+            </div>
+            <div>
+                <label>{format!("Octaves ({})", *octaves)}</label>
+                <input type="range" min="1" max="10" step="1" value={octaves.to_string()} oninput={number_callback(octaves.clone())} />
+            </div>
+            <div>
+                <label>{format!("Frequency ({:.1})", *frequency)}</label>
+                <input type="range" min="0.5" max="8" step="0.1" value={frequency.to_string()} oninput={number_callback(frequency.clone())} />
+            </div>
+            <div>
+                <label>{format!("Domain Warp ({:.2})", *warp_strength)}</label>
+                <input type="range" min="0" max="1" step="0.01" value={warp_strength.to_string()} oninput={number_callback(warp_strength.clone())} />
+            </div>
+            <div>
+                <label>{format!("Thermal Erosion ({})", *thermal_iterations)}</label>
+                <input type="range" min="0" max="100" step="1" value={thermal_iterations.to_string()} oninput={number_callback(thermal_iterations.clone())} />
+            </div>
+            <div>
+                <label>{format!("Hydraulic Droplets ({})", *hydraulic_iterations)}</label>
+                <input type="range" min="0" max="200000" step="1000" value={hydraulic_iterations.to_string()} oninput={number_callback(hydraulic_iterations.clone())} />
+            </div>
+
+            <MdButton
+                label="Generate Landscape"
+                icon={""}
+                on_click={Callback::from({
+                    let local_context = local_context.clone();
+                    let saved_context = saved_context.clone();
+                    let loading = loading.clone();
+                    let error = error.clone();
+
+                    let seed = seed.clone();
+                    let size = size.clone();
+                    let noise = noise.clone();
+                    let octaves = octaves.clone();
+                    let frequency = frequency.clone();
+                    let warp_strength = warp_strength.clone();
+                    let thermal_iterations = thermal_iterations.clone();
+                    let hydraulic_iterations = hydraulic_iterations.clone();
+
+                    move |_| {
+                        let local_context = local_context.clone();
+                        let saved_context = saved_context.clone();
+                        let loading = loading.clone();
+                        let error = error.clone();
+
+                        let projectId = match local_context.current_project_id.clone() {
+                            Some(project_id) => project_id,
+                            None => {
+                                error.set(Some("Open a project to generate a landscape".to_string()));
+                                return;
+                            }
+                        };
+
+                        let params = GeneratorParams {
+                            seed: *seed,
+                            size: *size,
+                            noise: (*noise).clone(),
+                            octaves: *octaves,
+                            frequency: *frequency,
+                            lacunarity: 2.0,
+                            persistence: 0.5,
+                            warpStrength: *warp_strength,
+                            thermalIterations: *thermal_iterations,
+                            hydraulicIterations: *hydraulic_iterations,
+                        };
+
+                        loading.set(true);
+                        error.set(None);
+
+                        web_sys::console::log_1(&"Generating landscape...".into());
+
+                        spawn_local(async move {
+                            let params = to_value(&GenerateLandscapeParams {
+                                projectId,
+                                params,
+                            }).unwrap();
+
+                            match crate::invoke::try_invoke("generate_landscape", params).await {
+                                Ok(result) => match from_value::<GeneratedLandscape>(result) {
+                                    Ok(generated) => {
+                                        // list the new landscape right away instead of after a reload
+                                        saved_context.dispatch(SavedAction::RecordLandscape(LandscapeData {
+                                            id: generated.landscapeId,
+                                            heightmap: landscape_file(generated.heightmapFilename),
+                                            rockmap: landscape_file(generated.rockmapFilename),
+                                            soil: landscape_file(generated.soilFilename),
+                                        }));
+
+                                        local_context.dispatch(LocalAction::AssetChanged);
+                                    }
+                                    Err(e) => {
+                                        let message = format!("Couldn't parse generated landscape: {}", e);
+                                        web_sys::console::error_1(&message.clone().into());
+                                        error.set(Some(message));
+                                    }
+                                },
+                                Err(e) => {
+                                    let message = invoke_error(e, "Couldn't generate landscape");
+                                    web_sys::console::error_1(&message.clone().into());
+                                    error.set(Some(message));
+                                }
+                            }
+
+                            loading.set(false);
+                        });
+                    }
+                })}
+                disabled={*loading}
+                loading={*loading}
+                kind={MdButtonKind::SmallShort}
+                variant={MdButtonVariant::Green}
+            />
+
+            if let Some(error) = (*error).clone() {
+                <pre>{error}</pre>
+            }
+        </div>
+    }
+}
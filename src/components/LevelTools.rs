@@ -0,0 +1,155 @@
+use uuid::Uuid;
+use web_sys::{HtmlInputElement, HtmlSelectElement};
+use yew::prelude::*;
+
+use crate::components::MdButton::{MdButton, MdButtonKind, MdButtonVariant};
+use crate::contexts::local::{LocalAction, LocalContextType};
+use crate::contexts::saved::{LevelData, SavedAction, SavedContextType};
+
+#[function_component]
+pub fn LevelTools() -> Html {
+    let local_context = use_context::<LocalContextType>().expect("No LocalContext found");
+    let saved_context = use_context::<SavedContextType>().expect("No SavedContext found");
+
+    let levels = saved_context.levels.clone().unwrap_or_default();
+    let current_level = saved_context
+        .current_level(local_context.current_level_id.as_ref())
+        .cloned();
+    let current_level_id = current_level.as_ref().map(|level| level.id.clone());
+
+    let on_select = {
+        let local_context = local_context.clone();
+
+        Callback::from(move |e: Event| {
+            let input = e.target_dyn_into::<HtmlSelectElement>();
+
+            if let Some(input) = input {
+                local_context.dispatch(LocalAction::SetCurrentLevel(input.value()));
+            }
+        })
+    };
+
+    // applied on change rather than per keystroke, so a rename is a single undo step
+    let on_rename = {
+        let saved_context = saved_context.clone();
+        let current_level_id = current_level_id.clone();
+
+        Callback::from(move |e: Event| {
+            let input: HtmlInputElement = e.target_unchecked_into();
+
+            if let Some(level_id) = current_level_id.clone() {
+                saved_context.dispatch(SavedAction::RenameLevel(level_id, input.value()));
+            }
+        })
+    };
+
+    let on_add = {
+        let local_context = local_context.clone();
+        let saved_context = saved_context.clone();
+        let level_count = levels.len();
+
+        Callback::from(move |_| {
+            let level_id = Uuid::new_v4().to_string();
+
+            web_sys::console::log_1(&"Adding level...".into());
+
+            saved_context.dispatch(SavedAction::AddLevel(LevelData {
+                id: level_id.clone(),
+                name: format!("Level {}", level_count + 1),
+                components: None,
+                water: None,
+                sky: None,
+            }));
+
+            local_context.dispatch(LocalAction::SetCurrentLevel(level_id));
+        })
+    };
+
+    let on_duplicate = {
+        let local_context = local_context.clone();
+        let saved_context = saved_context.clone();
+        let current_level_id = current_level_id.clone();
+
+        Callback::from(move |_| {
+            if let Some(level_id) = current_level_id.clone() {
+                let new_level_id = Uuid::new_v4().to_string();
+
+                saved_context.dispatch(SavedAction::DuplicateLevel(level_id, new_level_id.clone()));
+                local_context.dispatch(LocalAction::SetCurrentLevel(new_level_id));
+            }
+        })
+    };
+
+    // the newest remaining level becomes current once the selected one is gone
+    let on_delete = {
+        let saved_context = saved_context.clone();
+        let current_level_id = current_level_id.clone();
+
+        Callback::from(move |_| {
+            if let Some(level_id) = current_level_id.clone() {
+                saved_context.dispatch(SavedAction::DeleteLevel(level_id));
+            }
+        })
+    };
+
+    html! {
+        <div class="panel level-tools">
+            <span>{"Levels"}</span>
+            if levels.is_empty() {
+                <p>{"Add a level to start placing models and landscapes."}</p>
+            } else {
+                <div>
+                    <label>{"Current Level"}</label>
+                    <select onchange={on_select}>
+                        {levels.iter().map(|level| {
+                            html!{
+                                <option value={level.id.clone()} selected={current_level_id.as_ref() == Some(&level.id)}>
+                                    {level.display_name()}
+                                </option>
+                            }
+                        }).collect::<Html>()}
+                    </select>
+                </div>
+                <div>
+                    <label>{"Name"}</label>
+                    <input
+                        type="text"
+                        value={current_level.as_ref().map(|level| level.name.clone()).unwrap_or_default()}
+                        onchange={on_rename}
+                    />
+                </div>
+            }
+            <div class="btn-row">
+                <MdButton
+                    label="Add Level"
+                    icon={""}
+                    on_click={on_add}
+                    disabled={false}
+                    loading={false}
+                    kind={MdButtonKind::SmallShort}
+                    variant={MdButtonVariant::Green}
+                />
+                if current_level_id.is_some() {
+                    <MdButton
+                        label="Duplicate"
+                        icon={""}
+                        on_click={on_duplicate}
+                        disabled={false}
+                        loading={false}
+                        kind={MdButtonKind::SmallShort}
+                        variant={MdButtonVariant::Light}
+                    />
+                    <MdButton
+                        label="Delete"
+                        icon={""}
+                        on_click={on_delete}
+                        disabled={false}
+                        loading={false}
+                        kind={MdButtonKind::SmallShort}
+                        variant={MdButtonVariant::Negative}
+                    />
+                }
+            </div>
+        </div>
+    }
+}
@@ -0,0 +1,168 @@
+use wasm_bindgen::closure::Closure;
+use wasm_bindgen::{JsCast, JsValue};
+use web_sys::HtmlSelectElement;
+use yew::prelude::*;
+
+use crate::components::BrushControls::BrushControls;
+use crate::components::MdButton::{MdButton, MdButtonKind, MdButtonVariant};
+use crate::contexts::local::{LandscapeTool, LocalAction, LocalContextType};
+use crate::contexts::renderer::RendererContextType;
+
+const MASK_LAYERS: [&str; 3] = ["Primary", "Rockmap", "Soil"];
+
+#[derive(Clone, PartialEq, Properties)]
+pub struct MaskPaintToolsProps {
+    pub landscape_component_id: String,
+    pub landscape_asset_id: String,
+    pub primary_mask_filename: String,
+    pub rockmap_mask_filename: String,
+    pub soil_mask_filename: String,
+}
+
+#[function_component]
+pub fn MaskPaintTools(props: &MaskPaintToolsProps) -> Html {
+    let local_context = use_context::<LocalContextType>().expect("No LocalContext found");
+    let renderer_context = use_context::<RendererContextType>().expect("No RendererContext found");
+
+    let layer = match &local_context.landscape_tool {
+        Some(LandscapeTool::PaintMask(layer)) => layer.clone(),
+        _ => "None".to_string(),
+    };
+    let radius = use_state(|| 20.0f32);
+    let strength = use_state(|| 0.5f32);
+    let falloff = use_state(|| 0.5f32);
+    let saving = use_state(|| false);
+    let error = use_state(|| None::<String>);
+
+    // keep the renderer's active mask brush in sync with the controls
+    use_effect_with(
+        (
+            (*renderer_context).clone(),
+            layer.clone(),
+            *radius,
+            *strength,
+            *falloff,
+        ),
+        move |(renderer, layer, radius, strength, falloff)| {
+            let renderer = renderer.clone();
+            let active = layer != "None";
+
+            if let Some(renderer) = renderer.as_ref().filter(|_| active) {
+                renderer.handle_set_mask_brush(layer.clone(), *radius, *strength, *falloff);
+            }
+
+            move || {
+                if let Some(renderer) = renderer.as_ref().filter(|_| active) {
+                    renderer.handle_clear_mask_brush();
+                }
+            }
+        },
+    );
+
+    let on_layer_change = {
+        let local_context = local_context.clone();
+
+        Callback::from(move |e: Event| {
+            let input = e.target_dyn_into::<HtmlSelectElement>();
+
+            if let Some(input) = input {
+                let layer = input.value();
+                let tool = if layer == "None" {
+                    None
+                } else {
+                    Some(LandscapeTool::PaintMask(layer))
+                };
+
+                local_context.dispatch(LocalAction::SetLandscapeTool(tool));
+            }
+        })
+    };
+
+    let callback = {
+        let local_context = local_context.clone();
+        let saving = saving.clone();
+        let error = error.clone();
+
+        Callback::from(move |result: JsValue| {
+            saving.set(false);
+
+            match result.as_string() {
+                Some(message) => error.set(Some(message)),
+                None => local_context.dispatch(LocalAction::AssetChanged),
+            }
+        })
+    };
+
+    // Convert the callback to a js_sys::Function, it gets null or the error message
+    let js_callback: js_sys::Function = Closure::wrap(Box::new(move |result: JsValue| {
+        callback.emit(result);
+    }) as Box<dyn FnMut(JsValue)>)
+    .into_js_value()
+    .unchecked_into();
+
+    html! {
+        <div class="panel mask-paint-tools">
+            <span>{"Paint Masks"}</span>
+            <div>
+                <label>{"Layer"}</label>
+                <select onchange={on_layer_change}>
+                    <option value="None" selected={layer == "None"}>{"Off (camera)"}</option>
+                    {MASK_LAYERS.iter().map(|kind| {
+                        html!{
+                            <option value={kind.to_string()} selected={layer == *kind}>{kind.to_string()}</option>
+                        }
+                    }).collect::<Html>()}
+                </select>
+            </div>
+            <BrushControls radius={radius.clone()} strength={strength.clone()} falloff={falloff.clone()} />
+            <MdButton
+                label="Save Masks"
+                icon={""}
+                on_click={Callback::from({
+                    let local_context = local_context.clone();
+                    let renderer_context = renderer_context.clone();
+                    let saving = saving.clone();
+                    let error = error.clone();
+                    let props = props.clone();
+
+                    move |_| {
+                        let renderer = match renderer_context.as_ref() {
+                            Some(renderer) => renderer,
+                            None => return,
+                        };
+
+                        let project_id = match local_context.current_project_id.clone() {
+                            Some(project_id) => project_id,
+                            None => {
+                                error.set(Some("Open a project to save the masks".to_string()));
+                                return;
+                            }
+                        };
+
+                        saving.set(true);
+                        error.set(None);
+
+                        web_sys::console::log_1(&"Saving landscape masks...".into());
+
+                        renderer.handle_save_landscape_masks(
+                            project_id,
+                            props.landscape_component_id.clone(),
+                            props.landscape_asset_id.clone(),
+                            props.primary_mask_filename.clone(),
+                            props.rockmap_mask_filename.clone(),
+                            props.soil_mask_filename.clone(),
+                            js_callback.clone(),
+                        );
+                    }
+                })}
+                disabled={*saving}
+                loading={*saving}
+                kind={MdButtonKind::SmallShort}
+                variant={MdButtonVariant::Green}
+            />
+            if let Some(error) = (*error).clone() {
+                <pre>{error}</pre>
+            }
+        </div>
+    }
+}
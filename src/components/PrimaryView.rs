@@ -1,20 +1,24 @@
 use serde::Serialize;
 use serde_wasm_bindgen::to_value;
-use uuid::Uuid;
+use wasm_bindgen::JsCast;
 use wasm_bindgen_futures::spawn_local;
 use yew::prelude::*;
 
+use crate::backend::{project_backend, store_backend_kind, BackendKind, ProjectBackend};
 use crate::components::ComponentView::ComponentView;
+use crate::components::ExportTools::ExportTools;
 use crate::components::FileBrowser::{FileBrowser, FileKind, FileVariant};
 use crate::components::FileViewer::FileViewer;
+use crate::components::JobsPanel::JobsPanel;
+use crate::components::LevelTools::LevelTools;
 use crate::components::MdButton::{MdButton, MdButtonKind, MdButtonVariant};
 use crate::components::SceneView::SceneView;
+use crate::components::SkyTools::SkyTools;
+use crate::components::WaterTools::WaterTools;
 use crate::contexts::local::{LocalAction, LocalContextType};
 use crate::contexts::localAsync::LocalAsync;
-use crate::contexts::saved::{LevelData, SavedAction, SavedContextType};
-use crate::gql::createMdProject::create_md_project;
-use crate::gql::deleteMdProject::delete_md_project;
-use crate::gql::getMdProject::get_md_project;
+use crate::contexts::saved::{SavedAction, SavedContextType};
+use crate::generation::{store_provider_kind, ProviderKind};
 
 #[derive(Serialize)]
 struct CreateProjectParams {
@@ -41,24 +45,67 @@ pub fn primary_view() -> Html {
     {
         let local_context = local_context.clone();
 
-        use_effect_with(local_context.token.clone(), move |_| {
-            if local_context.token.is_some() {
-                on_refresh();
-            }
-        });
+        use_effect_with(
+            (local_context.token.clone(), local_context.backend),
+            move |_| {
+                if local_context.token.is_some() || local_context.backend == BackendKind::Offline {
+                    on_refresh();
+                }
+            },
+        );
     }
 
+    let on_backend_change = {
+        let local_context = local_context.clone();
+
+        Callback::from(move |e: Event| {
+            let value = e
+                .target()
+                .unwrap()
+                .unchecked_into::<web_sys::HtmlSelectElement>()
+                .value();
+            let backend = if value == "offline" {
+                BackendKind::Offline
+            } else {
+                BackendKind::Remote
+            };
+
+            store_backend_kind(backend);
+            local_context.dispatch(LocalAction::SetBackend(backend));
+        })
+    };
+
+    let on_generation_change = {
+        let local_context = local_context.clone();
+
+        Callback::from(move |e: Event| {
+            let value = e
+                .target()
+                .unwrap()
+                .unchecked_into::<web_sys::HtmlSelectElement>()
+                .value();
+            let generation = if value == "local" {
+                ProviderKind::Local
+            } else {
+                ProviderKind::Remote
+            };
+
+            store_provider_kind(generation);
+            local_context.dispatch(LocalAction::SetGenerationProvider(generation));
+        })
+    };
+
+    let offline = local_context.backend == BackendKind::Offline;
+    let local_generation = local_context.generation == ProviderKind::Local;
+
     let mut scene_display = "none".to_string();
     if local_context.route == "/scene".to_string() {
         scene_display = "flex".to_string();
     }
 
     let id_option = saved_context
-        .levels
-        .clone()
-        .expect("Couldn't get levels")
-        .get(0)
-        .map(|level| level.clone().id);
+        .current_level(local_context.current_level_id.as_ref())
+        .map(|level| level.id.clone());
 
     html! {
         <section>
@@ -69,9 +116,26 @@ pub fn primary_view() -> Html {
 
                     <pre>{"CommonOS File Manager should be running to assure file syncing. New projects are automatically placed into your Sync Folder."}</pre>
 
-                    if local_context.token.is_some() {
+                    <div class="btn-row">
+                        <label>{"Project Storage"}</label>
+                        <select onchange={on_backend_change}>
+                            <option value="remote" selected={!offline}>{"Cloud"}</option>
+                            <option value="offline" selected={offline}>{"Offline"}</option>
+                        </select>
+                        <label>{"Generation"}</label>
+                        <select onchange={on_generation_change}>
+                            <option value="remote" selected={!local_generation}>{"Cloud"}</option>
+                            <option value="local" selected={local_generation}>{"Local Placeholders"}</option>
+                        </select>
+                    </div>
+
+                    if local_context.token.is_some() || offline {
                         <>
-                            <h6>{"Great! You're logged in via CommonOS File Manager."}</h6>
+                            if offline {
+                                <h6>{"Projects are stored on this machine only, no server required."}</h6>
+                            } else {
+                                <h6>{"Great! You're logged in via CommonOS File Manager."}</h6>
+                            }
                             <p>{"Begin by creating a new project (with default settings) or opening an existing one:"}</p>
 
                             if local_context.projects.is_some() {
@@ -105,14 +169,17 @@ pub fn primary_view() -> Html {
                                                                 spawn_local(async move {
                                                                     let projectId = project.clone().id;
 
-                                                                    let md_project = get_md_project(
-                                                                        local_context.token.clone().expect("Failed token fetch"),
-                                                                        projectId.clone(),
-                                                                    )
-                                                                    .await;
-                                                                    let the_project = md_project
-                                                                        .expect("Couldn't unwrap project context")
-                                                                        .getMdProject;
+                                                                    let the_project = match project_backend(&local_context)
+                                                                        .get_project(projectId.clone())
+                                                                        .await
+                                                                    {
+                                                                        Ok(the_project) => the_project,
+                                                                        Err(e) => {
+                                                                            web_sys::console::error_1(&e.into());
+                                                                            loading.set(false);
+                                                                            return;
+                                                                        }
+                                                                    };
                                                                     let updated_context = the_project.context;
 
                                                                     saved_context
@@ -152,11 +219,12 @@ pub fn primary_view() -> Html {
                                                                 web_sys::console::log_1(&"Deleting project...".into());
 
                                                                 spawn_local(async move {
-                                                                    let message = delete_md_project(
-                                                                        local_context.token.clone().expect("Failed token fetch"),
-                                                                        project.id,
-                                                                    )
-                                                                    .await;
+                                                                    if let Err(e) = project_backend(&local_context)
+                                                                        .delete_project(project.id)
+                                                                        .await
+                                                                    {
+                                                                        web_sys::console::error_1(&e.into());
+                                                                    }
 
                                                                     local_async.refresh_projects();
 
@@ -193,13 +261,19 @@ pub fn primary_view() -> Html {
                                             web_sys::console::log_1(&"Creating project...".into());
 
                                             spawn_local(async move {
-                                                // create cloud project via GraphQL or Socket
-                                                let md_project = create_md_project(local_context.token.clone().expect("Failed token fetch")).await;
-                                                let projectId = md_project.expect("Couldn't unwrap project").createMdProject.id;
+                                                // create the project record in the cloud or the local project store
+                                                let projectId = match project_backend(&local_context).create_project().await {
+                                                    Ok(projectId) => projectId,
+                                                    Err(e) => {
+                                                        web_sys::console::error_1(&e.into());
+                                                        loading.set(false);
+                                                        return;
+                                                    }
+                                                };
 
                                                 // create project folder within sync folder: /CommonOSFiles/midpoint/projects/project_id/
                                                 let params = to_value(&CreateProjectParams { projectId: projectId.clone() }).unwrap();
-                                                let result = crate::app::invoke("create_project", params).await;
+                                                let result = crate::invoke::invoke("create_project", params).await;
 
                                                 local_context.dispatch(LocalAction::SetCurrentProject(projectId.clone()));
 
@@ -226,6 +300,7 @@ pub fn primary_view() -> Html {
                     <FileBrowser variant={FileVariant::Concept} kind={FileKind::Image} files={saved_context.concepts.clone()} landscapes={None} />
                     <section>
                         <FileViewer />
+                        <JobsPanel />
                     </section>
                 </div>
             }
@@ -233,35 +308,17 @@ pub fn primary_view() -> Html {
             <div class="view-row" style={"display: ".to_owned() + &scene_display}>
                 if saved_context.levels.is_some() {
                     <section>
-                        <MdButton
-                            label="Add Level"
-                            icon={""}
-                            on_click={Callback::from({
-                                let saved_context = saved_context.clone();
-
-                                move |_| {
-                                    let saved_context = saved_context.clone();
-
-                                    web_sys::console::log_1(&"Adding level...".into());
-
-                                    saved_context.dispatch(SavedAction::AddLevel(LevelData {
-                                        id: Uuid::new_v4().to_string(),
-                                        components: None
-                                    }));
-                                }
-                            })}
-                            disabled={false}
-                            loading={false}
-                            kind={MdButtonKind::SmallShort}
-                            variant={MdButtonVariant::Negative}
-                        />
+                        <LevelTools />
 
                         if id_option.is_some() {
-                            <span>{"Has Level"}</span>
-                        } else {
-                            <span>{"No Level!"}</span>
+                            <SkyTools />
+                            <WaterTools />
+                            <ExportTools />
                         }
 
+                        <JobsPanel />
+                        <FileViewer />
+
                         <div class="btn-row">
                             <MdButton
                                 label="Models"
@@ -9,6 +9,7 @@ use web_sys::{MessageEvent, WebSocket};
 use yew::functional::*;
 use yew::prelude::*;
 
+use crate::backend::BackendKind;
 use crate::contexts::local::LocalContextType;
 use crate::contexts::saved::SavedAction;
 use crate::contexts::saved::SavedContextType;
@@ -85,7 +86,10 @@ pub fn project_socket(props: &ProjectSocketProps) -> Html {
     use_effect_with(local_context.current_project_id.clone(), move |_| {
         web_sys::console::info_1(&"Check ws".into());
 
-        if local_context.current_project_id.is_some() {
+        // offline projects have no server to push changes, so there's nothing to listen to
+        if local_context.current_project_id.is_some()
+            && local_context.backend == BackendKind::Remote
+        {
             web_sys::console::info_1(&"Check ws again".into());
 
             let ws = WebSocket::new("ws://localhost:4000").unwrap();
@@ -106,22 +110,29 @@ pub fn project_socket(props: &ProjectSocketProps) -> Html {
 
                         // {"command":"refreshContext"}
                         if txt == r#"{"command":"refreshContext"}"#.to_string() {
+                            // signing out or closing the project can race a refresh
+                            let (token, project_id) = match (
+                                local_context.token.clone(),
+                                local_context.current_project_id.clone(),
+                            ) {
+                                (Some(token), Some(project_id)) => (token, project_id),
+                                _ => return,
+                            };
+
                             spawn_local(async move {
-                                let md_project = get_md_project(
-                                    local_context.token.clone().expect("Failed token fetch"),
-                                    local_context
-                                        .current_project_id
-                                        .clone()
-                                        .expect("Couldn't fetch project id"),
-                                )
-                                .await;
-                                let latest_project = md_project
-                                    .expect("Couldn't unwrap project context")
-                                    .getMdProject;
+                                let md_project = match get_md_project(token, project_id).await {
+                                    Ok(md_project) => md_project,
+                                    Err(e) => {
+                                        web_sys::console::error_1(
+                                            &format!("Couldn't refresh project: {}", e).into(),
+                                        );
+                                        return;
+                                    }
+                                };
+                                let latest_project = md_project.getMdProject;
                                 let updated_context = latest_project.context;
 
-                                saved_context
-                                    .dispatch(SavedAction::RefreshContext(updated_context));
+                                saved_context.dispatch(SavedAction::SyncContext(updated_context));
                             });
                         }
                     }
@@ -1,44 +1,109 @@
-use std::cell::RefCell;
+use std::cell::{Cell, RefCell};
 use std::rc::Rc;
 use wasm_bindgen::closure::Closure;
 use wasm_bindgen::JsCast;
 use yew::prelude::*;
 
-use crate::renderer::core::handle_key_press;
-use crate::renderer::core::handle_mouse_move;
-use crate::renderer::core::start_render_loop;
+use crate::contexts::renderer::RendererContextType;
+use crate::renderer::core::{start_render_loop, Renderer};
+
+#[derive(Clone, PartialEq, Properties)]
+pub struct SceneCanvasProps {
+    pub canvas_id: String,
+}
+
+// pointer position relative to the scene canvas, along with the canvas size
+fn canvas_point(canvas_id: &str, client_x: i32, client_y: i32) -> Option<(f32, f32, f32, f32)> {
+    let document = web_sys::window()?.document()?;
+    let canvas = document.get_element_by_id(canvas_id)?;
+    let rect = canvas.get_bounding_client_rect();
+
+    Some((
+        client_x as f32 - rect.left() as f32,
+        client_y as f32 - rect.top() as f32,
+        rect.width() as f32,
+        rect.height() as f32,
+    ))
+}
 
 #[function_component]
-pub fn SceneCanvas() -> Html {
+pub fn SceneCanvas(props: &SceneCanvasProps) -> Html {
     // gizmo tool (translate, rotate, scale)
     let gizmo = "translate";
 
     // camera tool (pan, zoom, rotate, orbit)
     let camera = "rotate";
 
+    let renderer_context = use_context::<RendererContextType>().expect("No RendererContext found");
+
+    // event listeners outlive renders, so they read the renderer through a stable ref
+    let renderer: Rc<RefCell<Option<Renderer>>> = use_mut_ref(|| None);
+
     let is_dragging = Rc::new(RefCell::new(false));
     let last_mouse_pos = Rc::new(RefCell::new((0.0, 0.0)));
 
     {
+        let renderer = renderer.clone();
         let is_dragging = is_dragging.clone();
         let last_mouse_pos = last_mouse_pos.clone();
 
-        use_effect_with((), move |_| {
+        use_effect_with(props.canvas_id.clone(), move |canvas_id| {
             web_sys::console::log_1(&"Init SceneCanvas".into());
-            wasm_bindgen_futures::spawn_local(async {
-                start_render_loop().await;
-            });
 
+            // set on unmount, so a renderer that finishes starting afterwards is stopped straight away
+            let unmounted = Rc::new(Cell::new(false));
+
+            {
+                let renderer = renderer.clone();
+                let renderer_context = renderer_context.clone();
+                let unmounted = unmounted.clone();
+                let canvas_id = canvas_id.clone();
+
+                wasm_bindgen_futures::spawn_local(async move {
+                    let started = start_render_loop(canvas_id).await;
+
+                    if unmounted.get() {
+                        started.stop();
+                        return;
+                    }
+
+                    *renderer.borrow_mut() = Some(started.clone());
+                    renderer_context.set(Some(started));
+                });
+            }
+
+            let canvas_id = canvas_id.clone();
+            let listener_renderer = renderer.clone();
             let document = web_sys::window().unwrap().document().unwrap();
             let mouse_move_callback = Closure::wrap(Box::new(move |event: web_sys::PointerEvent| {
+                let renderer = match listener_renderer.borrow().clone() {
+                    Some(renderer) => renderer,
+                    None => return,
+                };
+
                 if *is_dragging.borrow() {
                     let dx = event.client_x() as f32 - last_mouse_pos.borrow().0;
                     let dy = event.client_y() as f32 - last_mouse_pos.borrow().1;
 
                     web_sys::console::log_1(&format!("dx: {}, dy: {}", dx, dy).into());
 
-                    // Call a function to rotate the camera based on dx and dy
-                    handle_mouse_move(dx, dy);
+                    if renderer.is_sculpting() {
+                        // an active terrain brush takes over dragging from the camera
+                        if let Some((x, y, width, height)) =
+                            canvas_point(&canvas_id, event.client_x(), event.client_y())
+                        {
+                            renderer.handle_sculpt_move(x, y, width, height);
+                        }
+                    } else if renderer.is_painting() {
+                        if let Some((x, y, width, height)) =
+                            canvas_point(&canvas_id, event.client_x(), event.client_y())
+                        {
+                            renderer.handle_paint_move(x, y, width, height);
+                        }
+                    } else {
+                        // Call a function to rotate the camera based on dx and dy
+                        renderer.handle_mouse_move(dx, dy);
+                    }
 
                     *last_mouse_pos.borrow_mut() =
                         (event.client_x() as f32, event.client_y() as f32);
@@ -51,15 +116,31 @@ pub fn SceneCanvas() -> Html {
                     mouse_move_callback.as_ref().unchecked_ref(),
                 )
                 .unwrap();
-            mouse_move_callback.forget();
 
             // TODO: mouseup on document in case the mouse is released outside the canvas
 
-            // || ()
+            move || {
+                unmounted.set(true);
+
+                document
+                    .remove_event_listener_with_callback(
+                        "pointermove",
+                        mouse_move_callback.as_ref().unchecked_ref(),
+                    )
+                    .ok();
+                drop(mouse_move_callback);
+
+                if let Some(renderer) = renderer.borrow_mut().take() {
+                    renderer.stop();
+                    renderer_context.set(None);
+                }
+            }
         });
     }
 
     let onmousedown = {
+        let renderer = renderer.clone();
+        let canvas_id = props.canvas_id.clone();
         let is_dragging = is_dragging.clone();
         let last_mouse_pos = last_mouse_pos.clone();
 
@@ -70,26 +151,59 @@ pub fn SceneCanvas() -> Html {
 
             *is_dragging.borrow_mut() = true;
             *last_mouse_pos.borrow_mut() = (event.client_x() as f32, event.client_y() as f32);
+
+            let renderer = match renderer.borrow().clone() {
+                Some(renderer) => renderer,
+                None => return,
+            };
+
+            if renderer.is_sculpting() {
+                if let Some((x, y, width, height)) =
+                    canvas_point(&canvas_id, event.client_x(), event.client_y())
+                {
+                    renderer.handle_sculpt_begin(x, y, width, height);
+                }
+            } else if renderer.is_painting() {
+                if let Some((x, y, width, height)) =
+                    canvas_point(&canvas_id, event.client_x(), event.client_y())
+                {
+                    renderer.handle_paint_begin(x, y, width, height);
+                }
+            }
         })
     };
 
     let onmouseup = {
+        let renderer = renderer.clone();
         let is_dragging = is_dragging.clone();
         Callback::from(move |_event: web_sys::PointerEvent| {
             *is_dragging.borrow_mut() = false;
+
+            if let Some(renderer) = renderer.borrow().as_ref() {
+                if renderer.is_sculpting() {
+                    renderer.handle_sculpt_end();
+                } else if renderer.is_painting() {
+                    renderer.handle_paint_end();
+                }
+            }
         })
     };
 
-    let onkeydown = Callback::from(|event: KeyboardEvent| {
-        let key = event.key();
-        web_sys::console::log_1(&format!("Key pressed (1): {}", key).into());
-        handle_key_press(key, true);
-    });
+    let onkeydown = {
+        let renderer = renderer.clone();
+        Callback::from(move |event: KeyboardEvent| {
+            let key = event.key();
+            web_sys::console::log_1(&format!("Key pressed (1): {}", key).into());
+            if let Some(renderer) = renderer.borrow().as_ref() {
+                renderer.handle_key_press(key, true);
+            }
+        })
+    };
 
     html! {
         <div>
             <canvas
-                id="scene-canvas"
+                id={props.canvas_id.clone()}
                 width="1000"
                 height="600"
                 onkeydown={onkeydown}
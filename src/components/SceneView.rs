@@ -3,10 +3,38 @@ use yew::prelude::*;
 use crate::components::MdButton::{MdButton, MdButtonKind, MdButtonVariant};
 use crate::components::SceneCanvas::SceneCanvas;
 use crate::contexts::local::LocalContextType;
+use crate::contexts::renderer::RendererContextType;
+use crate::contexts::saved::SavedContextType;
 
 #[function_component(SceneView)]
 pub fn scene_view() -> Html {
-    // let local_context = use_context::<LocalContextType>().expect("No LocalContext found");
+    let local_context = use_context::<LocalContextType>().expect("No LocalContext found");
+    let saved_context = use_context::<SavedContextType>().expect("No SavedContext found");
+    let renderer_context = use_context::<RendererContextType>().expect("No RendererContext found");
+
+    let level = saved_context
+        .current_level(local_context.current_level_id.as_ref())
+        .cloned();
+
+    // keep the scene in step with the level, whether it changed here or through a socket refresh
+    use_effect_with(
+        (
+            (*renderer_context).clone(),
+            local_context.current_project_id.clone(),
+            level,
+            // components whose assets weren't listed yet get another go once they are
+            (
+                saved_context.models.clone(),
+                saved_context.landscapes.clone(),
+                saved_context.textures.clone(),
+            ),
+        ),
+        move |(renderer, project_id, level, _)| {
+            if let (Some(renderer), Some(project_id)) = (renderer, project_id) {
+                renderer.sync_level(project_id.clone(), &saved_context, level.as_ref());
+            }
+        },
+    );
 
     html! {
         <>
@@ -22,7 +50,7 @@ pub fn scene_view() -> Html {
             //     />
             // </div>
 
-            <SceneCanvas />
+            <SceneCanvas canvas_id="scene-canvas" />
         </>
     }
 }
@@ -0,0 +1,194 @@
+use serde_wasm_bindgen::to_value;
+use web_sys::HtmlInputElement;
+use yew::prelude::*;
+
+use crate::components::inputs::{color_to_hex, hex_to_color};
+use crate::contexts::local::LocalContextType;
+use crate::contexts::renderer::RendererContextType;
+use crate::contexts::saved::{SavedAction, SavedContextType, SkySettings};
+
+type Edit = fn(&mut SkySettings, String);
+
+#[function_component]
+pub fn SkyTools() -> Html {
+    let local_context = use_context::<LocalContextType>().expect("No LocalContext found");
+    let saved_context = use_context::<SavedContextType>().expect("No SavedContext found");
+    let renderer_context = use_context::<RendererContextType>().expect("No RendererContext found");
+
+    let level = saved_context.current_level(local_context.current_level_id.as_ref());
+    let level_id = level.map(|level| level.id.clone()).unwrap_or_default();
+
+    // levels without saved sky settings use the defaults
+    let saved_settings = level
+        .and_then(|level| level.sky.clone())
+        .unwrap_or_default();
+
+    // a slider being dragged only previews, the level is saved once it's let go
+    let preview = use_state(|| None::<SkySettings>);
+    let settings = (*preview).clone().unwrap_or_else(|| saved_settings.clone());
+
+    {
+        let preview = preview.clone();
+        use_effect_with(level_id.clone(), move |_| preview.set(None));
+    }
+
+    use_effect_with(
+        ((*renderer_context).clone(), settings.clone()),
+        move |(renderer, settings)| {
+            if let Some(renderer) = renderer {
+                renderer.handle_set_sky(to_value(settings).unwrap());
+            }
+        },
+    );
+
+    let update = {
+        let preview = preview.clone();
+        let settings = settings.clone();
+
+        move |apply: Edit| {
+            let preview = preview.clone();
+            let settings = settings.clone();
+
+            Callback::from(move |e: InputEvent| {
+                let input: HtmlInputElement = e.target_unchecked_into();
+                let mut settings = settings.clone();
+                apply(&mut settings, input.value());
+                preview.set(Some(settings));
+            })
+        }
+    };
+
+    let commit = {
+        let saved_context = saved_context.clone();
+        let level_id = level_id.clone();
+        let preview = preview.clone();
+
+        move |apply: Edit| {
+            let saved_context = saved_context.clone();
+            let level_id = level_id.clone();
+            let preview = preview.clone();
+            let settings = saved_settings.clone();
+
+            Callback::from(move |e: Event| {
+                let input: HtmlInputElement = e.target_unchecked_into();
+                let mut settings = settings.clone();
+                apply(&mut settings, input.value());
+                preview.set(None);
+                saved_context.dispatch(SavedAction::SetLevelSky(level_id.clone(), settings));
+            })
+        }
+    };
+
+    let sun_azimuth: Edit = |s, v| {
+        if let Ok(v) = v.parse() {
+            s.sun_azimuth = v
+        }
+    };
+    let sun_elevation: Edit = |s, v| {
+        if let Ok(v) = v.parse() {
+            s.sun_elevation = v
+        }
+    };
+    let sun_intensity: Edit = |s, v| {
+        if let Ok(v) = v.parse() {
+            s.sun_intensity = v
+        }
+    };
+    let zenith_color: Edit = |s, v| {
+        if let Some(c) = hex_to_color(&v) {
+            s.zenith_color = c
+        }
+    };
+    let horizon_color: Edit = |s, v| {
+        if let Some(c) = hex_to_color(&v) {
+            s.horizon_color = c
+        }
+    };
+    let ground_color: Edit = |s, v| {
+        if let Some(c) = hex_to_color(&v) {
+            s.ground_color = c
+        }
+    };
+    let fog_color: Edit = |s, v| {
+        if let Some(c) = hex_to_color(&v) {
+            s.fog_color = c
+        }
+    };
+    let fog_density: Edit = |s, v| {
+        if let Ok(v) = v.parse() {
+            s.fog_density = v
+        }
+    };
+    let fog_height_falloff: Edit = |s, v| {
+        if let Ok(v) = v.parse() {
+            s.fog_height_falloff = v
+        }
+    };
+    let fog_base_height: Edit = |s, v| {
+        if let Ok(v) = v.parse() {
+            s.fog_base_height = v
+        }
+    };
+    let fog_max_opacity: Edit = |s, v| {
+        if let Ok(v) = v.parse() {
+            s.fog_max_opacity = v
+        }
+    };
+
+    html! {
+        <div class="panel sky-tools">
+            <span>{"Sky & Fog"}</span>
+            <div>
+                <label>{format!("Sun Azimuth ({:.0}°)", settings.sun_azimuth)}</label>
+                <input type="range" min="0" max="360" step="1" value={settings.sun_azimuth.to_string()}
+                    oninput={update(sun_azimuth)} onchange={commit(sun_azimuth)} />
+            </div>
+            <div>
+                <label>{format!("Sun Elevation ({:.0}°)", settings.sun_elevation)}</label>
+                <input type="range" min="-20" max="90" step="1" value={settings.sun_elevation.to_string()}
+                    oninput={update(sun_elevation)} onchange={commit(sun_elevation)} />
+            </div>
+            <div>
+                <label>{format!("Sun Intensity ({:.2})", settings.sun_intensity)}</label>
+                <input type="range" min="0" max="3" step="0.05" value={settings.sun_intensity.to_string()}
+                    oninput={update(sun_intensity)} onchange={commit(sun_intensity)} />
+            </div>
+            <div>
+                <label>{"Zenith"}</label>
+                <input type="color" value={color_to_hex(settings.zenith_color)}
+                    oninput={update(zenith_color)} onchange={commit(zenith_color)} />
+                <label>{"Horizon"}</label>
+                <input type="color" value={color_to_hex(settings.horizon_color)}
+                    oninput={update(horizon_color)} onchange={commit(horizon_color)} />
+                <label>{"Ground"}</label>
+                <input type="color" value={color_to_hex(settings.ground_color)}
+                    oninput={update(ground_color)} onchange={commit(ground_color)} />
+            </div>
+            <div>
+                <label>{"Fog Color"}</label>
+                <input type="color" value={color_to_hex(settings.fog_color)}
+                    oninput={update(fog_color)} onchange={commit(fog_color)} />
+            </div>
+            <div>
+                <label>{format!("Fog Density ({:.4})", settings.fog_density)}</label>
+                <input type="range" min="0" max="0.02" step="0.0001" value={settings.fog_density.to_string()}
+                    oninput={update(fog_density)} onchange={commit(fog_density)} />
+            </div>
+            <div>
+                <label>{format!("Fog Height Falloff ({:.3})", settings.fog_height_falloff)}</label>
+                <input type="range" min="0" max="0.2" step="0.001" value={settings.fog_height_falloff.to_string()}
+                    oninput={update(fog_height_falloff)} onchange={commit(fog_height_falloff)} />
+            </div>
+            <div>
+                <label>{format!("Fog Base Height ({:.0})", settings.fog_base_height)}</label>
+                <input type="range" min="-500" max="500" step="1" value={settings.fog_base_height.to_string()}
+                    oninput={update(fog_base_height)} onchange={commit(fog_base_height)} />
+            </div>
+            <div>
+                <label>{format!("Fog Max Opacity ({:.2})", settings.fog_max_opacity)}</label>
+                <input type="range" min="0" max="1" step="0.01" value={settings.fog_max_opacity.to_string()}
+                    oninput={update(fog_max_opacity)} onchange={commit(fog_max_opacity)} />
+            </div>
+        </div>
+    }
+}
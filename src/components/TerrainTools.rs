@@ -0,0 +1,199 @@
+use wasm_bindgen::closure::Closure;
+use wasm_bindgen::{JsCast, JsValue};
+use web_sys::HtmlSelectElement;
+use yew::prelude::*;
+
+use crate::components::BrushControls::BrushControls;
+use crate::components::MdButton::{MdButton, MdButtonKind, MdButtonVariant};
+use crate::contexts::local::{LandscapeTool, LocalAction, LocalContextType};
+use crate::contexts::renderer::RendererContextType;
+
+const BRUSH_KINDS: [&str; 5] = ["Raise", "Lower", "Smooth", "Flatten", "Noise"];
+
+#[derive(Clone, PartialEq, Properties)]
+pub struct TerrainToolsProps {
+    pub landscape_component_id: String,
+    pub landscape_asset_id: String,
+    pub heightmap_filename: String,
+}
+
+#[function_component]
+pub fn TerrainTools(props: &TerrainToolsProps) -> Html {
+    let local_context = use_context::<LocalContextType>().expect("No LocalContext found");
+    let renderer_context = use_context::<RendererContextType>().expect("No RendererContext found");
+
+    // the picked tool lives in the local context, so picking a mask layer shows this brush as off
+    let brush_kind = match &local_context.landscape_tool {
+        Some(LandscapeTool::Sculpt(kind)) => kind.clone(),
+        _ => "None".to_string(),
+    };
+    let radius = use_state(|| 20.0f32);
+    let strength = use_state(|| 0.5f32);
+    let falloff = use_state(|| 0.5f32);
+    let saving = use_state(|| false);
+    let error = use_state(|| None::<String>);
+
+    // keep the renderer's active brush in sync with the controls
+    use_effect_with(
+        (
+            (*renderer_context).clone(),
+            brush_kind.clone(),
+            *radius,
+            *strength,
+            *falloff,
+        ),
+        move |(renderer, kind, radius, strength, falloff)| {
+            let renderer = renderer.clone();
+            let active = kind != "None";
+
+            if let Some(renderer) = renderer.as_ref().filter(|_| active) {
+                renderer.handle_set_terrain_brush(kind.clone(), *radius, *strength, *falloff);
+            }
+
+            move || {
+                if let Some(renderer) = renderer.as_ref().filter(|_| active) {
+                    renderer.handle_clear_terrain_brush();
+                }
+            }
+        },
+    );
+
+    let on_kind_change = {
+        let local_context = local_context.clone();
+
+        Callback::from(move |e: Event| {
+            let input = e.target_dyn_into::<HtmlSelectElement>();
+
+            if let Some(input) = input {
+                let kind = input.value();
+                let tool = if kind == "None" {
+                    None
+                } else {
+                    Some(LandscapeTool::Sculpt(kind))
+                };
+
+                local_context.dispatch(LocalAction::SetLandscapeTool(tool));
+            }
+        })
+    };
+
+    let callback = {
+        let local_context = local_context.clone();
+        let saving = saving.clone();
+        let error = error.clone();
+
+        Callback::from(move |result: JsValue| {
+            saving.set(false);
+
+            match result.as_string() {
+                Some(message) => error.set(Some(message)),
+                None => local_context.dispatch(LocalAction::AssetChanged),
+            }
+        })
+    };
+
+    // Convert the callback to a js_sys::Function, it gets null or the error message
+    let js_callback: js_sys::Function = Closure::wrap(Box::new(move |result: JsValue| {
+        callback.emit(result);
+    }) as Box<dyn FnMut(JsValue)>)
+    .into_js_value()
+    .unchecked_into();
+
+    html! {
+        <div class="panel terrain-tools">
+            <span>{"Sculpt Terrain"}</span>
+            <div>
+                <label>{"Brush"}</label>
+                <select onchange={on_kind_change}>
+                    <option value="None" selected={brush_kind == "None"}>{"Off (camera)"}</option>
+                    {BRUSH_KINDS.iter().map(|kind| {
+                        html!{
+                            <option value={kind.to_string()} selected={brush_kind == *kind}>{kind.to_string()}</option>
+                        }
+                    }).collect::<Html>()}
+                </select>
+            </div>
+            <BrushControls radius={radius.clone()} strength={strength.clone()} falloff={falloff.clone()} />
+            <div class="btn-row">
+                <MdButton
+                    label="Undo"
+                    icon={""}
+                    on_click={Callback::from({
+                        let renderer_context = renderer_context.clone();
+                        move |_| {
+                            if let Some(renderer) = renderer_context.as_ref() {
+                                renderer.handle_sculpt_undo();
+                            }
+                        }
+                    })}
+                    disabled={false}
+                    loading={false}
+                    kind={MdButtonKind::SmallShort}
+                    variant={MdButtonVariant::Light}
+                />
+                <MdButton
+                    label="Redo"
+                    icon={""}
+                    on_click={Callback::from({
+                        let renderer_context = renderer_context.clone();
+                        move |_| {
+                            if let Some(renderer) = renderer_context.as_ref() {
+                                renderer.handle_sculpt_redo();
+                            }
+                        }
+                    })}
+                    disabled={false}
+                    loading={false}
+                    kind={MdButtonKind::SmallShort}
+                    variant={MdButtonVariant::Light}
+                />
+                <MdButton
+                    label="Save Heightmap"
+                    icon={""}
+                    on_click={Callback::from({
+                        let local_context = local_context.clone();
+                        let renderer_context = renderer_context.clone();
+                        let saving = saving.clone();
+                        let error = error.clone();
+                        let props = props.clone();
+
+                        move |_| {
+                            let renderer = match renderer_context.as_ref() {
+                                Some(renderer) => renderer,
+                                None => return,
+                            };
+
+                            let project_id = match local_context.current_project_id.clone() {
+                                Some(project_id) => project_id,
+                                None => {
+                                    error.set(Some("Open a project to save the heightmap".to_string()));
+                                    return;
+                                }
+                            };
+
+                            saving.set(true);
+                            error.set(None);
+
+                            web_sys::console::log_1(&"Saving heightmap...".into());
+
+                            renderer.handle_save_landscape_heightmap(
+                                project_id,
+                                props.landscape_component_id.clone(),
+                                props.landscape_asset_id.clone(),
+                                props.heightmap_filename.clone(),
+                                js_callback.clone(),
+                            );
+                        }
+                    })}
+                    disabled={*saving}
+                    loading={*saving}
+                    kind={MdButtonKind::SmallShort}
+                    variant={MdButtonVariant::Green}
+                />
+            </div>
+            if let Some(error) = (*error).clone() {
+                <pre>{error}</pre>
+            }
+        </div>
+    }
+}
@@ -0,0 +1,204 @@
+use serde::{Deserialize, Serialize};
+use serde_wasm_bindgen::{from_value, to_value};
+use wasm_bindgen_futures::spawn_local;
+use web_sys::HtmlInputElement;
+use yew::prelude::*;
+
+use crate::components::inputs::range_callback;
+use crate::components::MdButton::{MdButton, MdButtonKind, MdButtonVariant};
+use crate::contexts::local::{LocalAction, LocalContextType};
+use crate::contexts::saved::{File, GeneratedFileKind, SavedAction, SavedContextType};
+
+#[derive(Serialize)]
+struct TextureProcessOptions {
+    makeTileable: bool,
+    blend: f32,
+    normalMap: bool,
+    normalStrength: f32,
+    roughnessMap: bool,
+}
+
+#[derive(Serialize)]
+struct ProcessTextureParams {
+    projectId: String,
+    textureFilename: String,
+    options: TextureProcessOptions,
+}
+
+// the normal and roughness maps are only linked from the source texture's sidecar, they
+// aren't albedo textures so they stay off the texture list
+#[derive(Deserialize)]
+struct ProcessedTexture {
+    tileableFilename: Option<String>,
+    tileableUrl: Option<String>,
+}
+
+fn checkbox_callback(value: UseStateHandle<bool>) -> Callback<Event> {
+    Callback::from(move |e: Event| {
+        let input: HtmlInputElement = e.target_unchecked_into();
+        value.set(input.checked());
+    })
+}
+
+#[derive(Clone, PartialEq, Properties)]
+pub struct TextureProcessorProps {
+    pub filename: String,
+}
+
+// landscape layers repeat across the terrain, so textures usually need to tile
+#[function_component]
+pub fn TextureProcessor(props: &TextureProcessorProps) -> Html {
+    let local_context = use_context::<LocalContextType>().expect("No LocalContext found");
+    let saved_context = use_context::<SavedContextType>().expect("No SavedContext found");
+
+    let open = use_state(|| false);
+    let make_tileable = use_state(|| true);
+    let blend = use_state(|| 0.5f32);
+    let normal_map = use_state(|| true);
+    let normal_strength = use_state(|| 2.0f32);
+    let roughness_map = use_state(|| true);
+    let loading = use_state(|| false);
+    let error = use_state(|| None::<String>);
+
+    let on_toggle = {
+        let open = open.clone();
+
+        Callback::from(move |_| {
+            open.set(!*open);
+        })
+    };
+
+    let on_process = {
+        let filename = props.filename.clone();
+        let make_tileable = make_tileable.clone();
+        let blend = blend.clone();
+        let normal_map = normal_map.clone();
+        let normal_strength = normal_strength.clone();
+        let roughness_map = roughness_map.clone();
+        let loading = loading.clone();
+        let error = error.clone();
+
+        Callback::from(move |_| {
+            let projectId = match local_context.current_project_id.clone() {
+                Some(projectId) => projectId,
+                None => return,
+            };
+
+            let params = to_value(&ProcessTextureParams {
+                projectId,
+                textureFilename: filename.clone(),
+                options: TextureProcessOptions {
+                    makeTileable: *make_tileable,
+                    blend: *blend,
+                    normalMap: *normal_map,
+                    normalStrength: *normal_strength,
+                    roughnessMap: *roughness_map,
+                },
+            })
+            .unwrap();
+
+            let local_context = local_context.clone();
+            let saved_context = saved_context.clone();
+            let loading = loading.clone();
+            let error = error.clone();
+
+            loading.set(true);
+            error.set(None);
+
+            web_sys::console::log_1(&"Processing texture...".into());
+
+            spawn_local(async move {
+                match crate::invoke::try_invoke("process_texture", params).await {
+                    Ok(result) => match from_value::<ProcessedTexture>(result) {
+                        Ok(processed) => {
+                            // list the tileable texture right away, like freshly generated ones
+                            if let Some(filename) = processed.tileableFilename {
+                                saved_context.dispatch(SavedAction::RecordGeneratedFile(
+                                    GeneratedFileKind::Texture,
+                                    File {
+                                        id: uuid::Uuid::new_v4().to_string(),
+                                        fileName: filename,
+                                        cloudfrontUrl: processed.tileableUrl.unwrap_or_default(),
+                                        normalFilePath: String::new(),
+                                        generation: None,
+                                    },
+                                ));
+                            }
+
+                            // the source texture now has derived maps to show
+                            local_context.dispatch(LocalAction::AssetChanged);
+                        }
+                        Err(e) => {
+                            let message = format!("Couldn't parse processed texture: {}", e);
+                            web_sys::console::error_1(&message.clone().into());
+                            error.set(Some(message));
+                        }
+                    },
+                    Err(e) => {
+                        let message = e
+                            .as_string()
+                            .unwrap_or_else(|| "Couldn't process texture".to_string());
+                        web_sys::console::error_1(&message.clone().into());
+                        error.set(Some(message));
+                    }
+                }
+
+                loading.set(false);
+            });
+        })
+    };
+
+    html! {
+        <div class="texture-processor">
+            <MdButton
+                label={if *open { "Hide Processing" } else { "Make Tileable / Maps" }}
+                icon={""}
+                on_click={on_toggle}
+                disabled={false}
+                loading={false}
+                kind={MdButtonKind::SmallShort}
+                variant={MdButtonVariant::Light}
+            />
+            if *open {
+                <div>
+                    <label>
+                        <input type="checkbox" checked={*make_tileable} onchange={checkbox_callback(make_tileable.clone())} />
+                        {"Make Tileable"}
+                    </label>
+                    <label>{format!("Blend ({:.2})", *blend)}</label>
+                    <input type="range" min="0.1" max="1" step="0.05" value={blend.to_string()}
+                        disabled={!*make_tileable}
+                        oninput={range_callback(blend.clone())} />
+                </div>
+                <div>
+                    <label>
+                        <input type="checkbox" checked={*normal_map} onchange={checkbox_callback(normal_map.clone())} />
+                        {"Normal Map"}
+                    </label>
+                    <label>{format!("Strength ({:.1})", *normal_strength)}</label>
+                    <input type="range" min="0.5" max="8" step="0.5" value={normal_strength.to_string()}
+                        disabled={!*normal_map}
+                        oninput={range_callback(normal_strength.clone())} />
+                </div>
+                <div>
+                    <label>
+                        <input type="checkbox" checked={*roughness_map} onchange={checkbox_callback(roughness_map.clone())} />
+                        {"Roughness Map"}
+                    </label>
+                </div>
+                if let Some(error) = (*error).clone() {
+                    <pre>{error}</pre>
+                }
+                <MdButton
+                    label="Process"
+                    icon={""}
+                    on_click={on_process}
+                    disabled={*loading || !(*make_tileable || *normal_map || *roughness_map)}
+                    loading={*loading}
+                    kind={MdButtonKind::SmallShort}
+                    variant={MdButtonVariant::Green}
+                />
+            }
+        </div>
+    }
+}
@@ -21,7 +21,7 @@ pub fn TokenLoader(props: &FileBrowserProps) -> Html {
         spawn_local(async move {
             // invoke to read token from local file
             let params = to_value(&ReadAuthTokenParams {}).unwrap();
-            let result = crate::app::invoke("read_token", params).await;
+            let result = crate::invoke::invoke("read_token", params).await;
             let token = result.as_string().expect("Couldn't unwrap auth token");
 
             local_context.dispatch(LocalAction::SetToken(token));
@@ -0,0 +1,54 @@
+use wasm_bindgen::prelude::*;
+use wasm_bindgen::JsCast;
+use yew::prelude::*;
+
+use crate::contexts::saved::{SavedAction, SavedContextType};
+
+// text fields keep their own undo
+fn is_text_input(target: Option<web_sys::EventTarget>) -> bool {
+    target
+        .and_then(|target| target.dyn_into::<web_sys::Element>().ok())
+        .map(|element| matches!(element.tag_name().as_str(), "INPUT" | "TEXTAREA"))
+        .unwrap_or(false)
+}
+
+// Ctrl+Z undoes the last edit, Ctrl+Shift+Z or Ctrl+Y redoes it
+#[function_component]
+pub fn UndoShortcuts() -> Html {
+    let saved_context = use_context::<SavedContextType>().expect("No SavedContext found");
+
+    use_effect_with((), move |_| {
+        let window = web_sys::window().expect("Couldn't get window");
+
+        let on_keydown =
+            Closure::<dyn FnMut(web_sys::KeyboardEvent)>::new(move |e: web_sys::KeyboardEvent| {
+                if !(e.ctrl_key() || e.meta_key()) || is_text_input(e.target()) {
+                    return;
+                }
+
+                let action = match (e.key().to_lowercase().as_str(), e.shift_key()) {
+                    ("z", false) => SavedAction::Undo,
+                    ("z", true) | ("y", false) => SavedAction::Redo,
+                    _ => return,
+                };
+
+                e.prevent_default();
+                saved_context.dispatch(action);
+            });
+
+        window
+            .add_event_listener_with_callback("keydown", on_keydown.as_ref().unchecked_ref())
+            .expect("Couldn't listen for shortcuts");
+
+        move || {
+            let _ = window.remove_event_listener_with_callback(
+                "keydown",
+                on_keydown.as_ref().unchecked_ref(),
+            );
+        }
+    });
+
+    html! {
+        <></>
+    }
+}
@@ -2,7 +2,7 @@ use yew::prelude::*;
 
 use crate::{
     components::MdButton::{MdButton, MdButtonKind, MdButtonVariant},
-    contexts::local::{LocalAction, LocalContextType},
+    contexts::local::{LocalAction, LocalContextType, SaveStatus},
 };
 
 struct NavOption {
@@ -66,6 +66,13 @@ pub fn ViewNav() -> Html {
         };
     }
 
+    let (save_label, save_title) = match &local_context.save_status {
+        SaveStatus::Saved => ("Saved", String::new()),
+        SaveStatus::Unsaved => ("Unsaved", String::new()),
+        SaveStatus::Saving => ("Saving...", String::new()),
+        SaveStatus::Failed(e) => ("Save failed", e.clone()),
+    };
+
     html! {
         <nav class="view-nav">
             {nav_options.into_iter().map(|option| {
@@ -84,6 +91,7 @@ pub fn ViewNav() -> Html {
                     />
                 }
             }).collect::<Html>()}
+            <span class="save-status" title={save_title}>{save_label}</span>
         </nav>
     }
 }
@@ -0,0 +1,187 @@
+use serde_wasm_bindgen::to_value;
+use web_sys::HtmlInputElement;
+use yew::prelude::*;
+
+use crate::components::inputs::{color_to_hex, hex_to_color};
+use crate::contexts::local::LocalContextType;
+use crate::contexts::renderer::RendererContextType;
+use crate::contexts::saved::{SavedAction, SavedContextType, WaterSettings};
+
+type Edit = fn(&mut WaterSettings, String);
+
+#[function_component]
+pub fn WaterTools() -> Html {
+    let local_context = use_context::<LocalContextType>().expect("No LocalContext found");
+    let saved_context = use_context::<SavedContextType>().expect("No SavedContext found");
+    let renderer_context = use_context::<RendererContextType>().expect("No RendererContext found");
+
+    let level = saved_context.current_level(local_context.current_level_id.as_ref());
+    let level_id = level.map(|level| level.id.clone()).unwrap_or_default();
+    let water = level.and_then(|level| level.water.clone());
+
+    // a slider being dragged only previews, the level is saved once it's let go
+    let preview = use_state(|| None::<WaterSettings>);
+    let shown = water
+        .as_ref()
+        .map(|water| (*preview).clone().unwrap_or_else(|| water.clone()));
+
+    {
+        let preview = preview.clone();
+        use_effect_with(level_id.clone(), move |_| preview.set(None));
+    }
+
+    // the saved settings are the source of truth, the renderer just follows them and the preview
+    use_effect_with(
+        ((*renderer_context).clone(), shown.clone()),
+        move |(renderer, water)| {
+            if let Some(renderer) = renderer {
+                match water {
+                    Some(settings) => renderer.handle_set_water(to_value(settings).unwrap()),
+                    None => renderer.handle_clear_water(),
+                }
+            }
+        },
+    );
+
+    let update = {
+        let preview = preview.clone();
+        let shown = shown.clone();
+
+        move |apply: Edit| {
+            let preview = preview.clone();
+            let shown = shown.clone();
+
+            Callback::from(move |e: InputEvent| {
+                let input: HtmlInputElement = e.target_unchecked_into();
+                let mut settings = shown.clone().unwrap_or_default();
+                apply(&mut settings, input.value());
+                preview.set(Some(settings));
+            })
+        }
+    };
+
+    let commit = {
+        let saved_context = saved_context.clone();
+        let level_id = level_id.clone();
+        let preview = preview.clone();
+        let water = water.clone();
+
+        move |apply: Edit| {
+            let saved_context = saved_context.clone();
+            let level_id = level_id.clone();
+            let preview = preview.clone();
+            let water = water.clone();
+
+            Callback::from(move |e: Event| {
+                let input: HtmlInputElement = e.target_unchecked_into();
+                let mut settings = water.clone().unwrap_or_default();
+                apply(&mut settings, input.value());
+                preview.set(None);
+                saved_context
+                    .dispatch(SavedAction::SetLevelWater(level_id.clone(), Some(settings)));
+            })
+        }
+    };
+
+    let on_toggle = {
+        let saved_context = saved_context.clone();
+        let level_id = level_id.clone();
+        let preview = preview.clone();
+
+        Callback::from(move |e: Event| {
+            let input: HtmlInputElement = e.target_unchecked_into();
+            let settings = if input.checked() {
+                Some(WaterSettings::default())
+            } else {
+                None
+            };
+            preview.set(None);
+            saved_context.dispatch(SavedAction::SetLevelWater(level_id.clone(), settings));
+        })
+    };
+
+    let settings = shown.unwrap_or_default();
+
+    let sea_level: Edit = |s, v| {
+        if let Ok(v) = v.parse() {
+            s.sea_level = v
+        }
+    };
+    let color: Edit = |s, v| {
+        if let Some(c) = hex_to_color(&v) {
+            s.color = c
+        }
+    };
+    let opacity: Edit = |s, v| {
+        if let Ok(v) = v.parse() {
+            s.opacity = v
+        }
+    };
+    let wave_height: Edit = |s, v| {
+        if let Ok(v) = v.parse() {
+            s.wave_height = v
+        }
+    };
+    let wave_speed: Edit = |s, v| {
+        if let Ok(v) = v.parse() {
+            s.wave_speed = v
+        }
+    };
+    let wave_length: Edit = |s, v| {
+        if let Ok(v) = v.parse() {
+            s.wave_length = v
+        }
+    };
+    let shoreline_fade: Edit = |s, v| {
+        if let Ok(v) = v.parse() {
+            s.shoreline_fade = v
+        }
+    };
+
+    html! {
+        <div class="panel water-tools">
+            <span>{"Water"}</span>
+            <div>
+                <label>{"Sea Level Water"}</label>
+                <input type="checkbox" checked={water.is_some()} onchange={on_toggle} />
+            </div>
+            if water.is_some() {
+                <div>
+                    <label>{format!("Sea Level ({:.1})", settings.sea_level)}</label>
+                    <input type="range" min="-500" max="500" step="0.5" value={settings.sea_level.to_string()}
+                        oninput={update(sea_level)} onchange={commit(sea_level)} />
+                </div>
+                <div>
+                    <label>{"Color"}</label>
+                    <input type="color" value={color_to_hex(settings.color)}
+                        oninput={update(color)} onchange={commit(color)} />
+                </div>
+                <div>
+                    <label>{format!("Opacity ({:.2})", settings.opacity)}</label>
+                    <input type="range" min="0" max="1" step="0.01" value={settings.opacity.to_string()}
+                        oninput={update(opacity)} onchange={commit(opacity)} />
+                </div>
+                <div>
+                    <label>{format!("Wave Height ({:.2})", settings.wave_height)}</label>
+                    <input type="range" min="0" max="5" step="0.05" value={settings.wave_height.to_string()}
+                        oninput={update(wave_height)} onchange={commit(wave_height)} />
+                </div>
+                <div>
+                    <label>{format!("Wave Speed ({:.2})", settings.wave_speed)}</label>
+                    <input type="range" min="0" max="5" step="0.05" value={settings.wave_speed.to_string()}
+                        oninput={update(wave_speed)} onchange={commit(wave_speed)} />
+                </div>
+                <div>
+                    <label>{format!("Wave Length ({:.0})", settings.wave_length)}</label>
+                    <input type="range" min="1" max="200" step="1" value={settings.wave_length.to_string()}
+                        oninput={update(wave_length)} onchange={commit(wave_length)} />
+                </div>
+                <div>
+                    <label>{format!("Shoreline Fade ({:.1})", settings.shoreline_fade)}</label>
+                    <input type="range" min="0.1" max="50" step="0.1" value={settings.shoreline_fade.to_string()}
+                        oninput={update(shoreline_fade)} onchange={commit(shoreline_fade)} />
+                </div>
+            }
+        </div>
+    }
+}
@@ -0,0 +1,37 @@
+use web_sys::HtmlInputElement;
+use yew::prelude::*;
+
+// keeps a slider's state in step with its value, ignoring anything that doesn't parse
+pub fn range_callback(value: UseStateHandle<f32>) -> Callback<InputEvent> {
+    Callback::from(move |e: InputEvent| {
+        let input: HtmlInputElement = e.target_unchecked_into();
+        if let Ok(parsed) = input.value().parse::<f32>() {
+            value.set(parsed);
+        }
+    })
+}
+
+// color inputs work in hex, settings keep 0-1 channels
+pub fn color_to_hex(color: [f32; 3]) -> String {
+    format!(
+        "#{:02x}{:02x}{:02x}",
+        (color[0].clamp(0.0, 1.0) * 255.0).round() as u8,
+        (color[1].clamp(0.0, 1.0) * 255.0).round() as u8,
+        (color[2].clamp(0.0, 1.0) * 255.0).round() as u8
+    )
+}
+
+pub fn hex_to_color(hex: &str) -> Option<[f32; 3]> {
+    let hex = hex.trim_start_matches('#');
+    if hex.len() != 6 {
+        return None;
+    }
+
+    let channel = |i: usize| {
+        u8::from_str_radix(&hex[i..i + 2], 16)
+            .ok()
+            .map(|c| c as f32 / 255.0)
+    };
+
+    Some([channel(0)?, channel(2)?, channel(4)?])
+}
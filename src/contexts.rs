@@ -1,3 +1,5 @@
+pub mod jobs;
 pub mod local;
 pub mod localAsync;
+pub mod renderer;
 pub mod saved;
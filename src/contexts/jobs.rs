@@ -0,0 +1,174 @@
+// NOTE: jobs live for the editor session only, they aren't saved with the project
+
+use std::cell::Cell;
+use std::rc::Rc;
+use yew::prelude::*;
+
+use crate::contexts::saved::{AssetRef, GeneratedFileKind, GenerationParams};
+
+#[derive(Clone, PartialEq, Debug)]
+pub enum JobStatus {
+    Queued,
+    Running,
+    Done,
+    Failed(String),
+    Cancelled,
+}
+
+// shared by every copy of a job, so the runner sees a cancel while its request is still out
+#[derive(Clone, PartialEq, Debug, Default)]
+pub struct CancelToken(Rc<Cell<bool>>);
+
+impl CancelToken {
+    pub fn cancel(&self) {
+        self.0.set(true);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.0.get()
+    }
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct GenerationJob {
+    pub id: String,
+    pub kind: GeneratedFileKind,
+    pub project_id: String,
+    // the prompt, or the concept image a model is generated from
+    pub input: String,
+    // concepts and textures only
+    pub params: Option<GenerationParams>,
+    // the asset the result is made from, e.g. the concept behind a model
+    pub parent: Option<AssetRef>,
+    // where the result is saved within the project, extra variants get a numbered suffix
+    pub output_filename: String,
+    // every file the job saved, filled in once it's done
+    pub outputs: Vec<String>,
+    pub status: JobStatus,
+    pub cancel_token: CancelToken,
+    // the runner's request hasn't returned yet, a cancelled job keeps its slot until it has
+    pub in_flight: bool,
+    // milliseconds since the epoch
+    pub created_at: f64,
+    pub started_at: Option<f64>,
+    pub finished_at: Option<f64>,
+}
+
+impl GenerationJob {
+    pub fn new(
+        kind: GeneratedFileKind,
+        project_id: String,
+        input: String,
+        params: Option<GenerationParams>,
+        output_filename: String,
+    ) -> Self {
+        Self {
+            id: uuid::Uuid::new_v4().to_string(),
+            kind,
+            project_id,
+            input,
+            params,
+            parent: None,
+            output_filename,
+            outputs: Vec::new(),
+            status: JobStatus::Queued,
+            cancel_token: CancelToken::default(),
+            in_flight: false,
+            created_at: js_sys::Date::now(),
+            started_at: None,
+            finished_at: None,
+        }
+    }
+
+    pub fn is_finished(&self) -> bool {
+        matches!(
+            self.status,
+            JobStatus::Done | JobStatus::Failed(_) | JobStatus::Cancelled
+        )
+    }
+}
+
+#[derive(Clone, PartialEq, Default)]
+pub struct JobsState {
+    pub jobs: Vec<GenerationJob>,
+}
+
+pub enum JobsAction {
+    Enqueue(GenerationJob),
+    Start(String),
+    Complete(String, Vec<String>),
+    Fail(String, String),
+    Cancel(String),
+    ClearFinished,
+}
+
+impl JobsState {
+    fn update_job(&self, job_id: &str, update: impl FnOnce(&mut GenerationJob)) -> JobsState {
+        let mut jobs = self.jobs.clone();
+
+        if let Some(job) = jobs.iter_mut().find(|job| job.id == job_id) {
+            update(job);
+        }
+
+        JobsState { jobs }
+    }
+}
+
+impl Reducible for JobsState {
+    type Action = JobsAction;
+
+    fn reduce(self: Rc<Self>, action: Self::Action) -> Rc<Self> {
+        let now = js_sys::Date::now();
+
+        let next_state = match action {
+            JobsAction::Enqueue(job) => {
+                let mut jobs = self.jobs.clone();
+                jobs.push(job);
+
+                JobsState { jobs }
+            }
+            JobsAction::Start(job_id) => self.update_job(&job_id, |job| {
+                if job.status == JobStatus::Queued {
+                    job.status = JobStatus::Running;
+                    job.in_flight = true;
+                    job.started_at = Some(now);
+                }
+            }),
+            // a job cancelled while running keeps its cancelled status when the result arrives
+            JobsAction::Complete(job_id, outputs) => self.update_job(&job_id, |job| {
+                job.in_flight = false;
+                if job.status == JobStatus::Running {
+                    job.status = JobStatus::Done;
+                    job.outputs = outputs;
+                    job.finished_at = Some(now);
+                }
+            }),
+            JobsAction::Fail(job_id, error) => self.update_job(&job_id, |job| {
+                job.in_flight = false;
+                if job.status == JobStatus::Running {
+                    job.status = JobStatus::Failed(error);
+                    job.finished_at = Some(now);
+                }
+            }),
+            JobsAction::Cancel(job_id) => self.update_job(&job_id, |job| {
+                if !job.is_finished() {
+                    job.cancel_token.cancel();
+                    job.status = JobStatus::Cancelled;
+                    job.finished_at = Some(now);
+                }
+            }),
+            JobsAction::ClearFinished => JobsState {
+                jobs: self
+                    .jobs
+                    .iter()
+                    .filter(|job| !job.is_finished() || job.in_flight)
+                    .cloned()
+                    .collect(),
+            },
+        };
+
+        Rc::new(next_state)
+    }
+}
+
+pub type JobsContextType = UseReducerHandle<JobsState>;
@@ -6,6 +6,10 @@ use yew::prelude::*;
 
 use serde::{Deserialize, Serialize};
 
+use crate::backend::BackendKind;
+use crate::contexts::saved::AssetRef;
+use crate::generation::ProviderKind;
+
 #[derive(Clone, PartialEq, Serialize, Deserialize)]
 pub struct MdProject {
     pub id: String,
@@ -15,13 +19,37 @@ pub struct MdProject {
     pub updatedAt: String,
 }
 
+#[derive(Clone, PartialEq, Serialize, Deserialize, Debug)]
+pub enum SaveStatus {
+    Saved,
+    Unsaved,
+    Saving,
+    Failed(String),
+}
+
+// sculpting and mask painting share the pointer, so only one landscape brush is picked at a time
+#[derive(Clone, PartialEq, Serialize, Deserialize, Debug)]
+pub enum LandscapeTool {
+    Sculpt(String),    // brush kind
+    PaintMask(String), // mask layer
+}
+
 #[derive(Clone, PartialEq, Serialize, Deserialize)]
 pub struct LocalState {
     pub route: String,
     pub token: Option<String>,
     pub current_project_id: Option<String>,
+    pub current_level_id: Option<String>,
     pub projects: Option<Vec<MdProject>>,
     pub selected_component_id: Option<String>,
+    // the concept, texture or model shown in the FileViewer
+    pub selected_asset: Option<AssetRef>,
+    pub backend: BackendKind,
+    pub generation: ProviderKind,
+    pub save_status: SaveStatus,
+    pub landscape_tool: Option<LandscapeTool>,
+    // bumped whenever an asset is rewritten or gains derived files, so views of it know to refresh
+    pub asset_revision: u32,
 }
 
 pub enum LocalAction {
@@ -30,8 +58,16 @@ pub enum LocalAction {
     ClearToken,
     SetCurrentProject(String),
     ClearCurrentProject,
+    SetCurrentLevel(String),
     SetProjects(Vec<MdProject>),
     SetSelectedComponent(String),
+    ClearSelectedComponent,
+    SetSelectedAsset(AssetRef),
+    SetBackend(BackendKind),
+    SetGenerationProvider(ProviderKind),
+    SetSaveStatus(SaveStatus),
+    SetLandscapeTool(Option<LandscapeTool>),
+    AssetChanged,
 }
 
 impl Default for LocalState {
@@ -40,8 +76,15 @@ impl Default for LocalState {
             route: "/".to_string(),
             token: None,
             current_project_id: None,
+            current_level_id: None,
             projects: None,
             selected_component_id: None,
+            selected_asset: None,
+            backend: BackendKind::Remote,
+            generation: ProviderKind::Remote,
+            save_status: SaveStatus::Saved,
+            landscape_tool: None,
+            asset_revision: 0,
         }
     }
 }
@@ -65,18 +108,62 @@ impl Reducible for LocalState {
             },
             LocalAction::SetCurrentProject(project_id) => LocalState {
                 current_project_id: Some(project_id),
+                current_level_id: None,
+                selected_asset: None,
                 ..(*self).clone() // Preserve other fields
             },
             LocalAction::ClearCurrentProject => LocalState {
                 current_project_id: None,
+                current_level_id: None,
+                selected_asset: None,
+                ..(*self).clone() // Preserve other fields
+            },
+            LocalAction::SetCurrentLevel(level_id) => LocalState {
+                current_level_id: Some(level_id),
                 ..(*self).clone() // Preserve other fields
             },
             LocalAction::SetProjects(projects) => LocalState {
                 projects: Some(projects),
                 ..(*self).clone() // Preserve other fields
             },
+            // brushes are put down when the landscape they were picked for is deselected
             LocalAction::SetSelectedComponent(asset_id) => LocalState {
                 selected_component_id: Some(asset_id),
+                landscape_tool: None,
+                ..(*self).clone() // Preserve other fields
+            },
+            LocalAction::ClearSelectedComponent => LocalState {
+                selected_component_id: None,
+                landscape_tool: None,
+                ..(*self).clone() // Preserve other fields
+            },
+            LocalAction::SetSelectedAsset(asset) => LocalState {
+                selected_asset: Some(asset),
+                ..(*self).clone() // Preserve other fields
+            },
+            // projects belong to a backend, so switching drops the current list
+            LocalAction::SetBackend(backend) => LocalState {
+                backend,
+                projects: None,
+                current_project_id: None,
+                current_level_id: None,
+                selected_asset: None,
+                ..(*self).clone() // Preserve other fields
+            },
+            LocalAction::SetGenerationProvider(generation) => LocalState {
+                generation,
+                ..(*self).clone() // Preserve other fields
+            },
+            LocalAction::SetSaveStatus(save_status) => LocalState {
+                save_status,
+                ..(*self).clone() // Preserve other fields
+            },
+            LocalAction::SetLandscapeTool(landscape_tool) => LocalState {
+                landscape_tool,
+                ..(*self).clone() // Preserve other fields
+            },
+            LocalAction::AssetChanged => LocalState {
+                asset_revision: self.asset_revision.wrapping_add(1),
                 ..(*self).clone() // Preserve other fields
             },
         };
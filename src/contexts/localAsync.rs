@@ -1,7 +1,7 @@
 use std::rc::Rc;
 use yew::prelude::*;
 
-use crate::gql::getMdProjects::get_md_projects;
+use crate::backend::{project_backend, BackendKind, ProjectBackend};
 
 use super::local::{LocalAction, LocalState};
 
@@ -19,12 +19,15 @@ impl LocalAsync {
     pub fn refresh_projects(&self) {
         let state = self.state.clone();
         wasm_bindgen_futures::spawn_local(async move {
-            if let Some(token) = &state.token {
+            // cloud projects need a login, offline ones are always available
+            if state.token.is_some() || state.backend == BackendKind::Offline {
                 web_sys::console::log_1(&"Refreshing projects...".into());
 
-                if let Ok(md_projects) = get_md_projects(token.clone()).await {
-                    let latest_projects = md_projects.getMdProjects;
-                    state.dispatch(LocalAction::SetProjects(latest_projects));
+                match project_backend(&state).list_projects().await {
+                    Ok(latest_projects) => {
+                        state.dispatch(LocalAction::SetProjects(latest_projects));
+                    }
+                    Err(e) => web_sys::console::error_1(&e.into()),
                 }
             }
         });
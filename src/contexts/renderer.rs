@@ -0,0 +1,6 @@
+use yew::prelude::*;
+
+use crate::renderer::core::Renderer;
+
+// filled in by the scene canvas once its render loop has started
+pub type RendererContextType = UseStateHandle<Option<Renderer>>;
@@ -1,6 +1,8 @@
-// NOTE: this context is saved in real-time to the database for each project
+// NOTE: this context is auto-saved to the project backend for each project, see AutoSave
 
+use std::collections::HashMap;
 use std::rc::Rc;
+use uuid::Uuid;
 use yew::functional::*;
 use yew::prelude::*;
 
@@ -12,6 +14,88 @@ pub struct File {
     pub fileName: String,
     pub cloudfrontUrl: String,
     pub normalFilePath: String,
+    // how a generated file was made, so it can be made again
+    #[serde(default)]
+    pub generation: Option<GenerationParams>,
+}
+
+#[derive(Clone, Copy, PartialEq, Serialize, Deserialize, Default, Debug)]
+pub enum StylePreset {
+    #[default]
+    None,
+    Photographic,
+    Painterly,
+    Stylized,
+    PixelArt,
+}
+
+impl StylePreset {
+    pub const ALL: [StylePreset; 5] = [
+        StylePreset::None,
+        StylePreset::Photographic,
+        StylePreset::Painterly,
+        StylePreset::Stylized,
+        StylePreset::PixelArt,
+    ];
+}
+
+#[derive(Clone, PartialEq, Serialize, Deserialize, Debug)]
+pub struct GenerationParams {
+    pub prompt: String,
+    #[serde(default)]
+    pub negative_prompt: String,
+    // variant n of a request uses seed + n
+    pub seed: u32,
+    pub width: u32,
+    pub height: u32,
+    #[serde(default)]
+    pub style: StylePreset,
+    pub variants: u32,
+    // textures only, makes the edges wrap
+    #[serde(default)]
+    pub seamless: bool,
+}
+
+impl GenerationParams {
+    // the params that reproduce just one of the variants
+    pub fn for_variant(&self, variant: u32) -> GenerationParams {
+        GenerationParams {
+            seed: self.seed.wrapping_add(variant),
+            variants: 1,
+            ..self.clone()
+        }
+    }
+}
+
+// also names the asset folders on the tauri side, hence lowercase
+#[derive(Clone, Copy, PartialEq, Serialize, Deserialize, Debug)]
+#[serde(rename_all = "lowercase")]
+pub enum GeneratedFileKind {
+    Concept,
+    Texture,
+    Model,
+}
+
+#[derive(Clone, PartialEq, Serialize, Deserialize, Debug)]
+pub struct AssetRef {
+    pub kind: GeneratedFileKind,
+    pub filename: String,
+}
+
+// Kept in a sidecar beside the asset file rather than in the project context, see
+// read_asset_metadata and save_asset_metadata
+#[derive(Clone, PartialEq, Serialize, Deserialize, Default, Debug)]
+#[serde(rename_all = "camelCase", default)]
+pub struct AssetMetadata {
+    pub source_prompt: Option<String>,
+    // the asset this one was made from, e.g. the concept behind a model
+    pub parent: Option<AssetRef>,
+    pub generator: Option<String>,
+    pub created_at: Option<String>,
+    pub tags: Vec<String>,
+    pub width: Option<u32>,
+    pub height: Option<u32>,
+    pub poly_count: Option<u32>,
 }
 
 #[derive(Clone, PartialEq, Serialize, Deserialize, Default, Debug)]
@@ -26,6 +110,7 @@ pub struct LandscapeData {
 pub enum ComponentKind {
     Model,
     Landscape,
+    Foliage,
 }
 
 #[derive(Clone, PartialEq, Serialize, Deserialize, Debug)]
@@ -56,6 +141,27 @@ pub struct ModelProperties {
     // pub id: String,
 }
 
+#[derive(Clone, PartialEq, Serialize, Deserialize, Default, Debug)]
+pub struct FoliageInstance {
+    pub position: [f32; 3],
+    pub rotation: f32, // radians around Y
+    pub scale: f32,
+}
+
+// scatter settings plus the resulting instances, so reloading doesn't reshuffle them
+#[derive(Clone, PartialEq, Serialize, Deserialize, Debug)]
+pub struct FoliageProperties {
+    pub landscape_component_id: String,
+    pub mask_kind: LandscapeTextureKinds,
+    pub density: f32, // instances per 100 square units, before mask and slope rejection
+    pub min_slope: f32, // degrees
+    pub max_slope: f32,
+    pub min_scale: f32,
+    pub max_scale: f32,
+    pub seed: u32,
+    pub instances: Vec<FoliageInstance>,
+}
+
 #[derive(Clone, PartialEq, Serialize, Deserialize, Default, Debug)]
 pub struct ComponentData {
     pub id: String,
@@ -64,12 +170,157 @@ pub struct ComponentData {
     pub generic_properties: GenericProperties,
     pub landscape_properties: Option<LandscapeProperties>,
     pub model_properties: Option<ModelProperties>,
+    pub foliage_properties: Option<FoliageProperties>,
+}
+
+// sea level water body, one per level
+#[derive(Clone, PartialEq, Serialize, Deserialize, Debug)]
+pub struct WaterSettings {
+    pub sea_level: f32,
+    pub color: [f32; 3],
+    pub opacity: f32,
+    pub wave_height: f32,
+    pub wave_speed: f32,
+    pub wave_length: f32,
+    pub shoreline_fade: f32, // depth in world units over which the water fades out at the shore
+}
+
+impl Default for WaterSettings {
+    fn default() -> Self {
+        Self {
+            sea_level: -90.0,
+            color: [0.1, 0.35, 0.5],
+            opacity: 0.8,
+            wave_height: 0.3,
+            wave_speed: 1.0,
+            wave_length: 20.0,
+            shoreline_fade: 4.0,
+        }
+    }
+}
+
+// procedural sky driven by the sun, plus the distance / height fog shared by every surface
+#[derive(Clone, PartialEq, Serialize, Deserialize, Debug)]
+pub struct SkySettings {
+    pub sun_azimuth: f32,   // degrees, clockwise from -Z
+    pub sun_elevation: f32, // degrees above the horizon
+    pub sun_intensity: f32,
+    pub zenith_color: [f32; 3],
+    pub horizon_color: [f32; 3],
+    pub ground_color: [f32; 3],
+    pub fog_color: [f32; 3],
+    pub fog_density: f32,
+    pub fog_height_falloff: f32, // how quickly fog thins out above fog_base_height
+    pub fog_base_height: f32,
+    pub fog_max_opacity: f32,
+}
+
+impl Default for SkySettings {
+    fn default() -> Self {
+        Self {
+            sun_azimuth: 135.0,
+            sun_elevation: 35.0,
+            sun_intensity: 1.0,
+            zenith_color: [0.18, 0.36, 0.7],
+            horizon_color: [0.65, 0.75, 0.85],
+            ground_color: [0.3, 0.3, 0.32],
+            fog_color: [0.65, 0.72, 0.8],
+            fog_density: 0.0015,
+            fog_height_falloff: 0.02,
+            fog_base_height: -100.0,
+            fog_max_opacity: 0.9,
+        }
+    }
 }
 
 #[derive(Clone, PartialEq, Serialize, Deserialize, Default, Debug)]
 pub struct LevelData {
     pub id: String,
+    #[serde(default)]
+    pub name: String,
     pub components: Option<Vec<ComponentData>>,
+    pub water: Option<WaterSettings>,
+    pub sky: Option<SkySettings>,
+}
+
+impl LevelData {
+    // levels saved before they could be named show as "Untitled Level"
+    pub fn display_name(&self) -> String {
+        if self.name.is_empty() {
+            "Untitled Level".to_string()
+        } else {
+            self.name.clone()
+        }
+    }
+
+    // the component plus any foliage scattered on it, which can't outlive its landscape
+    pub fn components_removed_with(&self, component_id: &str) -> Vec<String> {
+        self.components
+            .iter()
+            .flatten()
+            .filter(|component| {
+                component.id == component_id
+                    || component
+                        .foliage_properties
+                        .as_ref()
+                        .map_or(false, |foliage| {
+                            foliage.landscape_component_id == component_id
+                        })
+            })
+            .map(|component| component.id.clone())
+            .collect()
+    }
+}
+
+pub fn duplicate_component(component: &ComponentData, new_component_id: String) -> ComponentData {
+    ComponentData {
+        id: new_component_id,
+        generic_properties: GenericProperties {
+            name: format!("{} Copy", component.generic_properties.name),
+        },
+        ..component.clone()
+    }
+}
+
+// how many edits can be undone
+const HISTORY_LIMIT: usize = 100;
+
+// snapshots taken around each edit, only kept for the current session
+#[derive(Clone, Default, Debug)]
+pub struct EditHistory {
+    past: Vec<Rc<SavedState>>,
+    future: Vec<Rc<SavedState>>,
+}
+
+impl EditHistory {
+    pub fn can_undo(&self) -> bool {
+        !self.past.is_empty()
+    }
+
+    pub fn can_redo(&self) -> bool {
+        !self.future.is_empty()
+    }
+
+    // a new edit makes anything that was undone unreachable
+    fn record(&self, snapshot: Rc<SavedState>) -> Self {
+        let mut past = self.past.clone();
+        past.push(snapshot);
+        if past.len() > HISTORY_LIMIT {
+            past.remove(0);
+        }
+
+        Self {
+            past,
+            future: Vec::new(),
+        }
+    }
+}
+
+// the snapshots themselves never decide whether the view needs to update
+impl PartialEq for EditHistory {
+    fn eq(&self, other: &Self) -> bool {
+        self.past.len() == other.past.len() && self.future.len() == other.future.len()
+    }
 }
 
 #[derive(Clone, PartialEq, Serialize, Deserialize, Debug)]
@@ -79,13 +330,38 @@ pub struct SavedState {
     pub landscapes: Option<Vec<LandscapeData>>,
     pub textures: Option<Vec<File>>,
     pub levels: Option<Vec<LevelData>>,
+    // bumped by every edit but not by loads, so auto-save only reacts to changes made here
+    #[serde(skip)]
+    pub revision: u32,
+    #[serde(skip)]
+    pub history: EditHistory,
 }
 
+// everything below AddLevel is addressed to a level by its id
 pub enum SavedAction {
+    // a project was opened, its history starts over
     RefreshContext(SavedState),
+    // the open project changed elsewhere, what was done here can still be undone
+    SyncContext(SavedState),
     AddLevel(LevelData),
-    AddComponent(ComponentData),
-    SetLandscapeTexture(String, LandscapeTextureKinds, String),
+    RenameLevel(String, String),
+    DuplicateLevel(String, String), // source level id, id for the copy
+    DeleteLevel(String),
+    AddComponent(String, ComponentData),
+    RemoveComponent(String, String),
+    DuplicateComponent(String, String, String), // level id, source component id, id for the copy
+    RenameComponent(String, String, String),
+    SetLandscapeTexture(String, String, LandscapeTextureKinds, String),
+    SetFoliageProperties(String, String, FoliageProperties),
+    SetLevelWater(String, Option<WaterSettings>),
+    SetLevelSky(String, SkySettings),
+    // adds the file, or fills in the params if the sync already listed it.
+    // finished jobs dispatch this in the background, so it's saved but not undoable
+    RecordGeneratedFile(GeneratedFileKind, File),
+    // a landscape written by the backend, like generated files it's saved but not undoable
+    RecordLandscape(LandscapeData),
+    Undo,
+    Redo,
 }
 
 impl Default for SavedState {
@@ -96,16 +372,128 @@ impl Default for SavedState {
             landscapes: None,
             textures: Some(Vec::new()),
             levels: Some(Vec::new()),
+            revision: 0,
+            history: EditHistory::default(),
         }
     }
 }
 
+impl SavedState {
+    // the level being edited, falling back to the newest one until another is picked
+    pub fn current_level(&self, level_id: Option<&String>) -> Option<&LevelData> {
+        let levels = self.levels.as_ref()?;
+
+        level_id
+            .and_then(|level_id| levels.iter().find(|level| &level.id == level_id))
+            .or(levels.last())
+    }
+
+    fn update_level(&self, level_id: &str, apply: impl FnOnce(&mut LevelData)) -> SavedState {
+        let mut new_levels = self.levels.clone().unwrap_or_default();
+        if let Some(level) = new_levels.iter_mut().find(|level| level.id == level_id) {
+            apply(level);
+        }
+        SavedState {
+            levels: Some(new_levels),
+            ..self.clone()
+        }
+    }
+
+    // the state without its history, so snapshots don't nest
+    fn snapshot(&self) -> Rc<Self> {
+        Rc::new(SavedState {
+            history: EditHistory::default(),
+            ..self.clone()
+        })
+    }
+
+    // only levels are edited through undoable actions, so the asset lists are left as they
+    // are now, which keeps files recorded in the background from disappearing again
+    fn restore(&self, snapshot: &SavedState, history: EditHistory) -> Rc<Self> {
+        Rc::new(SavedState {
+            levels: snapshot.levels.clone(),
+            revision: self.revision.wrapping_add(1),
+            history,
+            ..self.clone()
+        })
+    }
+
+    fn undo(self: Rc<Self>) -> Rc<Self> {
+        let mut history = self.history.clone();
+
+        match history.past.pop() {
+            Some(previous) => {
+                history.future.push(self.snapshot());
+                self.restore(&previous, history)
+            }
+            None => self,
+        }
+    }
+
+    fn redo(self: Rc<Self>) -> Rc<Self> {
+        let mut history = self.history.clone();
+
+        match history.future.pop() {
+            Some(next) => {
+                history.past.push(self.snapshot());
+                self.restore(&next, history)
+            }
+            None => self,
+        }
+    }
+}
+
+// components get fresh ids so selection and the renderer can tell the copies apart
+fn duplicate_level(level: &LevelData, new_level_id: String) -> LevelData {
+    let mut components = level.components.clone().unwrap_or_default();
+    let new_ids: HashMap<String, String> = components
+        .iter()
+        .map(|component| (component.id.clone(), Uuid::new_v4().to_string()))
+        .collect();
+
+    for component in components.iter_mut() {
+        component.id = new_ids[&component.id].clone();
+
+        // foliage stays on the copied landscape rather than the original
+        if let Some(foliage) = &mut component.foliage_properties {
+            if let Some(landscape_id) = new_ids.get(&foliage.landscape_component_id) {
+                foliage.landscape_component_id = landscape_id.clone();
+            }
+        }
+    }
+
+    LevelData {
+        id: new_level_id,
+        name: format!("{} Copy", level.display_name()),
+        components: level.components.as_ref().map(|_| components),
+        ..level.clone()
+    }
+}
+
 impl Reducible for SavedState {
     type Action = SavedAction;
 
     fn reduce(self: Rc<Self>, action: Self::Action) -> Rc<Self> {
+        // loads don't need saving, so only changes made here bump the revision
+        let (revision, history) = match &action {
+            SavedAction::Undo => return self.undo(),
+            SavedAction::Redo => return self.redo(),
+            SavedAction::RefreshContext(_) => (self.revision, EditHistory::default()),
+            SavedAction::SyncContext(_) => (self.revision, self.history.clone()),
+            SavedAction::RecordGeneratedFile(..) | SavedAction::RecordLandscape(_) => {
+                (self.revision.wrapping_add(1), self.history.clone())
+            }
+            _ => (
+                self.revision.wrapping_add(1),
+                self.history.record(self.snapshot()),
+            ),
+        };
+
         let next_state = match action {
-            SavedAction::RefreshContext(saved_state) => saved_state,
+            SavedAction::RefreshContext(saved_state) | SavedAction::SyncContext(saved_state) => {
+                saved_state
+            }
+            SavedAction::Undo | SavedAction::Redo => unreachable!("handled above"),
             SavedAction::AddLevel(level_data) => {
                 let mut new_levels = self.levels.clone().unwrap_or_default();
                 new_levels.push(level_data);
@@ -114,21 +502,96 @@ impl Reducible for SavedState {
                     ..(*self).clone()
                 }
             }
-            SavedAction::AddComponent(component_data) => {
+            SavedAction::RecordGeneratedFile(kind, file) => {
+                let mut next_state = (*self).clone();
+                let files = match kind {
+                    GeneratedFileKind::Concept => &mut next_state.concepts,
+                    GeneratedFileKind::Model => &mut next_state.models,
+                    GeneratedFileKind::Texture => next_state.textures.get_or_insert_with(Vec::new),
+                };
+
+                match files.iter_mut().find(|f| f.fileName == file.fileName) {
+                    Some(existing) => {
+                        if existing.cloudfrontUrl.is_empty() {
+                            existing.cloudfrontUrl = file.cloudfrontUrl;
+                        }
+                        existing.generation = file.generation;
+                    }
+                    None => files.push(file),
+                }
+
+                next_state
+            }
+            SavedAction::RecordLandscape(landscape) => {
+                let mut next_state = (*self).clone();
+                let landscapes = next_state.landscapes.get_or_insert_with(Vec::new);
+
+                match landscapes.iter_mut().find(|l| l.id == landscape.id) {
+                    Some(existing) => *existing = landscape,
+                    None => landscapes.push(landscape),
+                }
+
+                next_state
+            }
+            SavedAction::RenameLevel(level_id, name) => self.update_level(&level_id, |level| {
+                level.name = name;
+            }),
+            SavedAction::DuplicateLevel(level_id, new_level_id) => {
                 let mut new_levels = self.levels.clone().unwrap_or_default();
-                if let Some(level) = new_levels.last_mut() {
-                    let mut new_components = level.components.clone().unwrap_or_default();
-                    new_components.push(component_data);
-                    level.components = Some(new_components);
+                if let Some(index) = new_levels.iter().position(|level| level.id == level_id) {
+                    let copy = duplicate_level(&new_levels[index], new_level_id);
+                    new_levels.insert(index + 1, copy);
                 }
                 SavedState {
                     levels: Some(new_levels),
                     ..(*self).clone()
                 }
             }
-            SavedAction::SetLandscapeTexture(component_id, texture_kind, value) => {
+            SavedAction::DeleteLevel(level_id) => {
                 let mut new_levels = self.levels.clone().unwrap_or_default();
-                if let Some(level) = new_levels.last_mut() {
+                new_levels.retain(|level| level.id != level_id);
+                SavedState {
+                    levels: Some(new_levels),
+                    ..(*self).clone()
+                }
+            }
+            SavedAction::AddComponent(level_id, component_data) => {
+                self.update_level(&level_id, |level| {
+                    let mut new_components = level.components.clone().unwrap_or_default();
+                    new_components.push(component_data);
+                    level.components = Some(new_components);
+                })
+            }
+            SavedAction::RemoveComponent(level_id, component_id) => {
+                self.update_level(&level_id, |level| {
+                    let removed = level.components_removed_with(&component_id);
+                    if let Some(components) = &mut level.components {
+                        components.retain(|component| !removed.contains(&component.id));
+                    }
+                })
+            }
+            SavedAction::DuplicateComponent(level_id, component_id, new_component_id) => self
+                .update_level(&level_id, |level| {
+                    if let Some(components) = &mut level.components {
+                        if let Some(index) = components.iter().position(|c| c.id == component_id) {
+                            let copy = duplicate_component(&components[index], new_component_id);
+                            components.insert(index + 1, copy);
+                        }
+                    }
+                }),
+            SavedAction::RenameComponent(level_id, component_id, name) => {
+                self.update_level(&level_id, |level| {
+                    if let Some(components) = &mut level.components {
+                        if let Some(component) =
+                            components.iter_mut().find(|c| c.id == component_id)
+                        {
+                            component.generic_properties.name = name;
+                        }
+                    }
+                })
+            }
+            SavedAction::SetLandscapeTexture(level_id, component_id, texture_kind, value) => self
+                .update_level(&level_id, |level| {
                     if let Some(components) = &mut level.components {
                         if let Some(component) =
                             components.iter_mut().find(|c| c.id == component_id)
@@ -155,16 +618,190 @@ impl Reducible for SavedState {
                             }
                         }
                     }
-                }
-                SavedState {
-                    levels: Some(new_levels),
-                    ..(*self).clone()
-                }
-            }
+                }),
+            SavedAction::SetFoliageProperties(level_id, component_id, foliage_properties) => self
+                .update_level(&level_id, |level| {
+                    if let Some(components) = &mut level.components {
+                        if let Some(component) =
+                            components.iter_mut().find(|c| c.id == component_id)
+                        {
+                            component.foliage_properties = Some(foliage_properties);
+                        }
+                    }
+                }),
+            SavedAction::SetLevelWater(level_id, water) => self.update_level(&level_id, |level| {
+                level.water = water;
+            }),
+            SavedAction::SetLevelSky(level_id, sky) => self.update_level(&level_id, |level| {
+                level.sky = Some(sky);
+            }),
         };
 
-        Rc::new(next_state)
+        Rc::new(SavedState {
+            revision,
+            history,
+            ..next_state
+        })
     }
 }
 
 pub type SavedContextType = UseReducerHandle<SavedState>;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn level(id: &str) -> LevelData {
+        LevelData {
+            id: id.to_string(),
+            ..LevelData::default()
+        }
+    }
+
+    fn level_ids(state: &SavedState) -> Vec<String> {
+        state
+            .levels
+            .iter()
+            .flatten()
+            .map(|level| level.id.clone())
+            .collect()
+    }
+
+    fn file(name: &str) -> File {
+        File {
+            fileName: name.to_string(),
+            ..File::default()
+        }
+    }
+
+    #[test]
+    fn undo_and_redo_round_trip_an_edit() {
+        let start = Rc::new(SavedState::default());
+        let edited = start.clone().reduce(SavedAction::AddLevel(level("one")));
+
+        let undone = edited.clone().reduce(SavedAction::Undo);
+        assert!(level_ids(&undone).is_empty());
+        assert!(undone.history.can_redo());
+
+        let redone = undone.reduce(SavedAction::Redo);
+        assert_eq!(level_ids(&redone), ["one"]);
+        assert!(redone.history.can_undo());
+        assert!(!redone.history.can_redo());
+    }
+
+    #[test]
+    fn new_edit_after_undo_clears_redo() {
+        let state = Rc::new(SavedState::default())
+            .reduce(SavedAction::AddLevel(level("one")))
+            .reduce(SavedAction::Undo)
+            .reduce(SavedAction::AddLevel(level("two")));
+
+        assert!(!state.history.can_redo());
+        assert_eq!(level_ids(&state), ["two"]);
+    }
+
+    #[test]
+    fn history_is_capped() {
+        let mut state = Rc::new(SavedState::default());
+        for index in 0..HISTORY_LIMIT + 10 {
+            state = state.reduce(SavedAction::AddLevel(level(&index.to_string())));
+        }
+
+        for _ in 0..HISTORY_LIMIT {
+            state = state.reduce(SavedAction::Undo);
+        }
+
+        // the oldest edits fell off, so undoing stops short of the empty project
+        assert!(!state.history.can_undo());
+        assert_eq!(level_ids(&state).len(), 10);
+    }
+
+    #[test]
+    fn refresh_resets_history_but_sync_keeps_it() {
+        let edited = Rc::new(SavedState::default()).reduce(SavedAction::AddLevel(level("one")));
+
+        let synced = edited
+            .clone()
+            .reduce(SavedAction::SyncContext(SavedState::default()));
+        assert!(synced.history.can_undo());
+        assert_eq!(synced.revision, edited.revision);
+
+        let refreshed = edited
+            .clone()
+            .reduce(SavedAction::RefreshContext(SavedState::default()));
+        assert!(!refreshed.history.can_undo());
+        assert!(!refreshed.history.can_redo());
+        assert_eq!(refreshed.revision, edited.revision);
+    }
+
+    #[test]
+    fn undo_and_redo_bump_the_revision() {
+        let edited = Rc::new(SavedState::default()).reduce(SavedAction::AddLevel(level("one")));
+        assert_eq!(edited.revision, 1);
+
+        let undone = edited.reduce(SavedAction::Undo);
+        assert_eq!(undone.revision, 2);
+
+        let redone = undone.reduce(SavedAction::Redo);
+        assert_eq!(redone.revision, 3);
+    }
+
+    #[test]
+    fn generated_files_are_saved_but_not_undone() {
+        let state = Rc::new(SavedState::default())
+            .reduce(SavedAction::AddLevel(level("one")))
+            .reduce(SavedAction::RecordGeneratedFile(
+                GeneratedFileKind::Concept,
+                file("concept.png"),
+            ));
+
+        assert_eq!(state.revision, 2);
+        assert_eq!(state.history.past.len(), 1);
+
+        // undoing the level keeps the file that arrived afterwards
+        let undone = state.reduce(SavedAction::Undo);
+        assert!(level_ids(&undone).is_empty());
+        assert_eq!(undone.concepts.len(), 1);
+    }
+
+    #[test]
+    fn recording_a_listed_file_fills_in_a_missing_url() {
+        let state = Rc::new(SavedState {
+            concepts: vec![file("concept.png")],
+            ..SavedState::default()
+        })
+        .reduce(SavedAction::RecordGeneratedFile(
+            GeneratedFileKind::Concept,
+            File {
+                cloudfrontUrl: "data:image/png;base64,AAAA".to_string(),
+                ..file("concept.png")
+            },
+        ));
+
+        assert_eq!(state.concepts.len(), 1);
+        assert_eq!(
+            state.concepts[0].cloudfrontUrl,
+            "data:image/png;base64,AAAA"
+        );
+    }
+
+    #[test]
+    fn recorded_landscapes_are_listed_but_not_undone() {
+        let landscape = LandscapeData {
+            id: "generated".to_string(),
+            heightmap: Some(file("generated-1.tif")),
+            ..LandscapeData::default()
+        };
+
+        let state = Rc::new(SavedState::default())
+            .reduce(SavedAction::AddLevel(level("one")))
+            .reduce(SavedAction::RecordLandscape(landscape.clone()))
+            .reduce(SavedAction::RecordLandscape(landscape.clone()));
+
+        assert_eq!(state.landscapes, Some(vec![landscape]));
+
+        let undone = state.reduce(SavedAction::Undo);
+        assert!(level_ids(&undone).is_empty());
+        assert_eq!(undone.landscapes.as_ref().map(Vec::len), Some(1));
+    }
+}
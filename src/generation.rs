@@ -0,0 +1,97 @@
+// Where concept art, textures and models come from. The cloud provider asks the GraphQL server,
+// the local one draws deterministic placeholders in the editor, so generating, saving and placing
+// assets can all be worked on without the server or any credits.
+
+use serde::{Deserialize, Serialize};
+
+use crate::contexts::local::LocalState;
+use crate::contexts::saved::GenerationParams;
+
+pub mod local;
+pub mod remote;
+
+use local::LocalProvider;
+use remote::RemoteProvider;
+
+const PROVIDER_STORAGE_KEY: &str = "midpoint-generation";
+
+#[derive(Clone, Copy, PartialEq, Serialize, Deserialize, Debug)]
+pub enum ProviderKind {
+    Remote,
+    Local,
+}
+
+// each result is a data url, ready for the matching save_* command. Images come back as one
+// data url per requested variant
+#[allow(async_fn_in_trait)]
+pub trait GenerationProvider {
+    async fn generate_concept(&self, params: &GenerationParams) -> Result<Vec<String>, String>;
+    async fn generate_texture(&self, params: &GenerationParams) -> Result<Vec<String>, String>;
+    // turns a concept image into a GLB model
+    async fn generate_model(&self, image_path: String) -> Result<String, String>;
+}
+
+pub enum Provider {
+    Remote(RemoteProvider),
+    Local(LocalProvider),
+}
+
+impl Provider {
+    // recorded as the generator in asset metadata
+    pub fn name(&self) -> &'static str {
+        match self {
+            Provider::Remote(_) => "cloud",
+            Provider::Local(_) => "local-placeholder",
+        }
+    }
+}
+
+impl GenerationProvider for Provider {
+    async fn generate_concept(&self, params: &GenerationParams) -> Result<Vec<String>, String> {
+        match self {
+            Provider::Remote(provider) => provider.generate_concept(params).await,
+            Provider::Local(provider) => provider.generate_concept(params).await,
+        }
+    }
+
+    async fn generate_texture(&self, params: &GenerationParams) -> Result<Vec<String>, String> {
+        match self {
+            Provider::Remote(provider) => provider.generate_texture(params).await,
+            Provider::Local(provider) => provider.generate_texture(params).await,
+        }
+    }
+
+    async fn generate_model(&self, image_path: String) -> Result<String, String> {
+        match self {
+            Provider::Remote(provider) => provider.generate_model(image_path).await,
+            Provider::Local(provider) => provider.generate_model(image_path).await,
+        }
+    }
+}
+
+// the provider chosen for this session, the cloud one carries the current auth token
+pub fn generation_provider(local_state: &LocalState) -> Provider {
+    match local_state.generation {
+        ProviderKind::Remote => Provider::Remote(RemoteProvider::new(local_state.token.clone())),
+        ProviderKind::Local => Provider::Local(LocalProvider),
+    }
+}
+
+fn local_storage() -> Option<web_sys::Storage> {
+    web_sys::window().and_then(|window| window.local_storage().ok().flatten())
+}
+
+pub fn load_provider_kind() -> ProviderKind {
+    let stored = local_storage().and_then(|storage| storage.get_item(PROVIDER_STORAGE_KEY).ok());
+
+    match stored.flatten().as_deref() {
+        Some("Local") => ProviderKind::Local,
+        _ => ProviderKind::Remote,
+    }
+}
+
+pub fn store_provider_kind(kind: ProviderKind) {
+    if let Some(storage) = local_storage() {
+        let _ = storage.set_item(PROVIDER_STORAGE_KEY, &format!("{:?}", kind));
+    }
+}
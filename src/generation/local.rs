@@ -0,0 +1,376 @@
+// Placeholder generation that runs entirely in the editor. The same prompt and seed always produce
+// the same image or model, which keeps offline sessions reproducible. Negative prompts are ignored.
+
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use image::imageops::{resize, FilterType};
+use image::{ImageFormat, Rgba, RgbaImage};
+use std::io::Cursor;
+
+use crate::contexts::saved::{GenerationParams, StylePreset};
+use crate::generation::GenerationProvider;
+use crate::rng::Rng;
+
+// noise cells across a texture, it wraps around when seamless so the result tiles
+const TEXTURE_CELLS: u32 = 8;
+// pixel art is drawn this many times smaller, then scaled back up
+const PIXEL_ART_SCALE: u32 = 8;
+
+pub struct LocalProvider;
+
+impl GenerationProvider for LocalProvider {
+    async fn generate_concept(&self, params: &GenerationParams) -> Result<Vec<String>, String> {
+        (0..params.variants.max(1))
+            .map(|variant| {
+                let image =
+                    concept_image(variant_seed(params, variant), params.width, params.height);
+                png_data_url(&stylize(image, params.style))
+            })
+            .collect()
+    }
+
+    async fn generate_texture(&self, params: &GenerationParams) -> Result<Vec<String>, String> {
+        (0..params.variants.max(1))
+            .map(|variant| {
+                let image = texture_image(
+                    variant_seed(params, variant),
+                    params.width,
+                    params.height,
+                    params.seamless,
+                );
+                png_data_url(&stylize(image, params.style))
+            })
+            .collect()
+    }
+
+    async fn generate_model(&self, image_path: String) -> Result<String, String> {
+        let glb = primitive_glb(seed_from(&image_path))?;
+
+        Ok(format!(
+            "data:model/gltf-binary;base64,{}",
+            STANDARD.encode(glb)
+        ))
+    }
+}
+
+// FNV-1a, stable across runs unlike the std hasher
+fn seed_from(input: &str) -> u64 {
+    input.bytes().fold(0xcbf29ce484222325, |hash, byte| {
+        (hash ^ byte as u64).wrapping_mul(0x100000001b3)
+    })
+}
+
+// the prompt picks the look and the seed varies it, so a saved seed reproduces a variant
+fn variant_seed(params: &GenerationParams, variant: u32) -> u64 {
+    seed_from(&params.prompt)
+        ^ seed_from(&format!("{:?}", params.style))
+        ^ (params.seed.wrapping_add(variant) as u64).wrapping_mul(0x9e3779b97f4a7c15)
+}
+
+fn random_color(rng: &mut Rng) -> [f32; 3] {
+    [rng.next_f32(), rng.next_f32(), rng.next_f32()]
+}
+
+fn mix(a: [f32; 3], b: [f32; 3], t: f32) -> [f32; 3] {
+    [
+        a[0] + (b[0] - a[0]) * t,
+        a[1] + (b[1] - a[1]) * t,
+        a[2] + (b[2] - a[2]) * t,
+    ]
+}
+
+fn to_pixel(color: [f32; 3]) -> Rgba<u8> {
+    Rgba([
+        (color[0].clamp(0.0, 1.0) * 255.0) as u8,
+        (color[1].clamp(0.0, 1.0) * 255.0) as u8,
+        (color[2].clamp(0.0, 1.0) * 255.0) as u8,
+        255,
+    ])
+}
+
+// a sky gradient over a horizon with a few blobs standing in for the subject
+fn concept_image(seed: u64, width: u32, height: u32) -> RgbaImage {
+    let mut rng = Rng::new(seed);
+
+    let sky_top = random_color(&mut rng);
+    let sky_bottom = random_color(&mut rng);
+    let ground = mix(random_color(&mut rng), [0.2, 0.2, 0.2], 0.5);
+    let horizon = 0.55 + rng.next_f32() * 0.2;
+
+    let blobs: Vec<(f32, f32, f32, [f32; 3])> = (0..3)
+        .map(|_| {
+            (
+                0.2 + rng.next_f32() * 0.6,
+                0.3 + rng.next_f32() * 0.4,
+                0.05 + rng.next_f32() * 0.15,
+                random_color(&mut rng),
+            )
+        })
+        .collect();
+
+    RgbaImage::from_fn(width, height, |x, y| {
+        let u = x as f32 / width as f32;
+        let v = y as f32 / height as f32;
+
+        let mut color = if v < horizon {
+            mix(sky_top, sky_bottom, v / horizon)
+        } else {
+            mix(ground, [0.0, 0.0, 0.0], (v - horizon) * 0.8)
+        };
+
+        for (cx, cy, radius, blob_color) in &blobs {
+            let distance = ((u - cx).powi(2) + (v - cy).powi(2)).sqrt();
+            if distance < *radius {
+                color = mix(*blob_color, color, distance / radius * 0.5);
+            }
+        }
+
+        to_pixel(color)
+    })
+}
+
+// two octaves of value noise between two colors
+fn texture_image(seed: u64, width: u32, height: u32, seamless: bool) -> RgbaImage {
+    let mut rng = Rng::new(seed);
+
+    let dark = random_color(&mut rng);
+    let light = random_color(&mut rng);
+    // one extra row and column so the far edge has its own values when not wrapping
+    let stride = TEXTURE_CELLS + 1;
+    let lattice: Vec<f32> = (0..stride * stride).map(|_| rng.next_f32()).collect();
+
+    let sample = |u: f32, v: f32, cells: u32| {
+        let gx = u * cells as f32;
+        let gy = v * cells as f32;
+        let (x0, y0) = (gx.floor() as u32, gy.floor() as u32);
+        let (tx, ty) = (gx.fract(), gy.fract());
+        // smoothstep hides the lattice
+        let (tx, ty) = (tx * tx * (3.0 - 2.0 * tx), ty * ty * (3.0 - 2.0 * ty));
+
+        let at = |x: u32, y: u32| {
+            let (x, y) = if seamless {
+                (x % cells, y % cells)
+            } else {
+                (x, y)
+            };
+            lattice[(y * stride + x) as usize]
+        };
+
+        let top = at(x0, y0) + (at(x0 + 1, y0) - at(x0, y0)) * tx;
+        let bottom = at(x0, y0 + 1) + (at(x0 + 1, y0 + 1) - at(x0, y0 + 1)) * tx;
+        top + (bottom - top) * ty
+    };
+
+    RgbaImage::from_fn(width, height, |x, y| {
+        let u = x as f32 / width as f32;
+        let v = y as f32 / height as f32;
+
+        let value = sample(u, v, TEXTURE_CELLS / 2) * 0.65 + sample(u, v, TEXTURE_CELLS) * 0.35;
+
+        to_pixel(mix(dark, light, value))
+    })
+}
+
+fn stylize(image: RgbaImage, style: StylePreset) -> RgbaImage {
+    match style {
+        StylePreset::PixelArt => {
+            let (width, height) = image.dimensions();
+            let small = resize(
+                &image,
+                (width / PIXEL_ART_SCALE).max(1),
+                (height / PIXEL_ART_SCALE).max(1),
+                FilterType::Triangle,
+            );
+            resize(&small, width, height, FilterType::Nearest)
+        }
+        _ => image,
+    }
+}
+
+fn png_data_url(image: &RgbaImage) -> Result<String, String> {
+    let mut bytes = Vec::new();
+    image
+        .write_to(&mut Cursor::new(&mut bytes), ImageFormat::Png)
+        .map_err(|e| format!("Couldn't encode image: {}", e))?;
+
+    Ok(format!("data:image/png;base64,{}", STANDARD.encode(bytes)))
+}
+
+// a vertex colored box, sized by the seed so different concepts are easy to tell apart
+pub(crate) fn primitive_glb(seed: u64) -> Result<Vec<u8>, String> {
+    let mut rng = Rng::new(seed);
+
+    let half = [
+        0.25 + rng.next_f32() * 0.5,
+        0.25 + rng.next_f32() * 0.75,
+        0.25 + rng.next_f32() * 0.5,
+    ];
+    let base_color = random_color(&mut rng);
+
+    // (normal axis, sign) for each face
+    let faces = [
+        (0, 1.0),
+        (0, -1.0),
+        (1, 1.0),
+        (1, -1.0),
+        (2, 1.0),
+        (2, -1.0),
+    ];
+
+    let mut positions: Vec<[f32; 3]> = Vec::new();
+    let mut normals: Vec<[f32; 3]> = Vec::new();
+    let mut colors: Vec<[f32; 3]> = Vec::new();
+    let mut indices: Vec<u16> = Vec::new();
+
+    for (axis, sign) in faces {
+        let (u_axis, v_axis) = ((axis + 1) % 3, (axis + 2) % 3);
+        let mut normal = [0.0; 3];
+        normal[axis] = sign;
+
+        // shade each face a little differently so the edges read without lighting
+        let shade = 0.7 + 0.3 * (axis as f32 / 2.0);
+        let color = [
+            base_color[0] * shade,
+            base_color[1] * shade,
+            base_color[2] * shade,
+        ];
+
+        let start = positions.len() as u16;
+        for (u, v) in [(-1.0, -1.0), (1.0, -1.0), (1.0, 1.0), (-1.0, 1.0)] {
+            let mut position = [0.0; 3];
+            position[axis] = sign * half[axis];
+            position[u_axis] = u * half[u_axis];
+            position[v_axis] = v * half[v_axis];
+
+            positions.push(position);
+            normals.push(normal);
+            colors.push(color);
+        }
+
+        // keep the winding counter-clockwise when seen from outside
+        if sign > 0.0 {
+            indices.extend([start, start + 1, start + 2, start, start + 2, start + 3]);
+        } else {
+            indices.extend([start, start + 2, start + 1, start, start + 3, start + 2]);
+        }
+    }
+
+    let mut bin: Vec<u8> = Vec::new();
+    let mut views = Vec::new();
+    for attribute in [&positions, &normals, &colors] {
+        let offset = bin.len();
+        for value in attribute.iter().flatten() {
+            bin.extend(value.to_le_bytes());
+        }
+        views.push((offset, bin.len() - offset));
+    }
+    let index_offset = bin.len();
+    for index in &indices {
+        bin.extend(index.to_le_bytes());
+    }
+    let index_length = bin.len() - index_offset;
+    while bin.len() % 4 != 0 {
+        bin.push(0);
+    }
+
+    let json = serde_json::json!({
+        "asset": { "version": "2.0", "generator": "midpoint local provider" },
+        "scene": 0,
+        "scenes": [{ "nodes": [0] }],
+        "nodes": [{ "mesh": 0 }],
+        "meshes": [{
+            "primitives": [{
+                "attributes": { "POSITION": 0, "NORMAL": 1, "COLOR_0": 2 },
+                "indices": 3
+            }]
+        }],
+        "buffers": [{ "byteLength": bin.len() }],
+        "bufferViews": [
+            { "buffer": 0, "byteOffset": views[0].0, "byteLength": views[0].1, "target": 34962 },
+            { "buffer": 0, "byteOffset": views[1].0, "byteLength": views[1].1, "target": 34962 },
+            { "buffer": 0, "byteOffset": views[2].0, "byteLength": views[2].1, "target": 34962 },
+            { "buffer": 0, "byteOffset": index_offset, "byteLength": index_length, "target": 34963 }
+        ],
+        "accessors": [
+            {
+                "bufferView": 0, "componentType": 5126, "count": positions.len(), "type": "VEC3",
+                "min": [-half[0], -half[1], -half[2]],
+                "max": [half[0], half[1], half[2]]
+            },
+            { "bufferView": 1, "componentType": 5126, "count": normals.len(), "type": "VEC3" },
+            { "bufferView": 2, "componentType": 5126, "count": colors.len(), "type": "VEC3" },
+            { "bufferView": 3, "componentType": 5123, "count": indices.len(), "type": "SCALAR" }
+        ]
+    });
+
+    let mut json = serde_json::to_vec(&json).map_err(|e| format!("Couldn't write model: {}", e))?;
+    // chunks are 4 byte aligned, json pads with spaces
+    while json.len() % 4 != 0 {
+        json.push(b' ');
+    }
+
+    let total_length = 12 + 8 + json.len() + 8 + bin.len();
+
+    let mut glb = Vec::with_capacity(total_length);
+    glb.extend(b"glTF");
+    glb.extend(2u32.to_le_bytes());
+    glb.extend((total_length as u32).to_le_bytes());
+    glb.extend((json.len() as u32).to_le_bytes());
+    glb.extend(b"JSON");
+    glb.extend(json);
+    glb.extend((bin.len() as u32).to_le_bytes());
+    glb.extend(b"BIN\0");
+    glb.extend(bin);
+
+    Ok(glb)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures::executor::block_on;
+
+    fn params(seed: u32) -> GenerationParams {
+        GenerationParams {
+            prompt: "a mossy ruin".to_string(),
+            negative_prompt: String::new(),
+            seed,
+            width: 32,
+            height: 32,
+            style: StylePreset::None,
+            variants: 2,
+            seamless: true,
+        }
+    }
+
+    #[test]
+    fn same_params_give_the_same_images() {
+        let first = block_on(LocalProvider.generate_concept(&params(3))).unwrap();
+        let second = block_on(LocalProvider.generate_concept(&params(3))).unwrap();
+
+        assert_eq!(first.len(), 2);
+        assert_eq!(first, second);
+        assert!(first[0].starts_with("data:image/png;base64,"));
+    }
+
+    #[test]
+    fn a_different_seed_gives_a_different_image() {
+        let first = block_on(LocalProvider.generate_texture(&params(3))).unwrap();
+        let second = block_on(LocalProvider.generate_texture(&params(4))).unwrap();
+
+        assert_ne!(first[0], second[0]);
+        // variant 1 of seed 3 is variant 0 of seed 4
+        assert_eq!(first[1], second[0]);
+    }
+
+    #[test]
+    fn primitive_is_a_valid_glb() {
+        let glb = primitive_glb(seed_from("concepts/ruin.png")).unwrap();
+        let gltf = gltf::Gltf::from_slice(&glb).unwrap();
+
+        let counts: Vec<usize> = gltf.accessors().map(|accessor| accessor.count()).collect();
+        // 6 faces of 4 vertices each, for positions, normals and colors, then 2 triangles a face
+        assert_eq!(counts, vec![24, 24, 24, 36]);
+        assert_eq!(gltf.meshes().count(), 1);
+    }
+}
@@ -0,0 +1,47 @@
+use crate::contexts::saved::GenerationParams;
+use crate::generation::GenerationProvider;
+use crate::gql::generateConcept::generate_concept;
+use crate::gql::generateModel::generate_model;
+use crate::gql::generateTexture::generate_texture;
+
+pub struct RemoteProvider {
+    token: Option<String>,
+}
+
+impl RemoteProvider {
+    pub fn new(token: Option<String>) -> Self {
+        Self { token }
+    }
+
+    fn token(&self) -> Result<String, String> {
+        self.token
+            .clone()
+            .ok_or("Log in via CommonOS File Manager to generate with the cloud".to_string())
+    }
+}
+
+impl GenerationProvider for RemoteProvider {
+    async fn generate_concept(&self, params: &GenerationParams) -> Result<Vec<String>, String> {
+        let data = generate_concept(self.token()?, params)
+            .await
+            .map_err(|e| format!("Couldn't generate concept: {}", e))?;
+
+        Ok(data.generateConcept.into_vec())
+    }
+
+    async fn generate_texture(&self, params: &GenerationParams) -> Result<Vec<String>, String> {
+        let data = generate_texture(self.token()?, params)
+            .await
+            .map_err(|e| format!("Couldn't generate texture: {}", e))?;
+
+        Ok(data.generateTexture.into_vec())
+    }
+
+    async fn generate_model(&self, image_path: String) -> Result<String, String> {
+        let data = generate_model(self.token()?, image_path)
+            .await
+            .map_err(|e| format!("Couldn't generate model: {}", e))?;
+
+        Ok(data.generateModel)
+    }
+}
@@ -1,3 +1,4 @@
+pub mod client;
 pub mod createMdProject;
 pub mod deleteMdProject;
 pub mod generateConcept;
@@ -5,4 +6,5 @@ pub mod generateModel;
 pub mod generateTexture;
 pub mod getMdProject;
 pub mod getMdProjects;
+pub mod getMutationArgs;
 pub mod updateMdProject;
@@ -0,0 +1,207 @@
+// Shared GraphQL client used by every query and mutation in this module. Handles the
+// endpoint, auth header, error reporting and retrying requests that failed in transit.
+
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use std::fmt;
+use wasm_bindgen::JsValue;
+use wasm_bindgen_futures::JsFuture;
+
+const DEV_ENDPOINT: &str = "http://localhost:4000/graphql";
+
+// release builds have to be told where the server is, shipping one that talks to localhost
+// would only show up as failed requests on someone else's machine
+#[cfg(not(debug_assertions))]
+const PRODUCTION_ENDPOINT: &str = env!(
+    "MIDPOINT_GRAPHQL_ENDPOINT",
+    "set MIDPOINT_GRAPHQL_ENDPOINT to the deployed GraphQL server for release builds"
+);
+
+// debug builds can still try a deployed server by setting the same variable
+#[cfg(debug_assertions)]
+const PRODUCTION_ENDPOINT: &str = match option_env!("MIDPOINT_GRAPHQL_ENDPOINT") {
+    Some(endpoint) => endpoint,
+    None => DEV_ENDPOINT,
+};
+
+const MAX_ATTEMPTS: u32 = 3;
+const INITIAL_BACKOFF_MS: u32 = 500;
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Environment {
+    Development,
+    Production,
+}
+
+impl Environment {
+    pub fn current() -> Self {
+        if cfg!(debug_assertions) {
+            Environment::Development
+        } else {
+            Environment::Production
+        }
+    }
+
+    pub fn endpoint(&self) -> &'static str {
+        match self {
+            Environment::Development => DEV_ENDPOINT,
+            Environment::Production => PRODUCTION_ENDPOINT,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum GqlError {
+    // the request never got a response
+    Network(String),
+    Http { status: u16, body: String },
+    // the server answered but the operation failed
+    GraphQL(Vec<String>),
+    Decode(String),
+}
+
+impl GqlError {
+    // only failures that might succeed when sent again
+    fn is_transient(&self) -> bool {
+        match self {
+            GqlError::Network(_) => true,
+            GqlError::Http { status, .. } => matches!(status, 429 | 502 | 503 | 504),
+            GqlError::GraphQL(_) | GqlError::Decode(_) => false,
+        }
+    }
+}
+
+impl fmt::Display for GqlError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GqlError::Network(message) => write!(f, "Network error: {}", message),
+            GqlError::Http { status, body } => write!(f, "Server returned {}: {}", status, body),
+            GqlError::GraphQL(messages) => write!(f, "{}", messages.join(", ")),
+            GqlError::Decode(message) => write!(f, "Invalid response: {}", message),
+        }
+    }
+}
+
+impl std::error::Error for GqlError {}
+
+#[derive(Serialize)]
+struct Request<'a, V> {
+    query: &'a str,
+    variables: V,
+}
+
+#[derive(Deserialize)]
+struct ErrorMessage {
+    message: String,
+}
+
+#[derive(Deserialize)]
+struct Response<T> {
+    data: Option<T>,
+    errors: Option<Vec<ErrorMessage>>,
+}
+
+pub struct GqlClient {
+    endpoint: String,
+    auth_token: Option<String>,
+    max_attempts: u32,
+}
+
+impl GqlClient {
+    pub fn new(auth_token: String) -> Self {
+        Self {
+            endpoint: Environment::current().endpoint().to_string(),
+            auth_token: Some(auth_token),
+            max_attempts: MAX_ATTEMPTS,
+        }
+    }
+
+    // for mutations that shouldn't run twice, like creating projects or paid generations
+    pub fn without_retries(mut self) -> Self {
+        self.max_attempts = 1;
+        self
+    }
+
+    pub async fn query<T, V>(&self, query: &str, variables: V) -> Result<T, GqlError>
+    where
+        T: DeserializeOwned,
+        V: Serialize,
+    {
+        let body = serde_json::to_value(Request { query, variables })
+            .map_err(|e| GqlError::Decode(e.to_string()))?;
+
+        let mut backoff_ms = INITIAL_BACKOFF_MS;
+        let mut attempt = 1;
+
+        loop {
+            match self.send(&body).await {
+                Err(e) if e.is_transient() && attempt < self.max_attempts => {
+                    web_sys::console::warn_1(
+                        &format!(
+                            "GraphQL request failed, retrying in {}ms: {}",
+                            backoff_ms, e
+                        )
+                        .into(),
+                    );
+
+                    sleep(backoff_ms).await;
+
+                    backoff_ms *= 2;
+                    attempt += 1;
+                }
+                result => return result,
+            }
+        }
+    }
+
+    async fn send<T: DeserializeOwned>(&self, body: &serde_json::Value) -> Result<T, GqlError> {
+        let mut request = reqwest::Client::new().post(&self.endpoint).json(body);
+
+        if let Some(auth_token) = &self.auth_token {
+            request = request.header("Authorization", format!("Bearer {}", auth_token));
+        }
+
+        let response = request
+            .send()
+            .await
+            .map_err(|e| GqlError::Network(e.to_string()))?;
+
+        let status = response.status();
+        if !status.is_success() {
+            let body = response.text().await.unwrap_or_default();
+            return Err(GqlError::Http {
+                status: status.as_u16(),
+                body,
+            });
+        }
+
+        let response = response
+            .json::<Response<T>>()
+            .await
+            .map_err(|e| GqlError::Decode(e.to_string()))?;
+
+        if let Some(errors) = response.errors.filter(|errors| !errors.is_empty()) {
+            return Err(GqlError::GraphQL(
+                errors.into_iter().map(|error| error.message).collect(),
+            ));
+        }
+
+        response
+            .data
+            .ok_or(GqlError::Decode("Response had no data".to_string()))
+    }
+}
+
+async fn sleep(ms: u32) {
+    let promise = js_sys::Promise::new(&mut |resolve, _| {
+        let scheduled = web_sys::window().map(|window| {
+            window.set_timeout_with_callback_and_timeout_and_arguments_0(&resolve, ms as i32)
+        });
+
+        // without a window there's nothing to wait on, so carry on straight away
+        if !matches!(scheduled, Some(Ok(_))) {
+            let _ = resolve.call0(&JsValue::NULL);
+        }
+    });
+
+    let _ = JsFuture::from(promise).await;
+}
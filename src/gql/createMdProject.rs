@@ -1,6 +1,6 @@
-use reqwest_graphql::Client;
 use serde::{Deserialize, Serialize};
-use std::{collections::HashMap, fmt::format};
+
+use crate::gql::client::{GqlClient, GqlError};
 
 #[derive(Deserialize)]
 pub struct MdProject {
@@ -26,8 +26,7 @@ pub struct Vars {
     // id: u32,
 }
 
-pub async fn create_md_project(auth_token: String) -> Result<Data, Box<dyn std::error::Error>> {
-    let endpoint = "http://localhost:4000/graphql";
+pub async fn create_md_project(auth_token: String) -> Result<Data, GqlError> {
     let query = r#"
         mutation CreateMdProject {
             createMdProject {
@@ -36,19 +35,10 @@ pub async fn create_md_project(auth_token: String) -> Result<Data, Box<dyn std::
         }
    "#;
 
-    let auth_header = "Bearer ".to_owned() + &auth_token.clone();
-    let auth_header_str: &str = &auth_header[..];
-
-    let mut headers = HashMap::new();
-    headers.insert("Authorization", auth_header_str);
-
-    let client = Client::new_with_headers(endpoint, headers);
+    let client = GqlClient::new(auth_token).without_retries();
 
     let vars = Vars {};
-    let data = client
-        .query_with_vars::<Data, Vars>(query, vars)
-        .await
-        .unwrap();
+    let data = client.query::<Data, Vars>(query, vars).await?;
 
     // println!("Id: {}, Name: {}", data.user.id, data.user.name);
 
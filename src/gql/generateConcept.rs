@@ -1,41 +1,92 @@
-use reqwest_graphql::Client;
 use serde::{Deserialize, Serialize};
-use std::{collections::HashMap, fmt::format};
+
+use crate::contexts::saved::{GenerationParams, StylePreset};
+use crate::gql::client::{GqlClient, GqlError};
+use crate::gql::getMutationArgs::{supports_args, GeneratedImages};
+
+// the args added with generation params
+const PARAM_ARGS: [&str; 6] = [
+    "negativePrompt",
+    "seed",
+    "width",
+    "height",
+    "style",
+    "variants",
+];
 
 #[derive(Deserialize)]
 pub struct Data {
-    pub generateConcept: String,
+    pub generateConcept: GeneratedImages,
+}
+
+#[derive(Serialize)]
+pub struct LegacyVars {
+    prompt: String,
 }
 
 #[derive(Serialize)]
 pub struct Vars {
     prompt: String,
+    negativePrompt: String,
+    seed: u32,
+    width: u32,
+    height: u32,
+    style: StylePreset,
+    variants: u32,
 }
 
 pub async fn generate_concept(
     auth_token: String,
-    prompt: String,
-) -> Result<Data, Box<dyn std::error::Error>> {
-    let endpoint = "http://localhost:4000/graphql";
+    params: &GenerationParams,
+) -> Result<Data, GqlError> {
     let query = r#"
-        mutation GenerateConcept($prompt: String!) {
-            generateConcept(prompt: $prompt)
+        mutation GenerateConcept(
+            $prompt: String!
+            $negativePrompt: String!
+            $seed: Int!
+            $width: Int!
+            $height: Int!
+            $style: String!
+            $variants: Int!
+        ) {
+            generateConcept(
+                prompt: $prompt
+                negativePrompt: $negativePrompt
+                seed: $seed
+                width: $width
+                height: $height
+                style: $style
+                variants: $variants
+            )
         }
    "#;
 
-    let auth_header = "Bearer ".to_owned() + &auth_token.clone();
-    let auth_header_str: &str = &auth_header[..];
+    let client = GqlClient::new(auth_token.clone()).without_retries();
 
-    let mut headers = HashMap::new();
-    headers.insert("Authorization", auth_header_str);
+    // servers from before generation params only take the prompt and return a single image
+    if !supports_args(auth_token, "generateConcept", &PARAM_ARGS).await? {
+        let legacy_query = r#"
+            mutation GenerateConcept($prompt: String!) {
+                generateConcept(prompt: $prompt)
+            }
+        "#;
 
-    let client = Client::new_with_headers(endpoint, headers);
+        let vars = LegacyVars {
+            prompt: params.prompt.clone(),
+        };
+        return client.query::<Data, LegacyVars>(legacy_query, vars).await;
+    }
 
-    let vars = Vars { prompt };
-    let data = client
-        .query_with_vars::<Data, Vars>(query, vars)
-        .await
-        .unwrap();
+    let vars = Vars {
+        prompt: params.prompt.clone(),
+        negativePrompt: params.negative_prompt.clone(),
+        seed: params.seed,
+        width: params.width,
+        height: params.height,
+        style: params.style,
+        variants: params.variants,
+    };
+    let data = client.query::<Data, Vars>(query, vars).await?;
 
     Ok(data)
 }
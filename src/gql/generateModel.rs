@@ -1,6 +1,6 @@
-use reqwest_graphql::Client;
 use serde::{Deserialize, Serialize};
-use std::{collections::HashMap, fmt::format};
+
+use crate::gql::client::{GqlClient, GqlError};
 
 #[derive(Deserialize)]
 pub struct Data {
@@ -12,30 +12,17 @@ pub struct Vars {
     imagePath: String,
 }
 
-pub async fn generate_model(
-    auth_token: String,
-    imagePath: String,
-) -> Result<Data, Box<dyn std::error::Error>> {
-    let endpoint = "http://localhost:4000/graphql";
+pub async fn generate_model(auth_token: String, imagePath: String) -> Result<Data, GqlError> {
     let query = r#"
         mutation GenerateModel($imagePath: String!) {
             generateModel(imagePath: $imagePath)
         }
    "#;
 
-    let auth_header = "Bearer ".to_owned() + &auth_token.clone();
-    let auth_header_str: &str = &auth_header[..];
-
-    let mut headers = HashMap::new();
-    headers.insert("Authorization", auth_header_str);
-
-    let client = Client::new_with_headers(endpoint, headers);
+    let client = GqlClient::new(auth_token).without_retries();
 
     let vars = Vars { imagePath };
-    let data = client
-        .query_with_vars::<Data, Vars>(query, vars)
-        .await
-        .unwrap();
+    let data = client.query::<Data, Vars>(query, vars).await?;
 
     Ok(data)
 }
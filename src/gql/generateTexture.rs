@@ -1,41 +1,97 @@
-use reqwest_graphql::Client;
 use serde::{Deserialize, Serialize};
-use std::{collections::HashMap, fmt::format};
+
+use crate::contexts::saved::{GenerationParams, StylePreset};
+use crate::gql::client::{GqlClient, GqlError};
+use crate::gql::getMutationArgs::{supports_args, GeneratedImages};
+
+// the args added with generation params
+const PARAM_ARGS: [&str; 7] = [
+    "negativePrompt",
+    "seed",
+    "width",
+    "height",
+    "style",
+    "variants",
+    "seamless",
+];
 
 #[derive(Deserialize)]
 pub struct Data {
-    pub generateTexture: String,
+    pub generateTexture: GeneratedImages,
+}
+
+#[derive(Serialize)]
+pub struct LegacyVars {
+    prompt: String,
 }
 
 #[derive(Serialize)]
 pub struct Vars {
     prompt: String,
+    negativePrompt: String,
+    seed: u32,
+    width: u32,
+    height: u32,
+    style: StylePreset,
+    variants: u32,
+    seamless: bool,
 }
 
 pub async fn generate_texture(
     auth_token: String,
-    prompt: String,
-) -> Result<Data, Box<dyn std::error::Error>> {
-    let endpoint = "http://localhost:4000/graphql";
+    params: &GenerationParams,
+) -> Result<Data, GqlError> {
     let query = r#"
-        mutation GenerateTexture($prompt: String!) {
-            generateTexture(prompt: $prompt)
+        mutation GenerateTexture(
+            $prompt: String!
+            $negativePrompt: String!
+            $seed: Int!
+            $width: Int!
+            $height: Int!
+            $style: String!
+            $variants: Int!
+            $seamless: Boolean!
+        ) {
+            generateTexture(
+                prompt: $prompt
+                negativePrompt: $negativePrompt
+                seed: $seed
+                width: $width
+                height: $height
+                style: $style
+                variants: $variants
+                seamless: $seamless
+            )
         }
    "#;
 
-    let auth_header = "Bearer ".to_owned() + &auth_token.clone();
-    let auth_header_str: &str = &auth_header[..];
+    let client = GqlClient::new(auth_token.clone()).without_retries();
 
-    let mut headers = HashMap::new();
-    headers.insert("Authorization", auth_header_str);
+    // servers from before generation params only take the prompt and return a single image
+    if !supports_args(auth_token, "generateTexture", &PARAM_ARGS).await? {
+        let legacy_query = r#"
+            mutation GenerateTexture($prompt: String!) {
+                generateTexture(prompt: $prompt)
+            }
+        "#;
 
-    let client = Client::new_with_headers(endpoint, headers);
+        let vars = LegacyVars {
+            prompt: params.prompt.clone(),
+        };
+        return client.query::<Data, LegacyVars>(legacy_query, vars).await;
+    }
 
-    let vars = Vars { prompt };
-    let data = client
-        .query_with_vars::<Data, Vars>(query, vars)
-        .await
-        .unwrap();
+    let vars = Vars {
+        prompt: params.prompt.clone(),
+        negativePrompt: params.negative_prompt.clone(),
+        seed: params.seed,
+        width: params.width,
+        height: params.height,
+        style: params.style,
+        variants: params.variants,
+        seamless: params.seamless,
+    };
+    let data = client.query::<Data, Vars>(query, vars).await?;
 
     Ok(data)
 }
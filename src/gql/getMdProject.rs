@@ -1,8 +1,7 @@
-use reqwest_graphql::Client;
 use serde::{Deserialize, Serialize};
-use std::{collections::HashMap, fmt::format};
 
 use crate::contexts::saved::SavedState;
+use crate::gql::client::{GqlClient, GqlError};
 
 #[derive(Deserialize)]
 pub struct MdProject {
@@ -24,11 +23,7 @@ pub struct Vars {
     projectId: String,
 }
 
-pub async fn get_md_project(
-    auth_token: String,
-    projectId: String,
-) -> Result<Data, Box<dyn std::error::Error>> {
-    let endpoint = "http://localhost:4000/graphql";
+pub async fn get_md_project(auth_token: String, projectId: String) -> Result<Data, GqlError> {
     let query = r#"
         query GetMdProject($projectId: String!) {
             getMdProject(projectId: $projectId) {
@@ -41,19 +36,10 @@ pub async fn get_md_project(
         }
    "#;
 
-    let auth_header = "Bearer ".to_owned() + &auth_token.clone();
-    let auth_header_str: &str = &auth_header[..];
-
-    let mut headers = HashMap::new();
-    headers.insert("Authorization", auth_header_str);
-
-    let client = Client::new_with_headers(endpoint, headers);
+    let client = GqlClient::new(auth_token);
 
     let vars = Vars { projectId };
-    let data = client
-        .query_with_vars::<Data, Vars>(query, vars)
-        .await
-        .unwrap();
+    let data = client.query::<Data, Vars>(query, vars).await?;
 
     // println!("Id: {}, Name: {}", data.user.id, data.user.name);
 
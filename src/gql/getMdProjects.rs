@@ -1,8 +1,7 @@
-use reqwest_graphql::Client;
 use serde::{Deserialize, Serialize};
-use std::{collections::HashMap, fmt::format};
 
 use crate::contexts::{local::MdProject, saved::SavedState};
+use crate::gql::client::{GqlClient, GqlError};
 
 // #[derive(Deserialize)]
 // pub struct MdProject {
@@ -21,8 +20,7 @@ pub struct Data {
 #[derive(Serialize)]
 pub struct Vars {}
 
-pub async fn get_md_projects(auth_token: String) -> Result<Data, Box<dyn std::error::Error>> {
-    let endpoint = "http://localhost:4000/graphql";
+pub async fn get_md_projects(auth_token: String) -> Result<Data, GqlError> {
     let query = r#"
         query GetMdProjects {
             getMdProjects {
@@ -34,19 +32,10 @@ pub async fn get_md_projects(auth_token: String) -> Result<Data, Box<dyn std::er
         }
    "#;
 
-    let auth_header = "Bearer ".to_owned() + &auth_token.clone();
-    let auth_header_str: &str = &auth_header[..];
-
-    let mut headers = HashMap::new();
-    headers.insert("Authorization", auth_header_str);
-
-    let client = Client::new_with_headers(endpoint, headers);
+    let client = GqlClient::new(auth_token);
 
     let vars = Vars {};
-    let data = client
-        .query_with_vars::<Data, Vars>(query, vars)
-        .await
-        .unwrap();
+    let data = client.query::<Data, Vars>(query, vars).await?;
 
     // println!("Id: {}, Name: {}", data.user.id, data.user.name);
 
@@ -0,0 +1,124 @@
+use std::cell::RefCell;
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::gql::client::{GqlClient, GqlError};
+
+#[derive(Deserialize)]
+pub struct Data {
+    pub __schema: Schema,
+}
+
+#[derive(Deserialize)]
+pub struct Schema {
+    pub mutationType: Option<MutationType>,
+}
+
+#[derive(Deserialize)]
+pub struct MutationType {
+    pub fields: Vec<Field>,
+}
+
+#[derive(Deserialize)]
+pub struct Field {
+    pub name: String,
+    pub args: Vec<Arg>,
+}
+
+#[derive(Deserialize)]
+pub struct Arg {
+    pub name: String,
+}
+
+#[derive(Serialize)]
+pub struct Vars {}
+
+thread_local! {
+    // the server's mutations and their argument names, asked for once per session
+    static MUTATION_ARGS: RefCell<Option<HashMap<String, Vec<String>>>> = RefCell::new(None);
+}
+
+pub async fn get_mutation_args(auth_token: String) -> Result<Data, GqlError> {
+    let query = r#"
+        query GetMutationArgs {
+            __schema {
+                mutationType {
+                    fields {
+                        name
+                        args {
+                            name
+                        }
+                    }
+                }
+            }
+        }
+   "#;
+
+    let client = GqlClient::new(auth_token);
+
+    let vars = Vars {};
+    let data = client.query::<Data, Vars>(query, vars).await?;
+
+    Ok(data)
+}
+
+// whether the server's mutation takes all of these arguments, so newer ones are only sent to
+// servers that know them
+pub async fn supports_args(
+    auth_token: String,
+    mutation: &str,
+    args: &[&str],
+) -> Result<bool, GqlError> {
+    if MUTATION_ARGS.with(|cached| cached.borrow().is_none()) {
+        let mutations = match get_mutation_args(auth_token).await {
+            Ok(data) => data
+                .__schema
+                .mutationType
+                .map(|mutation_type| mutation_type.fields)
+                .unwrap_or_default()
+                .into_iter()
+                .map(|field| {
+                    let args = field.args.into_iter().map(|arg| arg.name).collect();
+                    (field.name, args)
+                })
+                .collect(),
+            // a server with introspection turned off is treated as only knowing the original args
+            Err(GqlError::GraphQL(messages)) => {
+                web_sys::console::warn_1(
+                    &format!("Couldn't read server mutations: {}", messages.join(", ")).into(),
+                );
+                HashMap::new()
+            }
+            Err(e) => return Err(e),
+        };
+
+        MUTATION_ARGS.with(|cached| *cached.borrow_mut() = Some(mutations));
+    }
+
+    Ok(MUTATION_ARGS.with(|cached| {
+        cached
+            .borrow()
+            .as_ref()
+            .and_then(|mutations| mutations.get(mutation))
+            .map(|known| args.iter().all(|arg| known.iter().any(|name| name == arg)))
+            .unwrap_or(false)
+    }))
+}
+
+// newer servers return one data url per variant, older ones a single data url
+#[derive(Deserialize)]
+#[serde(untagged)]
+pub enum GeneratedImages {
+    One(String),
+    Many(Vec<String>),
+}
+
+impl GeneratedImages {
+    pub fn into_vec(self) -> Vec<String> {
+        match self {
+            GeneratedImages::One(image) => vec![image],
+            GeneratedImages::Many(images) => images,
+        }
+    }
+}
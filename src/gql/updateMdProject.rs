@@ -1,8 +1,7 @@
-use reqwest_graphql::Client;
 use serde::{Deserialize, Serialize};
-use std::{collections::HashMap, fmt::format};
 
-use crate::contexts::local::LocalState;
+use crate::contexts::saved::SavedState;
+use crate::gql::client::{GqlClient, GqlError};
 
 #[derive(Deserialize)]
 pub struct MdProject {
@@ -14,7 +13,7 @@ pub struct MdProject {
 
 #[derive(Deserialize)]
 pub struct Data {
-    pub createMdProject: MdProject,
+    pub updateMdProject: MdProject,
 }
 
 // #[derive(Deserialize)]
@@ -27,17 +26,16 @@ pub struct Data {
 pub struct Vars {
     // id: u32,
     projectId: String,
-    title: String,
+    title: Option<String>,
     context: String,
 }
 
 pub async fn update_md_project(
     auth_token: String,
     projectId: String,
-    title: String,
-    context: &LocalState,
-) -> Result<Data, Box<dyn std::error::Error>> {
-    let endpoint = "http://localhost:4000/graphql";
+    title: Option<String>,
+    context: &SavedState,
+) -> Result<Data, GqlError> {
     let query = r#"
         mutation UpdateMdProject($projectId: String!, $title: String, $context: String) {
             updateMdProject(projectId: $projectId, title: $title, context: $context) {
@@ -46,13 +44,7 @@ pub async fn update_md_project(
         }
    "#;
 
-    let auth_header = "Bearer ".to_owned() + &auth_token.clone();
-    let auth_header_str: &str = &auth_header[..];
-
-    let mut headers = HashMap::new();
-    headers.insert("Authorization", auth_header_str);
-
-    let client = Client::new_with_headers(endpoint, headers);
+    let client = GqlClient::new(auth_token);
 
     let context = serde_json::to_string(&*context).expect("Failed to serialize");
 
@@ -61,10 +53,7 @@ pub async fn update_md_project(
         title,
         context,
     };
-    let data = client
-        .query_with_vars::<Data, Vars>(query, vars)
-        .await
-        .unwrap();
+    let data = client.query::<Data, Vars>(query, vars).await?;
 
     // println!("Id: {}, Name: {}", data.user.id, data.user.name);
 
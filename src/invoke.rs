@@ -0,0 +1,18 @@
+// Bindings to Tauri's invoke, shared by the components, the backends and the renderer.
+
+use wasm_bindgen::prelude::*;
+
+#[wasm_bindgen]
+extern "C" {
+    #[wasm_bindgen(js_namespace = ["window", "__TAURI__", "tauri"])]
+    pub async fn invoke(cmd: &str, args: JsValue) -> JsValue;
+
+    // same as invoke, but a command returning Err comes back as Err instead of throwing
+    #[wasm_bindgen(catch, js_namespace = ["window", "__TAURI__", "tauri"], js_name = invoke)]
+    pub async fn try_invoke(cmd: &str, args: JsValue) -> Result<JsValue, JsValue>;
+}
+
+// commands reject with their error string, anything else gets the fallback
+pub fn invoke_error(e: JsValue, fallback: &str) -> String {
+    e.as_string().unwrap_or_else(|| fallback.to_string())
+}
@@ -1,3 +1,7 @@
+pub mod backend;
 pub mod contexts;
+pub mod generation;
 pub mod gql;
+pub mod invoke;
 pub mod renderer;
+pub mod rng;
@@ -2,10 +2,14 @@ mod app;
 
 use app::App;
 
+mod backend;
 mod components;
 mod contexts;
+mod generation;
 mod gql;
+mod invoke;
 pub mod renderer;
+mod rng;
 
 fn main() {
     console_error_panic_hook::set_once();
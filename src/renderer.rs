@@ -1,8 +1,19 @@
+pub mod Foliage;
 pub mod Grid;
+pub mod Headless;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod Ktx2;
 pub mod Landscape;
 pub mod Model;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod Physics;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod Runtime;
 pub mod SimpleCamera;
+pub mod Sky;
+pub mod TerrainBrush;
 pub mod Texture;
 pub mod Transform;
+pub mod Water;
 pub mod core;
 pub mod shapes;
@@ -0,0 +1,153 @@
+use nalgebra::{Matrix4, Vector3};
+use wgpu::util::DeviceExt;
+
+use bytemuck::{Pod, Zeroable};
+
+use crate::contexts::saved::{FoliageInstance, FoliageProperties, LandscapeTextureKinds};
+use crate::renderer::Landscape::Landscape;
+use crate::renderer::Model::Model;
+use crate::renderer::Transform::matrix4_to_raw_array;
+use crate::rng::Rng;
+
+// hard cap so a large landscape with a high density can't stall the editor
+pub const MAX_FOLIAGE_INSTANCES: usize = 50000;
+
+// density is measured in instances per this many square world units
+const DENSITY_AREA: f32 = 100.0;
+
+#[repr(C)]
+#[derive(Copy, Clone, Debug)]
+pub struct InstanceRaw {
+    pub model: [[f32; 4]; 4],
+}
+
+unsafe impl Pod for InstanceRaw {}
+unsafe impl Zeroable for InstanceRaw {}
+
+impl InstanceRaw {
+    // follows Vertex's locations 0 - 3
+    const ATTRIBS: [wgpu::VertexAttribute; 4] = wgpu::vertex_attr_array![
+        4 => Float32x4,
+        5 => Float32x4,
+        6 => Float32x4,
+        7 => Float32x4
+    ];
+
+    pub fn desc<'a>() -> wgpu::VertexBufferLayout<'a> {
+        wgpu::VertexBufferLayout {
+            array_stride: std::mem::size_of::<InstanceRaw>() as wgpu::BufferAddress,
+            step_mode: wgpu::VertexStepMode::Instance,
+            attributes: &Self::ATTRIBS,
+        }
+    }
+
+    pub fn from_instance(instance: &FoliageInstance) -> Self {
+        let translation = Matrix4::new_translation(&Vector3::from(instance.position));
+        let rotation = Matrix4::from_euler_angles(0.0, instance.rotation, 0.0);
+        let scale = Matrix4::new_scaling(instance.scale);
+        let matrix = (translation * rotation * scale).transpose(); // Transpose to match wgpu layout
+
+        Self {
+            model: matrix4_to_raw_array(&matrix),
+        }
+    }
+}
+
+// one scattered model, drawn once per mesh with all of its instances
+pub struct Foliage {
+    pub id: String,
+    pub model: Model,
+    pub instance_buffer: Option<wgpu::Buffer>,
+    pub instance_count: u32,
+}
+
+impl Foliage {
+    pub fn new(id: String, model: Model) -> Self {
+        Self {
+            id,
+            model,
+            instance_buffer: None,
+            instance_count: 0,
+        }
+    }
+
+    pub fn destroy(&self) {
+        self.model.destroy();
+
+        if let Some(instance_buffer) = &self.instance_buffer {
+            instance_buffer.destroy();
+        }
+    }
+
+    pub fn set_instances(&mut self, device: &wgpu::Device, instances: &[FoliageInstance]) {
+        let raw: Vec<InstanceRaw> = instances.iter().map(InstanceRaw::from_instance).collect();
+
+        self.instance_count = raw.len() as u32;
+        self.instance_buffer = if raw.is_empty() {
+            None
+        } else {
+            Some(
+                device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                    label: Some("Foliage Instance Buffer"),
+                    contents: bytemuck::cast_slice(&raw),
+                    usage: wgpu::BufferUsages::VERTEX,
+                }),
+            )
+        };
+    }
+}
+
+fn mask_layer(kind: &LandscapeTextureKinds) -> usize {
+    match kind {
+        LandscapeTextureKinds::Primary | LandscapeTextureKinds::PrimaryMask => 0,
+        LandscapeTextureKinds::Rockmap | LandscapeTextureKinds::RockmapMask => 1,
+        LandscapeTextureKinds::Soil | LandscapeTextureKinds::SoilMask => 2,
+    }
+}
+
+// place instances over the landscape, weighted by the chosen mask layer and limited by slope
+pub fn scatter(landscape: &Landscape, settings: &FoliageProperties) -> Vec<FoliageInstance> {
+    if landscape.grid_width < 2 || landscape.grid_height < 2 {
+        return Vec::new();
+    }
+
+    let (spacing_x, spacing_z) = landscape.spacing();
+    let area = ((landscape.grid_width - 1) as f32 * spacing_x).abs()
+        * ((landscape.grid_height - 1) as f32 * spacing_z).abs();
+    let candidates =
+        ((area / DENSITY_AREA * settings.density.max(0.0)) as usize).min(MAX_FOLIAGE_INSTANCES);
+
+    let layer = mask_layer(&settings.mask_kind);
+    let mut rng = Rng::new(settings.seed as u64);
+    let mut instances = Vec::new();
+
+    for _ in 0..candidates {
+        // always draw every value so one rejected candidate doesn't shift the rest
+        let gx = rng.range(0.0, (landscape.grid_width - 1) as f32);
+        let gy = rng.range(0.0, (landscape.grid_height - 1) as f32);
+        let keep = rng.next_f32();
+        let rotation = rng.range(0.0, std::f32::consts::TAU);
+        let scale = rng.range(settings.min_scale, settings.max_scale);
+
+        let slope = landscape.slope_degrees(gx, gy);
+        if slope < settings.min_slope || slope > settings.max_slope {
+            continue;
+        }
+
+        // masks may not be loaded yet, in which case the whole landscape is eligible
+        let weight = landscape.mask_value(layer, gx, gy).unwrap_or(1.0);
+        if keep >= weight {
+            continue;
+        }
+
+        let position = landscape.grid_to_world(gx, gy);
+
+        instances.push(FoliageInstance {
+            position: [position.x, position.y, position.z],
+            rotation,
+            scale,
+        });
+    }
+
+    instances
+}
@@ -103,6 +103,10 @@ impl Grid {
                     binding: 2,
                     resource: color_render_mode_buffer.as_entire_binding(),
                 },
+                wgpu::BindGroupEntry {
+                    binding: 3,
+                    resource: wgpu::BindingResource::TextureView(&default_texture_view),
+                },
             ],
             label: Some("grid_texture_bind_group"),
         });
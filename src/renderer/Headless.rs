@@ -0,0 +1,407 @@
+use nalgebra::{Point3, Vector3};
+use std::sync::Arc;
+
+use crate::contexts::saved::SkySettings;
+use crate::renderer::core::{
+    create_scene, default_camera, render_scene, LandscapeData, RenderContext, RendererState,
+};
+use crate::renderer::SimpleCamera::SimpleCamera;
+
+// read back as tightly packed rgba, one byte per channel
+const HEADLESS_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Rgba8UnormSrgb;
+
+// renders a scene into an offscreen texture, no canvas or surface required
+pub struct HeadlessRenderer {
+    device: Arc<wgpu::Device>,
+    queue: Arc<wgpu::Queue>,
+    context: RenderContext,
+    state: RendererState,
+    camera: SimpleCamera,
+    target: wgpu::Texture,
+    width: u32,
+    height: u32,
+    bounds: Option<(Point3<f32>, Point3<f32>)>,
+}
+
+impl HeadlessRenderer {
+    // the fallback adapter is wgpu's software rasterizer, slower but identical across machines
+    pub async fn new(
+        width: u32,
+        height: u32,
+        force_fallback_adapter: bool,
+    ) -> Result<Self, String> {
+        let instance = wgpu::Instance::new(wgpu::InstanceDescriptor {
+            backends: wgpu::Backends::all(),
+            ..Default::default()
+        });
+
+        let mut adapter = instance
+            .request_adapter(&wgpu::RequestAdapterOptions {
+                power_preference: wgpu::PowerPreference::default(),
+                compatible_surface: None,
+                force_fallback_adapter,
+            })
+            .await;
+
+        // machines without a gpu (like ci runners) still have the software adapter
+        if adapter.is_none() && !force_fallback_adapter {
+            adapter = instance
+                .request_adapter(&wgpu::RequestAdapterOptions {
+                    power_preference: wgpu::PowerPreference::default(),
+                    compatible_surface: None,
+                    force_fallback_adapter: true,
+                })
+                .await;
+        }
+
+        let adapter = adapter.ok_or("Failed to find an adapter for headless rendering")?;
+
+        let (device, queue) = adapter
+            .request_device(
+                &wgpu::DeviceDescriptor {
+                    label: Some("Headless Device"),
+                    required_features: wgpu::Features::empty(),
+                    // software adapters can fall short of the defaults, take what they offer
+                    required_limits: adapter.limits(),
+                },
+                None,
+            )
+            .await
+            .map_err(|e| format!("Failed to create headless device: {}", e))?;
+
+        let device = Arc::new(device);
+        let queue = Arc::new(queue);
+
+        let mut camera = default_camera();
+        camera.update_aspect_ratio(width as f32 / height as f32);
+        camera.update_view_projection_matrix();
+
+        let (context, mut state) =
+            create_scene(&device, &queue, HEADLESS_FORMAT, width, height, &camera);
+        state.clear_helpers();
+
+        let target = device.create_texture(&wgpu::TextureDescriptor {
+            label: Some("Headless Target"),
+            size: wgpu::Extent3d {
+                width,
+                height,
+                depth_or_array_layers: 1,
+            },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: HEADLESS_FORMAT,
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::COPY_SRC,
+            view_formats: &[],
+        });
+
+        Ok(Self {
+            device,
+            queue,
+            context,
+            state,
+            camera,
+            target,
+            width,
+            height,
+            bounds: None,
+        })
+    }
+
+    pub fn add_model(&mut self, bytes: &Vec<u8>) {
+        // thumbnails never remove models, so they don't need an id
+        let bounds = self.state.add_model(&String::new(), bytes);
+        self.include_bounds(bounds);
+    }
+
+    pub fn add_landscape(&mut self, landscape_component_id: &String, data: &LandscapeData) {
+        self.state.add_landscape(landscape_component_id, data);
+
+        // landscapes are drawn offset from their heightmap positions
+        let offset = self
+            .state
+            .landscape(landscape_component_id)
+            .map(|landscape| landscape.transform.position())
+            .unwrap_or_default();
+        if let Some((min, max)) = data.bounds() {
+            self.include_bounds((min + offset, max + offset));
+        }
+    }
+
+    pub fn set_sky(&mut self, settings: SkySettings) {
+        self.state.set_sky(settings);
+    }
+
+    pub fn camera_mut(&mut self) -> &mut SimpleCamera {
+        &mut self.camera
+    }
+
+    fn include_bounds(&mut self, (min, max): (Point3<f32>, Point3<f32>)) {
+        self.bounds = Some(match self.bounds {
+            Some((current_min, current_max)) => (current_min.inf(&min), current_max.sup(&max)),
+            None => (min, max),
+        });
+    }
+
+    // look at everything added so far from a three-quarter angle, far enough to fit it all
+    pub fn frame_scene(&mut self) {
+        let (min, max) = match self.bounds {
+            Some(bounds) => bounds,
+            None => return,
+        };
+
+        let center = nalgebra::center(&min, &max);
+        let radius = ((max - min).norm() / 2.0).max(0.01);
+        let distance = radius / (self.camera.fovy / 2.0).sin();
+        let direction = Vector3::new(-1.0, -0.6, -1.0).normalize();
+
+        self.camera.position = center - direction * distance;
+        self.camera.direction = direction;
+        self.camera.znear = (distance - radius).max(0.01) * 0.5;
+        self.camera.zfar = (distance + radius) * 2.0;
+        self.camera.update_view_projection_matrix();
+    }
+
+    // draw one frame and copy it back as rgba rows, top to bottom
+    pub async fn render(&mut self) -> Result<Vec<u8>, String> {
+        let view = self
+            .target
+            .create_view(&wgpu::TextureViewDescriptor::default());
+
+        render_scene(
+            &self.state,
+            &mut self.camera,
+            &view,
+            &self.device,
+            &self.queue,
+            &self.context,
+        );
+
+        // buffer rows must be padded to the copy alignment
+        let unpadded_bytes_per_row = self.width * 4;
+        let alignment = wgpu::COPY_BYTES_PER_ROW_ALIGNMENT;
+        let padded_bytes_per_row = (unpadded_bytes_per_row + alignment - 1) / alignment * alignment;
+
+        let readback = self.device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Headless Readback Buffer"),
+            size: (padded_bytes_per_row * self.height) as wgpu::BufferAddress,
+            usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::MAP_READ,
+            mapped_at_creation: false,
+        });
+
+        let mut encoder = self
+            .device
+            .create_command_encoder(&wgpu::CommandEncoderDescriptor {
+                label: Some("Headless Readback Encoder"),
+            });
+
+        encoder.copy_texture_to_buffer(
+            wgpu::ImageCopyTexture {
+                texture: &self.target,
+                mip_level: 0,
+                origin: wgpu::Origin3d::ZERO,
+                aspect: wgpu::TextureAspect::All,
+            },
+            wgpu::ImageCopyBuffer {
+                buffer: &readback,
+                layout: wgpu::ImageDataLayout {
+                    offset: 0,
+                    bytes_per_row: Some(padded_bytes_per_row),
+                    rows_per_image: Some(self.height),
+                },
+            },
+            wgpu::Extent3d {
+                width: self.width,
+                height: self.height,
+                depth_or_array_layers: 1,
+            },
+        );
+
+        self.queue.submit(Some(encoder.finish()));
+
+        let slice = readback.slice(..);
+        let (sender, receiver) = futures::channel::oneshot::channel();
+        slice.map_async(wgpu::MapMode::Read, move |result| {
+            let _ = sender.send(result);
+        });
+
+        // natively the map only completes while polling, the browser resolves it by itself
+        self.device.poll(wgpu::Maintain::Wait);
+
+        receiver
+            .await
+            .map_err(|_| "Headless readback was cancelled".to_string())?
+            .map_err(|e| format!("Failed to map headless readback: {}", e))?;
+
+        let mut pixels = Vec::with_capacity((unpadded_bytes_per_row * self.height) as usize);
+        {
+            let data = slice.get_mapped_range();
+            for row in data.chunks(padded_bytes_per_row as usize) {
+                pixels.extend_from_slice(&row[..unpadded_bytes_per_row as usize]);
+            }
+        }
+        readback.unmap();
+
+        Ok(pixels)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::contexts::saved::LandscapeTextureKinds;
+    use crate::generation::local::primitive_glb;
+    use crate::renderer::core::PixelData;
+    use crate::renderer::Texture::Texture;
+    use futures::executor::block_on;
+
+    const SIZE: u32 = 64;
+
+    fn pixel(pixels: &[u8], x: u32, y: u32) -> &[u8] {
+        let offset = ((y * SIZE + x) * 4) as usize;
+        &pixels[offset..offset + 4]
+    }
+
+    // only the software adapter, so results don't depend on the machine's gpu. the tests that
+    // need it are ignored by default, run them with `cargo test -- --ignored`
+    fn fallback_renderer() -> HeadlessRenderer {
+        block_on(HeadlessRenderer::new(SIZE, SIZE, true))
+            .expect("Couldn't create the software adapter")
+    }
+
+    // share of pixels mostly made of the given channel
+    fn dominated_by(pixels: &[u8], channel: usize) -> f32 {
+        let dominated = pixels
+            .chunks_exact(4)
+            .filter(|p| (0..3).all(|c| c == channel || p[channel] as u16 > p[c] as u16 + 40))
+            .count();
+
+        dominated as f32 / (pixels.len() / 4) as f32
+    }
+
+    // a flat square of the given size in world units
+    fn flat_landscape(grid: usize, size: f32) -> LandscapeData {
+        let pixel_data = (0..grid)
+            .map(|y| {
+                (0..grid)
+                    .map(|x| {
+                        let u = x as f32 / (grid - 1) as f32;
+                        let v = y as f32 / (grid - 1) as f32;
+                        PixelData {
+                            height_value: 0.0,
+                            position: [(u - 0.5) * size, 0.0, (v - 0.5) * size],
+                            tex_coords: [u, v],
+                        }
+                    })
+                    .collect()
+            })
+            .collect();
+
+        LandscapeData {
+            width: grid,
+            height: grid,
+            pixel_data,
+        }
+    }
+
+    #[test]
+    #[ignore = "needs a software adapter"]
+    fn textures_a_landscape_before_its_masks_are_painted() {
+        let mut scene = fallback_renderer();
+
+        let id = "landscape".to_string();
+        scene.add_landscape(&id, &flat_landscape(8, 10.0));
+
+        let material = Texture::new([200, 40, 40, 255].repeat(4 * 4), 4, 4);
+        scene.state.update_landscape_texture(
+            id.clone(),
+            LandscapeTextureKinds::Primary,
+            material,
+            None,
+        );
+
+        let landscape = scene.state.landscape(&id).expect("Landscape wasn't added");
+        assert!(landscape.mask_array.is_none());
+        assert!(landscape.texture_bind_group.is_some());
+
+        // the fallback mask has to match the layout the landscape pipeline expects
+        block_on(scene.render()).expect("Couldn't render the landscape");
+    }
+
+    #[test]
+    #[ignore = "needs a software adapter"]
+    fn renders_a_framed_model_over_the_sky() {
+        let (mut scene, mut empty) = (fallback_renderer(), fallback_renderer());
+
+        scene.add_model(&primitive_glb(3).expect("Couldn't build the test model"));
+        scene.frame_scene();
+
+        // the sky follows the camera, so the empty scene looks from the same place
+        let camera = empty.camera_mut();
+        camera.position = scene.camera.position;
+        camera.direction = scene.camera.direction;
+        camera.znear = scene.camera.znear;
+        camera.zfar = scene.camera.zfar;
+        camera.update_view_projection_matrix();
+
+        let first = block_on(scene.render()).expect("Couldn't render the model");
+        let second = block_on(scene.render()).expect("Couldn't render the model again");
+        let sky = block_on(empty.render()).expect("Couldn't render the empty scene");
+
+        assert_eq!(first.len(), (SIZE * SIZE * 4) as usize);
+
+        // the same scene reads back the same pixels
+        assert_eq!(first, second);
+
+        // framing puts the model in the middle, the corners are still sky
+        assert_ne!(
+            pixel(&first, SIZE / 2, SIZE / 2),
+            pixel(&sky, SIZE / 2, SIZE / 2)
+        );
+        assert_eq!(pixel(&first, 0, 0), pixel(&sky, 0, 0));
+        assert_eq!(
+            pixel(&first, SIZE - 1, SIZE - 1),
+            pixel(&sky, SIZE - 1, SIZE - 1)
+        );
+    }
+
+    #[test]
+    #[ignore = "needs a software adapter"]
+    fn frames_a_textured_landscape_where_it_is_drawn() {
+        let (mut scene, mut empty) = (fallback_renderer(), fallback_renderer());
+
+        let id = "landscape".to_string();
+        scene.add_landscape(&id, &flat_landscape(8, 10.0));
+
+        let material = Texture::new([220, 30, 30, 255].repeat(4 * 4), 4, 4);
+        scene.state.update_landscape_texture(
+            id.clone(),
+            LandscapeTextureKinds::Primary,
+            material,
+            None,
+        );
+        scene.frame_scene();
+
+        let camera = empty.camera_mut();
+        camera.position = scene.camera.position;
+        camera.direction = scene.camera.direction;
+        camera.znear = scene.camera.znear;
+        camera.zfar = scene.camera.zfar;
+        camera.update_view_projection_matrix();
+
+        let pixels = block_on(scene.render()).expect("Couldn't render the landscape");
+        let sky = block_on(empty.render()).expect("Couldn't render the empty scene");
+
+        // the sky has no red in it, so red pixels are the landscape
+        assert_eq!(dominated_by(&sky, 0), 0.0);
+
+        // framed around its drawn position the landscape fills a good part of the view, it would
+        // be out of sight if the bounds ignored the landscape's offset
+        let coverage = dominated_by(&pixels, 0);
+        assert!(coverage > 0.1, "landscape covers {} of the view", coverage);
+        assert!(dominated_by(pixel(&pixels, SIZE / 2, SIZE / 2), 0) == 1.0);
+
+        // and leaves sky around it
+        assert!(coverage < 0.9, "landscape covers {} of the view", coverage);
+    }
+}
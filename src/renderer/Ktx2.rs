@@ -0,0 +1,204 @@
+// Reads the block compressed KTX2 files exports write, see src-tauri/src/ktx2.rs.
+// Landscape layers are resampled to a shared size and masks stay editable, so the blocks are
+// expanded back to RGBA8 here rather than uploaded as they are.
+
+use std::path::Path;
+
+use crate::renderer::Texture::Texture;
+
+const KTX2_IDENTIFIER: [u8; 12] = [
+    0xab, 0x4b, 0x54, 0x58, 0x20, 0x32, 0x30, 0xbb, 0x0d, 0x0a, 0x1a, 0x0a,
+];
+
+const VK_FORMAT_BC1_RGB_UNORM_BLOCK: u32 = 131;
+const VK_FORMAT_BC1_RGB_SRGB_BLOCK: u32 = 132;
+const VK_FORMAT_BC4_UNORM_BLOCK: u32 = 139;
+
+const BLOCK_BYTES: usize = 8;
+
+fn unpack_565(color: u16) -> [u8; 3] {
+    let r = (color >> 11) & 0x1f;
+    let g = (color >> 5) & 0x3f;
+    let b = color & 0x1f;
+    [
+        ((r << 3) | (r >> 2)) as u8,
+        ((g << 2) | (g >> 4)) as u8,
+        ((b << 3) | (b >> 2)) as u8,
+    ]
+}
+
+fn decode_bc1_block(block: &[u8]) -> [[u8; 4]; 16] {
+    let color0 = u16::from_le_bytes([block[0], block[1]]);
+    let color1 = u16::from_le_bytes([block[2], block[3]]);
+    let indices = u32::from_le_bytes([block[4], block[5], block[6], block[7]]);
+
+    let (c0, c1) = (unpack_565(color0), unpack_565(color1));
+    let mix = |a: u8, b: u8, wa: u16, wb: u16| ((a as u16 * wa + b as u16 * wb) / (wa + wb)) as u8;
+
+    // the smaller endpoint first switches to three colors and transparent black
+    let palette = if color0 > color1 {
+        [
+            [c0[0], c0[1], c0[2], 255],
+            [c1[0], c1[1], c1[2], 255],
+            std::array::from_fn(|c| if c == 3 { 255 } else { mix(c0[c], c1[c], 2, 1) }),
+            std::array::from_fn(|c| if c == 3 { 255 } else { mix(c0[c], c1[c], 1, 2) }),
+        ]
+    } else {
+        [
+            [c0[0], c0[1], c0[2], 255],
+            [c1[0], c1[1], c1[2], 255],
+            std::array::from_fn(|c| if c == 3 { 255 } else { mix(c0[c], c1[c], 1, 1) }),
+            [0, 0, 0, 0],
+        ]
+    };
+
+    std::array::from_fn(|i| palette[(indices >> (i * 2)) as usize & 0x3])
+}
+
+// single channel, spread to gray like an 8-bit PNG mask loads
+fn decode_bc4_block(block: &[u8]) -> [[u8; 4]; 16] {
+    let (red0, red1) = (block[0] as u16, block[1] as u16);
+    let mut index_bytes = [0u8; 8];
+    index_bytes[0..6].copy_from_slice(&block[2..8]);
+    let indices = u64::from_le_bytes(index_bytes);
+
+    let palette: [u8; 8] = std::array::from_fn(|i| match i {
+        0 => red0 as u8,
+        1 => red1 as u8,
+        _ if red0 > red1 => (((8 - i) as u16 * red0 + (i - 1) as u16 * red1) / 7) as u8,
+        6 => 0,
+        7 => 255,
+        _ => (((6 - i) as u16 * red0 + (i - 1) as u16 * red1) / 5) as u8,
+    });
+
+    std::array::from_fn(|i| {
+        let value = palette[(indices >> (i * 3)) as usize & 0x7];
+        [value, value, value, 255]
+    })
+}
+
+pub fn decode_ktx2(data: &[u8]) -> Result<Texture, String> {
+    let word = |offset: usize| {
+        data.get(offset..offset + 4)
+            .map(|bytes| u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+            .ok_or("KTX2 file is truncated".to_string())
+    };
+    let long = |offset: usize| -> Result<usize, String> {
+        Ok(word(offset)? as usize | (word(offset + 4)? as usize) << 32)
+    };
+
+    if data.get(0..12) != Some(&KTX2_IDENTIFIER[..]) {
+        return Err("Not a KTX2 file".to_string());
+    }
+
+    let vk_format = word(12)?;
+    let (width, height) = (word(20)?, word(24)?);
+    let supercompression = word(44)?;
+
+    let decode_block: fn(&[u8]) -> [[u8; 4]; 16] = match vk_format {
+        VK_FORMAT_BC1_RGB_UNORM_BLOCK | VK_FORMAT_BC1_RGB_SRGB_BLOCK => decode_bc1_block,
+        VK_FORMAT_BC4_UNORM_BLOCK => decode_bc4_block,
+        _ => return Err(format!("Unsupported KTX2 format {}", vk_format)),
+    };
+    if supercompression != 0 {
+        return Err("Supercompressed KTX2 files aren't supported".to_string());
+    }
+    if width == 0 || height == 0 {
+        return Err("KTX2 file has no pixels".to_string());
+    }
+
+    // the first level is the full size image
+    let (level_offset, level_length) = (long(80)?, long(88)?);
+    let (blocks_x, blocks_y) = (width.div_ceil(4) as usize, height.div_ceil(4) as usize);
+    if level_length != blocks_x * blocks_y * BLOCK_BYTES {
+        return Err("KTX2 level doesn't match its dimensions".to_string());
+    }
+    let level = data
+        .get(level_offset..level_offset + level_length)
+        .ok_or("KTX2 file is truncated")?;
+
+    let mut pixels = vec![0u8; width as usize * height as usize * 4];
+    for (index, block) in level.chunks_exact(BLOCK_BYTES).enumerate() {
+        let (bx, by) = (index % blocks_x, index / blocks_x);
+
+        // pixels past the edge only pad the last blocks
+        for (i, pixel) in decode_block(block).iter().enumerate() {
+            let (x, y) = (bx * 4 + i % 4, by * 4 + i / 4);
+            if x < width as usize && y < height as usize {
+                let offset = (y * width as usize + x) * 4;
+                pixels[offset..offset + 4].copy_from_slice(pixel);
+            }
+        }
+    }
+
+    Ok(Texture::new(pixels, width, height))
+}
+
+pub fn read_ktx2(path: &Path) -> Result<Texture, String> {
+    let data =
+        std::fs::read(path).map_err(|e| format!("Failed to read texture {:?}: {}", path, e))?;
+
+    decode_ktx2(&data).map_err(|e| format!("Failed to decode texture {:?}: {}", path, e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // written by the exporter's encoder from the images below, its tests check they still match
+    fn fixture(name: &str) -> Vec<u8> {
+        std::fs::read(
+            Path::new(env!("CARGO_MANIFEST_DIR"))
+                .join("src-tauri/tests/fixtures")
+                .join(name),
+        )
+        .unwrap()
+    }
+
+    fn pixel(texture: &Texture, x: u32, y: u32) -> [u8; 4] {
+        let offset = ((y * texture.width + x) * 4) as usize;
+        texture.data[offset..offset + 4].try_into().unwrap()
+    }
+
+    #[test]
+    fn decodes_exported_textures() {
+        let texture = decode_ktx2(&fixture("sample-texture.ktx2")).unwrap();
+        assert_eq!((texture.width, texture.height), (6, 5));
+
+        // block compression is lossy, but stays close to the source gradient
+        for y in 0..5 {
+            for x in 0..6 {
+                let t = x + y * 6;
+                let expected = [t * 8, 255 - t * 6, 40 + t * 4];
+                let actual = pixel(&texture, x, y);
+                for c in 0..3 {
+                    assert!((actual[c] as i32 - expected[c] as i32).abs() <= 24);
+                }
+                assert_eq!(actual[3], 255);
+            }
+        }
+    }
+
+    #[test]
+    fn decodes_exported_masks() {
+        let mask = decode_ktx2(&fixture("sample-mask.ktx2")).unwrap();
+        assert_eq!((mask.width, mask.height), (6, 5));
+
+        for y in 0..5 {
+            for x in 0..6 {
+                let actual = pixel(&mask, x, y);
+                // within half of the widest block's palette step
+                assert!((actual[0] as i32 - (x * 30 + y * 20) as i32).abs() <= 11);
+                assert_eq!(actual[0], actual[1]);
+            }
+        }
+    }
+
+    #[test]
+    fn rejects_truncated_files() {
+        let mut data = fixture("sample-mask.ktx2");
+        data.truncate(data.len() - 1);
+
+        assert!(decode_ktx2(&data).is_err());
+    }
+}
@@ -1,10 +1,16 @@
-use nalgebra::{Matrix4, Vector3};
+use nalgebra::{Matrix4, Point3, Vector3};
 use rapier3d::math::Point;
+use std::collections::HashMap;
 use wgpu::util::{DeviceExt, TextureDataOrder};
 
 use crate::contexts::saved::LandscapeTextureKinds;
 use crate::renderer::core::LandscapeData;
 use crate::renderer::core::Vertex;
+use crate::renderer::core::{console_error, console_log};
+use crate::renderer::TerrainBrush::{
+    apply_brush, paint_mask_layers, sample_height, BrushSettings, HeightGrid, MaskBrush,
+    SculptStroke,
+};
 use crate::renderer::Texture::Texture;
 use crate::renderer::Transform::{matrix4_to_raw_array, Transform};
 
@@ -16,9 +22,78 @@ pub struct Landscape {
     pub index_count: u32,
     pub bind_group: wgpu::BindGroup,
     // pub texture_bind_group: wgpu::BindGroup,
-    pub texture_array: Option<wgpu::Texture>,
-    pub texture_array_view: Option<wgpu::TextureView>,
+    pub material_array: Option<wgpu::Texture>,
+    pub material_array_view: Option<wgpu::TextureView>,
+    pub mask_array: Option<wgpu::Texture>,
+    pub mask_array_view: Option<wgpu::TextureView>,
+    // stands in for the masks until one is uploaded, weighting every loaded material evenly
+    pub fallback_mask: Option<wgpu::Texture>,
+    // which layers of the material array have been written
+    pub material_layers: [bool; LANDSCAPE_LAYER_COUNT as usize],
     pub texture_bind_group: Option<wgpu::BindGroup>,
+    // CPU copy of the terrain, kept for sculpting and ray casts
+    pub grid_width: usize,
+    pub grid_height: usize,
+    pub heights: Vec<f32>,
+    pub vertices: Vec<Vertex>,
+    // CPU copy of each mask layer (RGBA, mask resolution), kept for painting and saving
+    pub mask_layers: Vec<Vec<u8>>,
+}
+
+// layers per array: Primary, Rockmap, Soil
+pub const LANDSCAPE_LAYER_COUNT: u32 = 3;
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum LandscapeTextureSlot {
+    Material,
+    Mask,
+}
+
+// materials and masks live in separate arrays so each can have its own resolution
+pub fn texture_slot(kind: &LandscapeTextureKinds) -> (LandscapeTextureSlot, u32) {
+    match kind {
+        LandscapeTextureKinds::Primary => (LandscapeTextureSlot::Material, 0),
+        LandscapeTextureKinds::Rockmap => (LandscapeTextureSlot::Material, 1),
+        LandscapeTextureKinds::Soil => (LandscapeTextureSlot::Material, 2),
+        LandscapeTextureKinds::PrimaryMask => (LandscapeTextureSlot::Mask, 0),
+        LandscapeTextureKinds::RockmapMask => (LandscapeTextureSlot::Mask, 1),
+        LandscapeTextureKinds::SoilMask => (LandscapeTextureSlot::Mask, 2),
+    }
+}
+
+// a 1x1 mask array with full weight on every loaded material, so the shader blends them evenly
+fn create_fallback_mask(
+    device: &wgpu::Device,
+    queue: &wgpu::Queue,
+    material_layers: &[bool; LANDSCAPE_LAYER_COUNT as usize],
+) -> wgpu::Texture {
+    let data: Vec<u8> = material_layers
+        .iter()
+        .flat_map(|loaded| {
+            let weight = if *loaded { 255 } else { 0 };
+            [weight, weight, weight, 255]
+        })
+        .collect();
+
+    device.create_texture_with_data(
+        queue,
+        &wgpu::TextureDescriptor {
+            size: wgpu::Extent3d {
+                width: 1,
+                height: 1,
+                depth_or_array_layers: LANDSCAPE_LAYER_COUNT,
+            },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: wgpu::TextureFormat::Rgba8Unorm,
+            usage: wgpu::TextureUsages::TEXTURE_BINDING,
+            label: Some("landscape_fallback_mask"),
+            view_formats: &[],
+        },
+        TextureDataOrder::LayerMajor,
+        &data,
+    )
 }
 
 impl Landscape {
@@ -38,9 +113,11 @@ impl Landscape {
         let vertex_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
             label: Some("Landscape Vertex Buffer"),
             contents: bytemuck::cast_slice(&vertices),
-            usage: wgpu::BufferUsages::VERTEX,
+            usage: wgpu::BufferUsages::VERTEX | wgpu::BufferUsages::COPY_DST,
         });
 
+        let heights = vertices.iter().map(|v| v.position[1]).collect();
+
         let index_buffer: wgpu::Buffer =
             device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
                 label: Some("Landscape Index Buffer"),
@@ -134,10 +211,233 @@ impl Landscape {
                 Vector3::new(1.0, 1.0, 1.0),
                 uniform_buffer,
             ),
-            texture_array: None,
-            texture_array_view: None,
+            material_array: None,
+            material_array_view: None,
+            mask_array: None,
+            mask_array_view: None,
+            fallback_mask: None,
+            material_layers: [false; LANDSCAPE_LAYER_COUNT as usize],
             texture_bind_group: None,
+            grid_width: data.width,
+            grid_height: data.height,
+            heights,
+            vertices,
+            mask_layers: Vec::new(),
+        }
+    }
+
+    // like Model::destroy, frees the gpu memory now instead of whenever the last reference drops
+    pub fn destroy(&self) {
+        self.vertex_buffer.destroy();
+        self.index_buffer.destroy();
+        self.transform.destroy();
+
+        if let Some(material_array) = &self.material_array {
+            material_array.destroy();
+        }
+        if let Some(mask_array) = &self.mask_array {
+            mask_array.destroy();
+        }
+        if let Some(fallback_mask) = &self.fallback_mask {
+            fallback_mask.destroy();
+        }
+    }
+
+    // world units between neighbouring columns and rows
+    pub fn spacing(&self) -> (f32, f32) {
+        let spacing_x = if self.grid_width > 1 {
+            self.vertices[1].position[0] - self.vertices[0].position[0]
+        } else {
+            1.0
+        };
+        let spacing_z = if self.grid_height > 1 {
+            self.vertices[self.grid_width].position[2] - self.vertices[0].position[2]
+        } else {
+            1.0
+        };
+
+        (spacing_x, spacing_z)
+    }
+
+    // fractional grid coordinates of a world position (ignores height)
+    pub fn world_to_grid(&self, world: &Point3<f32>) -> (f32, f32) {
+        let local = world.coords - self.transform.position();
+        let origin = self.vertices[0].position;
+        let (spacing_x, spacing_z) = self.spacing();
+
+        (
+            (local.x - origin[0]) / spacing_x,
+            (local.z - origin[2]) / spacing_z,
+        )
+    }
+
+    fn contains_grid(&self, gx: f32, gy: f32) -> bool {
+        gx >= 0.0
+            && gy >= 0.0
+            && gx <= (self.grid_width - 1) as f32
+            && gy <= (self.grid_height - 1) as f32
+    }
+
+    // world height of the terrain surface at fractional grid coordinates
+    pub fn surface_height(&self, gx: f32, gy: f32) -> f32 {
+        sample_height(&self.heights, self.grid_width, self.grid_height, gx, gy)
+            + self.transform.position().y
+    }
+
+    // world position on the terrain surface at fractional grid coordinates
+    pub fn grid_to_world(&self, gx: f32, gy: f32) -> Point3<f32> {
+        let origin = self.vertices[0].position;
+        let (spacing_x, spacing_z) = self.spacing();
+        let position = self.transform.position();
+
+        Point3::new(
+            position.x + origin[0] + gx * spacing_x,
+            self.surface_height(gx, gy),
+            position.z + origin[2] + gy * spacing_z,
+        )
+    }
+
+    // terrain steepness in degrees, from central differences of the heights
+    pub fn slope_degrees(&self, gx: f32, gy: f32) -> f32 {
+        let (spacing_x, spacing_z) = self.spacing();
+        let sample =
+            |x: f32, y: f32| sample_height(&self.heights, self.grid_width, self.grid_height, x, y);
+
+        let dx = (sample(gx + 1.0, gy) - sample(gx - 1.0, gy)) / (2.0 * spacing_x.abs());
+        let dz = (sample(gx, gy + 1.0) - sample(gx, gy - 1.0)) / (2.0 * spacing_z.abs());
+
+        (dx * dx + dz * dz).sqrt().atan().to_degrees()
+    }
+
+    // mask layer weight (0.0 - 1.0) at fractional grid coordinates, None before masks load
+    pub fn mask_value(&self, layer: usize, gx: f32, gy: f32) -> Option<f32> {
+        let size = self.mask_size()?;
+        let layer = self.mask_layers.get(layer)?;
+
+        let mx = ((gx / self.grid_width as f32 * size.width as f32) as u32).min(size.width - 1);
+        let my = ((gy / self.grid_height as f32 * size.height as f32) as u32).min(size.height - 1);
+        let index = ((my * size.width + mx) * 4) as usize;
+
+        layer.get(index).map(|value| *value as f32 / 255.0)
+    }
+
+    // march along the ray until it dips below the terrain, then refine the hit
+    pub fn raycast(&self, origin: Point3<f32>, direction: Vector3<f32>) -> Option<Point3<f32>> {
+        if self.grid_width < 2 || self.grid_height < 2 {
+            return None;
+        }
+
+        let direction = direction.normalize();
+        let (spacing_x, spacing_z) = self.spacing();
+        let step = spacing_x.abs().min(spacing_z.abs()).max(0.01);
+        let max_distance = 10000.0;
+
+        let mut previous = origin;
+        let mut previous_above = true;
+        let mut traveled = 0.0;
+
+        while traveled < max_distance {
+            let point = origin + direction * traveled;
+            let (gx, gy) = self.world_to_grid(&point);
+
+            if self.contains_grid(gx, gy) {
+                let above = point.y >= self.surface_height(gx, gy);
+
+                if previous_above && !above && traveled > 0.0 {
+                    // binary search between the last point above and this one below
+                    let mut low = previous;
+                    let mut high = point;
+                    for _ in 0..8 {
+                        let mid = Point3::from((low.coords + high.coords) * 0.5);
+                        let (mx, my) = self.world_to_grid(&mid);
+                        if mid.y >= self.surface_height(mx, my) {
+                            low = mid;
+                        } else {
+                            high = mid;
+                        }
+                    }
+                    return Some(high);
+                }
+
+                previous_above = above;
+            } else {
+                previous_above = true;
+            }
+
+            previous = point;
+            traveled += step;
+        }
+
+        None
+    }
+
+    // apply a brush dab centered on a world position, uploading only the rows it touched
+    pub fn sculpt(
+        &mut self,
+        queue: &wgpu::Queue,
+        settings: &BrushSettings,
+        center: &Point3<f32>,
+        stroke: &mut SculptStroke,
+    ) {
+        let center = self.world_to_grid(center);
+        let spacing = self.spacing();
+        let mut grid = HeightGrid {
+            heights: &mut self.heights,
+            width: self.grid_width,
+            height: self.grid_height,
+        };
+
+        if let Some((min_row, max_row)) = apply_brush(&mut grid, settings, center, spacing, stroke)
+        {
+            self.write_rows(queue, min_row, max_row);
+        }
+    }
+
+    // write the given heights back, returning the heights they replaced
+    pub fn restore_heights(
+        &mut self,
+        queue: &wgpu::Queue,
+        heights: &HashMap<usize, f32>,
+    ) -> HashMap<usize, f32> {
+        let mut replaced = HashMap::with_capacity(heights.len());
+        let mut min_row = usize::MAX;
+        let mut max_row = 0;
+
+        for (&index, &height) in heights {
+            if index >= self.heights.len() {
+                continue;
+            }
+
+            replaced.insert(index, self.heights[index]);
+            self.heights[index] = height;
+
+            let row = index / self.grid_width;
+            min_row = min_row.min(row);
+            max_row = max_row.max(row);
+        }
+
+        if min_row <= max_row {
+            self.write_rows(queue, min_row, max_row);
         }
+
+        replaced
+    }
+
+    // sync heights into the vertices for the inclusive row range and upload just those rows
+    fn write_rows(&mut self, queue: &wgpu::Queue, min_row: usize, max_row: usize) {
+        let start = min_row * self.grid_width;
+        let end = (max_row + 1) * self.grid_width;
+
+        for index in start..end {
+            self.vertices[index].position[1] = self.heights[index];
+        }
+
+        let offset = (start * std::mem::size_of::<Vertex>()) as wgpu::BufferAddress;
+        queue.write_buffer(
+            &self.vertex_buffer,
+            offset,
+            bytemuck::cast_slice(&self.vertices[start..end]),
+        );
     }
 
     pub fn update_texture(
@@ -150,20 +450,50 @@ impl Landscape {
         kind: LandscapeTextureKinds,
         new_texture: &Texture,
     ) {
-        let layer = match kind {
-            LandscapeTextureKinds::Primary => 0,
-            LandscapeTextureKinds::PrimaryMask => 1,
-            LandscapeTextureKinds::Rockmap => 2,
-            LandscapeTextureKinds::RockmapMask => 3,
-            LandscapeTextureKinds::Soil => 4,
-            LandscapeTextureKinds::SoilMask => 5,
+        if let Err(e) = new_texture.validate() {
+            console_error(&format!("Invalid landscape texture: {}", e));
+            return;
+        }
+
+        let (slot, layer) = texture_slot(&kind);
+
+        let existing = match slot {
+            LandscapeTextureSlot::Material => &self.material_array,
+            LandscapeTextureSlot::Mask => &self.mask_array,
         };
 
-        if self.texture_array.is_none() {
-            self.create_texture_array(device, new_texture.size());
+        // each array is sized by the first texture written into it
+        if existing.is_none() {
+            self.create_texture_array(device, slot, new_texture.size());
         }
 
-        if let Some(texture_array) = &self.texture_array {
+        let texture_array = match slot {
+            LandscapeTextureSlot::Material => &self.material_array,
+            LandscapeTextureSlot::Mask => &self.mask_array,
+        };
+
+        if let Some(texture_array) = texture_array {
+            let array_size = texture_array.size();
+
+            // resample anything that doesn't match the array resolution
+            let resampled;
+            let upload = if new_texture.width != array_size.width
+                || new_texture.height != array_size.height
+            {
+                console_log(&format!(
+                    "Resampling landscape {:?} from {}x{} to {}x{}",
+                    kind,
+                    new_texture.width,
+                    new_texture.height,
+                    array_size.width,
+                    array_size.height
+                ));
+                resampled = new_texture.resized(array_size.width, array_size.height);
+                &resampled
+            } else {
+                new_texture
+            };
+
             queue.write_texture(
                 wgpu::ImageCopyTexture {
                     texture: texture_array,
@@ -175,17 +505,25 @@ impl Landscape {
                     },
                     aspect: wgpu::TextureAspect::All,
                 },
-                &new_texture.data,
+                &upload.data,
                 wgpu::ImageDataLayout {
                     offset: 0,
-                    bytes_per_row: Some(4 * new_texture.size().width),
-                    rows_per_image: Some(new_texture.size().height),
+                    bytes_per_row: Some(4 * upload.width),
+                    rows_per_image: Some(upload.height),
                 },
-                new_texture.size(),
+                upload.size(),
             );
 
+            match slot {
+                LandscapeTextureSlot::Material => self.material_layers[layer as usize] = true,
+                LandscapeTextureSlot::Mask => {
+                    self.mask_layers[layer as usize] = upload.data.clone();
+                }
+            }
+
             self.update_bind_group(
                 device,
+                queue,
                 texture_bind_group_layout,
                 texture_render_mode_buffer,
                 color_render_mode_buffer,
@@ -193,19 +531,33 @@ impl Landscape {
         }
     }
 
-    fn create_texture_array(&mut self, device: &wgpu::Device, size: wgpu::Extent3d) {
+    fn create_texture_array(
+        &mut self,
+        device: &wgpu::Device,
+        slot: LandscapeTextureSlot,
+        size: wgpu::Extent3d,
+    ) {
+        let (label, format) = match slot {
+            LandscapeTextureSlot::Material => (
+                "landscape_material_array",
+                wgpu::TextureFormat::Rgba8UnormSrgb,
+            ),
+            // masks are weights, not colors, so they stay linear
+            LandscapeTextureSlot::Mask => ("landscape_mask_array", wgpu::TextureFormat::Rgba8Unorm),
+        };
+
         let texture_array = device.create_texture(&wgpu::TextureDescriptor {
             size: wgpu::Extent3d {
                 width: size.width,
                 height: size.height,
-                depth_or_array_layers: 6, // Primary, Rockmap, Soil and associated masks
+                depth_or_array_layers: LANDSCAPE_LAYER_COUNT, // Primary, Rockmap, Soil
             },
             mip_level_count: 1,
             sample_count: 1,
             dimension: wgpu::TextureDimension::D2,
-            format: wgpu::TextureFormat::Rgba8UnormSrgb,
+            format,
             usage: wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::COPY_DST,
-            label: Some("landscape_texture_array"),
+            label: Some(label),
             view_formats: &[],
         });
 
@@ -214,43 +566,165 @@ impl Landscape {
             ..Default::default()
         });
 
-        self.texture_array = Some(texture_array);
-        self.texture_array_view = Some(texture_array_view);
+        match slot {
+            LandscapeTextureSlot::Material => {
+                self.material_array = Some(texture_array);
+                self.material_array_view = Some(texture_array_view);
+            }
+            LandscapeTextureSlot::Mask => {
+                self.mask_array = Some(texture_array);
+                self.mask_array_view = Some(texture_array_view);
+                self.mask_layers = vec![
+                    vec![0; (size.width * size.height * 4) as usize];
+                    LANDSCAPE_LAYER_COUNT as usize
+                ];
+            }
+        }
+    }
+
+    pub fn mask_size(&self) -> Option<wgpu::Extent3d> {
+        self.mask_array.as_ref().map(|mask_array| mask_array.size())
+    }
+
+    // paint the mask layers around a world position, uploading only the touched region
+    pub fn paint_mask(&mut self, queue: &wgpu::Queue, brush: &MaskBrush, center: &Point3<f32>) {
+        let size = match self.mask_size() {
+            Some(size) => size,
+            None => {
+                console_error("Landscape has no masks to paint yet");
+                return;
+            }
+        };
+
+        // masks span the landscape the same way the vertex tex_coords do
+        let (gx, gy) = self.world_to_grid(center);
+        let center = (
+            gx / self.grid_width as f32 * size.width as f32,
+            gy / self.grid_height as f32 * size.height as f32,
+        );
+
+        let (spacing_x, spacing_z) = self.spacing();
+        let texel_size = (
+            (spacing_x * self.grid_width as f32 / size.width as f32).abs(),
+            (spacing_z * self.grid_height as f32 / size.height as f32).abs(),
+        );
+
+        if let Some(rect) = paint_mask_layers(
+            &mut self.mask_layers,
+            size.width,
+            size.height,
+            brush,
+            center,
+            texel_size,
+        ) {
+            self.write_mask_region(queue, rect);
+        }
+    }
+
+    // upload an inclusive pixel rectangle of every mask layer
+    fn write_mask_region(&self, queue: &wgpu::Queue, rect: (u32, u32, u32, u32)) {
+        let (mask_array, size) = match (&self.mask_array, self.mask_size()) {
+            (Some(mask_array), Some(size)) => (mask_array, size),
+            _ => return,
+        };
+
+        let (min_x, min_y, max_x, max_y) = rect;
+        let region_width = max_x - min_x + 1;
+        let region_height = max_y - min_y + 1;
+
+        for (layer, data) in self.mask_layers.iter().enumerate() {
+            let mut region = Vec::with_capacity((region_width * region_height * 4) as usize);
+            for y in min_y..=max_y {
+                let start = ((y * size.width + min_x) * 4) as usize;
+                let end = start + (region_width * 4) as usize;
+                region.extend_from_slice(&data[start..end]);
+            }
+
+            queue.write_texture(
+                wgpu::ImageCopyTexture {
+                    texture: mask_array,
+                    mip_level: 0,
+                    origin: wgpu::Origin3d {
+                        x: min_x,
+                        y: min_y,
+                        z: layer as u32,
+                    },
+                    aspect: wgpu::TextureAspect::All,
+                },
+                &region,
+                wgpu::ImageDataLayout {
+                    offset: 0,
+                    bytes_per_row: Some(4 * region_width),
+                    rows_per_image: Some(region_height),
+                },
+                wgpu::Extent3d {
+                    width: region_width,
+                    height: region_height,
+                    depth_or_array_layers: 1,
+                },
+            );
+        }
     }
 
     fn update_bind_group(
         &mut self,
         device: &wgpu::Device,
+        queue: &wgpu::Queue,
         texture_bind_group_layout: &wgpu::BindGroupLayout,
         texture_render_mode_buffer: &wgpu::Buffer,
         color_render_mode_buffer: &wgpu::Buffer,
     ) {
-        if let Some(texture_array_view) = &self.texture_array_view {
-            let sampler = device.create_sampler(&wgpu::SamplerDescriptor::default());
-
-            self.texture_bind_group = Some(device.create_bind_group(&wgpu::BindGroupDescriptor {
-                layout: texture_bind_group_layout,
-                entries: &[
-                    wgpu::BindGroupEntry {
-                        binding: 0,
-                        resource: wgpu::BindingResource::TextureView(texture_array_view),
-                    },
-                    wgpu::BindGroupEntry {
-                        binding: 1,
-                        resource: wgpu::BindingResource::Sampler(&sampler),
-                    },
-                    wgpu::BindGroupEntry {
-                        binding: 2,
-                        resource: wgpu::BindingResource::Buffer(wgpu::BufferBinding {
-                            buffer: texture_render_mode_buffer,
-                            offset: 0,
-                            size: None,
-                        }),
-                    },
-                ],
-                label: Some("landscape_texture_bind_group"),
-            }));
-        }
+        // only bindable once there are materials, masks can come later
+        let material_array_view = match &self.material_array_view {
+            Some(material_array_view) => material_array_view,
+            None => return,
+        };
+
+        self.fallback_mask = match self.mask_array_view {
+            Some(_) => None,
+            None => Some(create_fallback_mask(device, queue, &self.material_layers)),
+        };
+
+        let fallback_mask_view = self.fallback_mask.as_ref().map(|fallback_mask| {
+            fallback_mask.create_view(&wgpu::TextureViewDescriptor {
+                dimension: Some(wgpu::TextureViewDimension::D2Array),
+                ..Default::default()
+            })
+        });
+
+        let mask_array_view = match (&self.mask_array_view, &fallback_mask_view) {
+            (Some(mask_array_view), _) | (None, Some(mask_array_view)) => mask_array_view,
+            (None, None) => return,
+        };
+
+        let sampler = device.create_sampler(&wgpu::SamplerDescriptor::default());
+
+        self.texture_bind_group = Some(device.create_bind_group(&wgpu::BindGroupDescriptor {
+            layout: texture_bind_group_layout,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: wgpu::BindingResource::TextureView(material_array_view),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: wgpu::BindingResource::Sampler(&sampler),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 2,
+                    resource: wgpu::BindingResource::Buffer(wgpu::BufferBinding {
+                        buffer: texture_render_mode_buffer,
+                        offset: 0,
+                        size: None,
+                    }),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 3,
+                    resource: wgpu::BindingResource::TextureView(mask_array_view),
+                },
+            ],
+            label: Some("landscape_texture_bind_group"),
+        }));
     }
 
     // Generate vertex buffer from heightmap data
@@ -6,7 +6,7 @@ use gltf::Glb;
 use gltf::Gltf;
 use std::sync::Arc;
 
-use crate::renderer::core::Vertex;
+use crate::renderer::core::{console_log, Vertex};
 use crate::renderer::Transform::{matrix4_to_raw_array, Transform};
 
 pub struct Mesh {
@@ -20,12 +20,18 @@ pub struct Mesh {
 }
 
 pub struct Model {
+    // the component this model was placed as, empty for foliage and thumbnails
+    pub id: String,
     pub meshes: Vec<Mesh>,
+    // axis aligned bounds of every vertex, used to frame thumbnails
+    pub bounds: (Point3<f32>, Point3<f32>),
+    // the glb's images and the empty fallback, the bind groups only hold views of them
+    textures: Vec<wgpu::Texture>,
     // pub transform: Transform,
 }
 
 impl Model {
-    pub async fn from_glb(
+    pub fn from_glb(
         bytes: &Vec<u8>,
         device: &wgpu::Device,
         queue: &wgpu::Queue,
@@ -34,7 +40,7 @@ impl Model {
         texture_render_mode_buffer: &wgpu::Buffer,
         color_render_mode_buffer: &wgpu::Buffer,
     ) -> Self {
-        web_sys::console::log_1(&format!("Bytes len: {:?}", bytes.len()).into());
+        console_log(&format!("Bytes len: {:?}", bytes.len()));
 
         let glb = Glb::from_slice(&bytes).expect("Couldn't create glb from slice");
 
@@ -49,9 +55,10 @@ impl Model {
 
         let uses_textures = gltf.textures().len().gt(&0);
 
-        web_sys::console::log_1(&format!("Textures count: {:?}", gltf.textures().len()).into());
+        console_log(&format!("Textures count: {:?}", gltf.textures().len()));
 
         let mut textures = Vec::new();
+        let mut owned_textures = Vec::new();
         for texture in gltf.textures() {
             match texture.source().source() {
                 gltf::image::Source::View { view, mime_type: _ } => {
@@ -104,6 +111,7 @@ impl Model {
                     });
 
                     textures.push((texture_view, sampler));
+                    owned_textures.push(texture);
                 }
                 gltf::image::Source::Uri { uri, mime_type: _ } => {
                     panic!(
@@ -145,6 +153,9 @@ impl Model {
             ..Default::default()
         });
 
+        let mut bounds_min = Point3::new(f32::MAX, f32::MAX, f32::MAX);
+        let mut bounds_max = Point3::new(f32::MIN, f32::MIN, f32::MIN);
+
         for mesh in gltf.meshes() {
             for primitive in mesh.primitives() {
                 let reader = primitive.reader(|buffer| Some(&buffer_data));
@@ -184,8 +195,14 @@ impl Model {
 
                 let indices: Vec<u16> = indices_u32.iter().map(|&i| i as u16).collect();
 
-                web_sys::console::log_1(&format!("Model vertices: {:?}", vertices.len()).into());
-                web_sys::console::log_1(&format!("Model indices: {:?}", indices.len()).into());
+                console_log(&format!("Model vertices: {:?}", vertices.len()));
+                console_log(&format!("Model indices: {:?}", indices.len()));
+
+                for vertex in &vertices {
+                    let position = Point3::from(vertex.position);
+                    bounds_min = bounds_min.inf(&position);
+                    bounds_max = bounds_max.sup(&position);
+                }
 
                 let vertex_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
                     label: Some("Model GLB Vertex Buffer"),
@@ -252,6 +269,10 @@ impl Model {
                                     size: None,
                                 }),
                             },
+                            wgpu::BindGroupEntry {
+                                binding: 3,
+                                resource: wgpu::BindingResource::TextureView(&default_texture_view),
+                            },
                         ],
                         label: None,
                     })
@@ -275,6 +296,10 @@ impl Model {
                                     size: None,
                                 }),
                             },
+                            wgpu::BindGroupEntry {
+                                binding: 3,
+                                resource: wgpu::BindingResource::TextureView(&default_texture_view),
+                            },
                         ],
                         label: None,
                     })
@@ -297,6 +322,32 @@ impl Model {
             }
         }
 
-        Model { meshes }
+        // a model without vertices collapses to the origin
+        if bounds_min.x > bounds_max.x {
+            bounds_min = Point3::origin();
+            bounds_max = Point3::origin();
+        }
+
+        owned_textures.push(default_texture);
+
+        Model {
+            id: String::new(),
+            meshes,
+            bounds: (bounds_min, bounds_max),
+            textures: owned_textures,
+        }
+    }
+
+    // frees the gpu memory now instead of whenever the last reference drops
+    pub fn destroy(&self) {
+        for mesh in &self.meshes {
+            mesh.vertex_buffer.destroy();
+            mesh.index_buffer.destroy();
+            mesh.transform.destroy();
+        }
+
+        for texture in &self.textures {
+            texture.destroy();
+        }
     }
 }
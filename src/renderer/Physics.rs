@@ -0,0 +1,216 @@
+// Collision for the native runtime. Landscapes become heightfields and the camera is moved with
+// a character controller, so flying into the ground slides along it instead of through it.
+
+use nalgebra::{Point3, Vector3};
+use rapier3d::control::KinematicCharacterController;
+use rapier3d::prelude::{
+    Ball, CCDSolver, ColliderBuilder, ColliderSet, DMatrix, DefaultBroadPhase, ImpulseJointSet,
+    IntegrationParameters, IslandManager, Isometry, MultibodyJointSet, NarrowPhase,
+    PhysicsPipeline, QueryFilter, QueryPipeline, Real, RigidBodySet, Vector,
+};
+
+use crate::renderer::core::LandscapeData;
+
+// fixed steps keep the simulation the same at any frame rate
+const TIMESTEP: f32 = 1.0 / 60.0;
+// a long frame is only partly caught up, rather than stalling the next one
+const MAX_STEPS_PER_FRAME: u32 = 4;
+
+pub const CAMERA_RADIUS: f32 = 1.0;
+
+pub struct Physics {
+    gravity: Vector<Real>,
+    integration_parameters: IntegrationParameters,
+    pipeline: PhysicsPipeline,
+    islands: IslandManager,
+    broad_phase: DefaultBroadPhase,
+    narrow_phase: NarrowPhase,
+    bodies: RigidBodySet,
+    colliders: ColliderSet,
+    impulse_joints: ImpulseJointSet,
+    multibody_joints: MultibodyJointSet,
+    ccd_solver: CCDSolver,
+    query_pipeline: QueryPipeline,
+    controller: KinematicCharacterController,
+    accumulator: f32,
+}
+
+impl Physics {
+    pub fn new() -> Self {
+        Self {
+            gravity: Vector::new(0.0, -9.81, 0.0),
+            integration_parameters: IntegrationParameters {
+                dt: TIMESTEP,
+                ..Default::default()
+            },
+            pipeline: PhysicsPipeline::new(),
+            islands: IslandManager::new(),
+            broad_phase: DefaultBroadPhase::new(),
+            narrow_phase: NarrowPhase::new(),
+            bodies: RigidBodySet::new(),
+            colliders: ColliderSet::new(),
+            impulse_joints: ImpulseJointSet::new(),
+            multibody_joints: MultibodyJointSet::new(),
+            ccd_solver: CCDSolver::new(),
+            query_pipeline: QueryPipeline::new(),
+            // the camera flies, so it climbs any slope and is never pulled down onto one
+            controller: KinematicCharacterController {
+                max_slope_climb_angle: std::f32::consts::FRAC_PI_2,
+                min_slope_slide_angle: std::f32::consts::FRAC_PI_2,
+                snap_to_ground: None,
+                ..Default::default()
+            },
+            accumulator: 0.0,
+        }
+    }
+
+    // the grid is evenly spaced, so it maps straight onto a heightfield centered on the landscape
+    pub fn add_landscape(&mut self, data: &LandscapeData, offset: Vector3<f32>) {
+        if data.width < 2 || data.height < 2 {
+            return;
+        }
+
+        let first = data.pixel_data[0][0].position;
+        let last = data.pixel_data[data.height - 1][data.width - 1].position;
+
+        // rows run along z and columns along x
+        let heights = DMatrix::from_fn(data.height, data.width, |row, column| {
+            data.pixel_data[row][column].position[1]
+        });
+        let scale = Vector::new(last[0] - first[0], 1.0, last[2] - first[2]);
+
+        let collider = ColliderBuilder::heightfield(heights, scale)
+            .translation(Vector::new(
+                (first[0] + last[0]) / 2.0 + offset.x,
+                offset.y,
+                (first[2] + last[2]) / 2.0 + offset.z,
+            ))
+            .build();
+        self.colliders.insert(collider);
+
+        // queries only see colliders once a step has run
+        self.step_once();
+    }
+
+    // how far the camera can actually go towards the requested translation
+    pub fn move_camera(
+        &self,
+        position: Point3<f32>,
+        translation: Vector3<f32>,
+        delta: f32,
+    ) -> Vector3<f32> {
+        let movement = self.controller.move_shape(
+            delta,
+            &self.bodies,
+            &self.colliders,
+            &self.query_pipeline,
+            &Ball::new(CAMERA_RADIUS),
+            &Isometry::translation(position.x, position.y, position.z),
+            Vector::new(translation.x, translation.y, translation.z),
+            QueryFilter::default(),
+            |_| {},
+        );
+
+        Vector3::new(
+            movement.translation.x,
+            movement.translation.y,
+            movement.translation.z,
+        )
+    }
+
+    pub fn step(&mut self, delta: f32) {
+        self.accumulator = (self.accumulator + delta).min(TIMESTEP * MAX_STEPS_PER_FRAME as f32);
+
+        while self.accumulator >= TIMESTEP {
+            self.step_once();
+            self.accumulator -= TIMESTEP;
+        }
+    }
+
+    fn step_once(&mut self) {
+        self.pipeline.step(
+            &self.gravity,
+            &self.integration_parameters,
+            &mut self.islands,
+            &mut self.broad_phase,
+            &mut self.narrow_phase,
+            &mut self.bodies,
+            &mut self.colliders,
+            &mut self.impulse_joints,
+            &mut self.multibody_joints,
+            &mut self.ccd_solver,
+            Some(&mut self.query_pipeline),
+            &(),
+            &(),
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::renderer::core::PixelData;
+
+    // a 3x3 grid 10 units apart, rising along x
+    fn slope() -> LandscapeData {
+        let pixel_data = (0..3)
+            .map(|z| {
+                (0..3)
+                    .map(|x| PixelData {
+                        height_value: x as f32 * 5.0,
+                        position: [
+                            x as f32 * 10.0 - 10.0,
+                            x as f32 * 5.0,
+                            z as f32 * 10.0 - 10.0,
+                        ],
+                        tex_coords: [x as f32 / 3.0, z as f32 / 3.0],
+                    })
+                    .collect()
+            })
+            .collect();
+
+        LandscapeData {
+            width: 3,
+            height: 3,
+            pixel_data,
+        }
+    }
+
+    #[test]
+    fn camera_stops_at_the_ground() {
+        let mut physics = Physics::new();
+        physics.add_landscape(&slope(), Vector3::new(0.0, -100.0, 0.0));
+
+        // straight down onto the slope where it's 7.5 high, it'd be 2.5 with x and z swapped
+        let translation = physics.move_camera(
+            Point3::new(5.0, -80.0, -5.0),
+            Vector3::new(0.0, -50.0, 0.0),
+            TIMESTEP,
+        );
+        let landed = -80.0 + translation.y;
+
+        assert!(landed > -92.5 && landed < -92.5 + CAMERA_RADIUS * 2.0);
+    }
+
+    #[test]
+    fn camera_moves_freely_above_the_ground() {
+        let mut physics = Physics::new();
+        physics.add_landscape(&slope(), Vector3::zeros());
+
+        let translation = physics.move_camera(
+            Point3::new(0.0, 50.0, 0.0),
+            Vector3::new(3.0, 0.0, -2.0),
+            TIMESTEP,
+        );
+
+        assert!((translation - Vector3::new(3.0, 0.0, -2.0)).norm() < 0.01);
+    }
+
+    #[test]
+    fn long_frames_only_catch_up_partly() {
+        let mut physics = Physics::new();
+        physics.step(10.0);
+
+        assert!(physics.accumulator < TIMESTEP);
+    }
+}
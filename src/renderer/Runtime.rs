@@ -0,0 +1,594 @@
+use nalgebra::{Point3, Vector3};
+use std::collections::HashSet;
+use std::fs::File;
+use std::path::Path;
+use std::sync::Arc;
+use tiff::decoder::{Decoder, DecodingResult};
+use tiff::tags::Tag;
+use winit::keyboard::KeyCode;
+use winit::window::Window;
+
+use crate::contexts::saved::{
+    ComponentData, ComponentKind, LandscapeTextureKinds, LevelData, SavedState,
+};
+use crate::renderer::core::{
+    console_log, create_scene, default_camera, render_scene, LandscapeData, PixelData,
+    RenderContext, RendererState,
+};
+use crate::renderer::Ktx2::read_ktx2;
+use crate::renderer::Physics::Physics;
+use crate::renderer::SimpleCamera::SimpleCamera;
+use crate::renderer::Texture::Texture;
+
+// same dimensions the tauri backend scales heightmaps to, so levels match the editor
+const LANDSCAPE_WIDTH: f32 = 2048.0;
+const LANDSCAPE_LENGTH: f32 = 2048.0;
+const LANDSCAPE_HEIGHT: f32 = 250.0;
+
+// exported projects store heightfields as "MPHF", version, width and height as u32,
+// then normalized f32 heights, all little endian
+const HEIGHTFIELD_MAGIC: &[u8; 4] = b"MPHF";
+const HEIGHTFIELD_VERSION: u32 = 1;
+
+// marks heightmaps saved after sculpting, whose values are already normalized
+const NORMALIZED_HEIGHTS_DESCRIPTION: &str = "midpoint:normalized-heights";
+
+const MOVE_SPEED: f32 = 40.0; // units per second
+const FAST_MULTIPLIER: f32 = 4.0;
+const LOOK_SENSITIVITY: f32 = 0.005;
+
+// plays a saved level in a native window, reading every asset straight from the project folder
+pub struct RuntimeRenderer {
+    window: Arc<Window>,
+    device: Arc<wgpu::Device>,
+    queue: Arc<wgpu::Queue>,
+    surface: wgpu::Surface<'static>,
+    config: wgpu::SurfaceConfiguration,
+    context: RenderContext,
+    state: RendererState,
+    camera: SimpleCamera,
+    physics: Physics,
+    pressed_keys: HashSet<KeyCode>,
+    bounds: Option<(Point3<f32>, Point3<f32>)>,
+}
+
+impl RuntimeRenderer {
+    pub async fn new(window: Arc<Window>) -> Result<Self, String> {
+        let instance = wgpu::Instance::new(wgpu::InstanceDescriptor {
+            backends: wgpu::Backends::PRIMARY,
+            ..Default::default()
+        });
+
+        let surface = instance
+            .create_surface(window.clone())
+            .map_err(|e| format!("Couldn't create GPU Surface: {}", e))?;
+
+        let adapter = instance
+            .request_adapter(&wgpu::RequestAdapterOptions {
+                power_preference: wgpu::PowerPreference::HighPerformance,
+                compatible_surface: Some(&surface),
+                force_fallback_adapter: false,
+            })
+            .await
+            .ok_or("Failed to find an appropriate adapter")?;
+
+        let (device, queue) = adapter
+            .request_device(
+                &wgpu::DeviceDescriptor {
+                    label: Some("Runtime Device"),
+                    required_features: wgpu::Features::empty(),
+                    required_limits: wgpu::Limits::default(),
+                },
+                None,
+            )
+            .await
+            .map_err(|e| format!("Failed to create device: {}", e))?;
+
+        let device = Arc::new(device);
+        let queue = Arc::new(queue);
+
+        let size = window.inner_size();
+        let config = surface
+            .get_default_config(&adapter, size.width.max(1), size.height.max(1))
+            .ok_or("Surface isn't supported by the adapter")?;
+        surface.configure(&device, &config);
+
+        let mut camera = default_camera();
+        camera.update_aspect_ratio(config.width as f32 / config.height as f32);
+        camera.update_view_projection_matrix();
+
+        let (context, mut state) = create_scene(
+            &device,
+            &queue,
+            config.format,
+            config.width,
+            config.height,
+            &camera,
+        );
+        state.clear_helpers();
+
+        Ok(Self {
+            window,
+            device,
+            queue,
+            surface,
+            config,
+            context,
+            state,
+            camera,
+            physics: Physics::new(),
+            pressed_keys: HashSet::new(),
+            bounds: None,
+        })
+    }
+
+    pub fn window(&self) -> &Window {
+        &self.window
+    }
+
+    // loads the level with the given id, or the first level when none is given
+    pub fn load_level(
+        &mut self,
+        project_dir: &Path,
+        saved: &SavedState,
+        level_id: Option<&str>,
+    ) -> Result<(), String> {
+        let levels = saved.levels.as_deref().unwrap_or_default();
+        let level = match level_id {
+            Some(level_id) => levels.iter().find(|level| level.id == level_id),
+            None => levels.first(),
+        }
+        .ok_or("Level not found in saved state")?;
+
+        console_log(&format!("Loading level {}", level.id));
+
+        for component in level.components.iter().flatten() {
+            let result = match component.kind {
+                Some(ComponentKind::Landscape) => {
+                    self.load_landscape(project_dir, saved, component)
+                }
+                Some(ComponentKind::Model) => self.load_model(project_dir, saved, component),
+                Some(ComponentKind::Foliage) => self.load_foliage(project_dir, saved, component),
+                None => Ok(()),
+            };
+
+            // a missing asset only skips its component, the rest of the level still plays
+            if let Err(e) = result {
+                console_log(&format!("Skipping component {}: {}", component.id, e));
+            }
+        }
+
+        self.load_environment(level);
+        self.spawn_camera();
+
+        Ok(())
+    }
+
+    fn load_landscape(
+        &mut self,
+        project_dir: &Path,
+        saved: &SavedState,
+        component: &ComponentData,
+    ) -> Result<(), String> {
+        let asset = saved
+            .landscapes
+            .iter()
+            .flatten()
+            .find(|landscape| landscape.id == component.asset_id)
+            .ok_or("Landscape asset not found")?;
+        let heightmap = asset
+            .heightmap
+            .as_ref()
+            .ok_or("Landscape has no heightmap")?;
+
+        let landscape_dir = project_dir.join("landscapes").join(&asset.id);
+        let data = read_heightmap(&landscape_dir.join("heightmaps").join(&heightmap.fileName))?;
+
+        self.state.add_landscape(&component.id, &data);
+
+        // landscapes are drawn offset from their heightmap positions
+        let offset = self
+            .state
+            .landscape(&component.id)
+            .map(|landscape| landscape.transform.position())
+            .unwrap_or_default();
+        self.physics.add_landscape(&data, offset);
+        if let Some((min, max)) = data.bounds() {
+            self.include_bounds((min + offset, max + offset));
+        }
+
+        let properties = match &component.landscape_properties {
+            Some(properties) => properties,
+            None => return Ok(()),
+        };
+
+        // a painted primary mask replaces the heightmap it was derived from, as in the editor.
+        // exports always have one, compressed
+        let primary_mask = ["ktx2", "png"]
+            .iter()
+            .map(|extension| {
+                let mut painted_mask = Path::new(&heightmap.fileName).to_path_buf();
+                painted_mask.set_extension(extension);
+                landscape_dir.join("primarymasks").join(painted_mask)
+            })
+            .find(|painted_mask| painted_mask.exists())
+            .unwrap_or_else(|| landscape_dir.join("heightmaps").join(&heightmap.fileName));
+
+        let layers = [
+            (
+                &properties.primary_texture_id,
+                LandscapeTextureKinds::Primary,
+                LandscapeTextureKinds::PrimaryMask,
+                Some(primary_mask),
+            ),
+            (
+                &properties.rockmap_texture_id,
+                LandscapeTextureKinds::Rockmap,
+                LandscapeTextureKinds::RockmapMask,
+                asset
+                    .rockmap
+                    .as_ref()
+                    .map(|rockmap| landscape_dir.join("rockmaps").join(&rockmap.fileName)),
+            ),
+            (
+                &properties.soil_texture_id,
+                LandscapeTextureKinds::Soil,
+                LandscapeTextureKinds::SoilMask,
+                asset
+                    .soil
+                    .as_ref()
+                    .map(|soil| landscape_dir.join("soils").join(&soil.fileName)),
+            ),
+        ];
+
+        for (texture_id, kind, mask_kind, mask_path) in layers {
+            let texture_id = match texture_id {
+                Some(texture_id) => texture_id,
+                None => continue,
+            };
+
+            let texture = saved
+                .textures
+                .iter()
+                .flatten()
+                .find(|texture| &texture.id == texture_id)
+                .ok_or("Landscape texture not found")?;
+
+            let texture = read_rgba(&project_dir.join("textures").join(&texture.fileName))?;
+            // layers without a painted mask are blended evenly
+            let mask = match mask_path {
+                Some(mask_path) => Some((mask_kind, read_rgba(&mask_path)?)),
+                None => None,
+            };
+
+            self.state
+                .update_landscape_texture(component.id.clone(), kind, texture, mask);
+        }
+
+        Ok(())
+    }
+
+    fn load_model(
+        &mut self,
+        project_dir: &Path,
+        saved: &SavedState,
+        component: &ComponentData,
+    ) -> Result<(), String> {
+        let bytes = read_model_bytes(project_dir, saved, &component.asset_id)?;
+
+        let bounds = self.state.add_model(&component.id, &bytes);
+        self.include_bounds(bounds);
+
+        Ok(())
+    }
+
+    fn load_foliage(
+        &mut self,
+        project_dir: &Path,
+        saved: &SavedState,
+        component: &ComponentData,
+    ) -> Result<(), String> {
+        let properties = component
+            .foliage_properties
+            .as_ref()
+            .ok_or("Foliage has no properties")?;
+        let bytes = read_model_bytes(project_dir, saved, &component.asset_id)?;
+
+        // instances were scattered and saved in the editor, nothing is rescattered here
+        self.state.add_foliage(&component.id, &bytes);
+        self.state
+            .set_foliage_instances(&component.id, &properties.instances);
+
+        Ok(())
+    }
+
+    fn load_environment(&mut self, level: &LevelData) {
+        if let Some(water) = &level.water {
+            self.state.set_water(water.clone());
+        }
+
+        self.state.set_sky(level.sky.clone().unwrap_or_default());
+    }
+
+    fn include_bounds(&mut self, (min, max): (Point3<f32>, Point3<f32>)) {
+        self.bounds = Some(match self.bounds {
+            Some((current_min, current_max)) => (current_min.inf(&min), current_max.sup(&max)),
+            None => (min, max),
+        });
+    }
+
+    // start at the edge of the level looking across it, a little above its highest point
+    fn spawn_camera(&mut self) {
+        if let Some((min, max)) = self.bounds {
+            let center = nalgebra::center(&min, &max);
+
+            self.camera.position = Point3::new(center.x, max.y + 10.0, max.z);
+            self.camera.direction = Vector3::new(0.0, -0.3, -1.0).normalize();
+            self.camera.update_view_projection_matrix();
+        }
+    }
+
+    pub fn resize(&mut self, width: u32, height: u32) {
+        let max_dimension = self.device.limits().max_texture_dimension_2d;
+        let width = width.clamp(1, max_dimension);
+        let height = height.clamp(1, max_dimension);
+
+        self.config.width = width;
+        self.config.height = height;
+        self.surface.configure(&self.device, &self.config);
+
+        self.state.resize_depth(width, height);
+
+        self.camera
+            .update_aspect_ratio(width as f32 / height as f32);
+        self.camera.update_view_projection_matrix();
+    }
+
+    pub fn handle_key(&mut self, key_code: KeyCode, is_pressed: bool) {
+        if is_pressed {
+            self.pressed_keys.insert(key_code);
+        } else {
+            self.pressed_keys.remove(&key_code);
+        }
+    }
+
+    pub fn handle_mouse_motion(&mut self, dx: f32, dy: f32) {
+        self.camera
+            .rotate(-dx * LOOK_SENSITIVITY, dy * LOOK_SENSITIVITY);
+    }
+
+    // fly camera, wasd to move, e / q for up and down, shift to go faster.
+    // it collides with landscapes rather than passing through them
+    pub fn update(&mut self, delta: f32) {
+        let right = self.camera.direction.cross(&self.camera.up).normalize();
+        let bindings = [
+            (KeyCode::KeyW, self.camera.direction),
+            (KeyCode::KeyS, -self.camera.direction),
+            (KeyCode::KeyD, right),
+            (KeyCode::KeyA, -right),
+            (KeyCode::KeyE, self.camera.up),
+            (KeyCode::KeyQ, -self.camera.up),
+        ];
+
+        let movement: Vector3<f32> = bindings
+            .iter()
+            .filter(|(key_code, _)| self.pressed_keys.contains(key_code))
+            .map(|(_, direction)| *direction)
+            .sum();
+
+        if movement.norm() > f32::EPSILON {
+            let speed = if self.pressed_keys.contains(&KeyCode::ShiftLeft) {
+                MOVE_SPEED * FAST_MULTIPLIER
+            } else {
+                MOVE_SPEED
+            };
+
+            let translation = self.physics.move_camera(
+                self.camera.position,
+                movement.normalize() * speed * delta,
+                delta,
+            );
+            self.camera.position += translation;
+        }
+
+        self.physics.step(delta);
+    }
+
+    pub fn render(&mut self) -> Result<(), String> {
+        let frame = match self.surface.get_current_texture() {
+            Ok(frame) => frame,
+            // the surface goes stale after minimizing or moving between monitors
+            Err(wgpu::SurfaceError::Lost) | Err(wgpu::SurfaceError::Outdated) => {
+                self.surface.configure(&self.device, &self.config);
+                return Ok(());
+            }
+            // the frame is skipped and the next one tries again
+            Err(wgpu::SurfaceError::Timeout) => return Ok(()),
+            Err(e) => return Err(format!("Failed to acquire next surface texture: {}", e)),
+        };
+        let view = frame
+            .texture
+            .create_view(&wgpu::TextureViewDescriptor::default());
+
+        render_scene(
+            &self.state,
+            &mut self.camera,
+            &view,
+            &self.device,
+            &self.queue,
+            &self.context,
+        );
+
+        frame.present();
+
+        Ok(())
+    }
+}
+
+fn read_model_bytes(
+    project_dir: &Path,
+    saved: &SavedState,
+    model_id: &str,
+) -> Result<Vec<u8>, String> {
+    let model = saved
+        .models
+        .iter()
+        .find(|model| model.id == model_id)
+        .ok_or("Model asset not found")?;
+
+    std::fs::read(project_dir.join("models").join(&model.fileName))
+        .map_err(|e| format!("Failed to read model {}: {}", model.fileName, e))
+}
+
+fn read_rgba(path: &Path) -> Result<Texture, String> {
+    if path.extension().and_then(|e| e.to_str()) == Some("ktx2") {
+        return read_ktx2(path);
+    }
+
+    let image = image::open(path)
+        .map_err(|e| format!("Failed to open texture {:?}: {}", path, e))?
+        .to_rgba8();
+    let (width, height) = image.dimensions();
+
+    Ok(Texture::new(image.into_raw(), width, height))
+}
+
+fn read_heightfield(path: &Path) -> Result<(usize, usize, Vec<f32>), String> {
+    let data =
+        std::fs::read(path).map_err(|e| format!("Couldn't read heightfield {:?}: {}", path, e))?;
+
+    let word = |index: usize| {
+        data.get(index * 4..index * 4 + 4)
+            .map(|bytes| u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+            .ok_or("Heightfield is truncated".to_string())
+    };
+
+    if data.get(0..4) != Some(&HEIGHTFIELD_MAGIC[..]) || word(1)? != HEIGHTFIELD_VERSION {
+        return Err(format!("{:?} isn't a supported heightfield", path));
+    }
+
+    let width = word(2)? as usize;
+    let height = word(3)? as usize;
+    let values: Vec<f32> = data[16..]
+        .chunks_exact(4)
+        .map(|bytes| f32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+        .collect();
+
+    if values.len() != width * height {
+        return Err("Heightfield doesn't match its dimensions".to_string());
+    }
+
+    Ok((width, height, values))
+}
+
+// normalized like the editor does, imported heightmaps are stretched min to max
+fn read_tiff(path: &Path) -> Result<(usize, usize, Vec<f32>), String> {
+    let file = File::open(path).map_err(|e| format!("Couldn't open tif file {:?}: {}", path, e))?;
+    let mut decoder = Decoder::new(file).map_err(|e| format!("Couldn't decode tif file: {}", e))?;
+
+    let (width, height) = decoder
+        .dimensions()
+        .map_err(|e| format!("Couldn't get tif dimensions: {}", e))?;
+    let pre_normalized = decoder
+        .get_tag_ascii_string(Tag::ImageDescription)
+        .map_or(false, |description| {
+            description == NORMALIZED_HEIGHTS_DESCRIPTION
+        });
+
+    let values = match decoder
+        .read_image()
+        .map_err(|e| format!("Couldn't read image data from tif: {}", e))?
+    {
+        DecodingResult::F32(values) => values,
+        _ => return Err("Heightmap isn't a 32-bit float tif".to_string()),
+    };
+
+    if pre_normalized {
+        return Ok((width as usize, height as usize, values));
+    }
+
+    let min_height = values.iter().cloned().fold(f32::MAX, f32::min);
+    let max_height = values.iter().cloned().fold(f32::MIN, f32::max);
+    let height_range = (max_height - min_height).max(f32::EPSILON);
+
+    let values = values
+        .iter()
+        .map(|value| (value - min_height) / height_range)
+        .collect();
+
+    Ok((width as usize, height as usize, values))
+}
+
+// heightmap to terrain pixels, the same conversion get_landscape_pixels does for the editor
+fn read_heightmap(path: &Path) -> Result<LandscapeData, String> {
+    // exported heightfields are normalized already
+    let (width, height, values) = match path.extension().and_then(|e| e.to_str()) {
+        Some("heightfield") => read_heightfield(path)?,
+        _ => read_tiff(path)?,
+    };
+
+    let x_scale = LANDSCAPE_WIDTH / width as f32;
+    let y_scale = LANDSCAPE_LENGTH / height as f32;
+
+    let pixel_data = (0..height)
+        .map(|y| {
+            (0..width)
+                .map(|x| {
+                    let height_value = values[y * width + x] * LANDSCAPE_HEIGHT;
+
+                    PixelData {
+                        height_value,
+                        position: [
+                            x as f32 * x_scale - LANDSCAPE_WIDTH / 2.0,
+                            height_value,
+                            y as f32 * y_scale - LANDSCAPE_LENGTH / 2.0,
+                        ],
+                        tex_coords: [x as f32 / width as f32, y as f32 / height as f32],
+                    }
+                })
+                .collect()
+        })
+        .collect();
+
+    Ok(LandscapeData {
+        width,
+        height,
+        pixel_data,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // written by the exporter's encode_heightfield, its tests check they still match
+    fn sample_heightfield() -> std::path::PathBuf {
+        Path::new(env!("CARGO_MANIFEST_DIR")).join("src-tauri/tests/fixtures/sample.heightfield")
+    }
+
+    #[test]
+    fn reads_exported_heightfields() {
+        let (width, height, values) =
+            read_heightfield(&sample_heightfield()).expect("Couldn't read the sample heightfield");
+
+        assert_eq!((width, height), (3, 2));
+        assert_eq!(values, [0.0, 0.25, 0.5, 0.75, 1.0, 0.125]);
+    }
+
+    #[test]
+    fn rejects_heightfields_that_dont_match_their_size() {
+        let mut data = std::fs::read(sample_heightfield()).unwrap();
+        data.truncate(data.len() - 4);
+
+        let path = std::env::temp_dir().join(format!(
+            "midpoint-truncated-{}.heightfield",
+            std::process::id()
+        ));
+        std::fs::write(&path, data).unwrap();
+
+        let result = read_heightfield(&path);
+        std::fs::remove_file(&path).ok();
+
+        assert!(result.is_err());
+    }
+}
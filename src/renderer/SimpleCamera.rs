@@ -53,6 +53,28 @@ impl SimpleCamera {
         self.view_projection_matrix = projection_matrix * view_matrix;
     }
 
+    // world-space ray through a point on the canvas, in canvas pixels
+    pub fn screen_ray(
+        &self,
+        x: f32,
+        y: f32,
+        canvas_width: f32,
+        canvas_height: f32,
+    ) -> (Point3<f32>, Vector3<f32>) {
+        let ndc_x = (2.0 * x) / canvas_width - 1.0;
+        let ndc_y = 1.0 - (2.0 * y) / canvas_height;
+
+        let inverse = self
+            .view_projection_matrix
+            .try_inverse()
+            .unwrap_or_else(Matrix4::identity);
+
+        let near = inverse.transform_point(&Point3::new(ndc_x, ndc_y, -1.0));
+        let far = inverse.transform_point(&Point3::new(ndc_x, ndc_y, 1.0));
+
+        (self.position, (far - near).normalize())
+    }
+
     pub fn update(&mut self) {
         self.update_view_projection_matrix();
     }
@@ -0,0 +1,104 @@
+use bytemuck::{Pod, Zeroable};
+use nalgebra::{Matrix4, Vector3};
+
+use crate::contexts::saved::SkySettings;
+use crate::renderer::SimpleCamera::SimpleCamera;
+
+// matches EnvironmentUniforms in sky.wgsl and the fog helpers in the other shaders
+#[repr(C)]
+#[derive(Copy, Clone, Debug)]
+struct EnvironmentUniforms {
+    inverse_view_projection: [f32; 16],
+    camera_position: [f32; 4],
+    sun_direction: [f32; 4], // xyz towards the sun, w intensity
+    zenith_color: [f32; 4],
+    horizon_color: [f32; 4],
+    ground_color: [f32; 4],
+    fog_color: [f32; 4],  // rgb, density
+    fog_params: [f32; 4], // height falloff, base height, max opacity, unused
+}
+
+unsafe impl Pod for EnvironmentUniforms {}
+unsafe impl Zeroable for EnvironmentUniforms {}
+
+// sky and fog share one uniform buffer so every shader agrees on the sun and fog
+pub struct Sky {
+    pub settings: SkySettings,
+    pub uniform_buffer: wgpu::Buffer,
+    pub bind_group: wgpu::BindGroup,
+}
+
+impl Sky {
+    pub fn new(
+        device: &wgpu::Device,
+        bind_group_layout: &wgpu::BindGroupLayout,
+        settings: SkySettings,
+    ) -> Self {
+        let uniform_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Environment Uniform Buffer"),
+            size: std::mem::size_of::<EnvironmentUniforms>() as wgpu::BufferAddress,
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+
+        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            layout: bind_group_layout,
+            entries: &[wgpu::BindGroupEntry {
+                binding: 0,
+                resource: uniform_buffer.as_entire_binding(),
+            }],
+            label: Some("environment_bind_group"),
+        });
+
+        Self {
+            settings,
+            uniform_buffer,
+            bind_group,
+        }
+    }
+
+    // unit vector pointing towards the sun
+    pub fn sun_direction(&self) -> Vector3<f32> {
+        let azimuth = self.settings.sun_azimuth.to_radians();
+        let elevation = self.settings.sun_elevation.to_radians();
+
+        Vector3::new(
+            azimuth.sin() * elevation.cos(),
+            elevation.sin(),
+            -azimuth.cos() * elevation.cos(),
+        )
+        .normalize()
+    }
+
+    pub fn update_uniform_buffer(&self, queue: &wgpu::Queue, camera: &SimpleCamera) {
+        let settings = &self.settings;
+        let inverse = camera
+            .view_projection_matrix
+            .try_inverse()
+            .unwrap_or_else(Matrix4::identity);
+        let sun = self.sun_direction();
+
+        let mut inverse_view_projection = [0.0; 16];
+        inverse_view_projection.copy_from_slice(inverse.as_slice());
+
+        let rgba = |color: [f32; 3], w: f32| [color[0], color[1], color[2], w];
+
+        let uniforms = EnvironmentUniforms {
+            inverse_view_projection,
+            camera_position: [camera.position.x, camera.position.y, camera.position.z, 1.0],
+            sun_direction: [sun.x, sun.y, sun.z, settings.sun_intensity],
+            zenith_color: rgba(settings.zenith_color, 1.0),
+            horizon_color: rgba(settings.horizon_color, 1.0),
+            ground_color: rgba(settings.ground_color, 1.0),
+            fog_color: rgba(settings.fog_color, settings.fog_density.max(0.0)),
+            fog_params: [
+                settings.fog_height_falloff.max(0.0),
+                settings.fog_base_height,
+                settings.fog_max_opacity.clamp(0.0, 1.0),
+                0.0,
+            ],
+        };
+
+        queue.write_buffer(&self.uniform_buffer, 0, bytemuck::bytes_of(&uniforms));
+    }
+}
@@ -0,0 +1,595 @@
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::rng::hash2;
+
+// world units a full-strength, full-weight dab raises or lowers the terrain
+const BRUSH_HEIGHT_UNITS: f32 = 2.0;
+
+#[derive(Clone, Copy, PartialEq, Serialize, Deserialize, Debug)]
+pub enum BrushKind {
+    Raise,
+    Lower,
+    Smooth,
+    Flatten,
+    Noise,
+}
+
+impl BrushKind {
+    pub fn from_str(kind: &str) -> Option<BrushKind> {
+        match kind {
+            "Raise" => Some(BrushKind::Raise),
+            "Lower" => Some(BrushKind::Lower),
+            "Smooth" => Some(BrushKind::Smooth),
+            "Flatten" => Some(BrushKind::Flatten),
+            "Noise" => Some(BrushKind::Noise),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Serialize, Deserialize, Debug)]
+pub struct BrushSettings {
+    pub kind: BrushKind,
+    pub radius: f32,   // world units
+    pub strength: f32, // 0.0 - 1.0
+    pub falloff: f32,  // 0.0 (hard edge) - 1.0 (fades from the center)
+}
+
+impl BrushSettings {
+    // weight of the brush at `distance` from its center
+    pub fn weight(&self, distance: f32) -> f32 {
+        falloff_weight(self.radius, self.falloff, distance)
+    }
+}
+
+// paints one mask layer up while the others give way, keeping the layers normalized
+#[derive(Clone, Copy, PartialEq, Serialize, Deserialize, Debug)]
+pub struct MaskBrush {
+    pub layer: usize,  // Primary, Rockmap, Soil
+    pub radius: f32,   // world units
+    pub strength: f32, // 0.0 - 1.0
+    pub falloff: f32,  // 0.0 (hard edge) - 1.0 (fades from the center)
+}
+
+impl MaskBrush {
+    pub fn weight(&self, distance: f32) -> f32 {
+        falloff_weight(self.radius, self.falloff, distance)
+    }
+}
+
+// full weight inside the hard core of the brush, smoothstepping to zero at the radius
+pub fn falloff_weight(radius: f32, falloff: f32, distance: f32) -> f32 {
+    if distance >= radius {
+        return 0.0;
+    }
+
+    let falloff = falloff.clamp(0.0, 1.0);
+    let inner = radius * (1.0 - falloff);
+
+    if distance <= inner {
+        return 1.0;
+    }
+
+    let t = (distance - inner) / (radius - inner);
+    let t = 1.0 - t.clamp(0.0, 1.0);
+    t * t * (3.0 - 2.0 * t) // smoothstep
+}
+
+// a single mouse-down to mouse-up of sculpting, holding the original height
+// of every vertex it touched so it can be undone
+#[derive(Default)]
+pub struct SculptStroke {
+    pub landscape_id: String,
+    pub original_heights: HashMap<usize, f32>,
+    pub flatten_height: Option<f32>,
+}
+
+impl SculptStroke {
+    pub fn new(landscape_id: String) -> Self {
+        Self {
+            landscape_id,
+            original_heights: HashMap::new(),
+            flatten_height: None,
+        }
+    }
+
+    pub fn record(&mut self, index: usize, height: f32) {
+        self.original_heights.entry(index).or_insert(height);
+    }
+}
+
+// grid of heights that the brushes operate on, row-major
+pub struct HeightGrid<'a> {
+    pub heights: &'a mut [f32],
+    pub width: usize,
+    pub height: usize,
+}
+
+// returns the inclusive row range that was modified, if any
+pub fn apply_brush(
+    grid: &mut HeightGrid,
+    settings: &BrushSettings,
+    center: (f32, f32),  // grid coordinates
+    spacing: (f32, f32), // world units between columns and rows
+    stroke: &mut SculptStroke,
+) -> Option<(usize, usize)> {
+    let radius_x = (settings.radius / spacing.0).ceil() as isize;
+    let radius_y = (settings.radius / spacing.1).ceil() as isize;
+
+    let min_x = (center.0.floor() as isize - radius_x).max(0) as usize;
+    let max_x = (center.0.ceil() as isize + radius_x).min(grid.width as isize - 1);
+    let min_y = (center.1.floor() as isize - radius_y).max(0) as usize;
+    let max_y = (center.1.ceil() as isize + radius_y).min(grid.height as isize - 1);
+
+    if max_x < 0 || max_y < 0 || min_x as isize > max_x || min_y as isize > max_y {
+        return None;
+    }
+
+    let max_x = max_x as usize;
+    let max_y = max_y as usize;
+
+    if settings.kind == BrushKind::Flatten && stroke.flatten_height.is_none() {
+        stroke.flatten_height = Some(sample_height(
+            grid.heights,
+            grid.width,
+            grid.height,
+            center.0,
+            center.1,
+        ));
+    }
+
+    // smoothing reads neighbours, so work from a snapshot of the unmodified heights
+    let snapshot = if settings.kind == BrushKind::Smooth {
+        Some(Snapshot::capture(grid, min_x, max_x, min_y, max_y))
+    } else {
+        None
+    };
+
+    let strength = settings.strength.clamp(0.0, 1.0);
+    let mut touched = false;
+
+    for y in min_y..=max_y {
+        for x in min_x..=max_x {
+            let dx = (x as f32 - center.0) * spacing.0;
+            let dy = (y as f32 - center.1) * spacing.1;
+            let weight = settings.weight((dx * dx + dy * dy).sqrt());
+
+            if weight <= 0.0 {
+                continue;
+            }
+
+            let index = y * grid.width + x;
+            let current = grid.heights[index];
+            let amount = strength * weight;
+
+            let next = match settings.kind {
+                BrushKind::Raise => current + amount * BRUSH_HEIGHT_UNITS,
+                BrushKind::Lower => current - amount * BRUSH_HEIGHT_UNITS,
+                BrushKind::Smooth => {
+                    let average = snapshot
+                        .as_ref()
+                        .map_or(current, |snapshot| snapshot.neighbour_average(x, y));
+                    current + (average - current) * amount
+                }
+                BrushKind::Flatten => {
+                    let target = stroke.flatten_height.unwrap_or(current);
+                    current + (target - current) * amount
+                }
+                BrushKind::Noise => {
+                    let frequency = 1.0 / settings.radius.max(0.001);
+                    let noise = value_noise(
+                        x as f32 * spacing.0 * frequency * 4.0,
+                        y as f32 * spacing.1 * frequency * 4.0,
+                    );
+                    current + (noise * 2.0 - 1.0) * amount * BRUSH_HEIGHT_UNITS
+                }
+            };
+
+            stroke.record(index, current);
+            grid.heights[index] = next;
+            touched = true;
+        }
+    }
+
+    if touched {
+        Some((min_y, max_y))
+    } else {
+        None
+    }
+}
+
+// paint RGBA mask layers (weights in the red channel) around a center in mask pixels,
+// returning the modified pixel rectangle as (min_x, min_y, max_x, max_y), inclusive
+pub fn paint_mask_layers(
+    layers: &mut [Vec<u8>],
+    width: u32,
+    height: u32,
+    brush: &MaskBrush,
+    center: (f32, f32),
+    texel_size: (f32, f32), // world units per mask pixel
+) -> Option<(u32, u32, u32, u32)> {
+    if brush.layer >= layers.len() || width == 0 || height == 0 {
+        return None;
+    }
+
+    let radius_x = (brush.radius / texel_size.0).ceil() as i64;
+    let radius_y = (brush.radius / texel_size.1).ceil() as i64;
+
+    let min_x = (center.0.floor() as i64 - radius_x).max(0);
+    let max_x = (center.0.ceil() as i64 + radius_x).min(width as i64 - 1);
+    let min_y = (center.1.floor() as i64 - radius_y).max(0);
+    let max_y = (center.1.ceil() as i64 + radius_y).min(height as i64 - 1);
+
+    if min_x > max_x || min_y > max_y {
+        return None;
+    }
+
+    let strength = brush.strength.clamp(0.0, 1.0);
+    let mut touched = false;
+
+    for y in min_y..=max_y {
+        for x in min_x..=max_x {
+            let dx = (x as f32 - center.0) * texel_size.0;
+            let dy = (y as f32 - center.1) * texel_size.1;
+            let weight = brush.weight((dx * dx + dy * dy).sqrt());
+
+            if weight <= 0.0 {
+                continue;
+            }
+
+            let offset = ((y as usize) * (width as usize) + x as usize) * 4;
+            let amount = strength * weight;
+
+            let current = layers[brush.layer][offset] as f32 / 255.0;
+            let painted = current + (1.0 - current) * amount;
+
+            // the remaining weight is shared by the other layers in their existing proportions
+            let others: f32 = (0..layers.len())
+                .filter(|&i| i != brush.layer)
+                .map(|i| layers[i][offset] as f32 / 255.0)
+                .sum();
+            let remaining = 1.0 - painted;
+
+            for i in 0..layers.len() {
+                let value = if i == brush.layer {
+                    painted
+                } else if others > 0.0 {
+                    (layers[i][offset] as f32 / 255.0) / others * remaining
+                } else {
+                    0.0
+                };
+
+                let byte = (value.clamp(0.0, 1.0) * 255.0).round() as u8;
+                layers[i][offset] = byte;
+                layers[i][offset + 1] = byte;
+                layers[i][offset + 2] = byte;
+                layers[i][offset + 3] = 255;
+            }
+
+            touched = true;
+        }
+    }
+
+    if touched {
+        Some((min_x as u32, min_y as u32, max_x as u32, max_y as u32))
+    } else {
+        None
+    }
+}
+
+// bilinear height lookup at fractional grid coordinates
+pub fn sample_height(heights: &[f32], width: usize, height: usize, gx: f32, gy: f32) -> f32 {
+    let gx = gx.clamp(0.0, (width - 1) as f32);
+    let gy = gy.clamp(0.0, (height - 1) as f32);
+
+    let x0 = gx.floor() as usize;
+    let y0 = gy.floor() as usize;
+    let x1 = (x0 + 1).min(width - 1);
+    let y1 = (y0 + 1).min(height - 1);
+
+    let tx = gx - x0 as f32;
+    let ty = gy - y0 as f32;
+
+    let h00 = heights[y0 * width + x0];
+    let h10 = heights[y0 * width + x1];
+    let h01 = heights[y1 * width + x0];
+    let h11 = heights[y1 * width + x1];
+
+    let top = h00 + (h10 - h00) * tx;
+    let bottom = h01 + (h11 - h01) * tx;
+    top + (bottom - top) * ty
+}
+
+// copy of the brush region plus a one vertex border
+struct Snapshot {
+    heights: Vec<f32>,
+    min_x: usize,
+    max_x: usize,
+    min_y: usize,
+    max_y: usize,
+}
+
+impl Snapshot {
+    fn capture(grid: &HeightGrid, min_x: usize, max_x: usize, min_y: usize, max_y: usize) -> Self {
+        let min_x = min_x.saturating_sub(1);
+        let max_x = (max_x + 1).min(grid.width - 1);
+        let min_y = min_y.saturating_sub(1);
+        let max_y = (max_y + 1).min(grid.height - 1);
+
+        let mut heights = Vec::with_capacity((max_x - min_x + 1) * (max_y - min_y + 1));
+        for y in min_y..=max_y {
+            heights.extend_from_slice(&grid.heights[y * grid.width + min_x..=y * grid.width + max_x]);
+        }
+
+        Self {
+            heights,
+            min_x,
+            max_x,
+            min_y,
+            max_y,
+        }
+    }
+
+    fn neighbour_average(&self, x: usize, y: usize) -> f32 {
+        let row_len = self.max_x - self.min_x + 1;
+        let mut sum = 0.0;
+        let mut count = 0.0;
+
+        for ny in y.saturating_sub(1).max(self.min_y)..=(y + 1).min(self.max_y) {
+            for nx in x.saturating_sub(1).max(self.min_x)..=(x + 1).min(self.max_x) {
+                sum += self.heights[(ny - self.min_y) * row_len + (nx - self.min_x)];
+                count += 1.0;
+            }
+        }
+
+        sum / count
+    }
+}
+
+// smooth value noise in 0.0 - 1.0
+fn value_noise(x: f32, y: f32) -> f32 {
+    let x0 = x.floor();
+    let y0 = y.floor();
+    let tx = x - x0;
+    let ty = y - y0;
+    let tx = tx * tx * (3.0 - 2.0 * tx);
+    let ty = ty * ty * (3.0 - 2.0 * ty);
+
+    let (xi, yi) = (x0 as i32, y0 as i32);
+    let top = hash2(xi, yi) + (hash2(xi + 1, yi) - hash2(xi, yi)) * tx;
+    let bottom = hash2(xi, yi + 1) + (hash2(xi + 1, yi + 1) - hash2(xi, yi + 1)) * tx;
+    top + (bottom - top) * ty
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn flat_grid(width: usize, height: usize, value: f32) -> Vec<f32> {
+        vec![value; width * height]
+    }
+
+    fn settings(kind: BrushKind, radius: f32, strength: f32, falloff: f32) -> BrushSettings {
+        BrushSettings {
+            kind,
+            radius,
+            strength,
+            falloff,
+        }
+    }
+
+    #[test]
+    fn falloff_is_full_in_the_core_and_zero_at_the_radius() {
+        assert_eq!(falloff_weight(10.0, 0.5, 0.0), 1.0);
+        assert_eq!(falloff_weight(10.0, 0.5, 5.0), 1.0);
+        assert_eq!(falloff_weight(10.0, 0.5, 10.0), 0.0);
+        assert_eq!(falloff_weight(10.0, 0.5, 12.0), 0.0);
+
+        let middle = falloff_weight(10.0, 0.5, 7.5);
+        assert!(middle > 0.0 && middle < 1.0);
+    }
+
+    #[test]
+    fn falloff_decreases_towards_the_edge() {
+        let mut previous = 1.0;
+        for step in 0..=20 {
+            let weight = falloff_weight(10.0, 1.0, step as f32 * 0.5);
+            assert!(weight <= previous);
+            previous = weight;
+        }
+    }
+
+    #[test]
+    fn zero_falloff_is_a_hard_edge() {
+        assert_eq!(falloff_weight(4.0, 0.0, 3.99), 1.0);
+        assert_eq!(falloff_weight(4.0, 0.0, 4.0), 0.0);
+    }
+
+    #[test]
+    fn raise_lifts_the_center_and_leaves_the_rest() {
+        let mut heights = flat_grid(9, 9, 10.0);
+        let mut grid = HeightGrid {
+            heights: &mut heights,
+            width: 9,
+            height: 9,
+        };
+        let mut stroke = SculptStroke::new("landscape".to_string());
+
+        let rows = apply_brush(
+            &mut grid,
+            &settings(BrushKind::Raise, 2.0, 0.5, 0.0),
+            (4.0, 4.0),
+            (1.0, 1.0),
+            &mut stroke,
+        );
+
+        assert_eq!(rows, Some((2, 6)));
+        assert_eq!(heights[4 * 9 + 4], 10.0 + 0.5 * BRUSH_HEIGHT_UNITS);
+        // corners are well outside the radius
+        assert_eq!(heights[0], 10.0);
+        assert_eq!(heights[8 * 9 + 8], 10.0);
+        assert_eq!(stroke.original_heights.get(&(4 * 9 + 4)), Some(&10.0));
+    }
+
+    #[test]
+    fn lower_mirrors_raise() {
+        let mut heights = flat_grid(5, 5, 0.0);
+        let mut grid = HeightGrid {
+            heights: &mut heights,
+            width: 5,
+            height: 5,
+        };
+        let mut stroke = SculptStroke::new("landscape".to_string());
+
+        apply_brush(
+            &mut grid,
+            &settings(BrushKind::Lower, 1.0, 1.0, 0.0),
+            (2.0, 2.0),
+            (1.0, 1.0),
+            &mut stroke,
+        );
+
+        assert_eq!(heights[2 * 5 + 2], -BRUSH_HEIGHT_UNITS);
+    }
+
+    #[test]
+    fn flatten_pulls_towards_the_height_under_the_first_dab() {
+        let mut heights: Vec<f32> = (0..25).map(|i| i as f32).collect();
+        let center_height = heights[2 * 5 + 2];
+        let mut grid = HeightGrid {
+            heights: &mut heights,
+            width: 5,
+            height: 5,
+        };
+        let mut stroke = SculptStroke::new("landscape".to_string());
+
+        apply_brush(
+            &mut grid,
+            &settings(BrushKind::Flatten, 1.5, 1.0, 0.0),
+            (2.0, 2.0),
+            (1.0, 1.0),
+            &mut stroke,
+        );
+
+        assert_eq!(stroke.flatten_height, Some(center_height));
+        assert_eq!(heights[1 * 5 + 2], center_height);
+        assert_eq!(heights[3 * 5 + 2], center_height);
+    }
+
+    #[test]
+    fn smooth_flattens_a_spike() {
+        let mut heights = flat_grid(5, 5, 0.0);
+        heights[2 * 5 + 2] = 9.0;
+        let mut grid = HeightGrid {
+            heights: &mut heights,
+            width: 5,
+            height: 5,
+        };
+        let mut stroke = SculptStroke::new("landscape".to_string());
+
+        apply_brush(
+            &mut grid,
+            &settings(BrushKind::Smooth, 0.5, 1.0, 0.0),
+            (2.0, 2.0),
+            (1.0, 1.0),
+            &mut stroke,
+        );
+
+        assert_eq!(heights[2 * 5 + 2], 1.0);
+    }
+
+    #[test]
+    fn recorded_heights_undo_a_stroke() {
+        let original: Vec<f32> = (0..49).map(|i| (i % 7) as f32).collect();
+        let mut heights = original.clone();
+        let mut grid = HeightGrid {
+            heights: &mut heights,
+            width: 7,
+            height: 7,
+        };
+        let mut stroke = SculptStroke::new("landscape".to_string());
+
+        for center in [(2.0, 2.0), (3.0, 3.0), (4.0, 3.5)] {
+            apply_brush(
+                &mut grid,
+                &settings(BrushKind::Noise, 2.0, 1.0, 0.5),
+                center,
+                (1.0, 1.0),
+                &mut stroke,
+            );
+        }
+
+        for (index, height) in stroke.original_heights.iter() {
+            heights[*index] = *height;
+        }
+
+        assert_eq!(heights, original);
+    }
+
+    #[test]
+    fn brush_off_the_grid_changes_nothing() {
+        let mut heights = flat_grid(4, 4, 1.0);
+        let mut grid = HeightGrid {
+            heights: &mut heights,
+            width: 4,
+            height: 4,
+        };
+        let mut stroke = SculptStroke::new("landscape".to_string());
+
+        let rows = apply_brush(
+            &mut grid,
+            &settings(BrushKind::Raise, 1.0, 1.0, 0.0),
+            (-20.0, -20.0),
+            (1.0, 1.0),
+            &mut stroke,
+        );
+
+        assert_eq!(rows, None);
+        assert!(stroke.original_heights.is_empty());
+        assert_eq!(heights, flat_grid(4, 4, 1.0));
+    }
+
+    #[test]
+    fn sample_height_interpolates_between_vertices() {
+        let heights = vec![0.0, 2.0, 4.0, 6.0];
+
+        assert_eq!(sample_height(&heights, 2, 2, 0.5, 0.0), 1.0);
+        assert_eq!(sample_height(&heights, 2, 2, 0.5, 0.5), 3.0);
+        assert_eq!(sample_height(&heights, 2, 2, 5.0, 5.0), 6.0);
+    }
+
+    #[test]
+    fn painting_a_mask_keeps_the_layers_normalized() {
+        let (width, height) = (8, 8);
+        let pixel = |value: u8| [value, value, value, 255];
+        let mut layers: Vec<Vec<u8>> = [153, 51, 51]
+            .iter()
+            .map(|&value| pixel(value).repeat(width * height))
+            .collect();
+        let brush = MaskBrush {
+            layer: 1,
+            radius: 3.0,
+            strength: 0.8,
+            falloff: 0.5,
+        };
+
+        let rect = paint_mask_layers(
+            &mut layers,
+            width as u32,
+            height as u32,
+            &brush,
+            (4.0, 4.0),
+            (1.0, 1.0),
+        );
+
+        assert!(rect.is_some());
+        let center = (4 * width + 4) * 4;
+        assert!(layers[1][center] > 51);
+        for offset in (0..width * height * 4).step_by(4) {
+            let total: u32 = layers.iter().map(|layer| layer[offset] as u32).sum();
+            assert!((254..=256).contains(&total), "layers sum to {}", total);
+        }
+    }
+}
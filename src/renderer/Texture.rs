@@ -1,3 +1,5 @@
+use image::{imageops, imageops::FilterType, RgbaImage};
+
 pub struct Texture {
     pub data: Vec<u8>,
     pub width: u32,
@@ -22,4 +24,50 @@ impl Texture {
             depth_or_array_layers: 1,
         }
     }
+
+    // check that the byte length matches the stated RGBA dimensions before uploading
+    pub fn validate(&self) -> Result<(), String> {
+        if self.width == 0 || self.height == 0 {
+            return Err(format!(
+                "Texture has empty dimensions: {}x{}",
+                self.width, self.height
+            ));
+        }
+
+        let expected_len = (self.width as usize) * (self.height as usize) * 4;
+        if self.data.len() != expected_len {
+            return Err(format!(
+                "Texture data is {} bytes, expected {} for {}x{} RGBA",
+                self.data.len(),
+                expected_len,
+                self.width,
+                self.height
+            ));
+        }
+
+        Ok(())
+    }
+
+    // resample to the target dimensions, returning a copy when the size already matches
+    pub fn resized(&self, width: u32, height: u32) -> Texture {
+        if self.width == width && self.height == height {
+            return Texture {
+                data: self.data.clone(),
+                width,
+                height,
+                format: self.format,
+            };
+        }
+
+        let source = RgbaImage::from_raw(self.width, self.height, self.data.clone())
+            .expect("Couldn't create image from texture data");
+        let resized = imageops::resize(&source, width, height, FilterType::Triangle);
+
+        Texture {
+            data: resized.into_raw(),
+            width,
+            height,
+            format: self.format,
+        }
+    }
 }
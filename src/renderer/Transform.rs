@@ -41,6 +41,14 @@ impl Transform {
         queue.write_buffer(&self.uniform_buffer, 0, bytemuck::cast_slice(&raw_matrix));
     }
 
+    pub fn destroy(&self) {
+        self.uniform_buffer.destroy();
+    }
+
+    pub fn position(&self) -> Vector3<f32> {
+        self.position
+    }
+
     pub fn translate(&mut self, translation: Vector3<f32>) {
         self.position += translation;
     }
@@ -0,0 +1,170 @@
+use bytemuck::{Pod, Zeroable};
+use wgpu::util::DeviceExt;
+
+use crate::contexts::saved::WaterSettings;
+use crate::renderer::core::Vertex;
+use crate::renderer::SimpleCamera::SimpleCamera;
+
+// the plane is centered on the origin and large enough to reach past any landscape
+const WATER_EXTENT: f32 = 8192.0;
+const WATER_SUBDIVISIONS: u32 = 256;
+
+// matches WaterUniforms in water.wgsl, packed into vec4s
+#[repr(C)]
+#[derive(Copy, Clone, Debug)]
+struct WaterUniforms {
+    color: [f32; 4],  // rgb, opacity
+    waves: [f32; 4],  // sea level, wave height, wave speed, wave length
+    params: [f32; 4], // time, shoreline fade distance, camera near, camera far
+}
+
+unsafe impl Pod for WaterUniforms {}
+unsafe impl Zeroable for WaterUniforms {}
+
+pub struct Water {
+    pub settings: WaterSettings,
+    pub vertex_buffer: wgpu::Buffer,
+    pub index_buffer: wgpu::Buffer,
+    pub index_count: u32,
+    pub uniform_buffer: wgpu::Buffer,
+    pub bind_group: wgpu::BindGroup,
+}
+
+impl Water {
+    pub fn new(
+        device: &wgpu::Device,
+        bind_group_layout: &wgpu::BindGroupLayout,
+        depth_view: &wgpu::TextureView,
+        settings: WaterSettings,
+    ) -> Self {
+        let (vertices, indices) = Self::generate_plane(WATER_EXTENT, WATER_SUBDIVISIONS);
+
+        let vertex_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Water Vertex Buffer"),
+            contents: bytemuck::cast_slice(&vertices),
+            usage: wgpu::BufferUsages::VERTEX,
+        });
+
+        let index_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Water Index Buffer"),
+            contents: bytemuck::cast_slice(&indices),
+            usage: wgpu::BufferUsages::INDEX,
+        });
+
+        let uniform_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Water Uniform Buffer"),
+            size: std::mem::size_of::<WaterUniforms>() as wgpu::BufferAddress,
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+
+        let bind_group =
+            Self::create_bind_group(device, bind_group_layout, &uniform_buffer, depth_view);
+
+        Self {
+            settings,
+            vertex_buffer,
+            index_buffer,
+            index_count: indices.len() as u32,
+            uniform_buffer,
+            bind_group,
+        }
+    }
+
+    pub fn create_bind_group(
+        device: &wgpu::Device,
+        bind_group_layout: &wgpu::BindGroupLayout,
+        uniform_buffer: &wgpu::Buffer,
+        depth_view: &wgpu::TextureView,
+    ) -> wgpu::BindGroup {
+        device.create_bind_group(&wgpu::BindGroupDescriptor {
+            layout: bind_group_layout,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: uniform_buffer.as_entire_binding(),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: wgpu::BindingResource::TextureView(depth_view),
+                },
+            ],
+            label: Some("water_bind_group"),
+        })
+    }
+
+    // the scene depth is read for the shoreline fade, so the bind group follows the depth texture
+    pub fn rebind_depth(
+        &mut self,
+        device: &wgpu::Device,
+        bind_group_layout: &wgpu::BindGroupLayout,
+        depth_view: &wgpu::TextureView,
+    ) {
+        self.bind_group =
+            Self::create_bind_group(device, bind_group_layout, &self.uniform_buffer, depth_view);
+    }
+
+    pub fn update_uniform_buffer(&self, queue: &wgpu::Queue, camera: &SimpleCamera, time: f32) {
+        let settings = &self.settings;
+        let uniforms = WaterUniforms {
+            color: [
+                settings.color[0],
+                settings.color[1],
+                settings.color[2],
+                settings.opacity,
+            ],
+            waves: [
+                settings.sea_level,
+                settings.wave_height,
+                settings.wave_speed,
+                settings.wave_length.max(0.01),
+            ],
+            params: [
+                time,
+                settings.shoreline_fade.max(0.01),
+                camera.znear,
+                camera.zfar,
+            ],
+        };
+
+        queue.write_buffer(&self.uniform_buffer, 0, bytemuck::bytes_of(&uniforms));
+    }
+
+    // flat grid at y = 0, the shader lifts it to sea level and adds waves
+    fn generate_plane(extent: f32, subdivisions: u32) -> (Vec<Vertex>, Vec<u32>) {
+        let mut vertices = Vec::with_capacity(((subdivisions + 1) * (subdivisions + 1)) as usize);
+        let mut indices = Vec::with_capacity((subdivisions * subdivisions * 6) as usize);
+
+        let half = extent / 2.0;
+        let step = extent / subdivisions as f32;
+
+        for z in 0..=subdivisions {
+            for x in 0..=subdivisions {
+                vertices.push(Vertex {
+                    position: [-half + x as f32 * step, 0.0, -half + z as f32 * step],
+                    normal: [0.0, 1.0, 0.0],
+                    tex_coords: [
+                        x as f32 / subdivisions as f32,
+                        z as f32 / subdivisions as f32,
+                    ],
+                    color: [1.0, 1.0, 1.0],
+                });
+            }
+        }
+
+        let row = subdivisions + 1;
+        for z in 0..subdivisions {
+            for x in 0..subdivisions {
+                let top_left = z * row + x;
+                let top_right = top_left + 1;
+                let bottom_left = top_left + row;
+                let bottom_right = bottom_left + 1;
+
+                indices.extend_from_slice(&[top_left, bottom_left, top_right]);
+                indices.extend_from_slice(&[top_right, bottom_left, bottom_right]);
+            }
+        }
+
+        (vertices, indices)
+    }
+}
@@ -12,8 +12,11 @@ use winit::{
     window::{Window, WindowBuilder},
 };
 
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
 use bytemuck::{Pod, Zeroable};
-use std::cell::RefCell;
+use std::cell::{Cell, RefCell};
+use std::collections::HashMap;
 use std::rc::Rc;
 use wasm_bindgen::prelude::*;
 use wasm_bindgen::JsCast;
@@ -24,27 +27,29 @@ use gltf::buffer::{Source, View};
 use gltf::Glb;
 use gltf::Gltf;
 use std::ops::{Deref, DerefMut};
-use std::sync::atomic::{AtomicBool, Ordering};
-use std::sync::Mutex;
 use std::sync::{Arc, RwLock, RwLockReadGuard, RwLockWriteGuard};
 use wasm_bindgen_futures::future_to_promise;
 
+use crate::renderer::Foliage::{scatter, Foliage, InstanceRaw};
 use crate::renderer::Grid::Grid;
 use crate::renderer::Landscape::Landscape;
 use crate::renderer::Model::{Mesh, Model};
 use crate::renderer::SimpleCamera::SimpleCamera;
+use crate::renderer::Sky::Sky;
+use crate::renderer::TerrainBrush::{BrushKind, BrushSettings, MaskBrush, SculptStroke};
 use crate::renderer::Texture::Texture;
-use crate::{contexts::saved::LandscapeTextureKinds, renderer::shapes::Pyramid::Pyramid};
+use crate::renderer::Water::Water;
+use crate::{
+    contexts::saved::{
+        ComponentData, ComponentKind, FoliageInstance, FoliageProperties, LandscapeTextureKinds,
+        LevelData, SavedState, SkySettings, WaterSettings,
+    },
+    invoke::{invoke, invoke_error, try_invoke},
+    renderer::shapes::Pyramid::Pyramid,
+};
 
 use super::shapes::Cube::Cube;
 
-// TODO: test this separate invoke
-#[wasm_bindgen]
-extern "C" {
-    #[wasm_bindgen(js_namespace = ["window", "__TAURI__", "tauri"])]
-    pub async fn invoke(cmd: &str, args: JsValue) -> JsValue;
-}
-
 #[derive(Serialize)]
 pub struct ReadModelParams {
     pub projectId: String,
@@ -74,6 +79,25 @@ pub struct GetMaskParams {
     pub maskKind: String,
 }
 
+#[derive(Serialize)]
+pub struct SaveHeightmapParams {
+    pub projectId: String,
+    pub landscapeAssetId: String,
+    pub landscapeFilename: String,
+    pub width: usize,
+    pub height: usize,
+    pub heights: Vec<f32>,
+}
+
+#[derive(Serialize)]
+pub struct SaveMaskParams {
+    pub projectId: String,
+    pub landscapeId: String,
+    pub maskFilename: String,
+    pub maskKind: String,
+    pub maskBase64: String,
+}
+
 #[repr(C)]
 #[derive(Copy, Clone, Debug)]
 pub struct Vertex {
@@ -100,59 +124,114 @@ impl Vertex {
     }
 }
 
-static mut CAMERA: Option<SimpleCamera> = None;
+// console logging that also works outside the browser, for the headless renderer
+pub fn console_log(message: &str) {
+    #[cfg(target_arch = "wasm32")]
+    web_sys::console::log_1(&message.into());
+    #[cfg(not(target_arch = "wasm32"))]
+    println!("{}", message);
+}
 
-thread_local! {
-    static CAMERA_INIT: std::cell::Cell<bool> = std::cell::Cell::new(false);
+pub fn console_error(message: &str) {
+    #[cfg(target_arch = "wasm32")]
+    web_sys::console::error_1(&message.into());
+    #[cfg(not(target_arch = "wasm32"))]
+    eprintln!("{}", message);
 }
 
-pub fn get_camera() -> &'static mut SimpleCamera {
-    CAMERA_INIT.with(|init| {
-        if !init.get() {
-            unsafe {
-                CAMERA = Some(SimpleCamera::new(
-                    Point3::new(0.0, 1.0, 5.0),
-                    Vector3::new(0.0, 0.0, -1.0),
-                    Vector3::new(0.0, 1.0, 0.0),
-                    45.0f32.to_radians(),
-                    0.1,
-                    10000.0,
-                ));
-            }
-            init.set(true);
-        }
-    });
+// seconds since midnight, drives animated surfaces like water
+fn time_of_day() -> f32 {
+    #[cfg(target_arch = "wasm32")]
+    let now = js_sys::Date::now() / 1000.0;
+    #[cfg(not(target_arch = "wasm32"))]
+    let now = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map_or(0.0, |elapsed| elapsed.as_secs_f64());
 
-    unsafe { CAMERA.as_mut().unwrap() }
+    (now % 86400.0) as f32
+}
+
+pub(crate) fn default_camera() -> SimpleCamera {
+    SimpleCamera::new(
+        Point3::new(0.0, 1.0, 5.0),
+        Vector3::new(0.0, 0.0, -1.0),
+        Vector3::new(0.0, 1.0, 0.0),
+        45.0f32.to_radians(),
+        0.1,
+        10000.0,
+    )
 }
 
 // struct RendererState<'a> {
 
 // #[derive(std::ops::DerefMut)]
-struct RendererState {
+pub(crate) struct RendererState {
     cubes: Vec<Cube>,
     pyramids: Vec<Pyramid>,
     grids: Vec<Grid>,
     models: Vec<Model>,
     landscapes: Vec<Landscape>,
+    foliage: Vec<Foliage>,
+    water: Option<Water>,
+    sky: Sky,
 
     device: Arc<wgpu::Device>,
     queue: Arc<wgpu::Queue>,
     model_bind_group_layout: Arc<wgpu::BindGroupLayout>,
     texture_bind_group_layout: Arc<wgpu::BindGroupLayout>,
+    water_bind_group_layout: Arc<wgpu::BindGroupLayout>,
     texture_render_mode_buffer: Arc<wgpu::Buffer>,
     color_render_mode_buffer: Arc<wgpu::Buffer>,
+    depth_view: Arc<wgpu::TextureView>,
+
+    terrain_brush: Option<BrushSettings>,
+    active_stroke: Option<SculptStroke>,
+    sculpt_undo: Vec<SculptStroke>,
+    sculpt_redo: Vec<SculptStroke>,
+
+    mask_brush: Option<MaskBrush>,
+    painting_landscape_id: Option<String>,
+
+    // what the scene currently mirrors, see Renderer::sync_level
+    synced_level: Option<(String, Option<String>)>,
+    synced_components: HashMap<String, SyncedComponent>,
+    next_load_id: u32,
+}
+
+// a component as it was when the scene last synced to it
+pub(crate) struct SyncedComponent {
+    data: ComponentData,
+    // assets that finish loading for an older load id are dropped
+    load_id: u32,
+    // the textures resolved for it at that sync, read by landscapes once they finish loading
+    layers: Vec<LandscapeTextureLayer>,
 }
 
+// one textured layer of a landscape component
+#[derive(Clone)]
+struct LandscapeTextureLayer {
+    kind: String,
+    texture_id: String,
+    texture_filename: String,
+    // a layer can be textured before its mask is painted
+    mask_filename: Option<String>,
+}
+
+// number of sculpt strokes kept for undo
+const SCULPT_HISTORY_LIMIT: usize = 32;
+
 // impl<'a> RendererState<'a> {
 impl RendererState {
-    async fn new(
+    fn new(
         device: Arc<wgpu::Device>,
         queue: Arc<wgpu::Queue>,
         model_bind_group_layout: Arc<wgpu::BindGroupLayout>,
         texture_bind_group_layout: Arc<wgpu::BindGroupLayout>,
+        water_bind_group_layout: Arc<wgpu::BindGroupLayout>,
+        environment_bind_group_layout: &wgpu::BindGroupLayout,
         texture_render_mode_buffer: Arc<wgpu::Buffer>,
         color_render_mode_buffer: Arc<wgpu::Buffer>,
+        depth_view: Arc<wgpu::TextureView>,
     ) -> Self {
         // create the utility grid(s)
         let mut grids = Vec::new();
@@ -174,24 +253,116 @@ impl RendererState {
 
         let mut landscapes = Vec::new();
 
+        let mut foliage = Vec::new();
+
+        let sky = Sky::new(
+            &device,
+            environment_bind_group_layout,
+            SkySettings::default(),
+        );
+
         Self {
             cubes,
             pyramids,
             grids,
             models,
             landscapes,
+            foliage,
+            water: None,
+            sky,
 
             device,
             queue,
             model_bind_group_layout,
             texture_bind_group_layout,
+            water_bind_group_layout,
             texture_render_mode_buffer,
             color_render_mode_buffer,
+            depth_view,
+
+            terrain_brush: None,
+            active_stroke: None,
+            sculpt_undo: Vec::new(),
+            sculpt_redo: Vec::new(),
+
+            mask_brush: None,
+            painting_landscape_id: None,
+
+            synced_level: None,
+            synced_components: HashMap::new(),
+            next_load_id: 0,
         }
     }
 
-    async fn add_model(&mut self, bytes: &Vec<u8>) {
-        let model = Model::from_glb(
+    // drop every component from the scene, keeping the helpers, water and sky
+    pub(crate) fn clear_scene(&mut self) {
+        for model in self.models.drain(..) {
+            model.destroy();
+        }
+        for landscape in self.landscapes.drain(..) {
+            landscape.destroy();
+        }
+        for foliage in self.foliage.drain(..) {
+            foliage.destroy();
+        }
+
+        // strokes and paint refer to landscapes that no longer exist
+        self.active_stroke = None;
+        self.sculpt_undo.clear();
+        self.sculpt_redo.clear();
+        self.painting_landscape_id = None;
+
+        self.synced_components.clear();
+    }
+
+    // drop a single component, along with any sculpt or paint state that points at it
+    pub(crate) fn remove_component(&mut self, component_id: &String) {
+        self.models.retain(|model| {
+            let removed = &model.id == component_id;
+            if removed {
+                model.destroy();
+            }
+            !removed
+        });
+        self.landscapes.retain(|landscape| {
+            let removed = &landscape.id == component_id;
+            if removed {
+                landscape.destroy();
+            }
+            !removed
+        });
+        self.foliage.retain(|foliage| {
+            let removed = &foliage.id == component_id;
+            if removed {
+                foliage.destroy();
+            }
+            !removed
+        });
+
+        if self.painting_landscape_id.as_ref() == Some(component_id) {
+            self.painting_landscape_id = None;
+        }
+        if self
+            .active_stroke
+            .as_ref()
+            .map_or(false, |stroke| &stroke.landscape_id == component_id)
+        {
+            self.active_stroke = None;
+        }
+        self.sculpt_undo
+            .retain(|stroke| &stroke.landscape_id != component_id);
+        self.sculpt_redo
+            .retain(|stroke| &stroke.landscape_id != component_id);
+
+        self.synced_components.remove(component_id);
+    }
+
+    pub(crate) fn add_model(
+        &mut self,
+        model_component_id: &String,
+        bytes: &Vec<u8>,
+    ) -> (Point3<f32>, Point3<f32>) {
+        let mut model = Model::from_glb(
             bytes,
             &self.device,
             &self.queue,
@@ -199,13 +370,110 @@ impl RendererState {
             &self.texture_bind_group_layout,
             &self.texture_render_mode_buffer,
             &self.color_render_mode_buffer,
-        )
-        .await;
+        );
+        model.id = model_component_id.to_owned();
+        let bounds = model.bounds;
+
+        // a component is only ever drawn once, even if it was loaded twice
+        if let Some(index) = self.models.iter().position(|m| &m.id == model_component_id) {
+            self.models.remove(index).destroy();
+        }
 
         self.models.push(model);
+
+        bounds
+    }
+
+    pub(crate) fn add_foliage(&mut self, foliage_component_id: &String, bytes: &Vec<u8>) {
+        // scattering and syncing can both load the same foliage, its instances are set separately
+        if self.foliage.iter().any(|f| &f.id == foliage_component_id) {
+            return;
+        }
+
+        let model = Model::from_glb(
+            bytes,
+            &self.device,
+            &self.queue,
+            &self.model_bind_group_layout,
+            &self.texture_bind_group_layout,
+            &self.texture_render_mode_buffer,
+            &self.color_render_mode_buffer,
+        );
+
+        self.foliage
+            .push(Foliage::new(foliage_component_id.to_owned(), model));
+    }
+
+    pub fn set_foliage_instances(
+        &mut self,
+        foliage_component_id: &String,
+        instances: &[FoliageInstance],
+    ) {
+        if let Some(foliage) = self
+            .foliage
+            .iter_mut()
+            .find(|f| &f.id == foliage_component_id)
+        {
+            foliage.set_instances(&self.device, instances);
+        }
+    }
+
+    // scatter over the target landscape and upload the result, returning it for saving
+    pub fn scatter_foliage(
+        &mut self,
+        foliage_component_id: &String,
+        settings: &FoliageProperties,
+    ) -> Option<Vec<FoliageInstance>> {
+        let landscape = self
+            .landscapes
+            .iter()
+            .find(|l| l.id == settings.landscape_component_id)?;
+
+        let instances = scatter(landscape, settings);
+        self.set_foliage_instances(foliage_component_id, &instances);
+
+        Some(instances)
+    }
+
+    // drop the editor grid and placeholder cube, headless renders only show what was added
+    pub(crate) fn clear_helpers(&mut self) {
+        self.grids.clear();
+        self.cubes.clear();
+    }
+
+    pub(crate) fn set_sky(&mut self, settings: SkySettings) {
+        self.sky.settings = settings;
     }
 
-    fn add_landscape(&mut self, landscapeComponentId: &String, data: &LandscapeData) {
+    // create the level's water plane, or update it in place if it already exists
+    pub fn set_water(&mut self, settings: WaterSettings) {
+        match self.water.as_mut() {
+            Some(water) => water.settings = settings,
+            None => {
+                self.water = Some(Water::new(
+                    &self.device,
+                    &self.water_bind_group_layout,
+                    &self.depth_view,
+                    settings,
+                ));
+            }
+        }
+    }
+
+    // the depth texture follows the canvas size, and water samples it for the shoreline fade
+    pub(crate) fn resize_depth(&mut self, width: u32, height: u32) {
+        self.depth_view = create_depth_view(&self.device, width, height);
+
+        if let Some(water) = self.water.as_mut() {
+            water.rebind_depth(
+                &self.device,
+                &self.water_bind_group_layout,
+                &self.depth_view,
+            );
+        }
+    }
+
+    pub(crate) fn add_landscape(&mut self, landscapeComponentId: &String, data: &LandscapeData) {
         let landscape = Landscape::new(
             landscapeComponentId,
             data,
@@ -217,16 +485,27 @@ impl RendererState {
             &self.color_render_mode_buffer,
         );
 
+        if let Some(index) = self
+            .landscapes
+            .iter()
+            .position(|l| &l.id == landscapeComponentId)
+        {
+            self.landscapes.remove(index).destroy();
+        }
+
         self.landscapes.push(landscape);
     }
 
+    pub(crate) fn landscape(&self, landscape_id: &str) -> Option<&Landscape> {
+        self.landscapes.iter().find(|l| l.id == landscape_id)
+    }
+
     pub fn update_landscape_texture(
         &mut self,
         landscape_id: String,
         kind: LandscapeTextureKinds,
         texture: Texture,
-        maskKind: LandscapeTextureKinds,
-        mask: Texture,
+        mask: Option<(LandscapeTextureKinds, Texture)>,
     ) {
         if let Some(landscape) = self.landscapes.iter_mut().find(|l| l.id == landscape_id) {
             landscape.update_texture(
@@ -238,64 +517,191 @@ impl RendererState {
                 kind,
                 &texture,
             );
-            landscape.update_texture(
-                &self.device,
-                &self.queue,
-                &self.texture_bind_group_layout,
-                &self.texture_render_mode_buffer,
-                &self.color_render_mode_buffer,
-                maskKind,
-                &mask,
-            );
+
+            if let Some((mask_kind, mask)) = mask {
+                landscape.update_texture(
+                    &self.device,
+                    &self.queue,
+                    &self.texture_bind_group_layout,
+                    &self.texture_render_mode_buffer,
+                    &self.color_render_mode_buffer,
+                    mask_kind,
+                    &mask,
+                );
+            }
         }
     }
-}
 
-static RENDERING_PAUSED: AtomicBool = AtomicBool::new(false);
+    // closest landscape hit by the ray, as (landscape index, hit point)
+    fn raycast_landscapes(
+        &self,
+        origin: Point3<f32>,
+        direction: Vector3<f32>,
+    ) -> Option<(usize, Point3<f32>)> {
+        self.landscapes
+            .iter()
+            .enumerate()
+            .filter_map(|(i, landscape)| landscape.raycast(origin, direction).map(|hit| (i, hit)))
+            .min_by(|a, b| {
+                let da = (a.1 - origin).norm();
+                let db = (b.1 - origin).norm();
+                da.partial_cmp(&db).unwrap_or(std::cmp::Ordering::Equal)
+            })
+    }
 
-// Pause rendering
-fn pause_rendering() {
-    RENDERING_PAUSED.store(true, Ordering::SeqCst);
-}
+    pub fn begin_sculpt(&mut self, origin: Point3<f32>, direction: Vector3<f32>) {
+        if self.terrain_brush.is_none() {
+            return;
+        }
 
-// Resume rendering
-fn resume_rendering() {
-    RENDERING_PAUSED.store(false, Ordering::SeqCst);
-}
+        if let Some((index, _)) = self.raycast_landscapes(origin, direction) {
+            let landscape_id = self.landscapes[index].id.clone();
+            self.active_stroke = Some(SculptStroke::new(landscape_id));
+            self.sculpt(origin, direction);
+        }
+    }
 
-// Check if rendering is paused
-fn is_rendering_paused() -> bool {
-    RENDERING_PAUSED.load(Ordering::SeqCst)
-}
+    pub fn sculpt(&mut self, origin: Point3<f32>, direction: Vector3<f32>) {
+        let settings = match self.terrain_brush {
+            Some(settings) => settings,
+            None => return,
+        };
+
+        if let Some(stroke) = self.active_stroke.as_mut() {
+            if let Some(landscape) = self
+                .landscapes
+                .iter_mut()
+                .find(|l| l.id == stroke.landscape_id)
+            {
+                if let Some(hit) = landscape.raycast(origin, direction) {
+                    landscape.sculpt(&self.queue, &settings, &hit, stroke);
+                }
+            }
+        }
+    }
+
+    pub fn end_sculpt(&mut self) {
+        if let Some(stroke) = self.active_stroke.take() {
+            if stroke.original_heights.is_empty() {
+                return;
+            }
 
-// mutex approach
+            self.sculpt_undo.push(stroke);
+            if self.sculpt_undo.len() > SCULPT_HISTORY_LIMIT {
+                self.sculpt_undo.remove(0);
+            }
+            self.sculpt_redo.clear();
+        }
+    }
 
-// Global mutable static variable for RendererState protected by a Mutex
-static mut RENDERER_STATE: Option<Mutex<RendererState>> = None;
+    // swap a stroke's stored heights into its landscape, returning the inverse stroke
+    fn apply_stroke_heights(&mut self, stroke: SculptStroke) -> Option<SculptStroke> {
+        let landscape = self
+            .landscapes
+            .iter_mut()
+            .find(|l| l.id == stroke.landscape_id)?;
 
-thread_local! {
-    static RENDERER_STATE_INIT: std::cell::Cell<bool> = std::cell::Cell::new(false);
-}
+        let replaced = landscape.restore_heights(&self.queue, &stroke.original_heights);
 
-// Function to initialize the RendererState
-fn initialize_renderer_state(state: RendererState) {
-    unsafe {
-        RENDERER_STATE = Some(Mutex::new(state));
+        Some(SculptStroke {
+            landscape_id: stroke.landscape_id,
+            original_heights: replaced,
+            flatten_height: None,
+        })
     }
-    RENDERER_STATE_INIT.with(|init| {
-        init.set(true);
-    });
+
+    pub fn begin_paint(&mut self, origin: Point3<f32>, direction: Vector3<f32>) {
+        if self.mask_brush.is_none() {
+            return;
+        }
+
+        if let Some((index, _)) = self.raycast_landscapes(origin, direction) {
+            self.painting_landscape_id = Some(self.landscapes[index].id.clone());
+            self.paint(origin, direction);
+        }
+    }
+
+    pub fn paint(&mut self, origin: Point3<f32>, direction: Vector3<f32>) {
+        let brush = match self.mask_brush {
+            Some(brush) => brush,
+            None => return,
+        };
+
+        if let Some(landscape_id) = &self.painting_landscape_id {
+            if let Some(landscape) = self.landscapes.iter_mut().find(|l| &l.id == landscape_id) {
+                if let Some(hit) = landscape.raycast(origin, direction) {
+                    landscape.paint_mask(&self.queue, &brush, &hit);
+                }
+            }
+        }
+    }
+
+    pub fn end_paint(&mut self) {
+        self.painting_landscape_id = None;
+    }
+
+    pub fn undo_sculpt(&mut self) {
+        if let Some(stroke) = self.sculpt_undo.pop() {
+            if let Some(inverse) = self.apply_stroke_heights(stroke) {
+                self.sculpt_redo.push(inverse);
+            }
+        }
+    }
+
+    pub fn redo_sculpt(&mut self) {
+        if let Some(stroke) = self.sculpt_redo.pop() {
+            if let Some(inverse) = self.apply_stroke_heights(stroke) {
+                self.sculpt_undo.push(inverse);
+            }
+        }
+    }
+}
+
+// css size of the canvas scaled by the device pixel ratio
+fn canvas_physical_size(canvas: &HtmlCanvasElement) -> (u32, u32) {
+    let ratio = window().map_or(1.0, |window| window.device_pixel_ratio());
+
+    (
+        (canvas.client_width() as f64 * ratio).round() as u32,
+        (canvas.client_height() as f64 * ratio).round() as u32,
+    )
 }
 
-// Function to get a mutable reference to the RendererState
-pub fn get_renderer_state() -> &'static Mutex<RendererState> {
-    RENDERER_STATE_INIT.with(|init| {
-        if !init.get() {
-            panic!("RendererState not initialized");
+// records the physical canvas size whenever it changes, applied before the next frame
+fn observe_canvas_resize(canvas: &HtmlCanvasElement, pending_resize: Rc<Cell<Option<(u32, u32)>>>) {
+    let observed = canvas.clone();
+    let callback = Closure::wrap(Box::new(move || {
+        let (width, height) = canvas_physical_size(&observed);
+
+        // a hidden or collapsed canvas reports zero, keep the last size until it comes back
+        if width > 0 && height > 0 {
+            pending_resize.set(Some((width, height)));
         }
+    }) as Box<dyn FnMut()>)
+    .into_js_value();
+
+    let observer = web_sys::ResizeObserver::new(callback.unchecked_ref())
+        .expect("Couldn't create ResizeObserver");
+    observer.observe(canvas);
+}
+
+fn create_depth_view(device: &wgpu::Device, width: u32, height: u32) -> Arc<wgpu::TextureView> {
+    let depth_texture = device.create_texture(&wgpu::TextureDescriptor {
+        size: wgpu::Extent3d {
+            width,
+            height,
+            depth_or_array_layers: 1,
+        },
+        mip_level_count: 1,
+        sample_count: 1,
+        dimension: wgpu::TextureDimension::D2,
+        format: wgpu::TextureFormat::Depth24Plus,
+        usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::TEXTURE_BINDING,
+        label: Some("Depth Texture"),
+        view_formats: &[],
     });
 
-    unsafe { RENDERER_STATE.as_ref().unwrap() }
+    Arc::new(depth_texture.create_view(&wgpu::TextureViewDescriptor::default()))
 }
 
 // native rendering loop
@@ -348,12 +754,54 @@ pub fn get_renderer_state() -> &'static Mutex<RendererState> {
 
 // Your other imports...
 
+// owns the gpu, pipelines, scene and camera for one canvas, cloning shares the same renderer
+#[wasm_bindgen]
+#[derive(Clone)]
+pub struct Renderer {
+    inner: Rc<RendererInner>,
+}
+
+struct RendererInner {
+    device: Arc<wgpu::Device>,
+    queue: Arc<wgpu::Queue>,
+    canvas: HtmlCanvasElement,
+    surface: wgpu::Surface<'static>,
+    config: RefCell<wgpu::SurfaceConfiguration>,
+    pending_resize: Rc<Cell<Option<(u32, u32)>>>,
+    device_pixel_ratio: Cell<f64>,
+
+    context: RenderContext,
+
+    state: RefCell<RendererState>,
+    camera: RefCell<SimpleCamera>,
+
+    // the frame loop holds a clone of the renderer, so it's kept here to be broken by stop()
+    frame_callback: Rc<RefCell<Option<Closure<dyn FnMut()>>>>,
+    frame_request: Rc<Cell<Option<i32>>>,
+}
+
+// pipelines and camera binding shared by the canvas and headless renderers
+pub(crate) struct RenderContext {
+    render_pipeline: wgpu::RenderPipeline,
+    instanced_render_pipeline: wgpu::RenderPipeline,
+    water_render_pipeline: wgpu::RenderPipeline,
+    sky_render_pipeline: wgpu::RenderPipeline,
+    camera_bind_group: wgpu::BindGroup,
+    camera_uniform_buffer: wgpu::Buffer,
+}
+
+impl PartialEq for Renderer {
+    fn eq(&self, other: &Self) -> bool {
+        Rc::ptr_eq(&self.inner, &other.inner)
+    }
+}
+
 #[wasm_bindgen]
-pub async fn start_render_loop() {
+pub async fn start_render_loop(canvas_id: String) -> Renderer {
     let window = web_sys::window().unwrap();
     let document = window.document().unwrap();
     let canvas = document
-        .get_element_by_id("scene-canvas")
+        .get_element_by_id(&canvas_id)
         .unwrap()
         .dyn_into::<HtmlCanvasElement>()
         .unwrap();
@@ -376,8 +824,15 @@ pub async fn start_render_loop() {
         gles_minor_version: wgpu::Gles3MinorVersion::Version2,
     });
 
-    let height = canvas.height();
-    let width = canvas.width();
+    // size the drawing buffer in physical pixels so high dpi screens stay sharp
+    let (width, height) = match canvas_physical_size(&canvas) {
+        (0, _) | (_, 0) => (canvas.width(), canvas.height()),
+        size => size,
+    };
+    canvas.set_width(width);
+    canvas.set_height(height);
+
+    let observed_canvas = canvas.clone();
 
     let event_loop = event_loop::EventLoop::new().unwrap();
     let builder = WindowBuilder::new().with_inner_size(LogicalSize::new(width, height));
@@ -388,6 +843,12 @@ pub async fn start_render_loop() {
     };
     let winit_window = builder.build(&event_loop).unwrap();
 
+    // winit pins the canvas css size, leave layout to the stylesheet so the canvas can resize
+    observed_canvas.remove_attribute("style").ok();
+
+    let pending_resize = Rc::new(Cell::new(None));
+    observe_canvas_resize(&observed_canvas, pending_resize.clone());
+
     let surface = unsafe {
         instance
             .create_surface(winit_window)
@@ -423,66 +884,529 @@ pub async fn start_render_loop() {
     let swapchain_capabilities = surface.get_capabilities(&adapter);
     let swap_chain_format = swapchain_capabilities.formats[0]; // Choosing the first available format
 
-    let mut config = surface.get_default_config(&adapter, width, height).unwrap();
+    let config = surface.get_default_config(&adapter, width, height).unwrap();
     surface.configure(&device, &config);
 
-    // Create the shader module
-    let vertex_shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
-        label: Some("Vertex Shader"),
-        source: wgpu::ShaderSource::Wgsl(include_str!("./shaders/primary_vertex.wgsl").into()),
-    });
-
-    let fragment_shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
-        label: Some("Fragment Shader"),
-        source: wgpu::ShaderSource::Wgsl(include_str!("./shaders/primary_fragment.wgsl").into()),
-    });
-
-    let camera = get_camera();
+    let mut camera = default_camera();
 
     camera.update_aspect_ratio(config.width as f32 / config.height as f32);
     camera.update_view_projection_matrix();
 
-    let camera_matrix = camera.view_projection_matrix;
-    let camera_uniform_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
-        label: Some("Camera Uniform Buffer"),
-        contents: bytemuck::cast_slice(camera_matrix.as_slice()),
-        usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
-    });
+    let (context, state) = create_scene(
+        &device,
+        &queue,
+        swap_chain_format,
+        config.width,
+        config.height,
+        &camera,
+    );
+
+    let renderer = Renderer {
+        inner: Rc::new(RendererInner {
+            device,
+            queue,
+            canvas: observed_canvas,
+            surface,
+            config: RefCell::new(config),
+            pending_resize,
+            device_pixel_ratio: Cell::new(window.device_pixel_ratio()),
 
-    // Create the bind group for the uniform buffer
-    let camera_bind_group_layout =
-        device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
-            label: Some("Bind Group Layout"),
-            entries: &[wgpu::BindGroupLayoutEntry {
-                binding: 0,
-                visibility: wgpu::ShaderStages::VERTEX,
-                ty: wgpu::BindingType::Buffer {
-                    ty: wgpu::BufferBindingType::Uniform,
-                    has_dynamic_offset: false,
-                    min_binding_size: None,
-                },
-                count: None,
-            }],
-        });
+            context,
 
-    let model_bind_group_layout =
-        device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
-            entries: &[wgpu::BindGroupLayoutEntry {
-                binding: 0,
-                visibility: wgpu::ShaderStages::VERTEX,
-                ty: wgpu::BindingType::Buffer {
-                    ty: wgpu::BufferBindingType::Uniform,
-                    has_dynamic_offset: false,
-                    min_binding_size: None,
-                },
-                count: None,
-            }],
-            label: Some("model_bind_group_layout"),
-        });
+            state: RefCell::new(state),
+            camera: RefCell::new(camera),
 
-    let model_bind_group_layout = Arc::new(model_bind_group_layout);
+            frame_callback: Rc::new(RefCell::new(None)),
+            frame_request: Rc::new(Cell::new(None)),
+        }),
+    };
 
-    let texture_bind_group_layout =
+    // web-based rendering loop
+    let f = renderer.inner.frame_callback.clone();
+    let frame_request = renderer.inner.frame_request.clone();
+
+    let frame_renderer = renderer.clone();
+    let closure = Closure::wrap(Box::new(move || {
+        frame_renderer.frame();
+
+        // Schedule the next frame, unless the loop was stopped during this one
+        if let Some(f) = f.borrow().as_ref() {
+            frame_request.set(Some(request_animation_frame(f)));
+        }
+    }) as Box<dyn FnMut()>);
+
+    *renderer.inner.frame_callback.borrow_mut() = Some(closure);
+
+    // Start the rendering loop
+    let request = request_animation_frame(renderer.inner.frame_callback.borrow().as_ref().unwrap());
+    renderer.inner.frame_request.set(Some(request));
+
+    renderer
+}
+
+impl Renderer {
+    // cancel the pending frame and drop the loop's closure, after this the renderer no longer draws
+    pub fn stop(&self) {
+        if let Some(request) = self.inner.frame_request.take() {
+            if let Some(window) = window() {
+                window.cancel_animation_frame(request).ok();
+            }
+        }
+
+        self.inner.frame_callback.borrow_mut().take();
+    }
+
+    // bring the scene in line with a level, loading and unloading only what changed
+    pub fn sync_level(
+        &self,
+        project_id: String,
+        saved_state: &SavedState,
+        level: Option<&LevelData>,
+    ) {
+        let level_key = (project_id.clone(), level.map(|level| level.id.clone()));
+        let components = level
+            .and_then(|level| level.components.clone())
+            .unwrap_or_default();
+
+        let mut loads = Vec::new();
+        let mut texture_loads = Vec::new();
+
+        {
+            let mut state_guard = self.inner.state.borrow_mut();
+
+            if state_guard.synced_level.as_ref() != Some(&level_key) {
+                state_guard.clear_scene();
+                state_guard.synced_level = Some(level_key);
+            }
+
+            // components that left the level or now point at a different asset
+            let stale: Vec<String> = state_guard
+                .synced_components
+                .iter()
+                .filter(|(id, synced)| {
+                    !components.iter().any(|component| {
+                        &component.id == *id
+                            && component.kind == synced.data.kind
+                            && component.asset_id == synced.data.asset_id
+                    })
+                })
+                .map(|(id, _)| id.clone())
+                .collect();
+
+            for component_id in stale {
+                state_guard.remove_component(&component_id);
+            }
+
+            for component in components.iter() {
+                let previous = match state_guard.synced_components.get(&component.id) {
+                    Some(synced) if &synced.data == component => continue,
+                    Some(synced) => synced.data.clone(),
+                    None => {
+                        state_guard.next_load_id = state_guard.next_load_id.wrapping_add(1);
+                        let load_id = state_guard.next_load_id;
+
+                        state_guard.synced_components.insert(
+                            component.id.clone(),
+                            SyncedComponent {
+                                data: component.clone(),
+                                load_id,
+                                layers: landscape_texture_layers(saved_state, component),
+                            },
+                        );
+                        loads.push((component.clone(), load_id));
+                        continue;
+                    }
+                };
+
+                let layers = landscape_texture_layers(saved_state, component);
+
+                if let Some(synced) = state_guard.synced_components.get_mut(&component.id) {
+                    synced.data = component.clone();
+                    synced.layers = layers.clone();
+                }
+
+                let instances = component
+                    .foliage_properties
+                    .as_ref()
+                    .map(|foliage| &foliage.instances);
+                if instances != previous.foliage_properties.as_ref().map(|f| &f.instances) {
+                    state_guard.set_foliage_instances(
+                        &component.id,
+                        instances.map(|i| i.as_slice()).unwrap_or_default(),
+                    );
+                }
+
+                // a landscape that's still loading picks up its current layers once it's added
+                let is_loaded = state_guard
+                    .landscapes
+                    .iter()
+                    .any(|landscape| landscape.id == component.id);
+
+                let changed_layers: Vec<LandscapeTextureLayer> = layers
+                    .into_iter()
+                    .filter(|layer| {
+                        texture_id_for(&previous, &layer.kind) != Some(&layer.texture_id)
+                    })
+                    .collect();
+                if is_loaded && !changed_layers.is_empty() {
+                    texture_loads.push((component.clone(), changed_layers));
+                }
+            }
+        }
+
+        for (component, load_id) in loads {
+            if !self.load_component(project_id.clone(), saved_state, &component, load_id) {
+                // try again on the next sync, e.g. once the asset list has arrived
+                self.inner
+                    .state
+                    .borrow_mut()
+                    .synced_components
+                    .remove(&component.id);
+            }
+        }
+
+        for (component, layers) in texture_loads {
+            let renderer = self.clone();
+            let project_id = project_id.clone();
+
+            spawn_local(async move {
+                for layer in layers {
+                    renderer
+                        .load_landscape_texture(project_id.clone(), &component, layer)
+                        .await;
+                }
+            });
+        }
+    }
+
+    // returns false when the component's asset isn't known yet
+    fn load_component(
+        &self,
+        project_id: String,
+        saved_state: &SavedState,
+        component: &ComponentData,
+        load_id: u32,
+    ) -> bool {
+        let renderer = self.clone();
+        let component_id = component.id.clone();
+
+        let model_filename = saved_state
+            .models
+            .iter()
+            .find(|model| model.id == component.asset_id)
+            .map(|model| model.fileName.clone());
+
+        match component.kind {
+            Some(ComponentKind::Model) => {
+                let model_filename = match model_filename {
+                    Some(model_filename) => model_filename,
+                    None => return false,
+                };
+
+                spawn_local(async move {
+                    let bytes = match read_model_bytes(project_id, model_filename).await {
+                        Ok(bytes) => bytes,
+                        Err(e) => {
+                            renderer.forget_failed_load(&component_id, load_id, &e);
+                            return;
+                        }
+                    };
+
+                    if renderer.load_is_current(&component_id, load_id) {
+                        renderer
+                            .inner
+                            .state
+                            .borrow_mut()
+                            .add_model(&component_id, &bytes);
+                    }
+                });
+            }
+            Some(ComponentKind::Foliage) => {
+                let model_filename = match model_filename {
+                    Some(model_filename) => model_filename,
+                    None => return false,
+                };
+
+                spawn_local(async move {
+                    let bytes = match read_model_bytes(project_id, model_filename).await {
+                        Ok(bytes) => bytes,
+                        Err(e) => {
+                            renderer.forget_failed_load(&component_id, load_id, &e);
+                            return;
+                        }
+                    };
+
+                    if !renderer.load_is_current(&component_id, load_id) {
+                        return;
+                    }
+
+                    let mut state_guard = renderer.inner.state.borrow_mut();
+
+                    // instances may have been rescattered while the model was loading
+                    let instances = state_guard
+                        .synced_components
+                        .get(&component_id)
+                        .and_then(|synced| synced.data.foliage_properties.as_ref())
+                        .map(|foliage| foliage.instances.clone())
+                        .unwrap_or_default();
+
+                    state_guard.add_foliage(&component_id, &bytes);
+                    state_guard.set_foliage_instances(&component_id, &instances);
+                });
+            }
+            Some(ComponentKind::Landscape) => {
+                let landscape_asset_id = component.asset_id.clone();
+                let heightmap = saved_state
+                    .landscapes
+                    .iter()
+                    .flatten()
+                    .find(|landscape| landscape.id == landscape_asset_id)
+                    .and_then(|landscape| landscape.heightmap.clone());
+                let heightmap = match heightmap {
+                    Some(heightmap) => heightmap,
+                    None => return false,
+                };
+
+                let component = component.clone();
+
+                spawn_local(async move {
+                    let data = match read_landscape_data(
+                        project_id.clone(),
+                        landscape_asset_id,
+                        heightmap.fileName,
+                    )
+                    .await
+                    {
+                        Ok(data) => data,
+                        Err(e) => {
+                            renderer.forget_failed_load(&component_id, load_id, &e);
+                            return;
+                        }
+                    };
+
+                    if !renderer.load_is_current(&component_id, load_id) {
+                        return;
+                    }
+
+                    // textures picked while the heightmap was loading were left for this
+                    let layers = {
+                        let mut state_guard = renderer.inner.state.borrow_mut();
+                        state_guard.add_landscape(&component_id, &data);
+
+                        state_guard
+                            .synced_components
+                            .get(&component_id)
+                            .map(|synced| synced.layers.clone())
+                            .unwrap_or_default()
+                    };
+
+                    for layer in layers {
+                        renderer
+                            .load_landscape_texture(project_id.clone(), &component, layer)
+                            .await;
+                    }
+                });
+            }
+            None => {}
+        }
+
+        true
+    }
+
+    async fn load_landscape_texture(
+        &self,
+        project_id: String,
+        component: &ComponentData,
+        layer: LandscapeTextureLayer,
+    ) {
+        let (kind, mask_kind) = match landscape_texture_kinds(&layer.kind) {
+            Some(kinds) => kinds,
+            None => return,
+        };
+
+        let texture = fetch_texture_data(
+            project_id.clone(),
+            component.asset_id.clone(),
+            layer.texture_filename,
+            layer.kind.clone(),
+        )
+        .await;
+        let mask = match layer.mask_filename {
+            Some(mask_filename) => Some((
+                mask_kind,
+                fetch_mask_data(
+                    project_id,
+                    component.asset_id.clone(),
+                    mask_filename,
+                    layer.kind.clone(),
+                )
+                .await,
+            )),
+            None => None,
+        };
+
+        // a newer texture may have been picked for this layer in the meantime
+        let is_current = self
+            .inner
+            .state
+            .borrow()
+            .synced_components
+            .get(&component.id)
+            .map_or(false, |synced| {
+                texture_id_for(&synced.data, &layer.kind) == Some(&layer.texture_id)
+            });
+        if !is_current {
+            return;
+        }
+
+        self.inner.state.borrow_mut().update_landscape_texture(
+            component.id.clone(),
+            kind,
+            texture,
+            mask,
+        );
+    }
+
+    // forgetting a component that failed to load lets the next sync try it again
+    fn forget_failed_load(&self, component_id: &String, load_id: u32, message: &str) {
+        console_error(&format!("Couldn't load {}: {}", component_id, message));
+
+        if self.load_is_current(component_id, load_id) {
+            self.inner
+                .state
+                .borrow_mut()
+                .synced_components
+                .remove(component_id);
+        }
+    }
+
+    fn load_is_current(&self, component_id: &String, load_id: u32) -> bool {
+        self.inner
+            .state
+            .borrow()
+            .synced_components
+            .get(component_id)
+            .map_or(false, |synced| synced.load_id == load_id)
+    }
+
+    fn frame(&self) {
+        let inner = &self.inner;
+
+        // moving between screens changes the pixel ratio without resizing the canvas
+        let current_ratio = window().map_or(1.0, |window| window.device_pixel_ratio());
+        if current_ratio != inner.device_pixel_ratio.get() {
+            inner.device_pixel_ratio.set(current_ratio);
+            let (width, height) = canvas_physical_size(&inner.canvas);
+            if width > 0 && height > 0 {
+                inner.pending_resize.set(Some((width, height)));
+            }
+        }
+
+        if let Some((width, height)) = inner.pending_resize.take() {
+            self.resize(width, height);
+        }
+
+        let state = inner.state.borrow();
+        let mut camera = inner.camera.borrow_mut();
+
+        render_frame(
+            &state,
+            &mut camera,
+            &inner.surface,
+            &inner.config.borrow(),
+            &inner.device,
+            &inner.queue,
+            &inner.context,
+        );
+    }
+
+    fn resize(&self, width: u32, height: u32) {
+        let inner = &self.inner;
+        let max_dimension = inner.device.limits().max_texture_dimension_2d;
+        let width = width.clamp(1, max_dimension);
+        let height = height.clamp(1, max_dimension);
+
+        let mut config = inner.config.borrow_mut();
+        if width == config.width && height == config.height {
+            return;
+        }
+
+        inner.canvas.set_width(width);
+        inner.canvas.set_height(height);
+
+        config.width = width;
+        config.height = height;
+        inner.surface.configure(&inner.device, &config);
+
+        inner.state.borrow_mut().resize_depth(width, height);
+
+        let mut camera = inner.camera.borrow_mut();
+        camera.update_aspect_ratio(width as f32 / height as f32);
+        camera.update_view_projection_matrix();
+    }
+}
+
+// shaders, layouts, pipelines and an empty scene for a color target of the given format and size
+pub(crate) fn create_scene(
+    device: &Arc<wgpu::Device>,
+    queue: &Arc<wgpu::Queue>,
+    format: wgpu::TextureFormat,
+    width: u32,
+    height: u32,
+    camera: &SimpleCamera,
+) -> (RenderContext, RendererState) {
+    // Create the shader module
+    let vertex_shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+        label: Some("Vertex Shader"),
+        source: wgpu::ShaderSource::Wgsl(include_str!("./shaders/primary_vertex.wgsl").into()),
+    });
+
+    let fragment_shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+        label: Some("Fragment Shader"),
+        source: wgpu::ShaderSource::Wgsl(include_str!("./shaders/primary_fragment.wgsl").into()),
+    });
+
+    let camera_matrix = camera.view_projection_matrix;
+    let camera_uniform_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+        label: Some("Camera Uniform Buffer"),
+        contents: bytemuck::cast_slice(camera_matrix.as_slice()),
+        usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+    });
+
+    // Create the bind group for the uniform buffer
+    let camera_bind_group_layout =
+        device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("Bind Group Layout"),
+            entries: &[wgpu::BindGroupLayoutEntry {
+                binding: 0,
+                visibility: wgpu::ShaderStages::VERTEX,
+                ty: wgpu::BindingType::Buffer {
+                    ty: wgpu::BufferBindingType::Uniform,
+                    has_dynamic_offset: false,
+                    min_binding_size: None,
+                },
+                count: None,
+            }],
+        });
+
+    let model_bind_group_layout =
+        device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            entries: &[wgpu::BindGroupLayoutEntry {
+                binding: 0,
+                visibility: wgpu::ShaderStages::VERTEX,
+                ty: wgpu::BindingType::Buffer {
+                    ty: wgpu::BufferBindingType::Uniform,
+                    has_dynamic_offset: false,
+                    min_binding_size: None,
+                },
+                count: None,
+            }],
+            label: Some("model_bind_group_layout"),
+        });
+
+    let model_bind_group_layout = Arc::new(model_bind_group_layout);
+
+    let texture_bind_group_layout =
         device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
             entries: &[
                 wgpu::BindGroupLayoutEntry {
@@ -511,6 +1435,17 @@ pub async fn start_render_loop() {
                     },
                     count: None,
                 },
+                // landscape masks, kept apart from the materials so they can differ in resolution
+                wgpu::BindGroupLayoutEntry {
+                    binding: 3,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Texture {
+                        multisampled: false,
+                        view_dimension: wgpu::TextureViewDimension::D2Array,
+                        sample_type: wgpu::TextureSampleType::Float { filterable: true },
+                    },
+                    count: None,
+                },
             ],
             label: Some("Texture Bind Group Layout"),
         });
@@ -542,32 +1477,34 @@ pub async fn start_render_loop() {
 
     let texture_render_mode_buffer = Arc::new(texture_render_mode_buffer);
 
+    // sun, sky colors and fog, shared by the sky and every lit surface
+    let environment_bind_group_layout =
+        device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            entries: &[wgpu::BindGroupLayoutEntry {
+                binding: 0,
+                visibility: wgpu::ShaderStages::VERTEX | wgpu::ShaderStages::FRAGMENT,
+                ty: wgpu::BindingType::Buffer {
+                    ty: wgpu::BufferBindingType::Uniform,
+                    has_dynamic_offset: false,
+                    min_binding_size: None,
+                },
+                count: None,
+            }],
+            label: Some("Environment Bind Group Layout"),
+        });
+
     let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
         label: Some("Render Pipeline Layout"),
         bind_group_layouts: &[
             &camera_bind_group_layout,
             &model_bind_group_layout,
             &texture_bind_group_layout,
+            &environment_bind_group_layout,
         ],
         push_constant_ranges: &[],
     });
 
-    let depth_texture = device.create_texture(&wgpu::TextureDescriptor {
-        size: wgpu::Extent3d {
-            width: width,
-            height: height,
-            depth_or_array_layers: 1,
-        },
-        mip_level_count: 1,
-        sample_count: 1,
-        dimension: wgpu::TextureDimension::D2,
-        format: wgpu::TextureFormat::Depth24Plus,
-        usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::TEXTURE_BINDING,
-        label: Some("Depth Texture"),
-        view_formats: &[],
-    });
-
-    let depth_view = depth_texture.create_view(&wgpu::TextureViewDescriptor::default());
+    let depth_view = create_depth_view(device, width, height);
 
     let depth_stencil_state = wgpu::DepthStencilState {
         format: wgpu::TextureFormat::Depth24Plus,
@@ -593,7 +1530,7 @@ pub async fn start_render_loop() {
             module: &fragment_shader,
             entry_point: "main",
             targets: &[Some(wgpu::ColorTargetState {
-                format: swap_chain_format,
+                format: format,
                 blend: Some(wgpu::BlendState::REPLACE),
                 write_mask: wgpu::ColorWrites::ALL,
             })],
@@ -613,7 +1550,7 @@ pub async fn start_render_loop() {
             conservative: false,
         },
         // depth_stencil: None,
-        depth_stencil: Some(depth_stencil_state),
+        depth_stencil: Some(depth_stencil_state.clone()),
         multisample: wgpu::MultisampleState {
             count: 1,
             mask: !0,
@@ -622,100 +1559,281 @@ pub async fn start_render_loop() {
         multiview: None,
     });
 
-    // let mut state = RendererState::new(
-    //     &device,
-    //     &queue,
-    //     &model_bind_group_layout,
-    //     &texture_bind_group_layout,
-    //     &texture_render_mode_buffer,
-    //     &color_render_mode_buffer,
-    // )
-    // .await;
-    // unsafe {
-    //     RENDERER_STATE = Some(state);
-    //     RENDERER_STATE_INIT.with(|init| {
-    //         init.set(true);
-    //     });
-    // }
-
-    // let state = get_renderer_state();
+    // foliage shares the primary fragment shader, only the vertex stage reads instance transforms
+    let instanced_vertex_shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+        label: Some("Instanced Vertex Shader"),
+        source: wgpu::ShaderSource::Wgsl(include_str!("./shaders/instanced_vertex.wgsl").into()),
+    });
 
-    let state = RendererState::new(
-        device.clone(),
-        queue.clone(),
-        model_bind_group_layout.clone(),
-        texture_bind_group_layout.clone(),
-        texture_render_mode_buffer.clone(),
-        color_render_mode_buffer.clone(),
-    )
-    .await;
+    let instanced_render_pipeline =
+        device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("Instanced Render Pipeline"),
+            layout: Some(&pipeline_layout),
+            vertex: wgpu::VertexState {
+                module: &instanced_vertex_shader,
+                entry_point: "main",
+                buffers: &[Vertex::desc(), InstanceRaw::desc()],
+                compilation_options: wgpu::PipelineCompilationOptions {
+                    ..Default::default()
+                },
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: &fragment_shader,
+                entry_point: "main",
+                targets: &[Some(wgpu::ColorTargetState {
+                    format: format,
+                    blend: Some(wgpu::BlendState::REPLACE),
+                    write_mask: wgpu::ColorWrites::ALL,
+                })],
+                compilation_options: wgpu::PipelineCompilationOptions {
+                    ..Default::default()
+                },
+            }),
+            primitive: wgpu::PrimitiveState {
+                topology: wgpu::PrimitiveTopology::TriangleList,
+                strip_index_format: None,
+                front_face: wgpu::FrontFace::Ccw,
+                cull_mode: Some(wgpu::Face::Back),
+                polygon_mode: wgpu::PolygonMode::Fill,
+                unclipped_depth: false,
+                conservative: false,
+            },
+            depth_stencil: Some(depth_stencil_state.clone()),
+            multisample: wgpu::MultisampleState {
+                count: 1,
+                mask: !0,
+                alpha_to_coverage_enabled: false,
+            },
+            multiview: None,
+        });
 
-    initialize_renderer_state(state);
+    // water reads the scene depth for its shoreline fade, so it gets its own layout
+    let water_bind_group_layout =
+        device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            entries: &[
+                wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::VERTEX | wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 1,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Texture {
+                        multisampled: false,
+                        view_dimension: wgpu::TextureViewDimension::D2,
+                        sample_type: wgpu::TextureSampleType::Float { filterable: false },
+                    },
+                    count: None,
+                },
+            ],
+            label: Some("Water Bind Group Layout"),
+        });
 
-    let state = get_renderer_state();
-    // let mut state_guard = get_renderer_state_read_lock();
+    let water_bind_group_layout = Arc::new(water_bind_group_layout);
 
-    // web-based rendering loop
-    let f = Rc::new(RefCell::new(None));
-    let g = f.clone();
+    let water_pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+        label: Some("Water Pipeline Layout"),
+        bind_group_layouts: &[
+            &camera_bind_group_layout,
+            &water_bind_group_layout,
+            &environment_bind_group_layout,
+        ],
+        push_constant_ranges: &[],
+    });
 
-    let closure = Closure::wrap(Box::new(move || {
-        if !is_rendering_paused() {
-            let device = device.clone();
-            let state_guard = state.lock().unwrap();
-
-            render_frame(
-                &state_guard,
-                &surface,
-                &device,
-                &queue,
-                &render_pipeline,
-                &depth_view,
-                &camera_bind_group,
-                &camera_uniform_buffer,
-            );
+    let water_shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+        label: Some("Water Shader"),
+        source: wgpu::ShaderSource::Wgsl(include_str!("./shaders/water.wgsl").into()),
+    });
 
-            drop(state_guard);
-        }
+    // transparent surfaces: alpha blended, depth tested against the scene but not written
+    let water_render_pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+        label: Some("Water Render Pipeline"),
+        layout: Some(&water_pipeline_layout),
+        vertex: wgpu::VertexState {
+            module: &water_shader,
+            entry_point: "vs_main",
+            buffers: &[Vertex::desc()],
+            compilation_options: wgpu::PipelineCompilationOptions {
+                ..Default::default()
+            },
+        },
+        fragment: Some(wgpu::FragmentState {
+            module: &water_shader,
+            entry_point: "fs_main",
+            targets: &[Some(wgpu::ColorTargetState {
+                format: format,
+                blend: Some(wgpu::BlendState::ALPHA_BLENDING),
+                write_mask: wgpu::ColorWrites::ALL,
+            })],
+            compilation_options: wgpu::PipelineCompilationOptions {
+                ..Default::default()
+            },
+        }),
+        primitive: wgpu::PrimitiveState {
+            topology: wgpu::PrimitiveTopology::TriangleList,
+            strip_index_format: None,
+            front_face: wgpu::FrontFace::Ccw,
+            cull_mode: None, // visible from below the surface too
+            polygon_mode: wgpu::PolygonMode::Fill,
+            unclipped_depth: false,
+            conservative: false,
+        },
+        depth_stencil: Some(wgpu::DepthStencilState {
+            depth_write_enabled: false,
+            ..depth_stencil_state.clone()
+        }),
+        multisample: wgpu::MultisampleState {
+            count: 1,
+            mask: !0,
+            alpha_to_coverage_enabled: false,
+        },
+        multiview: None,
+    });
 
-        // Schedule the next frame
-        request_animation_frame(f.borrow().as_ref().unwrap());
-    }) as Box<dyn FnMut()>);
+    let sky_pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+        label: Some("Sky Pipeline Layout"),
+        bind_group_layouts: &[&environment_bind_group_layout],
+        push_constant_ranges: &[],
+    });
+
+    let sky_shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+        label: Some("Sky Shader"),
+        source: wgpu::ShaderSource::Wgsl(include_str!("./shaders/sky.wgsl").into()),
+    });
+
+    // full screen background, drawn first and never written to depth
+    let sky_render_pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+        label: Some("Sky Render Pipeline"),
+        layout: Some(&sky_pipeline_layout),
+        vertex: wgpu::VertexState {
+            module: &sky_shader,
+            entry_point: "vs_main",
+            buffers: &[],
+            compilation_options: wgpu::PipelineCompilationOptions {
+                ..Default::default()
+            },
+        },
+        fragment: Some(wgpu::FragmentState {
+            module: &sky_shader,
+            entry_point: "fs_main",
+            targets: &[Some(wgpu::ColorTargetState {
+                format: format,
+                blend: Some(wgpu::BlendState::REPLACE),
+                write_mask: wgpu::ColorWrites::ALL,
+            })],
+            compilation_options: wgpu::PipelineCompilationOptions {
+                ..Default::default()
+            },
+        }),
+        primitive: wgpu::PrimitiveState {
+            topology: wgpu::PrimitiveTopology::TriangleList,
+            strip_index_format: None,
+            front_face: wgpu::FrontFace::Ccw,
+            cull_mode: None,
+            polygon_mode: wgpu::PolygonMode::Fill,
+            unclipped_depth: false,
+            conservative: false,
+        },
+        depth_stencil: Some(wgpu::DepthStencilState {
+            depth_write_enabled: false,
+            depth_compare: wgpu::CompareFunction::Always,
+            ..depth_stencil_state.clone()
+        }),
+        multisample: wgpu::MultisampleState {
+            count: 1,
+            mask: !0,
+            alpha_to_coverage_enabled: false,
+        },
+        multiview: None,
+    });
 
-    *g.borrow_mut() = Some(closure);
+    let state = RendererState::new(
+        device.clone(),
+        queue.clone(),
+        model_bind_group_layout.clone(),
+        texture_bind_group_layout.clone(),
+        water_bind_group_layout.clone(),
+        &environment_bind_group_layout,
+        texture_render_mode_buffer.clone(),
+        color_render_mode_buffer.clone(),
+        depth_view.clone(),
+    );
+
+    let context = RenderContext {
+        render_pipeline,
+        instanced_render_pipeline,
+        water_render_pipeline,
+        sky_render_pipeline,
+        camera_bind_group,
+        camera_uniform_buffer,
+    };
 
-    // Start the rendering loop
-    request_animation_frame(g.borrow().as_ref().unwrap());
+    (context, state)
 }
 
-fn request_animation_frame(f: &Closure<dyn FnMut()>) {
+fn request_animation_frame(f: &Closure<dyn FnMut()>) -> i32 {
     window()
         .unwrap()
         .request_animation_frame(f.as_ref().unchecked_ref())
-        .expect("should register `requestAnimationFrame` OK");
+        .expect("should register `requestAnimationFrame` OK")
 }
 
 fn render_frame(
     state: &RendererState,
+    camera: &mut SimpleCamera,
     surface: &wgpu::Surface,
+    config: &wgpu::SurfaceConfiguration,
     device: &wgpu::Device,
     queue: &wgpu::Queue,
-    render_pipeline: &wgpu::RenderPipeline,
-    depth_view: &wgpu::TextureView,
-    camera_bind_group: &wgpu::BindGroup,
-    camera_uniform_buffer: &wgpu::Buffer,
+    context: &RenderContext,
 ) {
-    // draw frames...
-    let mut camera = get_camera();
-
-    // Render a frame
-    let frame = surface
-        .get_current_texture()
-        .expect("Failed to acquire next swap chain texture");
+    let frame = match surface.get_current_texture() {
+        Ok(frame) => frame,
+        // the surface goes stale after resizes and tab switches, the next frame draws again
+        Err(wgpu::SurfaceError::Lost) | Err(wgpu::SurfaceError::Outdated) => {
+            surface.configure(device, config);
+            return;
+        }
+        Err(wgpu::SurfaceError::Timeout) => return,
+        Err(e) => {
+            console_error(&format!("Failed to acquire next surface texture: {}", e));
+            return;
+        }
+    };
     let view = frame
         .texture
         .create_view(&wgpu::TextureViewDescriptor::default());
 
+    render_scene(state, camera, &view, device, queue, context);
+
+    frame.present();
+}
+
+// draws the scene into any color target matching the context's format, with or without a surface
+pub(crate) fn render_scene(
+    state: &RendererState,
+    camera: &mut SimpleCamera,
+    view: &wgpu::TextureView,
+    device: &wgpu::Device,
+    queue: &wgpu::Queue,
+    context: &RenderContext,
+) {
+    let RenderContext {
+        render_pipeline,
+        instanced_render_pipeline,
+        water_render_pipeline,
+        sky_render_pipeline,
+        camera_bind_group,
+        camera_uniform_buffer,
+    } = context;
+
     let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
         label: Some("Render Encoder"),
     });
@@ -730,7 +1848,7 @@ fn render_frame(
         let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
             label: Some("Render Pass"),
             color_attachments: &[Some(wgpu::RenderPassColorAttachment {
-                view: &view,
+                view,
                 resolve_target: None,
                 ops: wgpu::Operations {
                     load: wgpu::LoadOp::Clear(color),
@@ -739,7 +1857,7 @@ fn render_frame(
             })],
             // depth_stencil_attachment: None,
             depth_stencil_attachment: Some(wgpu::RenderPassDepthStencilAttachment {
-                view: &depth_view, // This is the depth texture view
+                view: &state.depth_view, // This is the depth texture view
                 depth_ops: Some(wgpu::Operations {
                     load: wgpu::LoadOp::Clear(1.0), // Clear to max depth
                     store: wgpu::StoreOp::Store,
@@ -750,9 +1868,6 @@ fn render_frame(
             occlusion_query_set: None,
         });
 
-        // draw calls...
-        render_pass.set_pipeline(&render_pipeline);
-
         camera.update();
         let camera_matrix = camera.view_projection_matrix;
         queue.write_buffer(
@@ -761,6 +1876,17 @@ fn render_frame(
             bytemuck::cast_slice(camera_matrix.as_slice()),
         );
 
+        state.sky.update_uniform_buffer(&queue, &camera);
+
+        // sky background replaces the clear color
+        render_pass.set_pipeline(&sky_render_pipeline);
+        render_pass.set_bind_group(0, &state.sky.bind_group, &[]);
+        render_pass.draw(0..3, 0..1);
+
+        // draw calls...
+        render_pass.set_pipeline(&render_pipeline);
+        render_pass.set_bind_group(3, &state.sky.bind_group, &[]);
+
         // draw utility grids
         for grid in &state.grids {
             render_pass.set_bind_group(0, &camera_bind_group, &[]);
@@ -836,265 +1962,697 @@ fn render_frame(
                 render_pass.draw_indexed(0..landscape.index_count as u32, 0, 0..1);
             }
         }
+
+        // draw scattered foliage, one instanced call per mesh
+        render_pass.set_pipeline(&instanced_render_pipeline);
+
+        for foliage in &state.foliage {
+            if let Some(instance_buffer) = &foliage.instance_buffer {
+                for mesh in &foliage.model.meshes {
+                    mesh.transform.update_uniform_buffer(&queue);
+                    render_pass.set_bind_group(0, &camera_bind_group, &[]);
+                    render_pass.set_bind_group(1, &mesh.bind_group, &[]);
+                    render_pass.set_bind_group(2, &mesh.texture_bind_group, &[]);
+
+                    render_pass.set_vertex_buffer(0, mesh.vertex_buffer.slice(..));
+                    render_pass.set_vertex_buffer(1, instance_buffer.slice(..));
+                    render_pass
+                        .set_index_buffer(mesh.index_buffer.slice(..), wgpu::IndexFormat::Uint16);
+
+                    render_pass.draw_indexed(
+                        0..mesh.index_count as u32,
+                        0,
+                        0..foliage.instance_count,
+                    );
+                }
+            }
+        }
+    }
+
+    // transparent pass, drawn over the opaque scene while sampling its depth
+    if let Some(water) = &state.water {
+        let time = time_of_day();
+        water.update_uniform_buffer(&queue, &camera, time);
+
+        let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label: Some("Water Render Pass"),
+            color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                view,
+                resolve_target: None,
+                ops: wgpu::Operations {
+                    load: wgpu::LoadOp::Load,
+                    store: wgpu::StoreOp::Store,
+                },
+            })],
+            // read-only depth, so it can be bound for the shoreline fade at the same time
+            depth_stencil_attachment: Some(wgpu::RenderPassDepthStencilAttachment {
+                view: &state.depth_view,
+                depth_ops: None,
+                stencil_ops: None,
+            }),
+            timestamp_writes: None,
+            occlusion_query_set: None,
+        });
+
+        render_pass.set_pipeline(&water_render_pipeline);
+        render_pass.set_bind_group(0, &camera_bind_group, &[]);
+        render_pass.set_bind_group(1, &water.bind_group, &[]);
+        render_pass.set_bind_group(2, &state.sky.bind_group, &[]);
+
+        render_pass.set_vertex_buffer(0, water.vertex_buffer.slice(..));
+        render_pass.set_index_buffer(water.index_buffer.slice(..), wgpu::IndexFormat::Uint32);
+
+        render_pass.draw_indexed(0..water.index_count, 0, 0..1);
     }
 
     queue.submit(Some(encoder.finish()));
-    frame.present();
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct LandscapeData {
+    // pub width: usize,
+    // pub height: usize,
+    pub width: usize,
+    pub height: usize,
+    // pub data: Vec<u8>,
+    pub pixel_data: Vec<Vec<PixelData>>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct PixelData {
+    pub height_value: f32,
+    pub position: [f32; 3],
+    pub tex_coords: [f32; 2],
+}
+
+impl LandscapeData {
+    // world space extent of the terrain, none if there are no pixels
+    pub fn bounds(&self) -> Option<(Point3<f32>, Point3<f32>)> {
+        let mut min = Point3::new(f32::MAX, f32::MAX, f32::MAX);
+        let mut max = Point3::new(f32::MIN, f32::MIN, f32::MIN);
+        for pixel in self.pixel_data.iter().flatten() {
+            let position = Point3::from(pixel.position);
+            min = min.inf(&position);
+            max = max.sup(&position);
+        }
+
+        if min.x <= max.x {
+            Some((min, max))
+        } else {
+            None
+        }
+    }
 }
 
 #[wasm_bindgen]
-pub fn handle_key_press(key_code: String, is_pressed: bool) {
-    let camera = get_camera();
-    let state = get_renderer_state();
-    let mut state_guard = state.lock().unwrap();
-
-    web_sys::console::log_1(&format!("Key pressed (2): {}", key_code).into());
-
-    match key_code.as_str() {
-        "w" => {
-            if is_pressed {
-                // Handle the key press for W
-                web_sys::console::log_1(&"Key W pressed".into());
-                camera.position += camera.direction * 0.1;
+impl Renderer {
+    pub fn handle_key_press(&self, key_code: String, is_pressed: bool) {
+        let mut camera = self.inner.camera.borrow_mut();
+        let mut state_guard = self.inner.state.borrow_mut();
+
+        web_sys::console::log_1(&format!("Key pressed (2): {}", key_code).into());
+
+        match key_code.as_str() {
+            "w" => {
+                if is_pressed {
+                    // Handle the key press for W
+                    web_sys::console::log_1(&"Key W pressed".into());
+                    let direction = camera.direction;
+                    camera.position += direction * 0.1;
+                }
             }
-        }
-        "s" => {
-            if is_pressed {
-                // Handle the key press for S
-                web_sys::console::log_1(&"Key S pressed".into());
-                camera.position -= camera.direction * 0.1;
+            "s" => {
+                if is_pressed {
+                    // Handle the key press for S
+                    web_sys::console::log_1(&"Key S pressed".into());
+                    let direction = camera.direction;
+                    camera.position -= direction * 0.1;
+                }
             }
-        }
-        "a" => {
-            if is_pressed {
-                // Handle the key press for A
-                web_sys::console::log_1(&"Key A pressed".into());
-                let right = camera.direction.cross(&camera.up).normalize();
-                camera.position -= right * 0.1;
+            "a" => {
+                if is_pressed {
+                    // Handle the key press for A
+                    web_sys::console::log_1(&"Key A pressed".into());
+                    let right = camera.direction.cross(&camera.up).normalize();
+                    camera.position -= right * 0.1;
+                }
             }
-        }
-        "d" => {
-            if is_pressed {
-                // Handle the key press for D
-                web_sys::console::log_1(&"Key D pressed".into());
-                let right = camera.direction.cross(&camera.up).normalize();
-                camera.position += right * 0.1;
+            "d" => {
+                if is_pressed {
+                    // Handle the key press for D
+                    web_sys::console::log_1(&"Key D pressed".into());
+                    let right = camera.direction.cross(&camera.up).normalize();
+                    camera.position += right * 0.1;
+                }
             }
-        }
-        "ArrowUp" => {
-            if is_pressed {
-                // Handle the key press for ArrowUp
-                web_sys::console::log_1(&"Key ArrowUp pressed".into());
-                // state.pyramids[0].translate(Vector3::new(0.0, 0.1, 0.0));
-                // test rotation
-                // state.pyramids[0].rotate(Vector3::new(0.0, 0.1, 0.0));
-                // test scale
-                // state.pyramids[0].scale(Vector3::new(1.1, 1.1, 1.1));
-
-                if state_guard.models.len() > 0 {
-                    state_guard.models[0].meshes[0]
-                        .transform
-                        .translate(Vector3::new(0.0, 0.1, 0.0));
+            "ArrowUp" => {
+                if is_pressed {
+                    // Handle the key press for ArrowUp
+                    web_sys::console::log_1(&"Key ArrowUp pressed".into());
+                    // state.pyramids[0].translate(Vector3::new(0.0, 0.1, 0.0));
+                    // test rotation
+                    // state.pyramids[0].rotate(Vector3::new(0.0, 0.1, 0.0));
+                    // test scale
+                    // state.pyramids[0].scale(Vector3::new(1.1, 1.1, 1.1));
+
+                    if state_guard.models.len() > 0 {
+                        state_guard.models[0].meshes[0]
+                            .transform
+                            .translate(Vector3::new(0.0, 0.1, 0.0));
+                    }
                 }
             }
-        }
-        "ArrowDown" => {
-            if is_pressed {
-                // Handle the key press for ArrowDown
-                web_sys::console::log_1(&"Key ArrowDown pressed".into());
-                // state.pyramids[0].translate(Vector3::new(0.0, -0.1, 0.0));
+            "ArrowDown" => {
+                if is_pressed {
+                    // Handle the key press for ArrowDown
+                    web_sys::console::log_1(&"Key ArrowDown pressed".into());
+                    // state.pyramids[0].translate(Vector3::new(0.0, -0.1, 0.0));
+                }
             }
-        }
-        "ArrowLeft" => {
-            if is_pressed {
-                // Handle the key press for ArrowLeft
-                web_sys::console::log_1(&"Key ArrowLeft pressed".into());
-                // state.pyramids[0].translate(Vector3::new(-0.1, 0.0, 0.0));
+            "ArrowLeft" => {
+                if is_pressed {
+                    // Handle the key press for ArrowLeft
+                    web_sys::console::log_1(&"Key ArrowLeft pressed".into());
+                    // state.pyramids[0].translate(Vector3::new(-0.1, 0.0, 0.0));
+                }
             }
-        }
-        "ArrowRight" => {
-            if is_pressed {
-                // Handle the key press for ArrowRight
-                web_sys::console::log_1(&"Key ArrowRight pressed".into());
-                // state.pyramids[0].translate(Vector3::new(0.1, 0.0, 0.0));
+            "ArrowRight" => {
+                if is_pressed {
+                    // Handle the key press for ArrowRight
+                    web_sys::console::log_1(&"Key ArrowRight pressed".into());
+                    // state.pyramids[0].translate(Vector3::new(0.1, 0.0, 0.0));
+                }
+            }
+            _ => {
+                // Handle any other keys if necessary
             }
         }
-        _ => {
-            // Handle any other keys if necessary
-        }
+
+        camera.update();
     }
 
-    camera.update();
-}
+    pub fn handle_mouse_move(&self, dx: f32, dy: f32) {
+        let mut camera = self.inner.camera.borrow_mut();
+        let sensitivity = 0.005;
 
-#[wasm_bindgen]
-pub fn handle_mouse_move(dx: f32, dy: f32) {
-    let camera = get_camera();
-    let sensitivity = 0.005;
+        let dx = -dx * sensitivity;
+        let dy = dy * sensitivity;
 
-    let dx = -dx * sensitivity;
-    let dy = dy * sensitivity;
+        camera.rotate(dx, dy);
 
-    camera.rotate(dx, dy);
+        camera.update();
+    }
 
-    camera.update();
-}
+    pub fn handle_set_terrain_brush(&self, kind: String, radius: f32, strength: f32, falloff: f32) {
+        let mut state_guard = self.inner.state.borrow_mut();
 
-#[wasm_bindgen]
-pub fn handle_add_model(projectId: String, modelFilename: String) {
-    pause_rendering();
+        let kind = match BrushKind::from_str(&kind) {
+            Some(kind) => kind,
+            None => {
+                web_sys::console::error_1(&format!("Invalid brush kind: {}", kind).into());
+                return;
+            }
+        };
 
-    let state = get_renderer_state();
-    let mut state_guard = state.lock().unwrap();
+        // sculpting and painting share the pointer, so only one brush is active at a time
+        state_guard.end_paint();
+        state_guard.mask_brush = None;
+        state_guard.terrain_brush = Some(BrushSettings {
+            kind,
+            radius,
+            strength,
+            falloff,
+        });
+    }
 
-    // TODO: this spawn and async may be unncessary
-    spawn_local(async move {
-        // let mut state_guard = get_renderer_state_write_lock();
+    pub fn handle_clear_terrain_brush(&self) {
+        let mut state_guard = self.inner.state.borrow_mut();
 
-        let params = to_value(&ReadModelParams {
-            projectId,
-            modelFilename,
-        })
-        .unwrap();
-        // let bytes = crate::app::invoke("read_model", params).await;
-        let bytes = invoke("read_model", params).await;
-        let bytes = bytes
-            .into_serde()
-            .expect("Failed to transform byte string to value");
+        state_guard.end_sculpt();
+        state_guard.terrain_brush = None;
+    }
 
-        state_guard.add_model(&bytes).await;
+    pub fn is_sculpting(&self) -> bool {
+        let state_guard = self.inner.state.borrow();
 
-        drop(state_guard);
+        state_guard.terrain_brush.is_some()
+    }
 
-        resume_rendering();
-    });
-}
+    pub fn handle_sculpt_begin(&self, x: f32, y: f32, canvas_width: f32, canvas_height: f32) {
+        let camera = self.inner.camera.borrow();
+        let mut state_guard = self.inner.state.borrow_mut();
 
-#[derive(Serialize, Deserialize)]
-pub struct LandscapeData {
-    // pub width: usize,
-    // pub height: usize,
-    pub width: usize,
-    pub height: usize,
-    // pub data: Vec<u8>,
-    pub pixel_data: Vec<Vec<PixelData>>,
-}
+        let (origin, direction) = camera.screen_ray(x, y, canvas_width, canvas_height);
+        state_guard.begin_sculpt(origin, direction);
+    }
 
-#[derive(Serialize, Deserialize)]
-pub struct PixelData {
-    pub height_value: f32,
-    pub position: [f32; 3],
-    pub tex_coords: [f32; 2],
-}
+    pub fn handle_sculpt_move(&self, x: f32, y: f32, canvas_width: f32, canvas_height: f32) {
+        let camera = self.inner.camera.borrow();
+        let mut state_guard = self.inner.state.borrow_mut();
 
-#[wasm_bindgen]
-pub fn handle_add_landscape(
-    projectId: String,
-    landscapeAssetId: String,
-    landscapeComponentId: String,
-    landscapeFilename: String,
-    callback: js_sys::Function,
-) {
-    pause_rendering();
+        let (origin, direction) = camera.screen_ray(x, y, canvas_width, canvas_height);
+        state_guard.sculpt(origin, direction);
+    }
+
+    pub fn handle_sculpt_end(&self) {
+        let mut state_guard = self.inner.state.borrow_mut();
+
+        state_guard.end_sculpt();
+    }
+
+    pub fn handle_sculpt_undo(&self) {
+        let mut state_guard = self.inner.state.borrow_mut();
+
+        state_guard.undo_sculpt();
+    }
 
-    let state = get_renderer_state();
-    let mut state_guard = state.lock().unwrap();
+    pub fn handle_sculpt_redo(&self) {
+        let mut state_guard = self.inner.state.borrow_mut();
+
+        state_guard.redo_sculpt();
+    }
+
+    pub fn handle_save_landscape_heightmap(
+        &self,
+        project_id: String,
+        landscape_component_id: String,
+        landscape_asset_id: String,
+        landscape_filename: String,
+        callback: js_sys::Function,
+    ) {
+        let state_guard = self.inner.state.borrow();
+
+        let landscape = match state_guard
+            .landscapes
+            .iter()
+            .find(|l| l.id == landscape_component_id)
+        {
+            Some(landscape) => landscape,
+            None => {
+                let message = format!("No landscape to save: {}", landscape_component_id);
+                console_error(&message);
+                let _ = callback.call1(&JsValue::null(), &message.into());
+                return;
+            }
+        };
 
-    spawn_local(async move {
-        let params = to_value(&GetLandscapeParams {
-            projectId,
-            landscapeAssetId,
-            landscapeFilename,
+        let params = to_value(&SaveHeightmapParams {
+            projectId: project_id,
+            landscapeAssetId: landscape_asset_id,
+            landscapeFilename: landscape_filename,
+            width: landscape.grid_width,
+            height: landscape.grid_height,
+            heights: landscape.heights.clone(),
         })
         .unwrap();
 
-        let js_data = invoke("get_landscape_pixels", params).await;
-        let data: LandscapeData = js_data
-            .into_serde()
-            .expect("Failed to transform byte string to value");
+        drop(state_guard);
+
+        // the callback gets null once saved, or the error message
+        spawn_local(async move {
+            let result = match try_invoke("save_landscape_heightmap", params).await {
+                Ok(_) => JsValue::null(),
+                Err(e) => {
+                    let message = invoke_error(e, "Couldn't save heightmap");
+                    console_error(&message);
+                    message.into()
+                }
+            };
+
+            let this = JsValue::null();
+            let _ = callback.call1(&this, &result);
+        });
+    }
+
+    pub fn handle_set_mask_brush(
+        &self,
+        layer_kind: String,
+        radius: f32,
+        strength: f32,
+        falloff: f32,
+    ) {
+        let mut state_guard = self.inner.state.borrow_mut();
+
+        let layer = match layer_kind.as_str() {
+            "Primary" => 0,
+            "Rockmap" => 1,
+            "Soil" => 2,
+            _ => {
+                web_sys::console::error_1(&format!("Invalid mask kind: {}", layer_kind).into());
+                return;
+            }
+        };
+
+        state_guard.end_sculpt();
+        state_guard.terrain_brush = None;
+        state_guard.mask_brush = Some(MaskBrush {
+            layer,
+            radius,
+            strength,
+            falloff,
+        });
+    }
+
+    pub fn handle_clear_mask_brush(&self) {
+        let mut state_guard = self.inner.state.borrow_mut();
+
+        state_guard.end_paint();
+        state_guard.mask_brush = None;
+    }
+
+    pub fn is_painting(&self) -> bool {
+        let state_guard = self.inner.state.borrow();
+
+        state_guard.mask_brush.is_some()
+    }
+
+    pub fn handle_paint_begin(&self, x: f32, y: f32, canvas_width: f32, canvas_height: f32) {
+        let camera = self.inner.camera.borrow();
+        let mut state_guard = self.inner.state.borrow_mut();
+
+        let (origin, direction) = camera.screen_ray(x, y, canvas_width, canvas_height);
+        state_guard.begin_paint(origin, direction);
+    }
+
+    pub fn handle_paint_move(&self, x: f32, y: f32, canvas_width: f32, canvas_height: f32) {
+        let camera = self.inner.camera.borrow();
+        let mut state_guard = self.inner.state.borrow_mut();
+
+        let (origin, direction) = camera.screen_ray(x, y, canvas_width, canvas_height);
+        state_guard.paint(origin, direction);
+    }
+
+    pub fn handle_paint_end(&self) {
+        let mut state_guard = self.inner.state.borrow_mut();
 
-        state_guard.add_landscape(&landscapeComponentId, &data);
+        state_guard.end_paint();
+    }
+
+    pub fn handle_save_landscape_masks(
+        &self,
+        project_id: String,
+        landscape_component_id: String,
+        landscape_asset_id: String,
+        primary_mask_filename: String,
+        rockmap_mask_filename: String,
+        soil_mask_filename: String,
+        callback: js_sys::Function,
+    ) {
+        let state_guard = self.inner.state.borrow();
+
+        let landscape = state_guard
+            .landscapes
+            .iter()
+            .find(|l| l.id == landscape_component_id);
+
+        let mut params = Vec::new();
+        let mut errors = Vec::new();
+
+        if let Some(landscape) = landscape {
+            if let Some(size) = landscape.mask_size() {
+                let filenames = [
+                    ("Primary", primary_mask_filename),
+                    ("Rockmap", rockmap_mask_filename),
+                    ("Soil", soil_mask_filename),
+                ];
+
+                for (layer, (mask_kind, mask_filename)) in filenames.into_iter().enumerate() {
+                    if mask_filename.is_empty() {
+                        continue;
+                    }
+
+                    let mask_base64 = match mask_png_data_url(
+                        size.width,
+                        size.height,
+                        landscape.mask_layers[layer].clone(),
+                    ) {
+                        Ok(mask_base64) => mask_base64,
+                        Err(e) => {
+                            console_error(&e);
+                            errors.push(e);
+                            continue;
+                        }
+                    };
+
+                    params.push(
+                        to_value(&SaveMaskParams {
+                            projectId: project_id.clone(),
+                            landscapeId: landscape_asset_id.clone(),
+                            maskFilename: mask_filename,
+                            maskKind: mask_kind.to_string(),
+                            maskBase64: mask_base64,
+                        })
+                        .unwrap(),
+                    );
+                }
+            }
+        } else {
+            let message = format!("No landscape to save: {}", landscape_component_id);
+            console_error(&message);
+            errors.push(message);
+        }
 
         drop(state_guard);
 
-        resume_rendering();
+        // the callback gets null once every mask is saved, or the error messages
+        spawn_local(async move {
+            for params in params {
+                if let Err(e) = try_invoke("save_landscape_mask", params).await {
+                    let message = invoke_error(e, "Couldn't save mask");
+                    console_error(&message);
+                    errors.push(message);
+                }
+            }
 
-        let this = JsValue::null();
-        let _ = callback.call0(&this);
-    });
-}
+            let result = if errors.is_empty() {
+                JsValue::null()
+            } else {
+                JsValue::from_str(&errors.join("\n"))
+            };
 
-#[wasm_bindgen]
-pub fn handle_add_landscape_texture(
-    project_id: String,
-    landscape_component_id: String,
-    landscape_asset_id: String,
-    texture_filename: String,
-    texture_kind: String,
-    mask_filename: String,
-) {
-    pause_rendering();
+            let this = JsValue::null();
+            let _ = callback.call1(&this, &result);
+        });
+    }
 
-    let state = get_renderer_state();
-    let mut state_guard = state.lock().unwrap();
+    pub fn handle_set_water(&self, settings: JsValue) {
+        let settings: WaterSettings = match settings.into_serde() {
+            Ok(settings) => settings,
+            Err(e) => {
+                web_sys::console::error_1(&format!("Invalid water settings: {}", e).into());
+                return;
+            }
+        };
 
-    // Clone the values that need to be moved into the closure
-    let landscape_component_id_clone = landscape_component_id.clone();
-    let texture_kind_clone = texture_kind.clone();
+        let mut state_guard = self.inner.state.borrow_mut();
 
-    spawn_local(async move {
-        let texture = fetch_texture_data(
-            project_id.clone(),
-            landscape_asset_id.clone(),
-            texture_filename,
-            texture_kind.clone(),
-        )
-        .await;
-        let mask = fetch_mask_data(
-            project_id.clone(),
-            landscape_asset_id.clone(),
-            mask_filename,
-            texture_kind.clone(),
-        )
-        .await;
+        state_guard.set_water(settings);
+    }
 
-        // if let Some(texture) = texture {
-        let kind = match texture_kind_clone.as_str() {
-            "Primary" => LandscapeTextureKinds::Primary,
-            "Rockmap" => LandscapeTextureKinds::Rockmap,
-            "Soil" => LandscapeTextureKinds::Soil,
-            _ => {
-                web_sys::console::error_1(
-                    &format!("Invalid texture kind: {}", texture_kind_clone).into(),
-                );
+    pub fn handle_clear_water(&self) {
+        let mut state_guard = self.inner.state.borrow_mut();
+
+        state_guard.water = None;
+    }
+
+    pub fn handle_set_sky(&self, settings: JsValue) {
+        let settings: SkySettings = match settings.into_serde() {
+            Ok(settings) => settings,
+            Err(e) => {
+                web_sys::console::error_1(&format!("Invalid sky settings: {}", e).into());
                 return;
             }
         };
 
-        let maskKind = match texture_kind_clone.as_str() {
-            "Primary" => LandscapeTextureKinds::PrimaryMask,
-            "Rockmap" => LandscapeTextureKinds::RockmapMask,
-            "Soil" => LandscapeTextureKinds::SoilMask,
-            _ => {
-                web_sys::console::error_1(
-                    &format!("Invalid texture kind: {}", texture_kind_clone).into(),
-                );
+        let mut state_guard = self.inner.state.borrow_mut();
+
+        state_guard.set_sky(settings);
+    }
+
+    pub fn handle_scatter_foliage(
+        &self,
+        project_id: String,
+        foliage_component_id: String,
+        model_filename: String,
+        settings: JsValue,
+        callback: js_sys::Function,
+    ) {
+        let settings: FoliageProperties = match settings.into_serde() {
+            Ok(settings) => settings,
+            Err(e) => {
+                web_sys::console::error_1(&format!("Invalid foliage settings: {}", e).into());
                 return;
             }
         };
 
-        state_guard.update_landscape_texture(
-            landscape_component_id_clone,
-            kind,
-            texture,
-            maskKind,
-            mask,
-        );
+        let renderer = self.clone();
+
+        spawn_local(async move {
+            let this = JsValue::null();
+
+            // the model only needs loading the first time this component is scattered
+            let loaded = renderer
+                .inner
+                .state
+                .borrow()
+                .foliage
+                .iter()
+                .any(|f| f.id == foliage_component_id);
+
+            if !loaded {
+                let bytes = match read_model_bytes(project_id, model_filename).await {
+                    Ok(bytes) => bytes,
+                    Err(e) => {
+                        console_error(&format!("Couldn't load foliage model: {}", e));
+                        let _ = callback.call1(&this, &JsValue::null());
+                        return;
+                    }
+                };
+                renderer
+                    .inner
+                    .state
+                    .borrow_mut()
+                    .add_foliage(&foliage_component_id, &bytes);
+            }
 
-        drop(state_guard);
+            let instances = renderer
+                .inner
+                .state
+                .borrow_mut()
+                .scatter_foliage(&foliage_component_id, &settings);
 
-        resume_rendering();
-    });
+            match instances {
+                Some(instances) => {
+                    let _ = callback.call1(&this, &to_value(&instances).unwrap());
+                }
+                None => {
+                    web_sys::console::error_1(
+                        &format!(
+                            "No landscape to scatter on: {}",
+                            settings.landscape_component_id
+                        )
+                        .into(),
+                    );
+                    let _ = callback.call1(&this, &JsValue::null());
+                }
+            }
+        });
+    }
+}
+
+// painted masks go to the backend as PNG data urls, like the other save_* commands
+fn mask_png_data_url(width: u32, height: u32, bytes: Vec<u8>) -> Result<String, String> {
+    let image = image::RgbaImage::from_raw(width, height, bytes)
+        .ok_or("Mask data doesn't match its dimensions")?;
+
+    let mut png = Vec::new();
+    image
+        .write_to(&mut std::io::Cursor::new(&mut png), image::ImageFormat::Png)
+        .map_err(|e| format!("Couldn't encode mask: {}", e))?;
+
+    Ok(format!("data:image/png;base64,{}", STANDARD.encode(png)))
+}
+
+// the texture and mask slots a landscape layer ("Primary", "Rockmap" or "Soil") draws into
+fn texture_id_for<'a>(component: &'a ComponentData, kind: &str) -> Option<&'a String> {
+    let properties = component.landscape_properties.as_ref()?;
+
+    match kind {
+        "Primary" => properties.primary_texture_id.as_ref(),
+        "Rockmap" => properties.rockmap_texture_id.as_ref(),
+        "Soil" => properties.soil_texture_id.as_ref(),
+        _ => None,
+    }
+}
+
+// each layer with a texture assigned, masks are read from the matching map of the landscape asset
+fn landscape_texture_layers(
+    saved_state: &SavedState,
+    component: &ComponentData,
+) -> Vec<LandscapeTextureLayer> {
+    let landscape = match saved_state
+        .landscapes
+        .iter()
+        .flatten()
+        .find(|landscape| landscape.id == component.asset_id)
+    {
+        Some(landscape) => landscape,
+        None => return Vec::new(),
+    };
+
+    [
+        ("Primary", &landscape.heightmap),
+        ("Rockmap", &landscape.rockmap),
+        ("Soil", &landscape.soil),
+    ]
+    .into_iter()
+    .filter_map(|(kind, map)| {
+        let texture_id = texture_id_for(component, kind)?;
+        let texture = saved_state
+            .textures
+            .iter()
+            .flatten()
+            .find(|texture| &texture.id == texture_id)?;
+
+        Some(LandscapeTextureLayer {
+            kind: kind.to_string(),
+            texture_id: texture_id.clone(),
+            texture_filename: texture.fileName.clone(),
+            mask_filename: map.as_ref().map(|map| map.fileName.clone()),
+        })
+    })
+    .collect()
+}
+
+fn landscape_texture_kinds(kind: &str) -> Option<(LandscapeTextureKinds, LandscapeTextureKinds)> {
+    match kind {
+        "Primary" => Some((
+            LandscapeTextureKinds::Primary,
+            LandscapeTextureKinds::PrimaryMask,
+        )),
+        "Rockmap" => Some((
+            LandscapeTextureKinds::Rockmap,
+            LandscapeTextureKinds::RockmapMask,
+        )),
+        "Soil" => Some((LandscapeTextureKinds::Soil, LandscapeTextureKinds::SoilMask)),
+        _ => None,
+    }
+}
+
+async fn read_model_bytes(project_id: String, model_filename: String) -> Result<Vec<u8>, String> {
+    let params = to_value(&ReadModelParams {
+        projectId: project_id,
+        modelFilename: model_filename,
+    })
+    .map_err(|e| format!("Couldn't serialize model params: {}", e))?;
+
+    let bytes = try_invoke("read_model", params)
+        .await
+        .map_err(|e| invoke_error(e, "Couldn't read model"))?;
+
+    bytes
+        .into_serde()
+        .map_err(|e| format!("Invalid model data: {}", e))
+}
+
+async fn read_landscape_data(
+    project_id: String,
+    landscape_asset_id: String,
+    landscape_filename: String,
+) -> Result<LandscapeData, String> {
+    let params = to_value(&GetLandscapeParams {
+        projectId: project_id,
+        landscapeAssetId: landscape_asset_id,
+        landscapeFilename: landscape_filename,
+    })
+    .map_err(|e| format!("Couldn't serialize landscape params: {}", e))?;
+
+    let data = try_invoke("get_landscape_pixels", params)
+        .await
+        .map_err(|e| invoke_error(e, "Couldn't read landscape"))?;
+
+    data.into_serde()
+        .map_err(|e| format!("Invalid landscape data: {}", e))
 }
 
 #[derive(Deserialize)]
@@ -0,0 +1,51 @@
+struct CameraUniforms {
+    view_projection: mat4x4<f32>
+};
+
+struct ModelUniforms {
+    model: mat4x4<f32>
+};
+
+@group(0) @binding(0) var<uniform> camera_uniforms: CameraUniforms;
+@group(1) @binding(0) var<uniform> model_uniforms: ModelUniforms; // mesh transform, shared by every instance
+
+struct VertexInput {
+    @location(0) position: vec3<f32>,
+    @location(1) normal: vec3<f32>,
+    @location(2) tex_coords: vec2<f32>,
+    @location(3) color: vec3<f32>
+};
+
+struct InstanceInput {
+    @location(4) model_0: vec4<f32>,
+    @location(5) model_1: vec4<f32>,
+    @location(6) model_2: vec4<f32>,
+    @location(7) model_3: vec4<f32>
+};
+
+struct VertexOutput {
+    @builtin(position) position: vec4<f32>,
+    @location(0) normal: vec3<f32>,
+    @location(1) tex_coords: vec2<f32>,
+    @location(2) color: vec3<f32>,
+    @location(3) world_position: vec3<f32>
+};
+
+@vertex
+fn main(input: VertexInput, instance: InstanceInput) -> VertexOutput {
+    let instance_model = mat4x4<f32>(
+        instance.model_0,
+        instance.model_1,
+        instance.model_2,
+        instance.model_3
+    );
+
+    var output: VertexOutput;
+    let model_position = instance_model * model_uniforms.model * vec4<f32>(input.position, 1.0);
+    output.position = camera_uniforms.view_projection * model_position;
+    output.color = input.color;
+    output.normal = (instance_model * vec4<f32>(input.normal, 0.0)).xyz;
+    output.tex_coords = input.tex_coords;
+    output.world_position = model_position.xyz;
+    return output;
+}
@@ -78,24 +78,52 @@
 struct FragmentInput {
     @location(0) normal: vec3<f32>,
     @location(1) tex_coords: vec2<f32>,
-    @location(2) color: vec3<f32>
+    @location(2) color: vec3<f32>,
+    @location(3) world_position: vec3<f32>
+};
+
+struct EnvironmentUniforms {
+    inverse_view_projection: mat4x4<f32>,
+    camera_position: vec4<f32>,
+    sun_direction: vec4<f32>, // xyz towards the sun, w intensity
+    zenith_color: vec4<f32>,
+    horizon_color: vec4<f32>,
+    ground_color: vec4<f32>,
+    fog_color: vec4<f32>,     // rgb, density
+    fog_params: vec4<f32>     // height falloff, base height, max opacity, unused
 };
 
 @group(2) @binding(0) var t_diffuse: texture_2d_array<f32>;
 @group(2) @binding(1) var s_diffuse: sampler;
 @group(2) @binding(2) var<uniform> renderMode: i32;
+@group(2) @binding(3) var t_masks: texture_2d_array<f32>;
+@group(3) @binding(0) var<uniform> environment: EnvironmentUniforms;
+
+// exponential distance fog, thinning out with height above the fog base
+fn apply_fog(color: vec3<f32>, world_position: vec3<f32>) -> vec3<f32> {
+    let distance = length(world_position - environment.camera_position.xyz);
+    let density = environment.fog_color.a;
+    let falloff = environment.fog_params.x;
+    let height = max(world_position.y - environment.fog_params.y, 0.0);
+
+    let thickness = density * exp(-falloff * height);
+    let fog = min(1.0 - exp(-distance * thickness), environment.fog_params.z);
+
+    return mix(color, environment.fog_color.rgb, fog);
+}
 
 @fragment
 fn main(in: FragmentInput) -> @location(0) vec4<f32> {
     let tiling_factor: f32 = 10.0;
     let tiled_tex_coords = fract(in.tex_coords * tiling_factor);
 
+    // materials are tiled, masks span the whole landscape
     let primary = textureSample(t_diffuse, s_diffuse, tiled_tex_coords, 0);
-    let primary_mask = textureSample(t_diffuse, s_diffuse, in.tex_coords, 1).r;
-    let rockmap = textureSample(t_diffuse, s_diffuse, tiled_tex_coords, 2);
-    let rockmap_mask = textureSample(t_diffuse, s_diffuse, in.tex_coords, 3).r;
-    let soil = textureSample(t_diffuse, s_diffuse, tiled_tex_coords, 4);
-    let soil_mask = textureSample(t_diffuse, s_diffuse, in.tex_coords, 5).r;
+    let primary_mask = textureSample(t_masks, s_diffuse, in.tex_coords, 0).r;
+    let rockmap = textureSample(t_diffuse, s_diffuse, tiled_tex_coords, 1);
+    let rockmap_mask = textureSample(t_masks, s_diffuse, in.tex_coords, 1).r;
+    let soil = textureSample(t_diffuse, s_diffuse, tiled_tex_coords, 2);
+    let soil_mask = textureSample(t_masks, s_diffuse, in.tex_coords, 2).r;
     
     // Normalize masks
     let total_mask = primary_mask + rockmap_mask + soil_mask;
@@ -111,9 +139,9 @@ fn main(in: FragmentInput) -> @location(0) vec4<f32> {
     // return vec4<f32>(final_color, 1.0);
     
     if (renderMode == 1) { // Assume 1 means rendering texture
-        return vec4<f32>(final_color, 1.0); // Texture rendering
+        return vec4<f32>(apply_fog(final_color, in.world_position), 1.0); // Texture rendering
     } else {
-        return vec4(in.color, 1.0); // Color mode
+        return vec4(apply_fog(in.color, in.world_position), 1.0); // Color mode
     }
 
     // debug color coating
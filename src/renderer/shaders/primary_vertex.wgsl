@@ -20,7 +20,8 @@ struct VertexOutput {
     @builtin(position) position: vec4<f32>,
     @location(0) normal: vec3<f32>,
     @location(1) tex_coords: vec2<f32>,
-    @location(2) color: vec3<f32>
+    @location(2) color: vec3<f32>,
+    @location(3) world_position: vec3<f32>
 };
 
 @vertex
@@ -31,5 +32,6 @@ fn main(input: VertexInput) -> VertexOutput {
     output.color = input.color;
     output.normal = input.normal;
     output.tex_coords = input.tex_coords;
+    output.world_position = model_position.xyz;
     return output;
 }
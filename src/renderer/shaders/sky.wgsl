@@ -0,0 +1,62 @@
+struct EnvironmentUniforms {
+    inverse_view_projection: mat4x4<f32>,
+    camera_position: vec4<f32>,
+    sun_direction: vec4<f32>, // xyz towards the sun, w intensity
+    zenith_color: vec4<f32>,
+    horizon_color: vec4<f32>,
+    ground_color: vec4<f32>,
+    fog_color: vec4<f32>,     // rgb, density
+    fog_params: vec4<f32>     // height falloff, base height, max opacity, unused
+};
+
+@group(0) @binding(0) var<uniform> environment: EnvironmentUniforms;
+
+struct VertexOutput {
+    @builtin(position) position: vec4<f32>,
+    @location(0) ndc: vec2<f32>
+};
+
+// one triangle that covers the whole screen
+@vertex
+fn vs_main(@builtin(vertex_index) index: u32) -> VertexOutput {
+    let x = f32(i32(index & 1u) * 4 - 1);
+    let y = f32(i32(index >> 1u) * 4 - 1);
+
+    var output: VertexOutput;
+    output.position = vec4<f32>(x, y, 0.0, 1.0);
+    output.ndc = vec2<f32>(x, y);
+    return output;
+}
+
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+    let far = environment.inverse_view_projection * vec4<f32>(in.ndc, 1.0, 1.0);
+    let direction = normalize(far.xyz / far.w - environment.camera_position.xyz);
+    let sun = normalize(environment.sun_direction.xyz);
+    let intensity = environment.sun_direction.w;
+
+    // warm the horizon as the sun gets low
+    let sunset = pow(1.0 - clamp(sun.y, 0.0, 1.0), 4.0);
+    let horizon = mix(environment.horizon_color.rgb, vec3<f32>(1.0, 0.55, 0.3), sunset * 0.6);
+
+    var color: vec3<f32>;
+    if (direction.y >= 0.0) {
+        color = mix(horizon, environment.zenith_color.rgb, pow(direction.y, 0.5));
+    } else {
+        color = mix(horizon, environment.ground_color.rgb, pow(-direction.y, 0.35));
+    }
+
+    // night falls as the sun drops below the horizon
+    color = color * clamp(sun.y * 4.0 + 0.6, 0.08, 1.0);
+
+    let sun_amount = max(dot(direction, sun), 0.0);
+    let sun_color = vec3<f32>(1.0, 0.92, 0.8) * intensity;
+    color = color + sun_color * (pow(sun_amount, 900.0) * 8.0 + pow(sun_amount, 12.0) * 0.25);
+
+    // blend into the fog color at the horizon so distant terrain meets the sky
+    let haze = 1.0 - clamp(abs(direction.y) * 6.0, 0.0, 1.0);
+    let fog_visible = clamp(environment.fog_color.a * 400.0, 0.0, 1.0);
+    color = mix(color, environment.fog_color.rgb, haze * fog_visible * environment.fog_params.z);
+
+    return vec4<f32>(color, 1.0);
+}
@@ -0,0 +1,106 @@
+struct CameraUniforms {
+    view_projection: mat4x4<f32>
+};
+
+struct WaterUniforms {
+    color: vec4<f32>,  // rgb, opacity
+    waves: vec4<f32>,  // sea level, wave height, wave speed, wave length
+    params: vec4<f32>  // time, shoreline fade distance, camera near, camera far
+};
+
+struct EnvironmentUniforms {
+    inverse_view_projection: mat4x4<f32>,
+    camera_position: vec4<f32>,
+    sun_direction: vec4<f32>, // xyz towards the sun, w intensity
+    zenith_color: vec4<f32>,
+    horizon_color: vec4<f32>,
+    ground_color: vec4<f32>,
+    fog_color: vec4<f32>,     // rgb, density
+    fog_params: vec4<f32>     // height falloff, base height, max opacity, unused
+};
+
+@group(0) @binding(0) var<uniform> camera_uniforms: CameraUniforms;
+@group(1) @binding(0) var<uniform> water: WaterUniforms;
+// read as a plain float texture, GL backends can't load from depth textures
+@group(1) @binding(1) var t_scene_depth: texture_2d<f32>;
+@group(2) @binding(0) var<uniform> environment: EnvironmentUniforms;
+
+struct VertexInput {
+    @location(0) position: vec3<f32>,
+    @location(1) normal: vec3<f32>,
+    @location(2) tex_coords: vec2<f32>,
+    @location(3) color: vec3<f32>
+};
+
+struct VertexOutput {
+    @builtin(position) position: vec4<f32>,
+    @location(0) normal: vec3<f32>,
+    @location(1) world_position: vec3<f32>
+};
+
+// two crossing sine waves, returns (height, d/dx, d/dz)
+fn wave(xz: vec2<f32>) -> vec3<f32> {
+    let height = water.waves.y;
+    let speed = water.waves.z;
+    let k = 6.28318 / water.waves.w;
+    let t = water.params.x * speed;
+
+    let a = dot(xz, vec2<f32>(0.8, 0.6)) * k + t;
+    let b = dot(xz, vec2<f32>(-0.4, 0.9)) * k * 1.7 + t * 1.3;
+
+    let h = height * (sin(a) + 0.5 * sin(b));
+    let dx = height * k * (0.8 * cos(a) + 0.5 * 1.7 * -0.4 * cos(b));
+    let dz = height * k * (0.6 * cos(a) + 0.5 * 1.7 * 0.9 * cos(b));
+
+    return vec3<f32>(h, dx, dz);
+}
+
+// exponential distance fog, thinning out with height above the fog base
+fn apply_fog(color: vec3<f32>, world_position: vec3<f32>) -> vec3<f32> {
+    let distance = length(world_position - environment.camera_position.xyz);
+    let density = environment.fog_color.a;
+    let falloff = environment.fog_params.x;
+    let height = max(world_position.y - environment.fog_params.y, 0.0);
+
+    let thickness = density * exp(-falloff * height);
+    let fog = min(1.0 - exp(-distance * thickness), environment.fog_params.z);
+
+    return mix(color, environment.fog_color.rgb, fog);
+}
+
+// the projection keeps OpenGL's depth mapping, so undo that to get distance from the camera
+fn linear_depth(depth: f32) -> f32 {
+    let near = water.params.z;
+    let far = water.params.w;
+    return (2.0 * near * far) / ((far + near) - depth * (far - near));
+}
+
+@vertex
+fn vs_main(input: VertexInput) -> VertexOutput {
+    let w = wave(input.position.xz);
+    let world_position = vec3<f32>(input.position.x, water.waves.x + w.x, input.position.z);
+
+    var output: VertexOutput;
+    output.position = camera_uniforms.view_projection * vec4<f32>(world_position, 1.0);
+    output.normal = normalize(vec3<f32>(-w.y, 1.0, -w.z));
+    output.world_position = world_position;
+    return output;
+}
+
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+    let scene_depth = textureLoad(t_scene_depth, vec2<i32>(in.position.xy), 0).r;
+
+    // fade out where the water is shallow, i.e. close to whatever is behind it
+    let water_distance = linear_depth(in.position.z);
+    let scene_distance = linear_depth(scene_depth);
+    let shore = clamp((scene_distance - water_distance) / water.params.y, 0.0, 1.0);
+
+    // cheap lighting so the waves read without a full lighting model
+    let light = normalize(vec3<f32>(0.3, 1.0, 0.2));
+    let shade = 0.75 + 0.25 * max(dot(in.normal, light), 0.0);
+
+    let color = apply_fog(water.color.rgb * shade, in.world_position);
+
+    return vec4<f32>(color, water.color.a * shore);
+}